@@ -0,0 +1,204 @@
+use chrono::NaiveDate;
+use std::collections::BTreeMap;
+use tui::{
+    backend::Backend,
+    layout::Rect,
+    style::{Color, Modifier, Style},
+    text::{Span, Spans},
+    widgets::{Block, Borders, Paragraph},
+    Frame,
+};
+
+use crate::clock;
+use crate::estimate;
+use crate::milestones;
+use crate::recurrence::{self, PROJECTION_DAYS};
+use crate::scope;
+use crate::{AppState, Task};
+
+/// Open tasks sharing a deadline day.
+pub struct AgendaDay {
+    pub date: NaiveDate,
+    /// Indices into the task list.
+    pub tasks: Vec<usize>,
+    /// Repeating tasks whose later occurrences fall on this day. They aren't
+    /// real tasks yet, but count towards the day's load.
+    pub projected: Vec<usize>,
+    /// Milestones of open tasks falling on this day, as task index and
+    /// milestone name.
+    pub milestones: Vec<(usize, String)>,
+    /// Combined estimate; tasks without one count as zero.
+    pub minutes: u32,
+}
+
+fn day(days: &mut BTreeMap<NaiveDate, AgendaDay>, date: NaiveDate) -> &mut AgendaDay {
+    days.entry(date).or_insert_with(|| AgendaDay {
+        date,
+        tasks: Vec::new(),
+        projected: Vec::new(),
+        milestones: Vec::new(),
+        minutes: 0,
+    })
+}
+
+/// Groups open tasks with a deadline by day, earliest first, along with the
+/// coming occurrences of repeating tasks and the tasks' milestones.
+pub fn build(tasks: &[Task], include: impl Fn(&Task) -> bool) -> Vec<AgendaDay> {
+    let today = clock::today();
+    let until = today + chrono::Duration::days(PROJECTION_DAYS);
+    let mut days: BTreeMap<NaiveDate, AgendaDay> = BTreeMap::new();
+    let open = tasks.iter().enumerate().filter(|(_, t)| !t.completed);
+    for (i, task) in open.filter(|(_, t)| include(t)) {
+        for milestone in milestones::milestones(&task.description) {
+            let name = milestone.name.to_string();
+            day(&mut days, milestone.date).milestones.push((i, name));
+        }
+        let Some(deadline) = task.deadline else {
+            continue;
+        };
+        let minutes = estimate::from_description(&task.description).unwrap_or(0);
+        let entry = day(&mut days, deadline.date());
+        entry.tasks.push(i);
+        entry.minutes += minutes;
+        for date in recurrence::occurrences(task, today, until) {
+            let entry = day(&mut days, date);
+            entry.projected.push(i);
+            entry.minutes += minutes;
+        }
+    }
+    days.into_values().collect()
+}
+
+/// Lists each day with its load; the selected day is expanded to show its
+/// tasks, so an over-capacity day can be drilled into.
+pub fn render_agenda<B: Backend>(f: &mut Frame<B>, app_state: &AppState, chunk: Rect) {
+    let days = build(&app_state.tasks, |t| app_state.in_scope(t));
+    let capacity = app_state.config.capacity_minutes();
+    let today = clock::today();
+    let title = format!("Agenda - {}", scope::label(app_state));
+    let block = Block::default().borders(Borders::ALL).title(title);
+    if days.is_empty() {
+        f.render_widget(
+            Paragraph::new("No open tasks with a deadline").block(block),
+            chunk,
+        );
+        return;
+    }
+
+    let selected = app_state.agenda_selected.min(days.len() - 1);
+    let mut lines = Vec::new();
+    let mut selected_line = 0;
+    for (i, day) in days.iter().enumerate() {
+        let is_selected = i == selected;
+        if is_selected {
+            selected_line = lines.len();
+        }
+        let date_style = if day.date < today {
+            app_state.styles.overdue
+        } else if is_selected {
+            app_state.styles.selected
+        } else {
+            Style::default()
+        };
+        let mut spans = vec![
+            Span::styled(
+                format!(
+                    "{} {}",
+                    if is_selected { ">" } else { " " },
+                    day.date.format("%a")
+                ),
+                date_style,
+            ),
+            Span::styled(
+                format!(" {}", day.date.format(&app_state.config.date_format)),
+                date_style.add_modifier(Modifier::BOLD),
+            ),
+            Span::styled(
+                format!("  {} tasks", day.tasks.len()),
+                Style::default().fg(Color::DarkGray),
+            ),
+        ];
+        if !day.projected.is_empty() {
+            spans.push(Span::styled(
+                format!(" + {} repeating", day.projected.len()),
+                Style::default()
+                    .fg(Color::DarkGray)
+                    .add_modifier(Modifier::ITALIC),
+            ));
+        }
+        if !day.milestones.is_empty() {
+            spans.push(Span::styled(
+                format!(" + {} milestones", day.milestones.len()),
+                Style::default().fg(Color::DarkGray),
+            ));
+        }
+        spans.push(Span::styled(
+            format!(", {}", estimate::format_minutes(day.minutes)),
+            Style::default().fg(Color::DarkGray),
+        ));
+        if day.minutes > capacity {
+            spans.push(Span::raw("  "));
+            spans.push(Span::styled(
+                format!(
+                    " ! {} over capacity ",
+                    estimate::format_minutes(day.minutes - capacity)
+                ),
+                Style::default()
+                    .fg(Color::White)
+                    .bg(Color::Red)
+                    .add_modifier(Modifier::BOLD),
+            ));
+        }
+        lines.push(Spans::from(spans));
+
+        if is_selected {
+            for &index in &day.tasks {
+                let task = &app_state.tasks[index];
+                let estimate = estimate::from_description(&task.description)
+                    .map_or("no estimate".to_string(), estimate::format_minutes);
+                lines.push(Spans::from(vec![
+                    Span::raw(format!("      {}", app_state.description(task))),
+                    Span::styled(
+                        format!("  ({})", estimate),
+                        Style::default().fg(Color::DarkGray),
+                    ),
+                ]));
+            }
+            for (index, name) in &day.milestones {
+                let task = &app_state.tasks[*index];
+                let text = if app_state.masked(task) {
+                    format!("    ◆ {}", app_state.description(task))
+                } else {
+                    format!("    ◆ {}: {}", name, task.description)
+                };
+                lines.push(Spans::from(Span::raw(text)));
+            }
+            // Projections stay visibly apart from tasks that exist.
+            for &index in &day.projected {
+                lines.push(Spans::from(Span::styled(
+                    format!(
+                        "    ↻ {}  (repeats)",
+                        app_state.description(&app_state.tasks[index])
+                    ),
+                    Style::default()
+                        .fg(Color::DarkGray)
+                        .add_modifier(Modifier::ITALIC),
+                )));
+            }
+        }
+    }
+
+    // Keep the selected day and its tasks in view.
+    let height = chunk.height.saturating_sub(2) as usize;
+    let expanded = days[selected].tasks.len()
+        + days[selected].projected.len()
+        + days[selected].milestones.len()
+        + 1;
+    let scroll = (selected_line + expanded)
+        .saturating_sub(height)
+        .min(selected_line);
+    let paragraph = Paragraph::new(lines)
+        .scroll((scroll as u16, 0))
+        .block(block);
+    f.render_widget(paragraph, chunk);
+}
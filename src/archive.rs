@@ -0,0 +1,38 @@
+use std::fs::File;
+use std::io::{self, BufReader};
+use std::path::Path;
+
+use crate::Task;
+
+pub const ARCHIVE_FILE: &str = "archive.json";
+
+/// Completed tasks leave the task file on save; the archive keeps them so
+/// completion history (`completed_at`) survives.
+pub fn load(data_dir: &Path) -> Result<Vec<Task>, io::Error> {
+    load_file(&data_dir.join(ARCHIVE_FILE))
+}
+
+pub fn load_file(path: &Path) -> Result<Vec<Task>, io::Error> {
+    match File::open(path) {
+        Ok(file) => serde_json::from_reader(BufReader::new(file)).map_err(io::Error::other),
+        Err(ref e) if e.kind() == io::ErrorKind::NotFound => Ok(Vec::new()),
+        Err(e) => Err(e),
+    }
+}
+
+/// Brings the archive in line with `tasks`: completed ones are added or
+/// replaced, and any that were reopened are dropped again.
+pub fn update(data_dir: &Path, tasks: &[Task]) -> Result<(), io::Error> {
+    update_file(&data_dir.join(ARCHIVE_FILE), tasks)
+}
+
+/// `update` for the archive at `path`, e.g. one of a workspace's files.
+pub fn update_file(path: &Path, tasks: &[Task]) -> Result<(), io::Error> {
+    let mut archived = load_file(path)?;
+    archived.retain(|a| !tasks.iter().any(|t| t.id == a.id));
+    archived.extend(tasks.iter().filter(|t| t.completed).cloned());
+
+    let file = File::create(path)?;
+    serde_json::to_writer(file, &archived)?;
+    Ok(())
+}
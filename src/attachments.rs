@@ -0,0 +1,66 @@
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+use crate::config;
+use crate::share;
+
+/// Folder in the data directory that copied attachments go into, one
+/// subfolder per task.
+pub const ATTACHMENTS_DIR: &str = "attachments";
+
+/// Checks that `path` is a file and returns the path to store for it:
+/// absolute, or that of a copy under the data directory when `copy` is set,
+/// so the attachment survives the original moving.
+pub fn store(path: &str, copy: bool, data_dir: &Path, task: u64) -> Result<String, io::Error> {
+    let source = fs::canonicalize(config::expand_home(path.trim()))
+        .map_err(|e| io::Error::new(e.kind(), format!("{}: {}", path.trim(), e)))?;
+    if !source.is_file() {
+        let message = format!("{} is not a file", source.display());
+        return Err(io::Error::new(io::ErrorKind::InvalidInput, message));
+    }
+    if !copy {
+        return Ok(source.display().to_string());
+    }
+    let dir = dir(data_dir).join(task.to_string());
+    fs::create_dir_all(&dir)?;
+    let target = free_name(&dir, &source);
+    fs::copy(&source, &target)?;
+    let target = fs::canonicalize(&target).unwrap_or(target);
+    Ok(target.display().to_string())
+}
+
+/// The folder holding copied attachments, one subfolder per task id.
+pub fn dir(data_dir: &Path) -> PathBuf {
+    data_dir.join(ATTACHMENTS_DIR)
+}
+
+/// `dir` joined with the source's file name, numbered when a file of that
+/// name is already there: `memo.m4a`, `memo-2.m4a`, ...
+fn free_name(dir: &Path, source: &Path) -> PathBuf {
+    let name = source.file_name().unwrap_or_default();
+    let stem = source.file_stem().unwrap_or_default().to_string_lossy();
+    let extension = source
+        .extension()
+        .map(|e| format!(".{}", e.to_string_lossy()));
+    let mut target = dir.join(name);
+    let mut n = 2;
+    while target.exists() {
+        let extension = extension.as_deref().unwrap_or_default();
+        target = dir.join(format!("{}-{}{}", stem, n, extension));
+        n += 1;
+    }
+    target
+}
+
+/// Opens each attachment with the system's handler for its type.
+pub fn open(paths: &[String]) -> Result<(), io::Error> {
+    for path in paths {
+        if !Path::new(path).exists() {
+            let message = format!("{} no longer exists", path);
+            return Err(io::Error::new(io::ErrorKind::NotFound, message));
+        }
+        share::open_url(path)?;
+    }
+    Ok(())
+}
@@ -0,0 +1,253 @@
+use chrono::NaiveDateTime;
+use std::collections::BTreeMap;
+use tui::{
+    backend::Backend,
+    layout::Rect,
+    style::{Color, Modifier, Style},
+    text::{Span, Spans},
+    widgets::{Block, Borders, Paragraph, Wrap},
+    Frame,
+};
+
+use crate::keymap::Key;
+use crate::maintenance;
+use crate::ops::Operation;
+use crate::privacy;
+use crate::snapshots;
+use crate::{AppState, Mode, Task};
+
+/// A change to many tasks or files at once, waiting in `Mode::BulkConfirm`
+/// for `y` after listing exactly what it will change, as `todo-rs done`
+/// lists its matches before asking.
+pub struct Pending {
+    pub question: String,
+    lines: Vec<String>,
+    change: Change,
+    /// Where to go back to, whether it goes ahead or not.
+    previous: Mode,
+}
+
+enum Change {
+    /// The batch as it was previewed, with how many tasks it changes.
+    RollBack {
+        operations: Vec<Operation>,
+        count: usize,
+        label: String,
+    },
+    PruneSnapshots,
+    CompactJournal,
+    DeferStale(Vec<u64>),
+}
+
+fn ask(app_state: &mut AppState, question: String, lines: Vec<String>, change: Change) {
+    let previous = std::mem::replace(&mut app_state.mode, Mode::BulkConfirm);
+    app_state.pending = Some(Pending {
+        question,
+        lines,
+        change,
+        previous,
+    });
+}
+
+/// `task`'s id and description as they may be shown right now.
+fn name(app_state: &AppState, task: &Task) -> String {
+    let description = if app_state.masked(task) {
+        privacy::mask(&task.description)
+    } else {
+        task.description.clone()
+    };
+    format!("{}: {}", task.id, description)
+}
+
+fn name_of(app_state: &AppState, id: u64) -> String {
+    match app_state.tasks.iter().find(|t| t.id == id) {
+        Some(task) => name(app_state, task),
+        None => id.to_string(),
+    }
+}
+
+/// One line per operation a roll-back is made of, e.g. `Delete 12: Call
+/// the bank`.
+fn describe(app_state: &AppState, operation: &Operation) -> String {
+    let format = format!("{} %H:%M", app_state.config.date_format);
+    match operation {
+        Operation::Delete(id) => format!("Delete {}", name_of(app_state, *id)),
+        Operation::Restore(task) => format!("Bring back {}", name(app_state, task)),
+        Operation::Complete(id) => format!("Complete {}", name_of(app_state, *id)),
+        Operation::Reopen(id) => format!("Reopen {}", name_of(app_state, *id)),
+        Operation::Edit { task, description } => {
+            let masked = (app_state.tasks.iter()).any(|t| t.id == *task && app_state.masked(t));
+            let to = if masked {
+                privacy::mask(description)
+            } else {
+                description.clone()
+            };
+            format!("Rename {} to {}", name_of(app_state, *task), to)
+        }
+        Operation::Reschedule { task, deadline } => {
+            let to = deadline.map_or("no deadline".to_string(), |d| d.format(&format).to_string());
+            format!("Move {} to {}", name_of(app_state, *task), to)
+        }
+        _ => "Another change".to_string(),
+    }
+}
+
+/// Asks before rolling the whole list back to the snapshot taken at `taken`.
+pub fn roll_back(app_state: &mut AppState, tasks: Vec<Task>, taken: NaiveDateTime, label: String) {
+    let (operations, count) = app_state.roll_back(tasks, taken);
+    if operations.is_empty() {
+        let text = format!("The list is as it was at {} already", label);
+        app_state.set_status(text, false);
+        return;
+    }
+    let lines = (operations.iter())
+        .map(|operation| describe(app_state, operation))
+        .collect();
+    let question = format!("Roll back {} tasks to {}?", count, label);
+    let change = Change::RollBack {
+        operations,
+        count,
+        label,
+    };
+    ask(app_state, question, lines, change);
+}
+
+/// Asks before removing all but the newest `PRUNE_KEEP` snapshots.
+pub fn prune_snapshots(app_state: &mut AppState) {
+    let format = format!("{} %H:%M", app_state.config.date_format);
+    let old: Vec<String> = (snapshots::list(&app_state.data_dir).into_iter())
+        .skip(maintenance::PRUNE_KEEP)
+        .map(|snapshot| format!("Remove the snapshot {}", snapshot.label(&format)))
+        .collect();
+    if old.is_empty() {
+        app_state.set_status("No snapshots to prune".to_string(), false);
+        return;
+    }
+    let question = format!("Remove {} snapshots?", old.len());
+    ask(app_state, question, old, Change::PruneSnapshots);
+}
+
+/// Asks before dropping the history of deleted tasks from the journal.
+pub fn compact_journal(app_state: &mut AppState) {
+    let mut dropped: BTreeMap<u64, usize> = BTreeMap::new();
+    for task in maintenance::compaction_preview(app_state) {
+        *dropped.entry(task).or_default() += 1;
+    }
+    if dropped.is_empty() {
+        app_state.set_status("Nothing to compact".to_string(), false);
+        return;
+    }
+    let lines = (dropped.iter())
+        .map(|(task, events)| format!("Drop {} events about deleted task {}", events, task))
+        .collect();
+    let total: usize = dropped.values().sum();
+    let question = format!("Remove {} events from the journal?", total);
+    ask(app_state, question, lines, Change::CompactJournal);
+}
+
+/// Asks before moving the stale tasks `ids` to Someday.
+pub fn defer_stale(app_state: &mut AppState, ids: Vec<u64>) {
+    let lines = (ids.iter())
+        .map(|id| format!("Move {} to Someday", name_of(app_state, *id)))
+        .collect();
+    let days = app_state.config.someday_after_days.unwrap_or_default();
+    let question = format!(
+        "Move {} tasks untouched for {} days to Someday?",
+        ids.len(),
+        days
+    );
+    ask(app_state, question, lines, Change::DeferStale(ids));
+}
+
+/// `y` goes ahead; any other key leaves everything as it is.
+pub fn handle_key(app_state: &mut AppState, key: Key) {
+    let Some(pending) = app_state.pending.take() else {
+        app_state.mode = Mode::Normal;
+        return;
+    };
+    app_state.mode = pending.previous;
+    if key != Key::Char('y') {
+        app_state.set_status("Cancelled".to_string(), false);
+        return;
+    }
+    match pending.change {
+        Change::RollBack {
+            operations,
+            count,
+            label,
+        } => {
+            if let Err(e) = app_state.snapshot_before("pre-rollback") {
+                let text = format!("Snapshot failed, nothing rolled back: {}", e);
+                app_state.set_status(text, true);
+                return;
+            }
+            if app_state.apply(operations).is_some() {
+                app_state.selected_task = app_state.visible_tasks().first().copied();
+                let text = format!("Rolled back {} tasks to {}", count, label);
+                app_state.set_status(text, false);
+            }
+        }
+        Change::PruneSnapshots => {
+            let done = match snapshots::prune(&app_state.data_dir, maintenance::PRUNE_KEEP) {
+                Ok(removed) => Ok(format!("Removed {} snapshots", removed)),
+                Err(e) => Err(format!("Could not prune snapshots: {}", e)),
+            };
+            maintenance::refresh(app_state, done);
+        }
+        Change::CompactJournal => {
+            let done = match maintenance::compact_journal(app_state) {
+                Ok(removed) => Ok(format!("Removed {} events from the journal", removed)),
+                Err(e) => Err(format!("Could not compact the journal: {}", e)),
+            };
+            maintenance::refresh(app_state, done);
+        }
+        Change::DeferStale(ids) => app_state.defer_tasks(ids),
+    }
+}
+
+pub fn render_bulk<B: Backend>(f: &mut Frame<B>, app_state: &AppState, chunk: Rect) {
+    let Some(pending) = &app_state.pending else {
+        return;
+    };
+    let mut lines: Vec<Spans> = (pending.lines.iter())
+        .map(|line| Spans::from(line.clone()))
+        .collect();
+    lines.push(Spans::default());
+    lines.push(Spans::from(Span::styled(
+        "Nothing changes until you press y.",
+        Style::default()
+            .fg(Color::DarkGray)
+            .add_modifier(Modifier::ITALIC),
+    )));
+    let title = format!("Preview - {} changes", pending.lines.len());
+    let block = Block::default().borders(Borders::ALL).title(title);
+    let paragraph = Paragraph::new(lines)
+        .block(block)
+        .wrap(Wrap { trim: false });
+    f.render_widget(paragraph, chunk);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn a_roll_back_waits_for_y() {
+        let mut app_state = AppState::new();
+        app_state.add_task("Kept".to_string(), None, None);
+        let taken = crate::clock::now() - chrono::Duration::seconds(1);
+        let snapshot = app_state.tasks.clone();
+        app_state.add_task("Added since".to_string(), None, None);
+
+        roll_back(&mut app_state, snapshot.clone(), taken, "noon".to_string());
+        let lines = &app_state.pending.as_ref().unwrap().lines;
+        assert_eq!(lines[..], ["Delete 2: Added since".to_string()]);
+        handle_key(&mut app_state, Key::Char('n'));
+        assert_eq!(app_state.tasks.len(), 2);
+        assert!(app_state.mode == Mode::Normal);
+
+        roll_back(&mut app_state, snapshot, taken, "noon".to_string());
+        handle_key(&mut app_state, Key::Char('y'));
+        assert_eq!(app_state.tasks.len(), 1);
+    }
+}
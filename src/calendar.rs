@@ -0,0 +1,172 @@
+use chrono::{NaiveDate, NaiveDateTime, NaiveTime};
+use chrono_tz::Tz;
+use std::fs;
+use std::io;
+use std::path::Path;
+use std::process::{Command, Stdio};
+
+use crate::clock;
+use crate::config::CalendarConfig;
+use crate::estimate;
+use crate::share::escape_ics;
+use crate::Task;
+
+/// Used when a task has no `~estimate`.
+pub const DEFAULT_MINUTES: u32 = 60;
+
+/// Same for every call on a task, so blocking it again updates the event.
+fn uid(task: &Task) -> String {
+    format!("todo-rs-block-{}", task.id)
+}
+
+/// A calendar event reserving the task's estimated time on its due day,
+/// titled without the words of a private task.
+fn to_event(task: &Task, start: NaiveDateTime) -> String {
+    let minutes = estimate::from_description(&task.description).unwrap_or(DEFAULT_MINUTES);
+    let end = start + chrono::Duration::minutes(minutes as i64);
+    let now = chrono::Utc::now().format("%Y%m%dT%H%M%SZ");
+    [
+        "BEGIN:VCALENDAR".to_string(),
+        "VERSION:2.0".to_string(),
+        "PRODID:-//todo-rs//EN".to_string(),
+        "BEGIN:VEVENT".to_string(),
+        format!("UID:{}", uid(task)),
+        format!("DTSTAMP:{}", now),
+        format!("DTSTART:{}", start.format("%Y%m%dT%H%M%S")),
+        format!("DTEND:{}", end.format("%Y%m%dT%H%M%S")),
+        format!("SUMMARY:{}", escape_ics(&task.shared_description())),
+        "END:VEVENT".to_string(),
+        "END:VCALENDAR".to_string(),
+    ]
+    .join("\r\n")
+        + "\r\n"
+}
+
+/// Puts a time block for the task into the configured calendar: a file in
+/// `drop_dir` (e.g. a vdir synced by vdirsyncer) or an import through khal.
+pub fn block_time(task: &Task, config: &CalendarConfig) -> Result<String, io::Error> {
+    let deadline = task.deadline.ok_or_else(|| {
+        io::Error::new(
+            io::ErrorKind::InvalidInput,
+            "the task needs a deadline first",
+        )
+    })?;
+    let start_time = NaiveTime::parse_from_str(&config.start, "%H:%M").map_err(io::Error::other)?;
+    let event = to_event(task, deadline.date().and_time(start_time));
+
+    if config.khal {
+        let path = std::env::temp_dir().join(format!("{}.ics", uid(task)));
+        fs::write(&path, event)?;
+        let status = Command::new("khal")
+            .args(["import", "--batch"])
+            .arg(&path)
+            .stdout(Stdio::null())
+            .stderr(Stdio::null())
+            .status();
+        let _ = fs::remove_file(&path);
+        let status = status?;
+        if !status.success() {
+            return Err(io::Error::other(format!("khal exited with {}", status)));
+        }
+        return Ok("Time blocked in khal".to_string());
+    }
+
+    let Some(dir) = &config.drop_dir else {
+        return Err(io::Error::new(
+            io::ErrorKind::NotFound,
+            "set calendar.drop_dir or calendar.khal in the config",
+        ));
+    };
+    let dir = crate::config::expand_home(dir);
+    fs::create_dir_all(&dir)?;
+    let path = dir.join(format!("{}.ics", uid(task)));
+    fs::write(&path, event)?;
+    Ok(format!("Time blocked in {}", path.display()))
+}
+
+/// The lines of an iCalendar file, with long lines folded onto
+/// continuation lines that start with a blank joined up again.
+pub fn unfold(text: &str) -> Vec<String> {
+    let mut lines: Vec<String> = Vec::new();
+    for line in text.lines() {
+        match (line.strip_prefix([' ', '\t']), lines.last_mut()) {
+            (Some(rest), Some(last)) => last.push_str(rest),
+            _ => lines.push(line.to_string()),
+        }
+    }
+    lines
+}
+
+/// Undoes `escape_ics`.
+fn unescape(text: &str) -> String {
+    let mut result = String::new();
+    let mut chars = text.chars();
+    while let Some(c) = chars.next() {
+        if c != '\\' {
+            result.push(c);
+            continue;
+        }
+        match chars.next() {
+            Some('n' | 'N') => result.push(' '),
+            Some(escaped) => result.push(escaped),
+            None => {}
+        }
+    }
+    result
+}
+
+/// A timed event read from a calendar.
+pub struct Event {
+    pub start: NaiveDateTime,
+    pub end: NaiveDateTime,
+    pub summary: String,
+}
+
+/// A `DTSTART` or `DTEND` value in the configured zone: UTC when it ends
+/// in `Z`, in its `TZID` when it has one, else wall-clock time already.
+fn event_time(name: &str, value: &str) -> Option<NaiveDateTime> {
+    let time = NaiveDateTime::parse_from_str(value.trim_end_matches('Z'), "%Y%m%dT%H%M%S").ok()?;
+    if value.ends_with('Z') {
+        return clock::from_zone(time, &chrono::Utc);
+    }
+    let zone = (name.split(';')).find_map(|param| param.strip_prefix("TZID="));
+    match zone.and_then(|zone| zone.parse::<Tz>().ok()) {
+        Some(zone) => clock::from_zone(time, &zone),
+        None => Some(time),
+    }
+}
+
+/// The timed events of the `.ics` file at `path` that fall on `day`, in
+/// order; all-day events and repeats aren't read.
+pub fn events_on(path: &Path, day: NaiveDate) -> Result<Vec<Event>, io::Error> {
+    let text = fs::read_to_string(path)?;
+    let mut events = Vec::new();
+    let (mut start, mut end, mut summary) = (None, None, String::new());
+    for line in unfold(&text) {
+        let Some((name, value)) = line.split_once(':') else {
+            continue;
+        };
+        let property = name.split(';').next().unwrap_or_default();
+        match property.to_uppercase().as_str() {
+            "BEGIN" => (start, end, summary) = (None, None, String::new()),
+            "DTSTART" => start = event_time(name, value),
+            "DTEND" => end = event_time(name, value),
+            "SUMMARY" => summary = unescape(value),
+            "END" if value.eq_ignore_ascii_case("VEVENT") => {
+                let Some(start) = start.filter(|s| s.date() == day) else {
+                    continue;
+                };
+                let minutes = chrono::Duration::minutes(DEFAULT_MINUTES as i64);
+                let end = end.filter(|e| *e > start).unwrap_or(start + minutes);
+                events.push(Event {
+                    start,
+                    end,
+                    summary: summary.clone(),
+                });
+            }
+            _ => {}
+        }
+    }
+    events.sort_by_key(|e| e.start);
+    Ok(events)
+}
@@ -0,0 +1,77 @@
+use crossterm::event::{self, Event};
+use std::io;
+use tui::{
+    layout::Rect,
+    widgets::{Block, Borders, Paragraph},
+};
+
+use crate::completion;
+use crate::keymap::Key;
+use crate::terminal;
+use crate::AppState;
+
+/// A one-line prompt that adds a single task and exits, sized to fit a tmux
+/// popup (`tmux display-popup -E -h 3 todo-rs capture`). Returns whether a
+/// task was added; Esc or Ctrl+C cancel.
+pub fn run(app_state: &mut AppState) -> Result<bool, io::Error> {
+    let mut terminal = terminal::initialize(false)?;
+    let result = prompt(app_state, &mut terminal);
+    terminal::restore(&mut terminal)?;
+
+    let description = result?;
+    let Some(description) = description.filter(|d| !d.trim().is_empty()) else {
+        return Ok(false);
+    };
+    app_state.add_task(description.trim().to_string(), None, None);
+    Ok(true)
+}
+
+fn prompt(
+    app_state: &mut AppState,
+    terminal: &mut tui::Terminal<terminal::Backend>,
+) -> Result<Option<String>, io::Error> {
+    loop {
+        terminal.draw(|f| {
+            let size = f.size();
+            let area = Rect::new(size.x, size.y, size.width, size.height.min(3));
+            let block = Block::default()
+                .borders(Borders::ALL)
+                .title("Capture (Enter: add, Tab: complete, Esc: cancel)");
+            f.render_widget(Paragraph::new(app_state.input.as_str()).block(block), area);
+            completion::render_popup(f, app_state, area.x + 1, area.bottom());
+        })?;
+
+        let key = match event::read()? {
+            Event::Key(key) => Key::from_event(key),
+            // Multi-line pastes collapse into the single description.
+            Event::Paste(text) => {
+                app_state.completion = None;
+                let text = text.split_whitespace().collect::<Vec<_>>().join(" ");
+                app_state.input.push_str(&text);
+                continue;
+            }
+            _ => continue,
+        };
+        match key {
+            Some(Key::Char('\n')) => return Ok(Some(std::mem::take(&mut app_state.input))),
+            Some(Key::Esc) | Some(Key::Ctrl('c')) => return Ok(None),
+            Some(key @ (Key::Char('\t') | Key::BackTab)) => {
+                completion::cycle(
+                    &mut app_state.input,
+                    &mut app_state.completion,
+                    &app_state.tasks,
+                    key != Key::BackTab,
+                );
+            }
+            Some(Key::Char(c)) => {
+                app_state.completion = None;
+                app_state.input.push(c);
+            }
+            Some(Key::Backspace) => {
+                app_state.completion = None;
+                app_state.input.pop();
+            }
+            _ => {}
+        }
+    }
+}
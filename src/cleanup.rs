@@ -0,0 +1,203 @@
+use std::collections::BTreeSet;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+use crate::archive;
+use crate::attachments;
+use crate::cli;
+use crate::jira;
+use crate::ops::Operation;
+use crate::tags;
+use crate::{AppState, Task};
+
+/// A reference left dangling by a deletion or a file moving away.
+enum Orphan {
+    /// A subtask of a deleted task; it moves to the top level.
+    Parent { task: u64, parent: u64 },
+    /// An attachment whose file is gone; the reference is dropped.
+    Attachment { task: u64, path: String },
+    /// Copied attachments of a deleted task; the folder is removed.
+    Folder { task: u64, path: PathBuf },
+    /// A fetched issue no task links to anymore; it is forgotten.
+    Issue { key: String },
+    /// A WIP limit on a `#tag` no task carries; it leaves the config.
+    TagLimit { tag: String },
+    /// A WIP limit on a `+project` no task is in; it leaves the config.
+    ProjectLimit { project: String },
+}
+
+impl Orphan {
+    fn describe(&self, app_state: &AppState) -> String {
+        let task = |id: &u64| {
+            let task = app_state.tasks.iter().find(|t| t.id == *id);
+            let description = task.map_or("", |t| t.description.as_str());
+            format!("Task {} ({})", id, description)
+        };
+        match self {
+            Orphan::Parent { task: id, parent } => format!(
+                "{} is a subtask of {}, which was deleted; move it to the top level",
+                task(id),
+                parent
+            ),
+            Orphan::Attachment { task: id, path } => {
+                format!("{} attaches {}, which is gone; detach it", task(id), path)
+            }
+            Orphan::Folder { task, path } => format!(
+                "{} holds copies for task {}, which was deleted; remove the folder",
+                path.display(),
+                task
+            ),
+            Orphan::Issue { key } => {
+                format!("Issue {} is cached but no task links it; forget it", key)
+            }
+            Orphan::TagLimit { tag } => format!(
+                "WIP limit on #{} applies to no task; remove it from the config",
+                tag
+            ),
+            Orphan::ProjectLimit { project } => format!(
+                "WIP limit on +{} applies to no task; remove it from the config",
+                project
+            ),
+        }
+    }
+}
+
+fn find(app_state: &AppState, issues: &jira::Issues) -> Result<Vec<Orphan>, io::Error> {
+    let archived = archive::load(&app_state.data_dir)?;
+    let ids: BTreeSet<u64> = (app_state.tasks.iter().chain(&archived))
+        .map(|t| t.id)
+        .collect();
+    let mut orphans = Vec::new();
+
+    for task in &app_state.tasks {
+        if let Some(parent) = task.parent.filter(|p| !ids.contains(p)) {
+            orphans.push(Orphan::Parent {
+                task: task.id,
+                parent,
+            });
+        }
+        for path in &task.attachments {
+            if !PathBuf::from(path).exists() {
+                orphans.push(Orphan::Attachment {
+                    task: task.id,
+                    path: path.clone(),
+                });
+            }
+        }
+    }
+
+    let folders = fs::read_dir(attachments::dir(&app_state.data_dir));
+    for entry in folders.into_iter().flatten().flatten() {
+        let id = entry.file_name().to_string_lossy().parse::<u64>().ok();
+        // Tasks of a workspace's other files are renumbered on every load,
+        // so a folder still attached somewhere stays.
+        let path = entry.path();
+        let attached = (app_state.tasks.iter()).any(|t| {
+            t.attachments
+                .iter()
+                .any(|a| Path::new(a).starts_with(&path))
+        });
+        if let Some(task) = id.filter(|id| !ids.contains(id) && !attached) {
+            orphans.push(Orphan::Folder { task, path });
+        }
+    }
+
+    let linked: BTreeSet<&str> = (app_state.tasks.iter())
+        .filter_map(|t| jira::key(&t.description))
+        .collect();
+    for key in issues.keys() {
+        if !linked.contains(key) {
+            orphans.push(Orphan::Issue {
+                key: key.to_string(),
+            });
+        }
+    }
+
+    let all = || app_state.tasks.iter().chain(&archived);
+    let limits = &app_state.config.wip;
+    for tag in limits.tags.keys() {
+        if !all().any(|t| tags::tags(&t.description).any(|t| t == tag)) {
+            orphans.push(Orphan::TagLimit { tag: tag.clone() });
+        }
+    }
+    for project in limits.projects.keys() {
+        if !all().any(|t| tags::projects(&t.description).any(|p| p == project)) {
+            orphans.push(Orphan::ProjectLimit {
+                project: project.clone(),
+            });
+        }
+    }
+    Ok(orphans)
+}
+
+/// Mends one orphan; returns whether the config changed.
+fn fix(app_state: &mut AppState, issues: &mut jira::Issues, orphan: Orphan) -> io::Result<bool> {
+    match orphan {
+        Orphan::Parent { task, parent } => {
+            let parented = |t: &Task| t.id == task && t.parent == Some(parent);
+            if app_state.tasks.iter().any(parented) {
+                app_state.apply(vec![Operation::Reparent { task, parent: None }]);
+            }
+        }
+        Orphan::Attachment { task, path } => {
+            if let Some(index) = app_state.tasks.iter().position(|t| t.id == task) {
+                app_state.detach(index, &path);
+            }
+        }
+        Orphan::Folder { path, .. } => fs::remove_dir_all(path)?,
+        Orphan::Issue { key } => issues.remove(&key),
+        Orphan::TagLimit { tag } => {
+            app_state.config.wip.tags.remove(&tag);
+            return Ok(true);
+        }
+        Orphan::ProjectLimit { project } => {
+            app_state.config.wip.projects.remove(&project);
+            return Ok(true);
+        }
+    }
+    Ok(false)
+}
+
+/// Finds references left dangling by deletions and offers to mend each
+/// one: `--dry-run` only lists them, `--yes` mends them all without asking.
+pub fn run(args: &[String]) -> Result<(), io::Error> {
+    let dry_run = args.iter().any(|a| a == "--dry-run");
+    let yes = args.iter().any(|a| a == "--yes");
+    let mut app_state = cli::open_state()?;
+    let mut issues = jira::Issues::load(&app_state.data_dir);
+
+    let had_issues = issues.keys().next().is_some();
+    let orphans = find(&app_state, &issues)?;
+    if orphans.is_empty() {
+        println!("Nothing to clean up");
+        return Ok(());
+    }
+    let mut fixed = 0;
+    let mut config_changed = false;
+    for orphan in orphans {
+        let text = orphan.describe(&app_state);
+        if dry_run {
+            println!("{}", text);
+            continue;
+        }
+        if !yes && !cli::confirm(&format!("{}?", text))? {
+            continue;
+        }
+        config_changed |= fix(&mut app_state, &mut issues, orphan)?;
+        fixed += 1;
+    }
+    if dry_run || fixed == 0 {
+        return Ok(());
+    }
+    app_state.save_tasks()?;
+    // Without a sync there's no file to rewrite.
+    if had_issues && app_state.store.persists() {
+        issues.save(&app_state.data_dir)?;
+    }
+    if config_changed {
+        app_state.config.save()?;
+    }
+    println!("Fixed {}", fixed);
+    Ok(())
+}
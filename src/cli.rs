@@ -0,0 +1,782 @@
+use std::collections::{BTreeMap, BTreeSet};
+use std::fs;
+use std::io::{self, BufRead, IsTerminal, Read, Write};
+use std::path::Path;
+
+use crate::archive;
+use crate::capture;
+use crate::cleanup;
+use crate::clock;
+use crate::config::Config;
+use crate::delegate;
+use crate::doctor;
+use crate::due;
+use crate::eventlog::{self, Format};
+use crate::focus;
+use crate::heatmap;
+use crate::importers::{self, Existing};
+use crate::jira;
+use crate::journal::EventKind;
+use crate::ops::{self, Changelog, Operation};
+use crate::privacy;
+use crate::query::{self, Query};
+use crate::secrets;
+use crate::selfupdate;
+use crate::share;
+use crate::tasks::SortKey;
+use crate::tasks::Tasks;
+use crate::{AppState, Task};
+
+const USAGE: &str = "Usage:
+  todo-rs                              start the interactive UI (lists tasks when piped)
+  todo-rs --exec <commands>            start the UI after running commands, e.g.
+                                       \"filter #work; sort due; view agenda\"
+  todo-rs --demo [...]                 try it out on sample tasks in a throwaway directory,
+                                       leaving your own config and tasks alone
+  todo-rs tutorial                     learn the basics step by step on the demo's tasks
+  todo-rs add <description> [--due <today|tomorrow|week|next-<weekday>|YYYY-MM-DD>] [--force]
+  todo-rs capture                      prompt for one task and exit, e.g. in a tmux popup
+  todo-rs list [--query <query>] [--filter <name>] [--regex <pattern>] [--archived]
+               [--sort <key>] [--reverse]
+                                       --archived lists completed tasks from the archive;
+                                       --sort orders them by due, created, urgency, etc.
+  todo-rs done (--query <query> | --filter <name> | --regex <pattern>) [--dry-run] [--yes]
+                                       complete every matching open task after listing
+                                       them and asking; --yes skips asking
+  todo-rs reopen <id> [--due <date> | --no-due]
+                                       bring back a completed task, even an archived one
+  todo-rs import [file] [--existing <skip|update|duplicate>] [--yes]
+                                       add tasks from indented text (stdin by default), or
+                                       from an Apple Reminders or Microsoft To Do export
+                                       (.ics, or CSV as Outlook writes it); tasks already
+                                       in the list are listed and, unless --existing says
+                                       otherwise, asked about or skipped; --existing
+                                       update asks first unless --yes
+  todo-rs attach <id> <file> [--copy]  attach a file to a task; --copy keeps a copy in the data
+                                       directory
+  todo-rs detach <id> <file>           remove an attachment from a task
+  todo-rs share <id> [--mail]          print the task as .ics, or open an email draft
+  todo-rs focus [--week]               print today's (or this week's) focus time per task as
+                                       Markdown, from the time tasks spent in progress
+  todo-rs events [--jsonl | --parquet] [--output <file>]
+                                       write the change journal as CSV (or JSON lines, or
+                                       Parquet), a row per event, for pandas, Grafana and
+                                       the like
+  todo-rs delegate [--csv] [--dir <dir>] [--query <query> | --filter <name>]
+                                       write a Markdown (or CSV) file per assignee, named
+                                       by assignee:name words, with their open tasks
+  todo-rs jira sync                    fetch the status of issues linked with jira:KEY-123
+  todo-rs secret (set|delete) <name>   store a secret in the system keyring, used in the
+                                       config as \"keyring:<name>\"
+  todo-rs self-update [--check]        install the latest release binary, checked against
+                                       the release's SHA256SUMS (which guards against a
+                                       broken download, not a tampered release)
+  todo-rs clean [--dry-run] [--yes]    mend references left dangling by deletions, asking
+                                       about each; --dry-run lists them, --yes mends all
+  todo-rs doctor                       check the terminal, config and data files";
+
+/// Runs a one-shot command. Changes go through the journal so a running TUI
+/// merges them instead of overwriting them on exit.
+pub fn run(args: &[String]) -> Result<(), io::Error> {
+    match args[0].as_str() {
+        "add" => add(&args[1..]),
+        "capture" => capture(),
+        "list" => list(&args[1..]),
+        "done" => done(&args[1..]),
+        "reopen" => reopen(&args[1..]),
+        "import" => import(&args[1..]),
+        "attach" => attach(&args[1..]),
+        "detach" => detach(&args[1..]),
+        "share" => share(&args[1..]),
+        "jira" => jira(&args[1..]),
+        "focus" => focus_report(&args[1..]),
+        "events" => export_events(&args[1..]),
+        "delegate" => delegate(&args[1..]),
+        "secret" => secret(&args[1..]),
+        "self-update" => self_update(&args[1..]),
+        "clean" => cleanup::run(&args[1..]),
+        "doctor" => doctor::run(),
+        "help" | "--help" | "-h" => {
+            println!("{}", USAGE);
+            Ok(())
+        }
+        other => Err(usage_error(&format!("unknown command '{}'", other))),
+    }
+}
+
+/// Makes the changes through `ops::apply`, all or none.
+fn apply(app_state: &mut AppState, operations: Vec<Operation>) -> Result<Changelog, io::Error> {
+    ops::apply(app_state, operations).map_err(|e| io::Error::new(io::ErrorKind::InvalidInput, e))
+}
+
+fn usage_error(message: &str) -> io::Error {
+    io::Error::new(
+        io::ErrorKind::InvalidInput,
+        format!("{}\n\n{}", message, USAGE),
+    )
+}
+
+/// Loads the config and the task file it points at.
+pub fn open_state() -> Result<AppState, io::Error> {
+    let loaded = Config::load()
+        .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, format!("Config error: {}", e)))?;
+    let mut app_state = AppState::new();
+    app_state.apply_config(loaded);
+    app_state.load_tasks()?;
+    Ok(app_state)
+}
+
+/// A `--due` value as `due::parse` reads it.
+fn due_text(value: Option<&String>) -> Result<String, io::Error> {
+    match value {
+        // Dates keep their dashes; presets are written `next-friday`.
+        Some(date) if date.starts_with(|c: char| c.is_ascii_digit()) => Ok(date.clone()),
+        Some(option) if option == "week" => Ok("this week".to_string()),
+        Some(option) => Ok(option.to_lowercase().replace('-', " ")),
+        None => Err(usage_error("--due needs a value")),
+    }
+}
+
+fn add(args: &[String]) -> Result<(), io::Error> {
+    let mut words = Vec::new();
+    let mut due_option = None;
+    let mut force = false;
+
+    let mut args = args.iter();
+    while let Some(arg) = args.next() {
+        if arg == "--due" {
+            due_option = Some(due_text(args.next())?);
+        } else if arg == "--force" {
+            force = true;
+        } else {
+            words.push(arg.as_str());
+        }
+    }
+
+    let description = words.join(" ");
+    if description.trim().is_empty() {
+        return Err(usage_error("add needs a description"));
+    }
+
+    let mut app_state = open_state()?;
+    let deadline = due_option
+        .map(|option| {
+            due::parse(&option, &app_state.config, &app_state.workdays)
+                .map_err(|e| io::Error::new(io::ErrorKind::InvalidInput, e))
+        })
+        .transpose()?;
+    if let Some(warning) = deadline.and_then(|d| due::warning(d, &app_state.config.date_format)) {
+        if !force {
+            let message = format!("{}; pass --force to keep it", warning);
+            return Err(io::Error::new(io::ErrorKind::InvalidInput, message));
+        }
+    }
+    if let Some(limit) = app_state.config.description_limit {
+        let chars = description.chars().count();
+        if chars > limit {
+            eprintln!("Warning: {} characters, over the limit of {}", chars, limit);
+        }
+    }
+    let add = Operation::Add {
+        description,
+        deadline,
+        parent: None,
+    };
+    let changelog = apply(&mut app_state, vec![add])?;
+    app_state.save_tasks()?;
+
+    for change in &changelog {
+        if let EventKind::Created { description, .. } = &change.kind {
+            println!("Added task {}: {}", change.task, description);
+        }
+    }
+    Ok(())
+}
+
+fn capture() -> Result<(), io::Error> {
+    let mut app_state = open_state()?;
+    if capture::run(&mut app_state)? {
+        app_state.save_tasks()?;
+    }
+    Ok(())
+}
+
+fn import(args: &[String]) -> Result<(), io::Error> {
+    let mut path = None;
+    let mut existing = None;
+    let mut yes = false;
+    let mut args = args.iter();
+    while let Some(arg) = args.next() {
+        if arg == "--yes" {
+            yes = true;
+        } else if arg == "--existing" {
+            let choice = args.next().and_then(|a| Existing::parse(a));
+            let message = "--existing needs skip, update or duplicate";
+            existing = Some(choice.ok_or_else(|| usage_error(message))?);
+        } else {
+            path = Some(arg);
+        }
+    }
+    let text = match path {
+        Some(path) => fs::read_to_string(path)?,
+        None => {
+            let mut text = String::new();
+            io::stdin().read_to_string(&mut text)?;
+            text
+        }
+    };
+
+    let mut app_state = open_state()?;
+    let Some(items) = importers::parse(&text) else {
+        app_state.snapshot_before("pre-import")?;
+        let count = app_state.import_outline(&text, None);
+        app_state.save_tasks()?;
+        println!("Imported {} tasks", count);
+        return Ok(());
+    };
+    // Finished tasks would go straight to the archive; leave them behind.
+    let (done, open): (Vec<_>, Vec<_>) = items.into_iter().partition(|item| item.completed);
+    let matches: Vec<Option<u64>> = (open.iter())
+        .map(|item| importers::existing(item, &app_state.tasks).map(|t| t.id))
+        .collect();
+
+    // What's about to happen, before anything does.
+    let found = matches.iter().flatten().count();
+    println!("{} new, {} already in the list", open.len() - found, found);
+    for (item, id) in open.iter().zip(&matches) {
+        let Some(task) = id.and_then(|id| app_state.tasks.iter().find(|t| t.id == id)) else {
+            continue;
+        };
+        println!(
+            "  {}  (task {}: {})",
+            item.description, task.id, task.description
+        );
+    }
+    if !done.is_empty() {
+        println!("{} completed, left out", done.len());
+    }
+    let existing = match existing {
+        // Overwriting tasks is asked about as `done` asks, unless --yes.
+        Some(Existing::Update)
+            if found > 0 && !yes && !confirm(&format!("Update {} tasks?", found))? =>
+        {
+            return Ok(());
+        }
+        Some(existing) => existing,
+        None if found > 0 && io::stdin().is_terminal() => {
+            eprint!("Skip, update or duplicate the ones already in the list? [S/u/d] ");
+            io::stderr().flush()?;
+            let mut answer = String::new();
+            io::stdin().lock().read_line(&mut answer)?;
+            Existing::parse(&answer).unwrap_or(Existing::Skip)
+        }
+        None => Existing::Skip,
+    };
+
+    let mut operations = Vec::new();
+    // The other app's ids for the tasks being added, in order, and for
+    // those updated.
+    let mut added_uids = Vec::new();
+    let mut updated_uids = Vec::new();
+    for (item, id) in open.iter().zip(&matches) {
+        let deadline = item.deadline.and_then(|d| d.and_hms_opt(0, 0, 0));
+        match (*id, existing) {
+            (Some(_), Existing::Skip) => {}
+            (Some(task), Existing::Update) => {
+                operations.push(Operation::Edit {
+                    task,
+                    description: item.description.clone(),
+                });
+                operations.push(Operation::Reschedule { task, deadline });
+                updated_uids.push((task, item.uid.clone()));
+            }
+            _ => {
+                operations.push(Operation::Add {
+                    description: item.description.clone(),
+                    deadline,
+                    parent: None,
+                });
+                // A duplicate leaves the id to the task it copies.
+                added_uids.push(item.uid.clone().filter(|_| id.is_none()));
+            }
+        }
+    }
+    app_state.snapshot_before("pre-import")?;
+    let changelog = apply(&mut app_state, operations)?;
+    let created: Vec<u64> = (changelog.iter())
+        .filter(|change| matches!(change.kind, EventKind::Created { .. }))
+        .map(|change| change.task)
+        .collect();
+    let updated: BTreeSet<u64> = (changelog.iter())
+        .map(|change| change.task)
+        .filter(|task| !created.contains(task))
+        .collect();
+    let uids = created.iter().copied().zip(added_uids).chain(updated_uids);
+    for (id, uid) in uids {
+        if let Some(task) = app_state.tasks.iter_mut().find(|t| t.id == id) {
+            task.external_id = task.external_id.take().or(uid);
+        }
+    }
+    app_state.save_tasks()?;
+    println!(
+        "Imported {} tasks, updated {}",
+        created.len(),
+        updated.len()
+    );
+    Ok(())
+}
+
+/// The position of the task with the id in `arg`.
+fn task_index(
+    app_state: &AppState,
+    arg: Option<&String>,
+    command: &str,
+) -> Result<usize, io::Error> {
+    let id: u64 = arg
+        .and_then(|id| id.parse().ok())
+        .ok_or_else(|| usage_error(&format!("{} needs a task id", command)))?;
+    (app_state.tasks.iter())
+        .position(|t| t.id == id)
+        .ok_or_else(|| io::Error::new(io::ErrorKind::NotFound, format!("no task {}", id)))
+}
+
+fn attach(args: &[String]) -> Result<(), io::Error> {
+    let mut app_state = open_state()?;
+    let index = task_index(&app_state, args.first(), "attach")?;
+    let path = args
+        .get(1)
+        .ok_or_else(|| usage_error("attach needs a file"))?;
+    let copy = args[2..].iter().any(|a| a == "--copy");
+    app_state.attach(index, path, copy)?;
+    app_state.save_tasks()?;
+    let task = &app_state.tasks[index];
+    if let Some(stored) = task.attachments.last() {
+        println!("Attached {} to task {}", stored, task.id);
+    }
+    Ok(())
+}
+
+fn detach(args: &[String]) -> Result<(), io::Error> {
+    let mut app_state = open_state()?;
+    let index = task_index(&app_state, args.first(), "detach")?;
+    let path = args
+        .get(1)
+        .ok_or_else(|| usage_error("detach needs a file"))?;
+    // Accept the path as typed as well as the absolute one stored.
+    let stored = fs::canonicalize(path).map(|p| p.display().to_string());
+    let removed = app_state.detach(index, path)
+        || stored.is_ok_and(|stored| app_state.detach(index, &stored));
+    if !removed {
+        let message = format!(
+            "task {} has no attachment {}",
+            app_state.tasks[index].id, path
+        );
+        return Err(io::Error::new(io::ErrorKind::NotFound, message));
+    }
+    app_state.save_tasks()?;
+    println!("Removed {}", path);
+    Ok(())
+}
+
+fn share(args: &[String]) -> Result<(), io::Error> {
+    let id: u64 = args
+        .first()
+        .and_then(|id| id.parse().ok())
+        .ok_or_else(|| usage_error("share needs a task id"))?;
+    let app_state = open_state()?;
+    let task = app_state
+        .tasks
+        .iter()
+        .find(|t| t.id == id)
+        .ok_or_else(|| io::Error::new(io::ErrorKind::NotFound, format!("no task {}", id)))?;
+
+    if args.iter().any(|a| a == "--mail") {
+        share::open_url(&share::mailto_url(task, &app_state.config.date_format))
+    } else {
+        print!("{}", share::to_ics(task));
+        Ok(())
+    }
+}
+
+/// Reads a `--query`, `--filter` or `--regex` option and its value.
+fn search_option(
+    option: &str,
+    value: Option<&String>,
+    filters: &BTreeMap<String, String>,
+) -> Result<Query, io::Error> {
+    let value = value.ok_or_else(|| usage_error(&format!("{} needs a value", option)))?;
+    let parsed = match option {
+        "--query" => query::parse(value, filters).map_err(|e| e.to_string()),
+        "--filter" => {
+            query::parse(&format!("filter:{}", value), filters).map_err(|e| e.to_string())
+        }
+        "--regex" => query::parse_regex(value),
+        other => return Err(usage_error(&format!("unknown option '{}'", other))),
+    };
+    parsed.map_err(|e| {
+        io::Error::new(
+            io::ErrorKind::InvalidInput,
+            format!("Invalid search: {}", e),
+        )
+    })
+}
+
+fn list(args: &[String]) -> Result<(), io::Error> {
+    let app_state = open_state()?;
+
+    let mut query = None;
+    let mut archived = false;
+    let mut sort = SortKey::Manual;
+    let mut reverse = false;
+    let mut args = args.iter();
+    while let Some(arg) = args.next() {
+        if arg == "--archived" {
+            archived = true;
+        } else if arg == "--sort" {
+            let Some(name) = args.next() else {
+                return Err(usage_error("--sort needs a key"));
+            };
+            sort = SortKey::parse(name).map_err(|e| usage_error(&e))?;
+        } else if arg == "--reverse" {
+            reverse = true;
+        } else {
+            query = Some(search_option(arg, args.next(), &app_state.config.filters)?);
+        }
+    }
+    let tasks = if archived {
+        archive::load(&app_state.data_dir)?
+    } else {
+        app_state.tasks
+    };
+
+    let mut found = (Tasks::new(&tasks).query())
+        .sorted_by(sort, reverse)
+        .weighted_by(&app_state.config.urgency);
+    if let Some(query) = &query {
+        found = found.matching(query);
+    }
+    for task in found.tasks() {
+        let mark = if task.completed { "x" } else { " " };
+        match task.deadline {
+            Some(d) => println!(
+                "{:>4} [{}] {} (due {})",
+                task.id,
+                mark,
+                task.description,
+                clock::format_deadline(d, "%Y-%m-%d")
+            ),
+            None => println!("{:>4} [{}] {}", task.id, mark, task.description),
+        }
+    }
+    Ok(())
+}
+
+/// Asks a yes/no question on the terminal; anything but yes is no.
+pub fn confirm(question: &str) -> Result<bool, io::Error> {
+    if !io::stdin().is_terminal() {
+        let message = "no terminal to confirm on; pass --yes to go ahead";
+        return Err(io::Error::new(io::ErrorKind::InvalidInput, message));
+    }
+    eprint!("{} [y/N] ", question);
+    io::stderr().flush()?;
+    let mut answer = String::new();
+    io::stdin().lock().read_line(&mut answer)?;
+    Ok(matches!(answer.trim().to_lowercase().as_str(), "y" | "yes"))
+}
+
+/// Completes the open tasks a search matches, e.g. once the shopping is
+/// done. A search is required so a typo can't complete everything, and the
+/// matches are listed for confirmation before anything changes.
+fn done(args: &[String]) -> Result<(), io::Error> {
+    let mut app_state = open_state()?;
+
+    let mut query = None;
+    let mut dry_run = false;
+    let mut yes = false;
+    let mut args = args.iter();
+    while let Some(arg) = args.next() {
+        if arg == "--dry-run" {
+            dry_run = true;
+        } else if arg == "--yes" {
+            yes = true;
+        } else {
+            query = Some(search_option(arg, args.next(), &app_state.config.filters)?);
+        }
+    }
+    let query = query.ok_or_else(|| usage_error("done needs --query, --filter or --regex"))?;
+    let today = clock::today();
+
+    let matching: Vec<usize> = (0..app_state.tasks.len())
+        .filter(|i| {
+            let task = &app_state.tasks[*i];
+            !task.completed && query.matches(task, today)
+        })
+        .collect();
+    if matching.is_empty() {
+        println!("No open tasks match");
+        return Ok(());
+    }
+    if dry_run || !yes {
+        for &i in &matching {
+            let task = &app_state.tasks[i];
+            println!("Would complete {}: {}", task.id, task.description);
+        }
+    }
+    if dry_run || (!yes && !confirm(&format!("Complete {} tasks?", matching.len()))?) {
+        return Ok(());
+    }
+    let operations = (matching.iter())
+        .map(|&i| Operation::Complete(app_state.tasks[i].id))
+        .collect();
+    app_state.snapshot_before("pre-bulk-done")?;
+    apply(&mut app_state, operations)?;
+    for &i in &matching {
+        let task = &app_state.tasks[i];
+        println!("Completed {}: {}", task.id, task.description);
+    }
+    app_state.save_tasks()
+}
+
+/// Prints focus time from the journal, e.g. to keep in a timesheet with
+/// `todo-rs focus --week >> timesheet.md`.
+fn focus_report(args: &[String]) -> Result<(), io::Error> {
+    let week = match args.first().map(String::as_str) {
+        None => false,
+        Some("--week") => true,
+        Some(other) => return Err(usage_error(&format!("unknown option '{}'", other))),
+    };
+    let app_state = open_state()?;
+    let archived = archive::load(&app_state.data_dir)?;
+    let events = app_state.store.events()?;
+    let sessions = focus::sessions(&events, clock::now());
+    let today = clock::today();
+    let from = if week {
+        heatmap::week_start_of(today, app_state.config.week_start())
+    } else {
+        today
+    };
+    let describe = |id: u64| {
+        let task = app_state.tasks.iter().chain(&archived).find(|t| t.id == id);
+        task.map_or(format!("task {}", id), |t| t.description.clone())
+    };
+    print!(
+        "{}",
+        focus::report(&sessions, from, today, today, describe).markdown()
+    );
+    Ok(())
+}
+
+/// Writes the journal as a table, e.g. `todo-rs events --output events.csv`.
+/// Private tasks' descriptions are masked as in the UI.
+fn export_events(args: &[String]) -> Result<(), io::Error> {
+    let mut format = Format::Csv;
+    let mut output = None;
+    let mut args = args.iter();
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--jsonl" => format = Format::Jsonl,
+            "--parquet" => format = Format::Parquet,
+            "--output" => {
+                let path = args
+                    .next()
+                    .ok_or_else(|| usage_error("--output needs a file"))?;
+                output = Some(path);
+            }
+            other => return Err(usage_error(&format!("unknown option '{}'", other))),
+        }
+    }
+    let app_state = open_state()?;
+    let archived = archive::load(&app_state.data_dir)?;
+    let private = |id: u64| {
+        let task = app_state.tasks.iter().chain(&archived).find(|t| t.id == id);
+        task.is_some_and(|t| t.private)
+    };
+    let mask = |id: u64, text: &str| {
+        if private(id) {
+            privacy::mask(text)
+        } else {
+            text.to_string()
+        }
+    };
+    let events = app_state.store.events()?;
+    let data = eventlog::write(&eventlog::rows(&events, mask), format)?;
+    match output {
+        Some(path) => {
+            fs::write(path, data)?;
+            eprintln!("Wrote {} events to {}", events.len(), path);
+        }
+        None => io::stdout().write_all(&data)?,
+    }
+    Ok(())
+}
+
+/// Writes each assignee's open tasks to a file of their own, e.g.
+/// `alice.md`, to hand out after a meeting. Private tasks are masked.
+fn delegate(args: &[String]) -> Result<(), io::Error> {
+    let app_state = open_state()?;
+    let mut format = delegate::Format::Markdown;
+    let mut dir = Path::new(".");
+    let mut query = None;
+    let mut args = args.iter();
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--csv" => format = delegate::Format::Csv,
+            "--dir" => {
+                let path = args
+                    .next()
+                    .ok_or_else(|| usage_error("--dir needs a folder"))?;
+                dir = Path::new(path);
+            }
+            _ => query = Some(search_option(arg, args.next(), &app_state.config.filters)?),
+        }
+    }
+    let today = clock::today();
+    let tasks: Vec<&Task> = (app_state.tasks.iter())
+        .filter(|t| query.as_ref().is_none_or(|q| q.matches(t, today)))
+        .collect();
+    let lists = delegate::by_assignee(&tasks);
+    if lists.is_empty() {
+        eprintln!("No open tasks with an assignee:name word");
+        return Ok(());
+    }
+    fs::create_dir_all(dir)?;
+    let describe = |task: &Task| {
+        if task.private {
+            privacy::mask(&task.description)
+        } else {
+            task.description.clone()
+        }
+    };
+    for (assignee, tasks) in &lists {
+        let name = format!("{}.{}", delegate::file_stem(assignee), format.extension());
+        let path = dir.join(name);
+        let date_format = &app_state.config.date_format;
+        fs::write(
+            &path,
+            delegate::render(assignee, tasks, &format, date_format, describe),
+        )?;
+        eprintln!("{}: {} tasks in {}", assignee, tasks.len(), path.display());
+    }
+    Ok(())
+}
+
+/// Refreshes the linked issues, completing tasks whose issue is done when
+/// the config asks for it. Run it from cron to keep an open app current.
+fn jira(args: &[String]) -> Result<(), io::Error> {
+    if args.first().map(String::as_str) != Some("sync") {
+        return Err(usage_error("expected 'jira sync'"));
+    }
+    let mut app_state = open_state()?;
+    let config = app_state.config.jira.clone().ok_or_else(|| {
+        io::Error::new(io::ErrorKind::NotFound, "no [jira] section in the config")
+    })?;
+    let linked: Vec<(usize, String)> = (app_state.tasks.iter().enumerate())
+        .filter(|(_, task)| !task.completed)
+        .filter_map(|(i, task)| Some((i, jira::key(&task.description)?.to_string())))
+        .collect();
+    if linked.is_empty() {
+        println!("No open tasks link a Jira issue");
+        return Ok(());
+    }
+
+    let token = secrets::resolve(config.token.as_deref(), "Jira API token")?;
+    let mut issues = jira::Issues::load(&app_state.data_dir);
+    let mut failed = 0;
+    let mut completions = Vec::new();
+    for (i, key) in linked {
+        let issue = match jira::fetch(&config, &token, &key) {
+            Ok(issue) => issue,
+            Err(e) => {
+                eprintln!("{}", e);
+                failed += 1;
+                continue;
+            }
+        };
+        println!("{} {}: {}", key, issue.status, issue.summary);
+        if config.complete_on_done && issue.done {
+            let task = &app_state.tasks[i];
+            println!("Completed {}: {}", task.id, task.description);
+            completions.push(Operation::Complete(task.id));
+        }
+        issues.insert(&key, issue);
+    }
+    if app_state.store.persists() {
+        issues.save(&app_state.data_dir)?;
+    }
+    if !completions.is_empty() {
+        apply(&mut app_state, completions)?;
+        app_state.save_tasks()?;
+    }
+    match failed {
+        0 => Ok(()),
+        n => Err(io::Error::other(format!(
+            "{} issues could not be fetched",
+            n
+        ))),
+    }
+}
+
+/// Reopens a completed task, taking it out of the archive if it was filed
+/// there. Its completion stays in the history.
+fn reopen(args: &[String]) -> Result<(), io::Error> {
+    let id: u64 = args
+        .first()
+        .and_then(|id| id.parse().ok())
+        .ok_or_else(|| usage_error("reopen needs a task id"))?;
+    let mut app_state = open_state()?;
+
+    let mut deadline = None;
+    let mut options = args[1..].iter();
+    while let Some(arg) = options.next() {
+        let text = match arg.as_str() {
+            "--due" => due_text(options.next())?,
+            "--no-due" => {
+                deadline = Some(None);
+                continue;
+            }
+            other => return Err(usage_error(&format!("unknown option '{}'", other))),
+        };
+        let parsed = due::parse(&text, &app_state.config, &app_state.workdays)
+            .map_err(|e| io::Error::new(io::ErrorKind::InvalidInput, e))?;
+        deadline = Some(Some(parsed));
+    }
+
+    let mut operations = vec![Operation::Reopen(id)];
+    if let Some(deadline) = deadline {
+        operations.push(Operation::Reschedule { task: id, deadline });
+    }
+    apply(&mut app_state, operations)?;
+    app_state.save_tasks()?;
+    if let Some(task) = app_state.tasks.iter().find(|t| t.id == id) {
+        println!("Reopened {}: {}", id, task.description);
+    }
+    Ok(())
+}
+
+fn secret(args: &[String]) -> Result<(), io::Error> {
+    let name = args
+        .get(1)
+        .filter(|name| !name.is_empty())
+        .ok_or_else(|| usage_error("secret needs a name"))?;
+    match args[0].as_str() {
+        "set" => {
+            let value = secrets::prompt(&format!("Secret for '{}'", name))?;
+            secrets::set(name, &value)?;
+            println!("Stored; use \"{}{}\" in the config", secrets::PREFIX, name);
+        }
+        "delete" if secrets::delete(name)? => println!("Deleted '{}'", name),
+        "delete" => println!("No keyring entry '{}'", name),
+        other => return Err(usage_error(&format!("unknown secret command '{}'", other))),
+    }
+    Ok(())
+}
+
+fn self_update(args: &[String]) -> Result<(), io::Error> {
+    let check_only = match args.first().map(String::as_str) {
+        None => false,
+        Some("--check") => true,
+        Some(other) => return Err(usage_error(&format!("unknown option '{}'", other))),
+    };
+    println!("{}", selfupdate::run(check_only)?);
+    Ok(())
+}
@@ -0,0 +1,184 @@
+use chrono::{DateTime, NaiveDate, NaiveDateTime, NaiveTime, TimeZone, Utc};
+use chrono_tz::Tz;
+use serde::Deserialize;
+use std::fmt::Display;
+use std::sync::RwLock;
+
+/// The configured `timezone`, or `None` to follow the system clock.
+static ZONE: RwLock<Option<Tz>> = RwLock::new(None);
+/// The configured `home_timezone`, shown next to deadline times.
+static HOME: RwLock<Option<Tz>> = RwLock::new(None);
+
+/// Deadlines and timestamps are stored as wall-clock times without a zone,
+/// so they are read and written in this zone. Pinning it keeps "today" and
+/// overdue status from shifting when a laptop's system zone changes.
+pub fn set_zone(zone: Option<Tz>) {
+    *ZONE.write().unwrap_or_else(|e| e.into_inner()) = zone;
+}
+
+pub fn set_home_zone(zone: Option<Tz>) {
+    *HOME.write().unwrap_or_else(|e| e.into_inner()) = zone;
+}
+
+/// Parses an IANA zone name such as `Europe/Berlin`.
+pub fn parse_zone(name: &str) -> Result<Tz, String> {
+    name.parse()
+        .map_err(|_| format!("unknown timezone '{}', expected e.g. Europe/Berlin", name))
+}
+
+/// The current wall-clock time in the configured zone.
+pub fn now() -> NaiveDateTime {
+    let zone = *ZONE.read().unwrap_or_else(|e| e.into_inner());
+    match zone {
+        Some(zone) => Utc::now().with_timezone(&zone).naive_local(),
+        None => chrono::Local::now().naive_local(),
+    }
+}
+
+/// `time` in `zone` as wall-clock time in the configured zone.
+pub fn from_zone<Z: TimeZone>(time: NaiveDateTime, zone: &Z) -> Option<NaiveDateTime> {
+    let utc = zone
+        .from_local_datetime(&time)
+        .earliest()?
+        .with_timezone(&Utc);
+    let configured = *ZONE.read().unwrap_or_else(|e| e.into_inner());
+    Some(match configured {
+        Some(configured) => utc.with_timezone(&configured).naive_local(),
+        None => utc.with_timezone(&chrono::Local).naive_local(),
+    })
+}
+
+pub fn today() -> NaiveDate {
+    now().date()
+}
+
+/// Wall-clock `time` in `zone` as a moment, and as shown, e.g. `17:00 CET`.
+fn moment<Z: TimeZone>(time: NaiveDateTime, zone: &Z) -> Option<(DateTime<Utc>, String)>
+where
+    Z::Offset: Display,
+{
+    let time = zone.from_local_datetime(&time).earliest()?;
+    let shown = time.format("%H:%M %Z").to_string();
+    Some((time.with_timezone(&Utc), shown))
+}
+
+/// A deadline as shown: its date, then for one with a time of day (not
+/// midnight) the time in the configured zone and, with `home_timezone`
+/// set, at home, e.g. `2024-06-28 17:00 CET / 11:00 EDT`.
+pub fn format_deadline(deadline: NaiveDateTime, date_format: &str) -> String {
+    let date = deadline.format(date_format).to_string();
+    if deadline.time() == NaiveTime::MIN {
+        return date;
+    }
+    let configured = *ZONE.read().unwrap_or_else(|e| e.into_inner());
+    let here = match configured {
+        Some(zone) => moment(deadline, &zone),
+        None => moment(deadline, &chrono::Local),
+    };
+    let Some((utc, here)) = here else {
+        return format!("{} {}", date, deadline.format("%H:%M"));
+    };
+    let home = *HOME.read().unwrap_or_else(|e| e.into_inner());
+    let Some(home) = home.map(|zone| utc.with_timezone(&zone)) else {
+        return format!("{} {}", date, here);
+    };
+    let mut at_home = home.format("%H:%M %Z").to_string();
+    if at_home == here {
+        return format!("{} {}", date, here);
+    }
+    if home.date_naive() != deadline.date() {
+        at_home = format!("{} {}", at_home, home.format(date_format));
+    }
+    format!("{} {} / {}", date, here, at_home)
+}
+
+/// Stored dates are wall-clock times, e.g. `2026-10-16 09:30:00`.
+pub fn serialize_date<S>(date: &Option<NaiveDateTime>, serializer: S) -> Result<S::Ok, S::Error>
+where
+    S: serde::Serializer,
+{
+    match date {
+        Some(d) => serializer.serialize_str(&d.format("%Y-%m-%d %H:%M:%S").to_string()),
+        None => serializer.serialize_none(),
+    }
+}
+
+pub fn deserialize_date<'de, D>(deserializer: D) -> Result<Option<NaiveDateTime>, D::Error>
+where
+    D: serde::Deserializer<'de>,
+{
+    let s: Option<String> = Option::deserialize(deserializer)?;
+    match s {
+        Some(str) => NaiveDateTime::parse_from_str(&str, "%Y-%m-%d %H:%M:%S")
+            .map(Some)
+            .map_err(serde::de::Error::custom),
+        None => Ok(None),
+    }
+}
+
+pub fn serialize_timestamp<S>(date: &NaiveDateTime, serializer: S) -> Result<S::Ok, S::Error>
+where
+    S: serde::Serializer,
+{
+    serializer.serialize_str(&date.format("%Y-%m-%d %H:%M:%S").to_string())
+}
+
+pub fn deserialize_timestamp<'de, D>(deserializer: D) -> Result<NaiveDateTime, D::Error>
+where
+    D: serde::Deserializer<'de>,
+{
+    let s = String::deserialize(deserializer)?;
+    NaiveDateTime::parse_from_str(&s, "%Y-%m-%d %H:%M:%S").map_err(serde::de::Error::custom)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn at(text: &str) -> NaiveDateTime {
+        NaiveDateTime::parse_from_str(text, "%Y-%m-%d %H:%M").unwrap()
+    }
+
+    /// One test, as the zones are process-wide.
+    #[test]
+    fn shows_deadlines_in_both_zones() {
+        set_zone(Some(parse_zone("Europe/Berlin").unwrap()));
+        set_home_zone(Some(parse_zone("America/New_York").unwrap()));
+        let shown = |text| format_deadline(at(text), "%Y-%m-%d");
+        assert_eq!(shown("2026-06-28 00:00"), "2026-06-28");
+        assert_eq!(
+            shown("2026-06-28 17:00"),
+            "2026-06-28 17:00 CEST / 11:00 EDT"
+        );
+        assert_eq!(
+            shown("2026-01-15 17:00"),
+            "2026-01-15 17:00 CET / 11:00 EST"
+        );
+        assert_eq!(
+            shown("2026-06-28 03:00"),
+            "2026-06-28 03:00 CEST / 21:00 EDT 2026-06-27"
+        );
+
+        let new_york: Tz = parse_zone("America/New_York").unwrap();
+        assert_eq!(
+            from_zone(at("2026-06-28 09:00"), &new_york),
+            Some(at("2026-06-28 15:00"))
+        );
+        // 02:30 doesn't happen the night clocks go forward.
+        let berlin: Tz = parse_zone("Europe/Berlin").unwrap();
+        assert_eq!(from_zone(at("2026-03-29 02:30"), &berlin), None);
+
+        set_home_zone(Some(berlin));
+        assert_eq!(shown("2026-06-28 17:00"), "2026-06-28 17:00 CEST");
+        set_zone(None);
+        set_home_zone(None);
+    }
+
+    #[test]
+    fn names_the_expected_zone_format() {
+        assert_eq!(
+            parse_zone("Berlin").unwrap_err(),
+            "unknown timezone 'Berlin', expected e.g. Europe/Berlin"
+        );
+    }
+}
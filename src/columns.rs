@@ -0,0 +1,212 @@
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use tui::{
+    layout::Constraint,
+    style::{Color, Style},
+    text::{Span, Spans},
+    widgets::Cell,
+};
+
+use crate::clock;
+use crate::estimate;
+use crate::links;
+use crate::privacy;
+use crate::tags;
+use crate::tasks::SortKey;
+use crate::{AppState, Task};
+
+/// A column of the task table.
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Debug)]
+#[serde(rename_all = "lowercase")]
+pub enum Column {
+    Description,
+    Deadline,
+    Tags,
+    Project,
+    Estimate,
+    /// Days since the task was created.
+    Age,
+    /// The workspace file a task comes from, blank for the main list.
+    File,
+    /// The urgency score; see `urgency`.
+    Urgency,
+}
+
+/// What a view shows when the config doesn't say.
+pub const DEFAULT: [Column; 2] = [Column::Description, Column::Deadline];
+
+impl Column {
+    pub fn title(self) -> &'static str {
+        match self {
+            Column::Description => "Task",
+            Column::Deadline => "Due",
+            Column::Tags => "Tags",
+            Column::Project => "Project",
+            Column::Estimate => "Est.",
+            Column::Age => "Age",
+            Column::File => "File",
+            Column::Urgency => "Urg.",
+        }
+    }
+
+    pub fn sort_key(self) -> SortKey {
+        match self {
+            Column::Description => SortKey::Description,
+            Column::Deadline => SortKey::Due,
+            Column::Tags => SortKey::Tags,
+            Column::Project => SortKey::Project,
+            Column::Estimate => SortKey::Estimate,
+            Column::Age => SortKey::Age,
+            Column::File => SortKey::File,
+            Column::Urgency => SortKey::Urgency,
+        }
+    }
+
+    /// `None` for the description, which takes what the others leave.
+    fn default_width(self, date_format: &str) -> Option<u16> {
+        match self {
+            Column::Description => None,
+            Column::Deadline => {
+                let shown = clock::today().format(date_format).to_string();
+                Some(shown.chars().count().max(3) as u16)
+            }
+            Column::Tags | Column::Project => Some(16),
+            Column::Estimate => Some(6),
+            Column::Age => Some(5),
+            Column::File => Some(10),
+            Column::Urgency => Some(5),
+        }
+    }
+
+    /// The cell for `task` in a row described by `row`.
+    pub fn cell<'a>(self, task: &'a Task, row: &RowInfo<'a>, date_format: &str) -> Cell<'a> {
+        let dim = Style::default().fg(Color::DarkGray);
+        let style = row.style;
+        let text = match self {
+            Column::Description => {
+                let mut spans = vec![Span::raw(row.lead.clone())];
+                if row.masked {
+                    spans.push(Span::styled(privacy::mask(&task.description), style));
+                } else if links::find(&task.description).next().is_some() {
+                    spans.extend(links::shorten(&task.description, style, dim));
+                } else {
+                    spans.push(Span::styled(task.description.as_str(), style));
+                }
+                if task.someday {
+                    spans.push(Span::styled("  someday", dim));
+                }
+                if let Some(status) = row.status {
+                    spans.push(Span::styled(format!("  [{}]", status), dim));
+                }
+                return Cell::from(Spans::from(spans));
+            }
+            Column::Deadline => task
+                .deadline
+                .map(|d| d.format(date_format).to_string())
+                .unwrap_or_default(),
+            Column::Tags => join(tags::tags(&task.description), "#"),
+            Column::Project => join(tags::projects(&task.description), "+"),
+            Column::Estimate => estimate::from_description(&task.description)
+                .map(estimate::format_minutes)
+                .unwrap_or_default(),
+            Column::Age => task
+                .created_at
+                .map(|at| format!("{}d", (clock::today() - at.date()).num_days()))
+                .unwrap_or_default(),
+            Column::File => (task.source.as_ref())
+                .map(|s| s.name.clone())
+                .unwrap_or_default(),
+            Column::Urgency if task.completed => String::new(),
+            Column::Urgency => format!("{:.1}", row.urgency),
+        };
+        Cell::from(text).style(dim)
+    }
+}
+
+/// What the cells of a task's row need besides the task itself.
+pub struct RowInfo<'a> {
+    /// Goes before the description: the indentation of a subtask and,
+    /// without colors, markers of its state.
+    pub lead: String,
+    pub style: Style,
+    /// The linked Jira issue's status, shown after the description.
+    pub status: Option<&'a str>,
+    /// Hides the description but for its tags.
+    pub masked: bool,
+    pub urgency: f64,
+}
+
+fn join<'a>(words: impl Iterator<Item = &'a str>, prefix: &str) -> String {
+    words
+        .map(|w| format!("{}{}", prefix, w))
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+/// The columns of one view, in order, with optional fixed widths.
+#[derive(Serialize, Deserialize, Clone)]
+#[serde(default)]
+pub struct ViewColumns {
+    pub show: Vec<Column>,
+    pub widths: BTreeMap<Column, u16>,
+}
+
+impl Default for ViewColumns {
+    fn default() -> ViewColumns {
+        ViewColumns {
+            show: DEFAULT.to_vec(),
+            widths: BTreeMap::new(),
+        }
+    }
+}
+
+impl ViewColumns {
+    pub fn validate(&self) -> Result<(), String> {
+        if !self.show.contains(&Column::Description) {
+            return Err("'show' must include description".to_string());
+        }
+        for (i, column) in self.show.iter().enumerate() {
+            if self.show[..i].contains(column) {
+                return Err(format!("{:?} is shown twice", column).to_lowercase());
+            }
+        }
+        if let Some((column, _)) = self.widths.iter().find(|(_, w)| **w == 0) {
+            return Err(format!("width of {:?} must be above 0", column).to_lowercase());
+        }
+        Ok(())
+    }
+
+    /// Column widths for a table `available` cells wide with `spacing`
+    /// between columns.
+    pub fn constraints(&self, date_format: &str, available: u16, spacing: u16) -> Vec<Constraint> {
+        let widths: Vec<Option<u16>> = self
+            .show
+            .iter()
+            .map(|column| {
+                (self.widths.get(column).copied()).or_else(|| column.default_width(date_format))
+            })
+            .collect();
+        let taken = widths.iter().flatten().sum::<u16>()
+            + spacing * (self.show.len() as u16).saturating_sub(1);
+        let rest = available.saturating_sub(taken).max(10);
+        widths
+            .into_iter()
+            .map(|width| Constraint::Length(width.unwrap_or(rest)))
+            .collect()
+    }
+}
+
+/// The columns for what the list shows now: those configured for the
+/// active saved filter, else for `list`, else the default.
+pub fn for_view(app_state: &AppState) -> ViewColumns {
+    let columns = &app_state.config.columns;
+    let saved = app_state
+        .filter
+        .as_ref()
+        .and_then(|(text, _)| text.strip_prefix("filter:"))
+        .and_then(|name| columns.get(name));
+    saved
+        .or_else(|| columns.get("list"))
+        .cloned()
+        .unwrap_or_default()
+}
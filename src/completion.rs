@@ -0,0 +1,134 @@
+use std::collections::BTreeSet;
+use tui::{
+    backend::Backend,
+    layout::Rect,
+    style::{Color, Style},
+    widgets::{Block, Borders, Clear, List, ListItem},
+    Frame,
+};
+
+use crate::tags;
+use crate::{AppState, Task};
+
+/// Most suggestions shown at once.
+const MAX_CANDIDATES: usize = 8;
+
+/// A Tab completion in progress: the token as typed before the first Tab,
+/// and which candidate is filled in now.
+pub struct Completion {
+    pub partial: String,
+    pub index: usize,
+}
+
+/// The `#tag`, `@context` or `+project` word being typed at the end of the
+/// input, with its byte offset.
+pub fn current_token(input: &str) -> Option<(usize, &str)> {
+    let start = input.rfind(char::is_whitespace).map_or(0, |i| i + 1);
+    let token = &input[start..];
+    token.starts_with(['#', '@', '+']).then_some((start, token))
+}
+
+/// Existing tokens with the same prefix character that start with `partial`.
+pub fn candidates(tasks: &[Task], partial: &str) -> Vec<String> {
+    let Some(prefix) = partial.chars().next() else {
+        return Vec::new();
+    };
+    let typed = partial[1..].to_lowercase();
+    let words: BTreeSet<&str> = tasks
+        .iter()
+        .flat_map(|t| -> Box<dyn Iterator<Item = &str>> {
+            match prefix {
+                '#' => Box::new(tags::tags(&t.description)),
+                '@' => Box::new(tags::contexts(&t.description)),
+                _ => Box::new(tags::projects(&t.description)),
+            }
+        })
+        .filter(|word| word.to_lowercase().starts_with(&typed))
+        .collect();
+    words
+        .into_iter()
+        .take(MAX_CANDIDATES)
+        .map(|word| format!("{}{}", prefix, word))
+        .collect()
+}
+
+/// Replaces the token at the end of `input` with the next (or previous)
+/// candidate. Returns false when there is nothing to complete.
+pub fn cycle(
+    input: &mut String,
+    completion: &mut Option<Completion>,
+    tasks: &[Task],
+    forward: bool,
+) -> bool {
+    let Some((start, token)) = current_token(input) else {
+        return false;
+    };
+    let state = completion.get_or_insert_with(|| Completion {
+        partial: token.to_string(),
+        // One step back from the first candidate, so the first Tab lands on it.
+        index: usize::MAX,
+    });
+    let options = candidates(tasks, &state.partial);
+    if options.is_empty() {
+        *completion = None;
+        return false;
+    }
+    state.index = match (state.index, forward) {
+        (usize::MAX, true) => 0,
+        (usize::MAX, false) => options.len() - 1,
+        (i, true) => (i + 1) % options.len(),
+        (i, false) => (i + options.len() - 1) % options.len(),
+    };
+    input.truncate(start);
+    input.push_str(&options[state.index]);
+    true
+}
+
+/// Suggestions under the input line, aligned with the token being typed.
+/// `text_x` is the screen column where the typed input begins.
+pub fn render_popup<B: Backend>(f: &mut Frame<B>, app_state: &AppState, text_x: u16, y: u16) {
+    let Some((start, token)) = current_token(&app_state.input) else {
+        return;
+    };
+    let partial = app_state
+        .completion
+        .as_ref()
+        .map_or(token, |c| c.partial.as_str());
+    let options = candidates(&app_state.tasks, partial);
+    // Nothing to offer once the only match is fully typed.
+    if options.is_empty() || (options.len() == 1 && options[0] == token) {
+        return;
+    }
+
+    let size = f.size();
+    let width = options.iter().map(|o| o.chars().count()).max().unwrap_or(0) as u16 + 2;
+    let height = options.len() as u16 + 2;
+    let column = app_state.input[..start].chars().count() as u16;
+    let x = (text_x + column).min(size.width.saturating_sub(width));
+    let area = Rect::new(x, y, width, height).intersection(size);
+
+    let selected = app_state.completion.as_ref().map(|c| c.index);
+    let items: Vec<ListItem> = options
+        .iter()
+        .enumerate()
+        .map(|(i, option)| {
+            let style = if Some(i) == selected {
+                Style::default()
+                    .fg(Color::Black)
+                    .bg(app_state.styles.highlight())
+            } else {
+                Style::default()
+            };
+            ListItem::new(option.as_str()).style(style)
+        })
+        .collect();
+    f.render_widget(Clear, area);
+    f.render_widget(
+        List::new(items).block(
+            Block::default()
+                .borders(Borders::ALL)
+                .border_style(Style::default().fg(Color::DarkGray)),
+        ),
+        area,
+    );
+}
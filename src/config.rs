@@ -0,0 +1,658 @@
+use chrono::format::{Item, StrftimeItems};
+use chrono_tz::Tz;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fs;
+use std::io;
+use std::path::PathBuf;
+use std::time::SystemTime;
+use tui::style::{Color, Modifier, Style};
+
+use crate::clock;
+use crate::columns::ViewColumns;
+use crate::estimate;
+use crate::export::Export;
+use crate::jira::JiraConfig;
+use crate::keymap::{parse_key, Action, Keymap};
+use crate::locale::Locale;
+use crate::notify::ChannelsConfig;
+use crate::query;
+use crate::rules::{RuleConfig, Rules};
+use crate::schedule::Scheduled;
+use crate::script;
+use crate::statusbar;
+use crate::store::Storage;
+use crate::templates;
+use crate::urgency::UrgencyConfig;
+use crate::workdays::Workdays;
+use crate::workspace;
+
+/// How each state of a task looks, as a style such as `red bold`,
+/// `yellow on blue underline` or `italic`; see `parse_style`.
+#[derive(Serialize, Deserialize, Clone)]
+#[serde(default)]
+pub struct Theme {
+    pub overdue: String,
+    /// Due within the next few days; empty leaves such tasks plain.
+    pub due_soon: String,
+    pub completed: String,
+    pub selected: String,
+    pub someday: String,
+    /// Open tasks with open subtasks, which can't be completed yet.
+    pub blocked: String,
+}
+
+impl Default for Theme {
+    fn default() -> Theme {
+        Theme {
+            overdue: "red".to_string(),
+            due_soon: String::new(),
+            completed: "lightgreen crossed_out".to_string(),
+            selected: "yellow".to_string(),
+            someday: "darkgray".to_string(),
+            blocked: "magenta".to_string(),
+        }
+    }
+}
+
+impl Theme {
+    /// Named starting points offered by the setup wizard.
+    pub fn presets() -> Vec<(&'static str, Theme)> {
+        let theme = |overdue: &str, due_soon: &str, completed: &str, selected: &str| Theme {
+            overdue: overdue.to_string(),
+            due_soon: due_soon.to_string(),
+            completed: completed.to_string(),
+            selected: selected.to_string(),
+            ..Theme::default()
+        };
+        vec![
+            ("Default", Theme::default()),
+            (
+                "Muted",
+                theme("lightred", "italic", "darkgray crossed_out", "lightblue"),
+            ),
+            (
+                "High contrast",
+                theme(
+                    "lightred bold",
+                    "lightyellow",
+                    "lightgreen crossed_out",
+                    "white bold",
+                ),
+            ),
+        ]
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum WeekStart {
+    Sunday,
+    Monday,
+}
+
+/// Work-in-progress limits for the kanban board.
+#[derive(Serialize, Deserialize, Clone, Default)]
+#[serde(default)]
+pub struct WipLimits {
+    /// Most tasks allowed In Progress at once.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub in_progress: Option<usize>,
+    /// Limits for in-progress tasks carrying a `#tag`.
+    pub tags: BTreeMap<String, usize>,
+    /// Limits for in-progress tasks in a `+project`.
+    pub projects: BTreeMap<String, usize>,
+    /// Refuse to start a task past a limit instead of only warning.
+    pub block: bool,
+}
+
+/// Where time blocks for tasks are created.
+#[derive(Serialize, Deserialize, Clone)]
+#[serde(default)]
+pub struct CalendarConfig {
+    /// Folder that events are written into as `.ics` files.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub drop_dir: Option<String>,
+    /// Import events with `khal import` instead of writing to `drop_dir`.
+    pub khal: bool,
+    /// Time of day (`HH:MM`) blocks start on the task's due day.
+    pub start: String,
+    /// An `.ics` file, e.g. one synced from your calendar, whose events
+    /// show on the day planner beside timed tasks.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub events_ics: Option<String>,
+    /// The hours the day planner shows, as `HH:MM`.
+    pub day_start: String,
+    pub day_end: String,
+}
+
+impl Default for CalendarConfig {
+    fn default() -> CalendarConfig {
+        CalendarConfig {
+            drop_dir: None,
+            khal: false,
+            start: "09:00".to_string(),
+            events_ics: None,
+            day_start: "08:00".to_string(),
+            day_end: "18:00".to_string(),
+        }
+    }
+}
+
+/// Keeps deadline presets and postponing off weekends and holidays.
+#[derive(Serialize, Deserialize, Clone, Default)]
+#[serde(default)]
+pub struct WorkdaysConfig {
+    pub enabled: bool,
+    /// Dates as `YYYY-MM-DD`.
+    pub holidays: Vec<String>,
+    /// An `.ics` calendar whose events are all holidays, e.g. a public
+    /// holiday feed saved to disk.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub holidays_ics: Option<String>,
+}
+
+/// Options for people who find fast or combined key presses hard.
+#[derive(Serialize, Deserialize, Clone)]
+#[serde(default)]
+pub struct AccessibilityConfig {
+    /// Deleting, completing and moving a task to Someday wait for `y`, so
+    /// a stray or repeated key can't do them.
+    pub confirm_actions: bool,
+    /// Presses of the same key closer together than this are dropped, so a
+    /// held or bouncing key acts once; 0 turns it off.
+    pub repeat_delay_ms: u64,
+    /// How long a follow-up such as undo stays possible.
+    pub undo_seconds: u64,
+    /// Accept Ctrl and Alt combinations in Normal mode. With this off,
+    /// actions bound to them are reached through the action menu.
+    pub chords: bool,
+    /// A key that holds Ctrl for the key after it, e.g. `\` then `r` for
+    /// Ctrl+r; pressed twice it holds Alt. Unset by default.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub sticky_key: Option<String>,
+    /// How long the key after `sticky_key` may take to come.
+    pub chord_timeout_ms: u64,
+}
+
+impl Default for AccessibilityConfig {
+    fn default() -> AccessibilityConfig {
+        AccessibilityConfig {
+            confirm_actions: false,
+            repeat_delay_ms: 0,
+            undo_seconds: 60,
+            chords: true,
+            sticky_key: None,
+            chord_timeout_ms: 2000,
+        }
+    }
+}
+
+/// Hiding the list when nobody is at the keyboard, for shared or
+/// unattended machines.
+#[derive(Serialize, Deserialize, Clone, Default)]
+#[serde(default)]
+pub struct LockConfig {
+    /// Minutes without a key press before the screen is blanked; never
+    /// when unset.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub after_minutes: Option<u32>,
+    /// Needed to unlock again, or `keyring:<name>` for one kept in the
+    /// keyring; any key unlocks when unset.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub passphrase: Option<String>,
+}
+
+#[derive(Serialize, Deserialize, Clone)]
+#[serde(default)]
+pub struct Config {
+    /// Directory holding the task file and journal; read once at startup.
+    pub data_dir: String,
+    /// `json` files in `data_dir`, a `sqlite` database there, or `memory`
+    /// to leave them untouched.
+    pub storage: Storage,
+    pub date_format: String,
+    /// The locale's first day of the week when not set.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub week_start: Option<WeekStart>,
+    /// Language of the deadline presets and of typed dates such as
+    /// `morgen`: `en` or `de`.
+    pub locale: Locale,
+    /// Name recorded with every change, for a list shared between people,
+    /// e.g. on a network drive or synced folder.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub author: Option<String>,
+    /// IANA zone (e.g. `Europe/Berlin`) that deadlines and "today" are
+    /// reckoned in; the system zone when unset.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub timezone: Option<String>,
+    /// A second zone deadlines with a time of day are shown in too, e.g.
+    /// `America/New_York` to keep home time in view while travelling.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub home_timezone: Option<String>,
+    /// Longest description, in characters, that the places the list is
+    /// synced to accept; longer ones are flagged while typing.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub description_limit: Option<usize>,
+    /// Choices offered when setting a deadline, picked with keys 1-9.
+    pub deadline_presets: Vec<String>,
+    pub autosave: bool,
+    pub notifications: bool,
+    /// Tasks overdue by more than this many days raise an urgent
+    /// notification once a day, whether or not `notifications` is on.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub escalate_after_days: Option<u32>,
+    /// Telegram and Matrix bots, and which reminders go where.
+    pub channels: ChannelsConfig,
+    /// Open tasks untouched for this many days move to Someday at startup.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub someday_after_days: Option<u32>,
+    /// Work that fits in one day, e.g. `8h`; days whose estimates add up to
+    /// more are flagged in the agenda.
+    pub daily_capacity: String,
+    /// Status bar text with `{placeholders}`, see `statusbar::PLACEHOLDERS`.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub status_format: Option<String>,
+    /// Enables mouse reporting; read once at startup.
+    pub mouse: bool,
+    /// For e-ink screens and slow links: the screen is redrawn only when
+    /// something changed, at most once a minute for the time passing, and
+    /// not until queued keys are handled.
+    pub low_refresh: bool,
+    /// Shows the keys the current mode takes at the right of the status bar.
+    pub key_hints: bool,
+    pub theme: Theme,
+    pub wip: WipLimits,
+    /// Weights of the urgency score that `sort urgency` orders by.
+    pub urgency: UrgencyConfig,
+    /// Tags and projects given to new tasks by what they say.
+    pub rules: Vec<RuleConfig>,
+    /// Other task files shown in the same list, by a short name, e.g.
+    /// `work = "~/work/tasks.json"`; changes go back to their own file.
+    pub workspace: BTreeMap<String, String>,
+    /// Saved search queries, usable as `filter:<name>`.
+    pub filters: BTreeMap<String, String>,
+    /// Task descriptions with `{{placeholders}}`, picked with keys 1-9.
+    pub templates: BTreeMap<String, String>,
+    /// Task table columns per view: `list`, or a saved filter's name for
+    /// when that filter is active.
+    pub columns: BTreeMap<String, ViewColumns>,
+    pub calendar: CalendarConfig,
+    /// Markdown files rewritten on every save, as `[[exports]]` tables.
+    pub exports: Vec<Export>,
+    pub workdays: WorkdaysConfig,
+    /// Tasks added on their own on a schedule, as `[[scheduled]]` tables.
+    pub scheduled: Vec<Scheduled>,
+    /// Jira site that `jira:KEY-123` links are synced with.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub jira: Option<JiraConfig>,
+    pub accessibility: AccessibilityConfig,
+    pub lock: LockConfig,
+    pub keybindings: BTreeMap<Action, String>,
+    /// Commands run at startup, e.g. `filter #work; sort due`; see `script`.
+    pub on_start: Vec<String>,
+}
+
+impl Default for Config {
+    fn default() -> Config {
+        Config {
+            data_dir: ".".to_string(),
+            storage: Storage::Json,
+            date_format: "%Y-%m-%d".to_string(),
+            week_start: None,
+            locale: Locale::En,
+            author: None,
+            timezone: None,
+            home_timezone: None,
+            description_limit: None,
+            deadline_presets: [
+                "Today",
+                "Tomorrow",
+                "This Week",
+                "Next Monday",
+                "Next Friday",
+            ]
+            .iter()
+            .map(|s| s.to_string())
+            .collect(),
+            autosave: false,
+            notifications: false,
+            escalate_after_days: None,
+            channels: ChannelsConfig::default(),
+            someday_after_days: None,
+            daily_capacity: "8h".to_string(),
+            status_format: None,
+            mouse: false,
+            low_refresh: false,
+            key_hints: true,
+            theme: Theme::default(),
+            wip: WipLimits::default(),
+            urgency: UrgencyConfig::default(),
+            rules: Vec::new(),
+            workspace: BTreeMap::new(),
+            filters: BTreeMap::new(),
+            templates: BTreeMap::new(),
+            columns: BTreeMap::new(),
+            calendar: CalendarConfig::default(),
+            exports: Vec::new(),
+            workdays: WorkdaysConfig::default(),
+            scheduled: Vec::new(),
+            jira: None,
+            accessibility: AccessibilityConfig::default(),
+            lock: LockConfig::default(),
+            keybindings: BTreeMap::new(),
+            on_start: Vec::new(),
+        }
+    }
+}
+
+/// A config that parsed and validated, ready to be swapped into the app.
+pub struct Loaded {
+    pub config: Config,
+    pub keymap: Keymap,
+    pub styles: Styles,
+    pub zone: Option<Tz>,
+    pub home_zone: Option<Tz>,
+    pub workdays: Workdays,
+    pub rules: Rules,
+}
+
+#[derive(Clone, Copy)]
+pub struct Styles {
+    pub overdue: Style,
+    pub due_soon: Style,
+    pub completed: Style,
+    pub selected: Style,
+    pub someday: Style,
+    pub blocked: Style,
+}
+
+impl Styles {
+    /// The selected style's color, for marking the selection with a
+    /// background instead.
+    pub fn highlight(&self) -> Color {
+        self.selected.fg.unwrap_or(Color::Yellow)
+    }
+}
+
+impl Config {
+    /// `$TODO_RS_CONFIG`, or `todo-rs/config.toml` in the platform config directory.
+    pub fn path() -> Option<PathBuf> {
+        if let Some(path) = std::env::var_os("TODO_RS_CONFIG") {
+            return Some(PathBuf::from(path));
+        }
+        dirs::config_dir().map(|dir| dir.join("todo-rs").join("config.toml"))
+    }
+
+    pub fn modified() -> Option<SystemTime> {
+        fs::metadata(Config::path()?)
+            .and_then(|m| m.modified())
+            .ok()
+    }
+
+    pub fn week_start(&self) -> WeekStart {
+        self.week_start.unwrap_or(self.locale.week_start())
+    }
+
+    /// True when neither a config file nor task data exists yet.
+    pub fn is_first_run() -> bool {
+        let no_config = Config::path().is_some_and(|p| !p.exists());
+        no_config
+            && !Config::default()
+                .data_path()
+                .join(crate::TASKS_FILE)
+                .exists()
+    }
+
+    /// The data directory with a leading `~` expanded.
+    pub fn data_path(&self) -> PathBuf {
+        expand_home(&self.data_dir)
+    }
+
+    /// Daily capacity in minutes; `validate` guarantees it parses.
+    pub fn capacity_minutes(&self) -> u32 {
+        estimate::parse_minutes(&self.daily_capacity).unwrap_or(8 * 60)
+    }
+
+    pub fn save(&self) -> Result<(), io::Error> {
+        let path = Config::path()
+            .ok_or_else(|| io::Error::new(io::ErrorKind::NotFound, "no config directory"))?;
+        if let Some(parent) = path.parent().filter(|p| !p.as_os_str().is_empty()) {
+            fs::create_dir_all(parent)?;
+        }
+        let text = toml::to_string_pretty(self).map_err(io::Error::other)?;
+        fs::write(path, text)
+    }
+
+    /// Reads and validates the config file; a missing file yields the defaults.
+    pub fn load() -> Result<Loaded, String> {
+        let config = match Config::path().map(fs::read_to_string) {
+            Some(Ok(text)) => toml::from_str(&text).map_err(|e| describe_toml_error(&text, e))?,
+            Some(Err(ref e)) if e.kind() != io::ErrorKind::NotFound => return Err(e.to_string()),
+            _ => Config::default(),
+        };
+        config.validate()
+    }
+
+    pub fn validate(self) -> Result<Loaded, String> {
+        let keymap = Keymap::from_config(&self.keybindings)?;
+        if let Some(spec) = &self.accessibility.sticky_key {
+            let key = parse_key(spec)
+                .ok_or_else(|| format!("accessibility.sticky_key: invalid key '{}'", spec))?;
+            if let Some(action) = keymap.action_for(key) {
+                return Err(format!(
+                    "accessibility.sticky_key: '{}' is already bound to {:?}",
+                    spec, action
+                ));
+            }
+        }
+        let style = |name: &str, text: &str| {
+            parse_style(text).map_err(|e| format!("theme.{}: {}", name, e))
+        };
+        let styles = Styles {
+            overdue: style("overdue", &self.theme.overdue)?,
+            due_soon: style("due_soon", &self.theme.due_soon)?,
+            completed: style("completed", &self.theme.completed)?,
+            selected: style("selected", &self.theme.selected)?,
+            someday: style("someday", &self.theme.someday)?,
+            blocked: style("blocked", &self.theme.blocked)?,
+        };
+        let zone = self
+            .timezone
+            .as_deref()
+            .map(clock::parse_zone)
+            .transpose()?;
+        let home_zone = (self.home_timezone.as_deref())
+            .map(clock::parse_zone)
+            .transpose()
+            .map_err(|e| format!("home_timezone: {}", e))?;
+        let workdays = Workdays::from_config(&self.workdays)?;
+        let rules = Rules::from_config(&self.rules)?;
+        let invalid_format = StrftimeItems::new(&self.date_format).any(|i| i == Item::Error);
+        if self.date_format.is_empty() || invalid_format {
+            return Err(format!("invalid date_format '{}'", self.date_format));
+        }
+        for (name, text) in &self.filters {
+            query::parse(text, &self.filters)
+                .map_err(|e| format!("saved filter '{}': {}", name, e))?;
+        }
+        if let Some(format) = &self.status_format {
+            statusbar::check(format)?;
+        }
+        for export in &self.exports {
+            export
+                .validate(&self.filters)
+                .map_err(|e| format!("export to {}: {}", export.path, e))?;
+        }
+        self.channels
+            .validate()
+            .map_err(|e| format!("channels: {}", e))?;
+        for name in self.workspace.keys() {
+            workspace::validate_name(name).map_err(|e| format!("workspace.{}: {}", name, e))?;
+        }
+        for scheduled in &self.scheduled {
+            scheduled
+                .validate()
+                .map_err(|e| format!("scheduled '{}': {}", scheduled.description, e))?;
+        }
+        if let Some(jira) = &self.jira {
+            jira.validate().map_err(|e| format!("jira: {}", e))?;
+        }
+        for (view, columns) in &self.columns {
+            if view != "list" && !self.filters.contains_key(view) {
+                return Err(format!(
+                    "columns.{}: expected 'list' or the name of a saved filter",
+                    view
+                ));
+            }
+            columns
+                .validate()
+                .map_err(|e| format!("columns.{}: {}", view, e))?;
+        }
+        for text in &self.on_start {
+            script::parse(text).map_err(|e| format!("on_start: {}", e))?;
+        }
+        if self.templates.len() > 9 {
+            return Err("templates can hold at most 9 entries".to_string());
+        }
+        for (name, text) in &self.templates {
+            templates::placeholders(text).map_err(|e| format!("template '{}': {}", name, e))?;
+        }
+        let time = |name: &str, text: &str| {
+            chrono::NaiveTime::parse_from_str(text, "%H:%M")
+                .map_err(|_| format!("invalid calendar.{} '{}', expected HH:MM", name, text))
+        };
+        time("start", &self.calendar.start)?;
+        let day_start = time("day_start", &self.calendar.day_start)?;
+        if time("day_end", &self.calendar.day_end)? <= day_start {
+            return Err("calendar.day_end must be after day_start".to_string());
+        }
+        if self.deadline_presets.len() > 9 {
+            return Err("deadline_presets can hold at most 9 entries".to_string());
+        }
+        if let Some(preset) = self.deadline_presets.iter().find(|p| {
+            crate::calculate_deadline(p, self.week_start(), self.locale, &workdays).is_none()
+        }) {
+            return Err(format!(
+                "unknown deadline preset '{}', expected today, tomorrow, this week or next <weekday>",
+                preset
+            ));
+        }
+        if estimate::parse_minutes(&self.daily_capacity).is_none_or(|m| m == 0) {
+            return Err(format!(
+                "invalid daily_capacity '{}', expected e.g. 8h or 7h30m",
+                self.daily_capacity
+            ));
+        }
+        Ok(Loaded {
+            config: self,
+            keymap,
+            styles,
+            zone,
+            home_zone,
+            workdays,
+            rules,
+        })
+    }
+}
+
+impl Default for Loaded {
+    fn default() -> Loaded {
+        Config::default()
+            .validate()
+            .expect("default config is valid")
+    }
+}
+
+/// The path with a leading `~/` (or `~\\` on Windows) expanded.
+pub fn expand_home(path: &str) -> PathBuf {
+    let home_relative = path.strip_prefix("~/").or_else(|| path.strip_prefix("~\\"));
+    match home_relative {
+        Some(rest) => dirs::home_dir().unwrap_or_default().join(rest),
+        None => PathBuf::from(path),
+    }
+}
+
+/// Condenses toml's multi-line report into one line that fits the status bar.
+fn describe_toml_error(text: &str, error: toml::de::Error) -> String {
+    match error.span() {
+        Some(span) => {
+            let line = text[..span.start].matches('\n').count() + 1;
+            format!("line {}: {}", line, error.message())
+        }
+        None => error.message().to_string(),
+    }
+}
+
+/// A color for the text, `on` and a color for the background, and any of
+/// `bold`, `dim`, `italic`, `underline`, `reversed` and `crossed_out`, in
+/// any order: `yellow on blue bold`. Empty is the terminal's own style.
+pub fn parse_style(s: &str) -> Result<Style, String> {
+    let mut style = Style::default();
+    let mut words = s.split_whitespace();
+    while let Some(word) = words.next() {
+        let modifier = match word.to_lowercase().as_str() {
+            "bold" => Modifier::BOLD,
+            "dim" => Modifier::DIM,
+            "italic" => Modifier::ITALIC,
+            "underline" | "underlined" => Modifier::UNDERLINED,
+            "reversed" => Modifier::REVERSED,
+            "crossed_out" | "strikethrough" => Modifier::CROSSED_OUT,
+            "on" => {
+                let color = words.next().ok_or("expected a color after 'on'")?;
+                style = style.bg(parse_color(color)?);
+                continue;
+            }
+            _ if style.fg.is_some() => {
+                return Err(format!(
+                    "'{}' after a color, expected a modifier or 'on'",
+                    word
+                ))
+            }
+            _ => {
+                style = style.fg(parse_color(word)?);
+                continue;
+            }
+        };
+        style = style.add_modifier(modifier);
+    }
+    Ok(style)
+}
+
+/// Accepts color names (`red`, `lightblue`, ...), `#rrggbb`, or a 0-255 palette index.
+pub fn parse_color(s: &str) -> Result<Color, String> {
+    let color = match s.to_lowercase().as_str() {
+        "reset" | "default" => Color::Reset,
+        "black" => Color::Black,
+        "red" => Color::Red,
+        "green" => Color::Green,
+        "yellow" => Color::Yellow,
+        "blue" => Color::Blue,
+        "magenta" => Color::Magenta,
+        "cyan" => Color::Cyan,
+        "gray" => Color::Gray,
+        "darkgray" => Color::DarkGray,
+        "lightred" => Color::LightRed,
+        "lightgreen" => Color::LightGreen,
+        "lightyellow" => Color::LightYellow,
+        "lightblue" => Color::LightBlue,
+        "lightmagenta" => Color::LightMagenta,
+        "lightcyan" => Color::LightCyan,
+        "white" => Color::White,
+        other => {
+            if let Some(hex) = other.strip_prefix('#') {
+                let rgb = u32::from_str_radix(hex, 16)
+                    .ok()
+                    .filter(|_| hex.len() == 6)
+                    .ok_or_else(|| format!("invalid color '{}'", s))?;
+                Color::Rgb((rgb >> 16) as u8, (rgb >> 8) as u8, rgb as u8)
+            } else {
+                let index = other
+                    .parse::<u8>()
+                    .map_err(|_| format!("invalid color '{}'", s))?;
+                Color::Indexed(index)
+            }
+        }
+    };
+    Ok(color)
+}
@@ -0,0 +1,213 @@
+use chrono::NaiveDateTime;
+use tui::{
+    backend::Backend,
+    layout::{Constraint, Rect},
+    style::{Color, Modifier, Style},
+    widgets::{Block, Borders, Cell, Row, Table},
+    Frame,
+};
+
+use crate::AppState;
+
+#[derive(Clone, Copy, PartialEq)]
+pub enum Field {
+    Description,
+    Deadline,
+}
+
+impl Field {
+    pub fn name(self) -> &'static str {
+        match self {
+            Field::Description => "description",
+            Field::Deadline => "deadline",
+        }
+    }
+}
+
+#[derive(Clone, PartialEq)]
+pub enum Value {
+    Text(String),
+    Date(Option<NaiveDateTime>),
+}
+
+impl Value {
+    fn show(&self, date_format: &str) -> String {
+        match self {
+            Value::Text(text) => text.clone(),
+            Value::Date(Some(date)) => date.format(date_format).to_string(),
+            Value::Date(None) => "none".to_string(),
+        }
+    }
+}
+
+pub struct FieldConflict {
+    pub field: Field,
+    pub local: Value,
+    pub remote: Value,
+    /// Whether the other instance's value wins.
+    pub take_remote: bool,
+}
+
+/// A task whose fields were changed both here and, concurrently, by another
+/// instance sharing the journal; nothing is applied until it's resolved.
+pub struct Conflict {
+    pub task: u64,
+    /// Who made the other changes, when they said.
+    pub author: Option<String>,
+    pub fields: Vec<FieldConflict>,
+    /// Row picked on the resolution screen.
+    pub selected: usize,
+}
+
+/// Queues a conflicting change, folding it into an earlier conflict on the
+/// same task; a later remote change to the same field replaces the earlier.
+pub fn add(
+    conflicts: &mut Vec<Conflict>,
+    task: u64,
+    author: Option<String>,
+    field: Field,
+    local: Value,
+    remote: Value,
+) {
+    let position = conflicts.iter().position(|c| c.task == task);
+    let conflict = match position {
+        Some(i) => &mut conflicts[i],
+        None => {
+            conflicts.push(Conflict {
+                task,
+                author: None,
+                fields: Vec::new(),
+                selected: 0,
+            });
+            conflicts.last_mut().unwrap()
+        }
+    };
+    if author.is_some() {
+        conflict.author = author;
+    }
+    match conflict.fields.iter_mut().find(|f| f.field == field) {
+        Some(existing) => existing.remote = remote,
+        None => conflict.fields.push(FieldConflict {
+            field,
+            local,
+            remote,
+            take_remote: false,
+        }),
+    }
+}
+
+/// Both versions side by side, with the one that will be kept highlighted.
+pub fn render_conflict<B: Backend>(f: &mut Frame<B>, app_state: &AppState, chunk: Rect) {
+    let Some(conflict) = app_state.conflicts.first() else {
+        return;
+    };
+    let date_format = &app_state.config.date_format;
+    let kept = app_state.styles.selected.add_modifier(Modifier::BOLD);
+    let dropped = Style::default().fg(Color::DarkGray);
+    let rows: Vec<Row> = conflict
+        .fields
+        .iter()
+        .enumerate()
+        .map(|(i, field)| {
+            let marker = if i == conflict.selected { ">" } else { " " };
+            let (local, remote) = if field.take_remote {
+                (dropped, kept)
+            } else {
+                (kept, dropped)
+            };
+            Row::new(vec![
+                Cell::from(format!("{} {}", marker, field.field.name())),
+                Cell::from(field.local.show(date_format)).style(local),
+                Cell::from(field.remote.show(date_format)).style(remote),
+            ])
+        })
+        .collect();
+
+    let more = match app_state.conflicts.len() - 1 {
+        0 => String::new(),
+        n => format!(", {} more after this", n),
+    };
+    let title = format!("Task {} was also changed elsewhere{}", conflict.task, more);
+    let other = match &conflict.author {
+        Some(author) => format!("Other instance ({})", author),
+        None => "Other instance".to_string(),
+    };
+    let table = Table::new(rows)
+        .header(
+            Row::new(vec!["", "This session", other.as_str()])
+                .style(Style::default().add_modifier(Modifier::BOLD))
+                .bottom_margin(1),
+        )
+        .widths(&[
+            Constraint::Length(14),
+            Constraint::Percentage(45),
+            Constraint::Percentage(45),
+        ])
+        .column_spacing(2)
+        .block(Block::default().borders(Borders::ALL).title(title));
+    f.render_widget(table, chunk);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn text(text: &str) -> Value {
+        Value::Text(text.to_string())
+    }
+
+    #[test]
+    fn folds_changes_to_one_task_together() {
+        let mut conflicts = Vec::new();
+        let author = Some("laptop".to_string());
+        add(
+            &mut conflicts,
+            4,
+            author,
+            Field::Description,
+            text("a"),
+            text("b"),
+        );
+        add(
+            &mut conflicts,
+            4,
+            None,
+            Field::Description,
+            text("a"),
+            text("c"),
+        );
+        add(
+            &mut conflicts,
+            4,
+            None,
+            Field::Deadline,
+            Value::Date(None),
+            Value::Date(None),
+        );
+        add(
+            &mut conflicts,
+            5,
+            None,
+            Field::Description,
+            text("x"),
+            text("y"),
+        );
+
+        assert_eq!(conflicts.len(), 2);
+        let first = &conflicts[0];
+        assert_eq!(first.author.as_deref(), Some("laptop"));
+        assert_eq!(first.fields.len(), 2);
+        assert!(first.fields[0].local == text("a"));
+        assert!(first.fields[0].remote == text("c"));
+        assert!(!first.fields[0].take_remote);
+    }
+
+    #[test]
+    fn shows_values_in_the_date_format() {
+        let date = chrono::NaiveDate::from_ymd_opt(2026, 10, 20)
+            .unwrap()
+            .and_hms_opt(9, 0, 0);
+        assert_eq!(Value::Date(date).show("%d.%m.%Y"), "20.10.2026");
+        assert_eq!(Value::Date(None).show("%d.%m.%Y"), "none");
+    }
+}
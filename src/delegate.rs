@@ -0,0 +1,92 @@
+use std::collections::BTreeMap;
+
+use crate::clock;
+use crate::eventlog;
+use crate::export;
+use crate::tags;
+use crate::Task;
+
+pub enum Format {
+    Markdown,
+    Csv,
+}
+
+impl Format {
+    pub fn extension(&self) -> &'static str {
+        match self {
+            Format::Markdown => "md",
+            Format::Csv => "csv",
+        }
+    }
+}
+
+/// A file name for `assignee`'s list: letters, digits, `-` and `_` kept,
+/// anything else as `_`, so a name can't reach outside the folder.
+pub fn file_stem(assignee: &str) -> String {
+    (assignee.chars())
+        .map(|c| match c {
+            c if c.is_alphanumeric() || matches!(c, '-' | '_') => c,
+            _ => '_',
+        })
+        .collect()
+}
+
+/// Open `tasks` grouped by `assignee:` word, names compared ignoring case
+/// and spelled as first seen. A task assigned to two people is on both
+/// lists.
+pub fn by_assignee<'a>(tasks: &[&'a Task]) -> BTreeMap<String, Vec<&'a Task>> {
+    let mut names: BTreeMap<String, String> = BTreeMap::new();
+    let mut lists: BTreeMap<String, Vec<&Task>> = BTreeMap::new();
+    for task in tasks.iter().filter(|t| !t.completed) {
+        for assignee in tags::assignees(&task.description) {
+            let name =
+                (names.entry(assignee.to_lowercase())).or_insert_with(|| assignee.to_string());
+            let list = lists.entry(name.clone()).or_default();
+            if !list.iter().any(|t| t.id == task.id) {
+                list.push(task);
+            }
+        }
+    }
+    lists
+}
+
+/// `assignee`'s items as a file to hand them, with descriptions as
+/// `describe` gives them, e.g. masked when private.
+pub fn render(
+    assignee: &str,
+    tasks: &[&Task],
+    format: &Format,
+    date_format: &str,
+    describe: impl Fn(&Task) -> String,
+) -> String {
+    match format {
+        Format::Markdown => {
+            let mut text = format!(
+                "# Action items for {}\n\n_Updated {}_\n\n",
+                assignee,
+                clock::now().format("%Y-%m-%d %H:%M")
+            );
+            for task in tasks {
+                let shown = Task {
+                    description: describe(task),
+                    ..(*task).clone()
+                };
+                text.push_str(&format!("{}\n", export::item(&shown, date_format)));
+            }
+            text
+        }
+        Format::Csv => {
+            let mut text = "id,description,due\n".to_string();
+            for task in tasks {
+                let due = task.deadline.map(|d| d.format(date_format).to_string());
+                text.push_str(&format!(
+                    "{},{},{}\n",
+                    task.id,
+                    eventlog::field(&describe(task)),
+                    eventlog::field(&due.unwrap_or_default())
+                ));
+            }
+            text
+        }
+    }
+}
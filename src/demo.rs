@@ -0,0 +1,122 @@
+use chrono::{Duration, NaiveDateTime};
+use std::collections::BTreeMap;
+use std::fs::{self, File};
+use std::io;
+use std::path::PathBuf;
+
+use crate::archive;
+use crate::clock;
+use crate::config::Config;
+use crate::store::Storage;
+use crate::{Task, TASKS_FILE};
+
+/// Sample open tasks and the days until they're due, showing off tags,
+/// contexts, projects, estimates and repeats.
+const TASKS: [(&str, Option<i64>); 10] = [
+    ("Welcome to todo-rs! Press Space for every action", Some(0)),
+    ("Renew passport #errand @town", Some(-3)),
+    ("Call the dentist #health @phone ~10m", Some(-1)),
+    ("Water the plants @home *3d", Some(0)),
+    ("Team standup #work *FREQ=WEEKLY;BYDAY=MO,WE,FR", Some(3)),
+    ("Pay rent #home *monthly", Some(12)),
+    ("Book flights for the conference #travel ~20m", Some(7)),
+    ("Read 'Thinking, Fast and Slow' #reading", None),
+    ("Reply to the landlord @phone", Some(1)),
+    ("Sort out the tax return #home ~2h", Some(30)),
+];
+
+/// A started task, and its subtasks.
+const PROJECT: (&str, i64) = ("Plan the release +website #work ~1h", 0);
+const SUBTASKS: [(&str, i64); 2] = [
+    ("Write the announcement +website #work ~45m", 1),
+    ("Update screenshots +website ~30m", 2),
+];
+
+const SOMEDAY: [&str; 2] = ["Learn to juggle", "Visit Iceland #travel"];
+
+/// Completed tasks and how many days ago, for the history views.
+const DONE: [(&str, i64); 2] = [
+    ("Set up the new laptop #work", 2),
+    ("Buy groceries @town", 1),
+];
+
+/// A throwaway data directory and config for `--demo`, filled with sample
+/// tasks that are then kept in memory. `$TODO_RS_CONFIG` points at it
+/// while the app runs, so nothing the user already has is read or
+/// written; it is removed when dropped.
+pub struct Sandbox {
+    dir: PathBuf,
+}
+
+impl Sandbox {
+    pub fn new() -> Result<Sandbox, io::Error> {
+        let dir = std::env::temp_dir().join(format!("todo-rs-demo-{}", std::process::id()));
+        fs::create_dir_all(&dir)?;
+        let sandbox = Sandbox { dir };
+        let config_path = sandbox.dir.join("config.toml");
+        std::env::set_var("TODO_RS_CONFIG", &config_path);
+
+        let config = Config {
+            data_dir: sandbox.dir.to_string_lossy().into_owned(),
+            storage: Storage::Memory,
+            filters: BTreeMap::from([
+                ("work".to_string(), "#work".to_string()),
+                ("errands".to_string(), "@town".to_string()),
+            ]),
+            templates: BTreeMap::from([(
+                "meeting".to_string(),
+                "Meet {{who}} about {{topic}} #work ~30m".to_string(),
+            )]),
+            ..Config::default()
+        };
+        config.save()?;
+
+        let tasks = sample_tasks();
+        let open: Vec<&Task> = tasks.iter().filter(|t| !t.completed).collect();
+        serde_json::to_writer(File::create(sandbox.dir.join(TASKS_FILE))?, &open)?;
+        archive::update(&sandbox.dir, &tasks)?;
+        Ok(sandbox)
+    }
+}
+
+impl Drop for Sandbox {
+    fn drop(&mut self) {
+        let _ = fs::remove_dir_all(&self.dir);
+    }
+}
+
+/// Adds a task created a week ago, numbering it after the others.
+fn add<'a>(
+    tasks: &'a mut Vec<Task>,
+    description: &str,
+    deadline: Option<NaiveDateTime>,
+) -> &'a mut Task {
+    let mut task = Task::new(tasks.len() as u64 + 1, description.to_string(), deadline);
+    task.created_at = Some(clock::now() - Duration::days(7));
+    tasks.push(task);
+    tasks.last_mut().unwrap()
+}
+
+fn sample_tasks() -> Vec<Task> {
+    let today = clock::today().and_hms_opt(0, 0, 0).unwrap();
+    let day = |days: i64| today + Duration::days(days);
+    let mut tasks = Vec::new();
+    for (description, due) in TASKS {
+        add(&mut tasks, description, due.map(day));
+    }
+    let project = add(&mut tasks, PROJECT.0, Some(day(PROJECT.1)));
+    project.in_progress = true;
+    let parent = project.id;
+    for (description, due) in SUBTASKS {
+        add(&mut tasks, description, Some(day(due))).parent = Some(parent);
+    }
+    for description in SOMEDAY {
+        add(&mut tasks, description, None).someday = true;
+    }
+    for (description, ago) in DONE {
+        let task = add(&mut tasks, description, Some(day(-ago)));
+        task.completed = true;
+        task.completed_at = task.deadline;
+    }
+    tasks
+}
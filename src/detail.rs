@@ -0,0 +1,127 @@
+use std::path::Path;
+use tui::{
+    backend::Backend,
+    layout::Rect,
+    style::{Color, Modifier, Style},
+    text::{Span, Spans},
+    widgets::{Block, Borders, Paragraph, Wrap},
+    Frame,
+};
+
+use crate::clock;
+use crate::estimate;
+use crate::jira;
+use crate::milestones;
+use crate::recurrence::{self, Recurrence};
+use crate::AppState;
+
+fn field<'a>(label: &'a str, value: String) -> Spans<'a> {
+    Spans::from(vec![
+        Span::styled(
+            format!("{:<10}", label),
+            Style::default().fg(Color::DarkGray),
+        ),
+        Span::raw(value),
+    ])
+}
+
+/// Shows every field of the selected task next to the list.
+pub fn render_detail<B: Backend>(f: &mut Frame<B>, app_state: &AppState, chunk: Rect) {
+    let block = Block::default().borders(Borders::ALL).title("Details");
+    let Some(task) = app_state.selected_task.and_then(|i| app_state.tasks.get(i)) else {
+        f.render_widget(Paragraph::new("No task selected").block(block), chunk);
+        return;
+    };
+
+    let date_format = app_state.config.date_format.as_str();
+    let by = |at: Option<chrono::NaiveDateTime>, author: &Option<String>| {
+        let date = at.map_or("-".to_string(), |d| d.format(date_format).to_string());
+        match author {
+            Some(author) => format!("{} by {}", date, author),
+            None => date,
+        }
+    };
+    let mut lines = vec![
+        Spans::from(Span::styled(
+            app_state.description(task),
+            Style::default().add_modifier(Modifier::BOLD),
+        )),
+        Spans::from(""),
+        field(
+            "Id",
+            match &task.source {
+                Some(source) => format!("{} ({} in {})", task.id, source.id, source.name),
+                None => task.id.to_string(),
+            },
+        ),
+        field(
+            "Status",
+            if task.completed {
+                "done"
+            } else if task.in_progress {
+                "in progress"
+            } else if task.someday {
+                "someday"
+            } else {
+                "open"
+            }
+            .to_string(),
+        ),
+        field(
+            "Due",
+            match task.deadline {
+                Some(d) if task.inherits_deadline => {
+                    format!("{} (from parent)", clock::format_deadline(d, date_format))
+                }
+                Some(d) => clock::format_deadline(d, date_format),
+                None => "-".to_string(),
+            },
+        ),
+        field(
+            "Estimate",
+            estimate::from_description(&task.description)
+                .map_or("-".to_string(), estimate::format_minutes),
+        ),
+        field(
+            "Repeats",
+            match Recurrence::from_description(&task.description) {
+                Some(recurrence) => recurrence.describe(),
+                None => recurrence::rule_error(&task.description)
+                    .map_or("-".to_string(), |e| format!("invalid rule: {}", e)),
+            },
+        ),
+        field("Command", task.command.clone().unwrap_or("-".to_string())),
+        field("Created", by(task.created_at, &task.created_by)),
+    ];
+    if !app_state.masked(task) {
+        for (i, milestone) in milestones::milestones(&task.description).enumerate() {
+            let label = if i == 0 { "Milestone" } else { "" };
+            let date = milestone.date.format(date_format);
+            lines.push(field(label, format!("{} by {}", milestone.name, date)));
+        }
+    }
+    for (i, path) in task.attachments.iter().enumerate() {
+        let label = if i == 0 { "Files" } else { "" };
+        let missing = if Path::new(path).exists() {
+            ""
+        } else {
+            " (missing)"
+        };
+        lines.push(field(label, format!("{}{}", path, missing)));
+    }
+    if let Some(key) = jira::key(&task.description) {
+        let issue = match app_state.jira.get(key) {
+            Some(issue) => format!("{} ({}): {}", key, issue.status, issue.summary),
+            None => format!("{} (not synced yet)", key),
+        };
+        lines.push(field("Jira", issue));
+    }
+    if task.completed {
+        lines.push(field("Done", by(task.completed_at, &task.completed_by)));
+    }
+
+    let paragraph = Paragraph::new(lines)
+        .wrap(Wrap { trim: false })
+        .block(block);
+    f.render_widget(paragraph, chunk);
+}
@@ -0,0 +1,427 @@
+use std::collections::HashSet;
+use std::env;
+use std::fs;
+use std::io::{self, IsTerminal};
+use std::path::Path;
+use std::process::{Command, Stdio};
+
+use crate::archive;
+use crate::config::{self, Config};
+use crate::journal;
+use crate::palette::{self, Support};
+use crate::secrets;
+use crate::store::{SqliteStore, Storage, TaskStore};
+use crate::{Task, DATABASE_FILE, JOURNAL_FILE, TASKS_FILE};
+
+#[derive(PartialEq)]
+enum Level {
+    Ok,
+    Warn,
+    Error,
+}
+
+/// One line of the report: what was checked, what was found, and for
+/// problems, what to do about it.
+struct Finding {
+    level: Level,
+    topic: &'static str,
+    message: String,
+    hint: Option<String>,
+}
+
+#[derive(Default)]
+struct Report {
+    findings: Vec<Finding>,
+}
+
+impl Report {
+    fn ok(&mut self, topic: &'static str, message: String) {
+        self.push(Level::Ok, topic, message, None);
+    }
+
+    fn warn(&mut self, topic: &'static str, message: String, hint: &str) {
+        self.push(Level::Warn, topic, message, Some(hint.to_string()));
+    }
+
+    fn error(&mut self, topic: &'static str, message: String, hint: &str) {
+        self.push(Level::Error, topic, message, Some(hint.to_string()));
+    }
+
+    fn push(&mut self, level: Level, topic: &'static str, message: String, hint: Option<String>) {
+        self.findings.push(Finding {
+            level,
+            topic,
+            message,
+            hint,
+        });
+    }
+}
+
+fn check_terminal(report: &mut Report) {
+    let term = env::var("TERM").unwrap_or_default();
+    if !io::stdout().is_terminal() {
+        report.warn(
+            "terminal",
+            "stdout is not a terminal".to_string(),
+            "run doctor directly in the terminal you use todo-rs in",
+        );
+    } else if term == "dumb" {
+        report.warn(
+            "terminal",
+            "TERM is 'dumb'".to_string(),
+            "set TERM to your terminal's type, e.g. xterm-256color",
+        );
+    } else {
+        report.ok("terminal", format!("interactive (TERM={})", term));
+    }
+
+    match palette::detect() {
+        Support::None => report.warn(
+            "colors",
+            "NO_COLOR is set or TERM is 'dumb'".to_string(),
+            "states are marked with symbols and text attributes instead of colors",
+        ),
+        Support::Basic => report.warn(
+            "colors",
+            "only basic colors advertised".to_string(),
+            "the theme is shown in 8 colors; use a TERM ending in -256color for all of them",
+        ),
+        Support::Indexed => report.ok("colors", "256 colors".to_string()),
+        Support::TrueColor => report.ok("colors", "24-bit color".to_string()),
+    }
+
+    if cfg!(windows) {
+        report.ok("unicode", "Windows console".to_string());
+        return;
+    }
+    let locale = ["LC_ALL", "LC_CTYPE", "LANG"]
+        .iter()
+        .filter_map(|name| env::var(name).ok())
+        .find(|value| !value.is_empty())
+        .unwrap_or_default();
+    let lower = locale.to_lowercase();
+    if lower.contains("utf-8") || lower.contains("utf8") {
+        report.ok("unicode", format!("UTF-8 locale ({})", locale));
+    } else if locale.is_empty() {
+        report.warn(
+            "unicode",
+            "no locale set".to_string(),
+            "set LANG to a UTF-8 locale such as en_US.UTF-8 so symbols and borders render",
+        );
+    } else {
+        report.warn(
+            "unicode",
+            format!("locale '{}' is not UTF-8", locale),
+            "set LANG to a UTF-8 locale such as en_US.UTF-8 so symbols and borders render",
+        );
+    }
+}
+
+/// Returns the config to check the data against, falling back to defaults.
+fn check_config(report: &mut Report) -> Config {
+    let Some(path) = Config::path() else {
+        report.error(
+            "config",
+            "no config directory on this system".to_string(),
+            "set TODO_RS_CONFIG to a file path",
+        );
+        return Config::default();
+    };
+    if !path.exists() {
+        report.ok(
+            "config",
+            format!("{} not created yet, using defaults", path.display()),
+        );
+        return Config::default();
+    }
+    match Config::load() {
+        Ok(loaded) => {
+            report.ok("config", format!("{} is valid", path.display()));
+            loaded.config
+        }
+        Err(e) => {
+            report.error(
+                "config",
+                format!("{}: {}", path.display(), e),
+                "fix the setting named above; the UI falls back to defaults meanwhile",
+            );
+            Config::default()
+        }
+    }
+}
+
+fn check_timezone(report: &mut Report, config: &Config) {
+    let system = chrono::Local::now().format("%:z").to_string();
+    match &config.timezone {
+        Some(zone) => report.ok(
+            "timezone",
+            format!("deadlines in {} (system offset {})", zone, system),
+        ),
+        None => report.ok(
+            "timezone",
+            format!("following the system zone (offset {})", system),
+        ),
+    }
+    if let Some(home) = &config.home_timezone {
+        report.ok("timezone", format!("deadline times also shown in {}", home));
+    }
+}
+
+fn check_data_dir(report: &mut Report, dir: &Path) -> bool {
+    if !dir.exists() {
+        report.ok(
+            "data",
+            format!("{} will be created on first use", dir.display()),
+        );
+        return false;
+    }
+    let probe = dir.join(".todo-rs-doctor");
+    match fs::write(&probe, b"") {
+        Ok(()) => {
+            let _ = fs::remove_file(&probe);
+            report.ok("data", format!("{} is writable", dir.display()));
+        }
+        Err(e) => report.error(
+            "data",
+            format!("cannot write to {}: {}", dir.display(), e),
+            "check the directory's permissions, or point data_dir elsewhere",
+        ),
+    }
+    true
+}
+
+fn check_tasks(report: &mut Report, dir: &Path) {
+    let path = dir.join(TASKS_FILE);
+    let text = match fs::read_to_string(&path) {
+        Ok(text) => text,
+        Err(ref e) if e.kind() == io::ErrorKind::NotFound => {
+            report.ok("tasks", format!("{} not created yet", TASKS_FILE));
+            return;
+        }
+        Err(e) => {
+            report.error(
+                "tasks",
+                format!("cannot read {}: {}", path.display(), e),
+                "check permissions",
+            );
+            return;
+        }
+    };
+    let tasks: Vec<Task> = match serde_json::from_str(&text) {
+        Ok(tasks) => tasks,
+        Err(e) => {
+            report.error(
+                "tasks",
+                format!("{} is corrupt: {}", path.display(), e),
+                "restore it from a backup; the UI won't load it",
+            );
+            return;
+        }
+    };
+    let mut seen = HashSet::new();
+    let duplicates = tasks
+        .iter()
+        .filter(|t| t.id != 0 && !seen.insert(t.id))
+        .count();
+    if duplicates > 0 {
+        report.warn(
+            "tasks",
+            format!("{} tasks share an id with another task", duplicates),
+            "edits to one of them may land on the other; renumber them in the file",
+        );
+    } else {
+        report.ok("tasks", format!("{} tasks", tasks.len()));
+    }
+}
+
+fn check_archive(report: &mut Report, dir: &Path) {
+    match archive::load(dir) {
+        Ok(archived) => report.ok("archive", format!("{} completed tasks", archived.len())),
+        Err(e) => report.error(
+            "archive",
+            format!("{} is corrupt: {}", archive::ARCHIVE_FILE, e),
+            "the heatmap will be missing older completions until it is restored",
+        ),
+    }
+}
+
+/// The open tasks and journal when `storage = "sqlite"` keeps them.
+fn check_database(report: &mut Report, dir: &Path) {
+    let path = dir.join(DATABASE_FILE);
+    if !path.exists() {
+        report.ok("database", format!("{} not created yet", DATABASE_FILE));
+        return;
+    }
+    let store = SqliteStore::open(&path);
+    let counts = store.and_then(|store| {
+        let tasks = store.load()?.unwrap_or_default();
+        Ok((tasks.len(), store.events()?.len()))
+    });
+    match counts {
+        Ok((tasks, events)) => report.ok("database", format!("{} tasks, {} events", tasks, events)),
+        Err(e) => report.error(
+            "database",
+            format!("cannot read {}: {}", path.display(), e),
+            "restore it from a backup; the UI won't load it",
+        ),
+    }
+}
+
+fn check_journal(report: &mut Report, dir: &Path) {
+    let Ok(text) = fs::read_to_string(dir.join(JOURNAL_FILE)) else {
+        report.ok("journal", "no journal yet".to_string());
+        return;
+    };
+    let lines: Vec<&str> = (text.lines())
+        .filter(|l| !l.trim().is_empty() && !journal::is_header(l))
+        .collect();
+    let bad = lines
+        .iter()
+        .filter(|line| serde_json::from_str::<serde_json::Value>(line).is_err())
+        .count();
+    if bad > 0 {
+        report.warn(
+            "journal",
+            format!("{} of {} lines are unreadable", bad, lines.len()),
+            "usually a write cut off by a crash; those events are skipped",
+        );
+    } else {
+        report.ok("journal", format!("{} events", lines.len()));
+    }
+}
+
+/// Other processes running this same executable; they all share the data
+/// files, and only the journal keeps them from overwriting each other.
+#[cfg(target_os = "linux")]
+fn check_instances(report: &mut Report) {
+    let Ok(exe) = env::current_exe() else {
+        return;
+    };
+    let me = std::process::id().to_string();
+    let others = fs::read_dir("/proc")
+        .into_iter()
+        .flatten()
+        .flatten()
+        // Skips `self` and `thread-self` along with our own pid.
+        .filter(|entry| {
+            let name = entry.file_name();
+            let name = name.to_string_lossy();
+            name != me && name.bytes().all(|b| b.is_ascii_digit())
+        })
+        .filter(|entry| fs::read_link(entry.path().join("exe")).is_ok_and(|p| p == exe))
+        .count();
+    if others > 0 {
+        report.warn(
+            "instances",
+            format!("{} other todo-rs processes running", others),
+            "changes sync through the journal, but quit extra instances if tasks go missing",
+        );
+    } else {
+        report.ok("instances", "no other todo-rs running".to_string());
+    }
+}
+
+#[cfg(not(target_os = "linux"))]
+fn check_instances(_report: &mut Report) {}
+
+fn command_works(program: &str) -> bool {
+    Command::new(program)
+        .arg("--version")
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .status()
+        .is_ok_and(|status| status.success())
+}
+
+fn check_integrations(report: &mut Report, config: &Config) {
+    if config.calendar.khal {
+        if command_works("khal") {
+            report.ok("calendar", "khal is available".to_string());
+        } else {
+            report.error(
+                "calendar",
+                "calendar.khal is set but khal doesn't run".to_string(),
+                "install khal or turn calendar.khal off",
+            );
+        }
+    } else if let Some(dir) = &config.calendar.drop_dir {
+        let dir = config::expand_home(dir);
+        if dir.is_dir() {
+            report.ok("calendar", format!("drop folder {}", dir.display()));
+        } else {
+            report.warn(
+                "calendar",
+                format!("drop folder {} doesn't exist yet", dir.display()),
+                "it is created when time is first blocked; check the path is right",
+            );
+        }
+    }
+
+    if secrets::available() {
+        report.ok("keyring", "the system keyring is available".to_string());
+    } else {
+        report.ok(
+            "keyring",
+            "no system keyring; keyring: secrets are asked for when needed".to_string(),
+        );
+    }
+
+    if command_works("curl") {
+        report.ok("updates", "curl is available for self-update".to_string());
+    } else {
+        report.warn(
+            "updates",
+            "curl not found".to_string(),
+            "self-update needs curl; everything else works without it",
+        );
+    }
+}
+
+/// Prints every finding and fails if any of them is an error, so the output
+/// can be pasted into a bug report as is.
+pub fn run() -> Result<(), io::Error> {
+    let mut report = Report::default();
+    check_terminal(&mut report);
+    let config = check_config(&mut report);
+    check_timezone(&mut report, &config);
+    let dir = config.data_path();
+    if check_data_dir(&mut report, &dir) {
+        if config.storage == Storage::Sqlite {
+            check_database(&mut report, &dir);
+        } else {
+            check_tasks(&mut report, &dir);
+            check_journal(&mut report, &dir);
+        }
+        check_archive(&mut report, &dir);
+    }
+    check_instances(&mut report);
+    check_integrations(&mut report, &config);
+
+    println!(
+        "todo-rs {} on {}-{}",
+        env!("CARGO_PKG_VERSION"),
+        env::consts::OS,
+        env::consts::ARCH
+    );
+    for finding in &report.findings {
+        let label = match finding.level {
+            Level::Ok => "ok",
+            Level::Warn => "warn",
+            Level::Error => "error",
+        };
+        println!("{:<6}{:<10}{}", label, finding.topic, finding.message);
+        if let Some(hint) = &finding.hint {
+            println!("{:<16}-> {}", "", hint);
+        }
+    }
+
+    let errors = report
+        .findings
+        .iter()
+        .filter(|f| f.level == Level::Error)
+        .count();
+    if errors > 0 {
+        return Err(io::Error::other(format!("{} problems found", errors)));
+    }
+    Ok(())
+}
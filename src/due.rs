@@ -0,0 +1,111 @@
+use chrono::{Months, NaiveDate, NaiveDateTime, NaiveTime};
+
+use crate::clock;
+use crate::config::Config;
+use crate::workdays::Workdays;
+
+/// Deadlines further back than this are more likely a typo than a record
+/// of something already late.
+const PAST_DAYS: i64 = 30;
+/// Deadlines further ahead than this ask for confirmation too.
+const FUTURE_YEARS: u32 = 5;
+
+/// Reads a typed deadline: a preset, `YYYY-MM-DD`, or a date in the
+/// configured `date_format`, any of them followed by a time such as
+/// `17:00`.
+pub fn parse(text: &str, config: &Config, workdays: &Workdays) -> Result<NaiveDateTime, String> {
+    let text = text.trim();
+    if text.is_empty() {
+        return Err("type a date, or Esc for the presets".to_string());
+    }
+    let time = (text.rsplit_once(' '))
+        .and_then(|(date, time)| Some((date, NaiveTime::parse_from_str(time, "%H:%M").ok()?)));
+    if let Some((date, time)) = time {
+        return parse(date, config, workdays).map(|deadline| deadline.date().and_time(time));
+    }
+    if let Some(deadline) =
+        crate::calculate_deadline(text, config.week_start(), config.locale, workdays)
+    {
+        return Ok(deadline);
+    }
+    let date = NaiveDate::parse_from_str(text, "%Y-%m-%d")
+        .or_else(|_| NaiveDate::parse_from_str(text, &config.date_format))
+        .map_err(|_| {
+            if text.chars().any(|c| c.is_ascii_digit()) {
+                format!("'{}' is not a valid date", text)
+            } else {
+                format!(
+                    "'{}' is not a date; use YYYY-MM-DD, today, tomorrow, this week or next <weekday>",
+                    text
+                )
+            }
+        })?;
+    Ok(date.and_hms_opt(0, 0, 0).unwrap())
+}
+
+/// Why the deadline looks like a mistake, if it does.
+pub fn warning(deadline: NaiveDateTime, date_format: &str) -> Option<String> {
+    let today = clock::today();
+    let date = deadline.date();
+    let shown = date.format(date_format);
+    let days_ago = (today - date).num_days();
+    if days_ago > PAST_DAYS {
+        return Some(format!("{} was {} days ago", shown, days_ago));
+    }
+    let limit = today.checked_add_months(Months::new(FUTURE_YEARS * 12))?;
+    (date > limit).then(|| format!("{} is more than {} years away", shown, FUTURE_YEARS))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Duration;
+
+    fn due(text: &str) -> Result<String, String> {
+        let config = Config {
+            date_format: "%d.%m.%Y".to_string(),
+            ..Config::default()
+        };
+        let deadline = parse(text, &config, &Workdays::default())?;
+        Ok(deadline.format("%Y-%m-%d %H:%M").to_string())
+    }
+
+    #[test]
+    fn reads_dates_in_either_format_with_a_time() {
+        assert_eq!(due("2026-10-20").unwrap(), "2026-10-20 00:00");
+        assert_eq!(due("20.10.2026 17:30").unwrap(), "2026-10-20 17:30");
+        let tomorrow = clock::today() + Duration::days(1);
+        assert_eq!(
+            due("tomorrow 09:00").unwrap(),
+            format!("{} 09:00", tomorrow.format("%Y-%m-%d"))
+        );
+    }
+
+    #[test]
+    fn says_why_a_date_was_not_understood() {
+        assert_eq!(
+            due("  ").unwrap_err(),
+            "type a date, or Esc for the presets"
+        );
+        assert_eq!(
+            due("2026-02-30").unwrap_err(),
+            "'2026-02-30' is not a valid date"
+        );
+        assert!(due("someday soon").unwrap_err().contains("next <weekday>"));
+    }
+
+    #[test]
+    fn warns_about_far_off_deadlines() {
+        let at = |days: i64| (clock::today() + Duration::days(days)).and_hms_opt(9, 0, 0);
+        let warn = |days| warning(at(days).unwrap(), "%Y-%m-%d");
+        assert_eq!(warn(-31).unwrap(), {
+            let date = clock::today() - Duration::days(31);
+            format!("{} was 31 days ago", date.format("%Y-%m-%d"))
+        });
+        assert!(warn(-30).is_none());
+        assert!(warn(365).is_none());
+        assert!(warn(6 * 366)
+            .unwrap()
+            .ends_with("is more than 5 years away"));
+    }
+}
@@ -0,0 +1,175 @@
+use tui::{
+    backend::Backend,
+    layout::Rect,
+    style::{Color, Modifier, Style},
+    text::{Span, Spans},
+    widgets::{Block, Borders, Clear, Paragraph},
+    Frame,
+};
+
+use unicode_width::UnicodeWidthStr;
+
+use crate::AppState;
+
+/// Glyphs offered by the picker, with the words they can be found by.
+/// Only single code points: variation selectors confuse terminal widths.
+const SYMBOLS: &[(&str, &str)] = &[
+    ("⭐", "star favourite important"),
+    ("🔥", "fire urgent hot"),
+    ("❗", "exclamation important"),
+    ("❓", "question unclear"),
+    ("✅", "check done yes"),
+    ("❌", "cross no cancel"),
+    ("🚨", "siren warning alert"),
+    ("🚧", "construction wip blocked"),
+    ("🐛", "bug fix"),
+    ("✨", "sparkles feature new"),
+    ("🔧", "wrench fix tool"),
+    ("🚀", "rocket release launch"),
+    ("📌", "pin pinned"),
+    ("📎", "paperclip attachment"),
+    ("📅", "calendar date"),
+    ("⏰", "alarm clock reminder"),
+    ("⏳", "hourglass waiting"),
+    ("📞", "phone call"),
+    ("📧", "email mail"),
+    ("💬", "speech chat message"),
+    ("📝", "memo note write"),
+    ("📖", "book read"),
+    ("📚", "books study"),
+    ("🎓", "graduation learn"),
+    ("💡", "bulb idea"),
+    ("🧠", "brain think"),
+    ("🔍", "magnifier search research"),
+    ("🔒", "lock security"),
+    ("🔑", "key password"),
+    ("💰", "money bag finance"),
+    ("💳", "card payment bill"),
+    ("🛒", "cart shopping buy"),
+    ("🎁", "gift present"),
+    ("🎉", "party celebrate"),
+    ("🏠", "house home"),
+    ("🏢", "office work"),
+    ("🚗", "car drive"),
+    ("🛫", "plane travel flight"),
+    ("🍴", "fork knife food dinner"),
+    ("☕", "coffee break"),
+    ("🏃", "runner exercise sport"),
+    ("💪", "muscle workout gym"),
+    ("🩺", "stethoscope doctor health"),
+    ("💊", "pill medicine"),
+    ("🐶", "dog pet"),
+    ("🌱", "seedling garden plant"),
+    ("🧹", "broom clean chores"),
+    ("🧺", "basket laundry"),
+    ("🎵", "music note"),
+    ("🎮", "game controller play"),
+    ("💻", "laptop computer code"),
+    ("📦", "package parcel delivery"),
+    ("💖", "heart love"),
+    ("👀", "eyes review look"),
+    ("👥", "people meeting team"),
+    ("→", "arrow right next"),
+    ("•", "bullet dot"),
+    ("★", "black star"),
+    ("☐", "ballot box checkbox"),
+    ("☑", "ballot box check"),
+    ("✓", "check mark tick"),
+    ("✗", "ballot x cross"),
+    ("…", "ellipsis dots"),
+    ("—", "em dash"),
+    ("°", "degree"),
+    ("€", "euro currency"),
+    ("£", "pound currency"),
+];
+
+/// Symbols per row in the picker grid.
+const COLUMNS: usize = 8;
+/// Screen columns per grid cell; emoji are two wide, plain symbols one.
+const CELL: usize = 4;
+
+/// State of the picker overlay while it is open.
+#[derive(Default)]
+pub struct EmojiPicker {
+    pub query: String,
+    pub selected: usize,
+}
+
+impl EmojiPicker {
+    /// Symbols whose keywords contain every word of the query.
+    pub fn matches(&self) -> Vec<&'static str> {
+        let query = self.query.to_lowercase();
+        SYMBOLS
+            .iter()
+            .filter(|(_, keywords)| query.split_whitespace().all(|w| keywords.contains(w)))
+            .map(|(symbol, _)| *symbol)
+            .collect()
+    }
+
+    /// Moves the selection by `step` cells, staying inside the matches.
+    pub fn step(&mut self, step: isize) {
+        let count = self.matches().len();
+        let next = self.selected as isize + step;
+        if (0..count as isize).contains(&next) {
+            self.selected = next as usize;
+        }
+    }
+
+    pub fn row_step(&mut self, down: bool) {
+        let step = COLUMNS as isize;
+        self.step(if down { step } else { -step });
+    }
+
+    pub fn selection(&self) -> Option<&'static str> {
+        self.matches().get(self.selected).copied()
+    }
+}
+
+pub fn render_picker<B: Backend>(f: &mut Frame<B>, app_state: &AppState, picker: &EmojiPicker) {
+    let matches = picker.matches();
+    let rows = matches.len().div_ceil(COLUMNS).max(1);
+    let size = f.size();
+    let width = ((COLUMNS * CELL) as u16 + 2).min(size.width);
+    let height = (rows as u16 + 3).min(size.height);
+    let area = Rect::new(
+        size.x + (size.width - width) / 2,
+        size.y + (size.height - height) / 2,
+        width,
+        height,
+    );
+
+    let mut lines = vec![Spans::from(vec![
+        Span::styled("Search: ", Style::default().fg(Color::DarkGray)),
+        Span::raw(picker.query.as_str()),
+    ])];
+    if matches.is_empty() {
+        lines.push(Spans::from(Span::styled(
+            "No matches",
+            Style::default().fg(Color::DarkGray),
+        )));
+    }
+    for (row, chunk) in matches.chunks(COLUMNS).enumerate() {
+        let spans: Vec<Span> = chunk
+            .iter()
+            .enumerate()
+            .map(|(column, symbol)| {
+                let style = if row * COLUMNS + column == picker.selected {
+                    Style::default()
+                        .bg(app_state.styles.highlight())
+                        .add_modifier(Modifier::BOLD)
+                } else {
+                    Style::default()
+                };
+                let padding = CELL.saturating_sub(symbol.width() + 1);
+                Span::styled(format!(" {}{}", symbol, " ".repeat(padding)), style)
+            })
+            .collect();
+        lines.push(Spans::from(spans));
+    }
+
+    let block = Block::default()
+        .borders(Borders::ALL)
+        .title("Insert symbol");
+    f.render_widget(Clear, area);
+    f.render_widget(Paragraph::new(lines).block(block), area);
+}
@@ -0,0 +1,34 @@
+/// Parses a duration like `45m`, `2h` or `1h30m` into minutes.
+pub fn parse_minutes(s: &str) -> Option<u32> {
+    let mut total = 0;
+    let mut number = String::new();
+    let mut seen_unit = false;
+    for c in s.trim().chars() {
+        match c {
+            '0'..='9' => number.push(c),
+            'h' | 'm' if !number.is_empty() => {
+                let value: u32 = number.parse().ok()?;
+                total += if c == 'h' { value * 60 } else { value };
+                number.clear();
+                seen_unit = true;
+            }
+            _ => return None,
+        }
+    }
+    (seen_unit && number.is_empty()).then_some(total)
+}
+
+/// The estimate written into a description as a `~duration` word, e.g. `~2h`.
+pub fn from_description(description: &str) -> Option<u32> {
+    description
+        .split_whitespace()
+        .find_map(|word| word.strip_prefix('~').and_then(parse_minutes))
+}
+
+pub fn format_minutes(minutes: u32) -> String {
+    match (minutes / 60, minutes % 60) {
+        (0, m) => format!("{}m", m),
+        (h, 0) => format!("{}h", h),
+        (h, m) => format!("{}h{}m", h, m),
+    }
+}
@@ -0,0 +1,217 @@
+use chrono::NaiveDateTime;
+use parquet::data_type::{ByteArray, ByteArrayType, Int64Type};
+use parquet::file::properties::WriterProperties;
+use parquet::file::writer::SerializedFileWriter;
+use parquet::schema::parser::parse_message_type;
+use serde::Serialize;
+use std::collections::HashMap;
+use std::io;
+use std::sync::Arc;
+
+use crate::journal::{Event, EventKind};
+
+/// One journal event as a flat row, the same columns for every kind, so
+/// the log loads straight into a data frame or a dashboard.
+#[derive(Serialize)]
+pub struct Row {
+    /// ISO 8601, e.g. `2026-10-16T09:30:00`.
+    pub at: String,
+    pub task: u64,
+    /// As the journal names it, e.g. `created` or `rescheduled`.
+    pub kind: String,
+    pub author: String,
+    /// The task's description when last created or edited, as of the
+    /// end of the log.
+    pub description: String,
+    /// The old and new value for changes, e.g. deadlines when rescheduled;
+    /// a created task's deadline is its `to`.
+    pub from: String,
+    pub to: String,
+}
+
+impl Row {
+    /// The columns in `COLUMNS` order, as text.
+    fn fields(&self) -> [String; 7] {
+        [
+            self.at.clone(),
+            self.task.to_string(),
+            self.kind.clone(),
+            self.author.clone(),
+            self.description.clone(),
+            self.from.clone(),
+            self.to.clone(),
+        ]
+    }
+}
+
+pub enum Format {
+    Csv,
+    /// One JSON object per line.
+    Jsonl,
+    /// Columnar and typed, for pandas or DuckDB.
+    Parquet,
+}
+
+const COLUMNS: [&str; 7] = ["at", "task", "kind", "author", "description", "from", "to"];
+
+fn date(date: &Option<NaiveDateTime>) -> String {
+    date.map(|d| d.format("%Y-%m-%dT%H:%M:%S").to_string())
+        .unwrap_or_default()
+}
+
+fn id(id: &Option<u64>) -> String {
+    id.map(|id| id.to_string()).unwrap_or_default()
+}
+
+/// The `from` and `to` columns of an event.
+fn change(kind: &EventKind) -> (String, String) {
+    match kind {
+        EventKind::Created { deadline, .. } => (String::new(), date(deadline)),
+        EventKind::Edited { from, to } => (from.clone(), to.clone()),
+        EventKind::Rescheduled { from, to } => (date(from), date(to)),
+        EventKind::Reparented { from, to } => (id(from), id(to)),
+        EventKind::CommandChanged { from, to } => (
+            from.clone().unwrap_or_default(),
+            to.clone().unwrap_or_default(),
+        ),
+        EventKind::Attached { path } => (String::new(), path.clone()),
+        EventKind::Detached { path } => (path.clone(), String::new()),
+        EventKind::ConflictResolved { kept, taken } => (kept.join(";"), taken.join(";")),
+        EventKind::Merged { from } => {
+            let ids: Vec<String> = from.iter().map(|(id, _)| id.to_string()).collect();
+            (ids.join(";"), String::new())
+        }
+        _ => (String::new(), String::new()),
+    }
+}
+
+/// A row per event in journal order. `mask` hides the text of tasks
+/// that are private now, given the task id.
+pub fn rows(events: &[Event], mask: impl Fn(u64, &str) -> String) -> Vec<Row> {
+    let mut descriptions: HashMap<u64, &str> = HashMap::new();
+    for event in events {
+        match &event.kind {
+            EventKind::Created { description, .. } => {
+                descriptions.insert(event.task, description);
+            }
+            EventKind::Edited { to, .. } => {
+                descriptions.insert(event.task, to);
+            }
+            _ => {}
+        }
+    }
+    (events.iter())
+        .map(|event| {
+            let kind = serde_json::to_value(&event.kind).ok();
+            let kind = kind.as_ref().and_then(|k| k["kind"].as_str());
+            let (from, to) = change(&event.kind);
+            let text = matches!(event.kind, EventKind::Edited { .. });
+            let shown = |text: String| mask(event.task, &text);
+            Row {
+                at: event.at.format("%Y-%m-%dT%H:%M:%S").to_string(),
+                task: event.task,
+                kind: kind.unwrap_or_default().to_string(),
+                author: event.author.clone().unwrap_or_default(),
+                description: shown(descriptions.get(&event.task).unwrap_or(&"").to_string()),
+                from: if text { shown(from) } else { from },
+                to: if text { shown(to) } else { to },
+            }
+        })
+        .collect()
+}
+
+/// Quotes a CSV field when it needs it, as RFC 4180 does.
+pub fn field(text: &str) -> String {
+    if text.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", text.replace('"', "\"\""))
+    } else {
+        text.to_string()
+    }
+}
+
+pub fn write(rows: &[Row], format: Format) -> Result<Vec<u8>, io::Error> {
+    let text = match format {
+        Format::Csv => {
+            let mut text = format!("{}\n", COLUMNS.join(","));
+            for row in rows {
+                let fields: Vec<String> = row.fields().iter().map(|f| field(f)).collect();
+                text.push_str(&fields.join(","));
+                text.push('\n');
+            }
+            text
+        }
+        Format::Jsonl => (rows.iter())
+            .filter_map(|row| serde_json::to_string(row).ok())
+            .map(|line| line + "\n")
+            .collect(),
+        Format::Parquet => return parquet(rows).map_err(io::Error::other),
+    };
+    Ok(text.into_bytes())
+}
+
+/// One row group: `task` as an unsigned integer, the other columns as
+/// strings, as in the CSV.
+fn parquet(rows: &[Row]) -> parquet::errors::Result<Vec<u8>> {
+    let fields: Vec<String> = (COLUMNS.iter())
+        .map(|column| match *column {
+            "task" => format!("REQUIRED INT64 {} (INTEGER(64, false));", column),
+            _ => format!("REQUIRED BYTE_ARRAY {} (STRING);", column),
+        })
+        .collect();
+    let schema = parse_message_type(&format!("message event {{ {} }}", fields.join(" ")))?;
+    let properties = WriterProperties::builder().build();
+    let mut writer = SerializedFileWriter::new(Vec::new(), Arc::new(schema), Arc::new(properties))?;
+    let mut group = writer.next_row_group()?;
+    let mut index = 0;
+    while let Some(mut column) = group.next_column()? {
+        if COLUMNS[index] == "task" {
+            let tasks: Vec<i64> = rows.iter().map(|row| row.task as i64).collect();
+            column
+                .typed::<Int64Type>()
+                .write_batch(&tasks, None, None)?;
+        } else {
+            let texts: Vec<ByteArray> = (rows.iter())
+                .map(|row| ByteArray::from(row.fields()[index].as_str()))
+                .collect();
+            column
+                .typed::<ByteArrayType>()
+                .write_batch(&texts, None, None)?;
+        }
+        column.close()?;
+        index += 1;
+    }
+    group.close()?;
+    writer.into_inner()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use parquet::file::reader::{FileReader, SerializedFileReader};
+    use parquet::record::RowAccessor;
+
+    #[test]
+    fn parquet_has_a_row_per_event() {
+        let created = EventKind::Created {
+            description: "Write, then \"ship\"".to_string(),
+            deadline: None,
+            parent: None,
+        };
+        let events = [Event::now(7, created), Event::now(7, EventKind::Completed)];
+        let rows = rows(&events, |_, text| text.to_string());
+        let path = std::env::temp_dir().join(format!("todo-rs-{}.parquet", std::process::id()));
+        std::fs::write(&path, write(&rows, Format::Parquet).unwrap()).unwrap();
+
+        let reader = SerializedFileReader::new(std::fs::File::open(&path).unwrap()).unwrap();
+        assert_eq!(reader.metadata().file_metadata().num_rows(), 2);
+        let read: Vec<_> = reader
+            .get_row_iter(None)
+            .unwrap()
+            .map(Result::unwrap)
+            .collect();
+        assert_eq!(read[1].get_ulong(1).unwrap(), 7);
+        assert_eq!(read[1].get_string(2).unwrap(), "completed");
+        assert_eq!(read[1].get_string(4).unwrap(), "Write, then \"ship\"");
+        std::fs::remove_file(&path).unwrap();
+    }
+}
@@ -0,0 +1,130 @@
+use crossterm::event::{self as term_event, MouseEvent};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{mpsc, Arc};
+use std::thread;
+use std::time::Duration;
+
+use crate::keymap::Key;
+
+pub enum Event {
+    Input(Key),
+    Mouse(MouseEvent),
+    Paste(String),
+    Resize,
+    Suspend,
+    Tick,
+}
+
+/// Merges keyboard input and a periodic tick into one channel, so the main
+/// loop can pick up changes made outside the TUI while waiting for keys.
+pub struct Events {
+    rx: mpsc::Receiver<Event>,
+    /// Asks the input thread to stop reading the terminal.
+    paused: Arc<AtomicBool>,
+    /// Set by the input thread once it has stopped.
+    idle: Arc<AtomicBool>,
+    /// Taken off the channel by `input_pending`, returned by the next `next`.
+    queued: Option<Event>,
+}
+
+impl Events {
+    pub fn new(tick_rate: Duration) -> Events {
+        let (tx, rx) = mpsc::channel();
+
+        let paused = Arc::new(AtomicBool::new(false));
+        let idle = Arc::new(AtomicBool::new(false));
+        let input_tx = tx.clone();
+        let (input_paused, input_idle) = (paused.clone(), idle.clone());
+        thread::spawn(move || loop {
+            // Polling rather than blocking in read, so a child process can be
+            // handed the terminal without us swallowing its keystrokes.
+            if input_paused.load(Ordering::SeqCst) {
+                input_idle.store(true, Ordering::SeqCst);
+                thread::sleep(Duration::from_millis(20));
+                continue;
+            }
+            input_idle.store(false, Ordering::SeqCst);
+            match term_event::poll(Duration::from_millis(50)) {
+                Ok(true) => {}
+                Ok(false) => continue,
+                Err(_) => return,
+            }
+            let Ok(event) = term_event::read() else {
+                return;
+            };
+            let event = match event {
+                term_event::Event::Key(key) => match Key::from_event(key) {
+                    Some(key) => Event::Input(key),
+                    None => continue,
+                },
+                term_event::Event::Mouse(mouse) => Event::Mouse(mouse),
+                term_event::Event::Paste(text) => Event::Paste(text),
+                term_event::Event::Resize(_, _) => Event::Resize,
+                _ => continue,
+            };
+            if input_tx.send(event).is_err() {
+                return;
+            }
+        });
+
+        // Raw mode turns Ctrl+Z into a key press, but `kill -TSTP` still arrives
+        // as a signal and must not stop us with the terminal left in raw mode.
+        #[cfg(unix)]
+        if let Ok(mut signals) = signal_hook::iterator::Signals::new([signal_hook::consts::SIGTSTP])
+        {
+            let suspend_tx = tx.clone();
+            thread::spawn(move || {
+                for _ in signals.forever() {
+                    if suspend_tx.send(Event::Suspend).is_err() {
+                        return;
+                    }
+                }
+            });
+        }
+
+        thread::spawn(move || loop {
+            if tx.send(Event::Tick).is_err() {
+                return;
+            }
+            thread::sleep(tick_rate);
+        });
+
+        Events {
+            rx,
+            paused,
+            idle,
+            queued: None,
+        }
+    }
+
+    /// Stops reading terminal input, returning once the reader has let go.
+    pub fn pause(&self) {
+        self.paused.store(true, Ordering::SeqCst);
+        while !self.idle.load(Ordering::SeqCst) {
+            thread::sleep(Duration::from_millis(5));
+        }
+    }
+
+    pub fn resume(&self) {
+        self.idle.store(false, Ordering::SeqCst);
+        self.paused.store(false, Ordering::SeqCst);
+    }
+
+    pub fn next(&mut self) -> Result<Event, mpsc::RecvError> {
+        match self.queued.take() {
+            Some(event) => Ok(event),
+            None => self.rx.recv(),
+        }
+    }
+
+    /// Whether the user's next input has already arrived.
+    pub fn input_pending(&mut self) -> bool {
+        if self.queued.is_none() {
+            self.queued = self.rx.try_recv().ok();
+        }
+        matches!(
+            self.queued,
+            Some(Event::Input(_) | Event::Mouse(_) | Event::Paste(_))
+        )
+    }
+}
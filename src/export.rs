@@ -0,0 +1,139 @@
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fs;
+
+use crate::agenda;
+use crate::clock;
+use crate::config;
+use crate::estimate;
+use crate::query::{self, Query};
+use crate::subtasks;
+use crate::Task;
+
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum ExportView {
+    /// Open tasks in list order, subtasks indented under their parents.
+    #[default]
+    List,
+    /// Open tasks with a deadline, grouped by day.
+    Agenda,
+}
+
+/// A Markdown file rewritten whenever the tasks are saved, for dashboards
+/// and wikis that should show the current state.
+#[derive(Serialize, Deserialize, Clone)]
+pub struct Export {
+    /// Where the file goes; `~/` is the home directory.
+    pub path: String,
+    #[serde(default)]
+    pub view: ExportView,
+    /// Search query limiting the tasks written, e.g. `#work` or
+    /// `filter:today`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub query: Option<String>,
+}
+
+impl Export {
+    pub fn validate(&self, filters: &BTreeMap<String, String>) -> Result<(), String> {
+        if self.path.trim().is_empty() {
+            return Err("path is empty".to_string());
+        }
+        if let Some(text) = &self.query {
+            query::parse(text, filters).map_err(|e| format!("query: {}", e))?;
+        }
+        Ok(())
+    }
+
+    fn render(
+        &self,
+        tasks: &[Task],
+        filters: &BTreeMap<String, String>,
+        date_format: &str,
+    ) -> String {
+        let query: Option<Query> =
+            (self.query.as_ref()).and_then(|q| query::parse(q, filters).ok());
+        let today = clock::today();
+        let shown =
+            |task: &Task| !task.completed && query.as_ref().is_none_or(|q| q.matches(task, today));
+
+        let title = match (self.view, &self.query) {
+            (ExportView::List, None) => "Tasks".to_string(),
+            (ExportView::List, Some(q)) => format!("Tasks matching {}", q),
+            (ExportView::Agenda, None) => "Agenda".to_string(),
+            (ExportView::Agenda, Some(q)) => format!("Agenda for {}", q),
+        };
+        let mut text = format!(
+            "# {}\n\n_Updated {}_\n",
+            title,
+            clock::now().format("%Y-%m-%d %H:%M")
+        );
+        match self.view {
+            ExportView::List => {
+                text.push('\n');
+                for (i, task) in tasks.iter().enumerate().filter(|(_, t)| shown(t)) {
+                    let indent = "  ".repeat(subtasks::depth(tasks, i));
+                    text.push_str(&format!("{}{}\n", indent, item(task, date_format)));
+                }
+            }
+            ExportView::Agenda => {
+                for day in agenda::build(tasks, |_| true) {
+                    let day_tasks: Vec<&Task> = day
+                        .tasks
+                        .iter()
+                        .map(|i| &tasks[*i])
+                        .filter(|t| shown(t))
+                        .collect();
+                    if day_tasks.is_empty() {
+                        continue;
+                    }
+                    let minutes: u32 = (day_tasks.iter())
+                        .filter_map(|t| estimate::from_description(&t.description))
+                        .sum();
+                    text.push_str(&format!(
+                        "\n## {} {}\n\n",
+                        day.date.format("%a"),
+                        day.date.format(date_format)
+                    ));
+                    if minutes > 0 {
+                        text.push_str(&format!(
+                            "Estimated {}\n\n",
+                            estimate::format_minutes(minutes)
+                        ));
+                    }
+                    for task in day_tasks {
+                        text.push_str(&format!("- [ ] {}\n", task.shared_description()));
+                    }
+                }
+            }
+        }
+        text
+    }
+}
+
+/// A Markdown checklist line; private tasks show only their tags.
+pub fn item(task: &Task, date_format: &str) -> String {
+    let description = task.shared_description();
+    match task.deadline {
+        Some(d) => format!("- [ ] {} (due {})", description, d.format(date_format)),
+        None => format!("- [ ] {}", description),
+    }
+}
+
+/// Writes every configured export, carrying on past failures; the error
+/// names the first file that couldn't be written.
+pub fn write_all(
+    exports: &[Export],
+    tasks: &[Task],
+    filters: &BTreeMap<String, String>,
+    date_format: &str,
+) -> Result<(), String> {
+    let mut first_error = None;
+    for export in exports {
+        let text = export.render(tasks, filters, date_format);
+        if let Err(e) = fs::write(config::expand_home(&export.path), text) {
+            first_error.get_or_insert(format!("{}: {}", export.path, e));
+        }
+    }
+    first_error.map_or(Ok(()), Err)
+}
@@ -0,0 +1,162 @@
+use chrono::{Duration, NaiveDate, NaiveDateTime};
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+
+use crate::estimate;
+use crate::journal::{Event, EventKind};
+
+/// A stretch of work on one task, from starting it to stopping, completing
+/// or putting it away.
+pub struct Session {
+    pub task: u64,
+    pub start: NaiveDateTime,
+    pub end: NaiveDateTime,
+}
+
+impl Session {
+    fn minutes(&self) -> u32 {
+        (self.end - self.start).num_minutes().max(0) as u32
+    }
+}
+
+/// Work sessions recorded in the journal, oldest first; one still running
+/// ends at `now`.
+pub fn sessions(events: &[Event], now: NaiveDateTime) -> Vec<Session> {
+    let mut running: HashMap<u64, NaiveDateTime> = HashMap::new();
+    let mut sessions = Vec::new();
+    for event in events {
+        match event.kind {
+            EventKind::Started => {
+                running.entry(event.task).or_insert(event.at);
+            }
+            EventKind::Stopped
+            | EventKind::Completed
+            | EventKind::Deferred
+            | EventKind::Deleted => {
+                if let Some(start) = running.remove(&event.task) {
+                    sessions.push(Session {
+                        task: event.task,
+                        start,
+                        end: event.at,
+                    });
+                }
+            }
+            _ => {}
+        }
+    }
+    sessions.extend(running.into_iter().map(|(task, start)| Session {
+        task,
+        start,
+        end: now,
+    }));
+    sessions.sort_by_key(|s| s.start);
+    sessions
+}
+
+/// Focus time between two days, both included. Sessions count towards the
+/// day they started on.
+pub struct Report {
+    pub from: NaiveDate,
+    pub to: NaiveDate,
+    pub minutes: u32,
+    pub sessions: usize,
+    /// Tasks with their sessions and minutes, most focused first.
+    pub tasks: Vec<(String, usize, u32)>,
+    /// Longest run of days with any focus time, in all the history.
+    pub best_streak: usize,
+    /// The run ending today, or yesterday while today is still empty.
+    pub current_streak: usize,
+}
+
+/// `describe` names a task by id, whether it is still listed or archived.
+pub fn report(
+    sessions: &[Session],
+    from: NaiveDate,
+    to: NaiveDate,
+    today: NaiveDate,
+    describe: impl Fn(u64) -> String,
+) -> Report {
+    let in_range: Vec<&Session> = (sessions.iter())
+        .filter(|s| (from..=to).contains(&s.start.date()))
+        .collect();
+    let mut tasks: BTreeMap<u64, (usize, u32)> = BTreeMap::new();
+    for session in &in_range {
+        let entry = tasks.entry(session.task).or_default();
+        entry.0 += 1;
+        entry.1 += session.minutes();
+    }
+    let mut tasks: Vec<(String, usize, u32)> = (tasks.into_iter())
+        .map(|(task, (count, minutes))| (describe(task), count, minutes))
+        .collect();
+    tasks.sort_by_key(|(_, _, minutes)| std::cmp::Reverse(*minutes));
+
+    let days: BTreeSet<NaiveDate> = sessions.iter().map(|s| s.start.date()).collect();
+    let mut best_streak = 0;
+    let mut streak = 0;
+    let mut previous: Option<NaiveDate> = None;
+    for day in &days {
+        let follows = previous.is_some_and(|p| p + Duration::days(1) == *day);
+        streak = if follows { streak + 1 } else { 1 };
+        best_streak = best_streak.max(streak);
+        previous = Some(*day);
+    }
+    let mut day = if days.contains(&today) {
+        today
+    } else {
+        today - Duration::days(1)
+    };
+    let mut current_streak = 0;
+    while days.contains(&day) {
+        current_streak += 1;
+        day -= Duration::days(1);
+    }
+
+    Report {
+        from,
+        to,
+        minutes: tasks.iter().map(|(_, _, minutes)| minutes).sum(),
+        sessions: in_range.len(),
+        tasks,
+        best_streak,
+        current_streak,
+    }
+}
+
+impl Report {
+    /// One line, e.g. `2h30m in 4 sessions`.
+    pub fn summary(&self) -> String {
+        let sessions = match self.sessions {
+            1 => "1 session".to_string(),
+            n => format!("{} sessions", n),
+        };
+        format!("{} in {}", estimate::format_minutes(self.minutes), sessions)
+    }
+
+    /// The report as Markdown, to paste into notes or a timesheet.
+    pub fn markdown(&self) -> String {
+        let mut text = if self.from == self.to {
+            format!("# Focus on {}\n\n", self.from)
+        } else {
+            format!("# Focus from {} to {}\n\n", self.from, self.to)
+        };
+        text.push_str(&format!("Total: {}\n", self.summary()));
+        text.push_str(&format!(
+            "Streak: {} days, best {} days\n",
+            self.current_streak, self.best_streak
+        ));
+        if self.tasks.is_empty() {
+            return text;
+        }
+        text.push_str("\n| Task | Sessions | Time |\n|---|---|---|\n");
+        for (task, count, minutes) in &self.tasks {
+            // Pipes would end the cell early.
+            let description = task.replace('|', "\\|");
+            text.push_str(&format!(
+                "| {} | {} | {} |\n",
+                description,
+                count,
+                estimate::format_minutes(*minutes)
+            ));
+        }
+        text
+    }
+}
@@ -0,0 +1,126 @@
+use chrono::{NaiveDate, NaiveDateTime};
+use std::collections::HashMap;
+
+use crate::Task;
+
+/// Open tasks untouched this long count as stale, unless
+/// `someday_after_days` says otherwise.
+pub const STALE_DAYS: u32 = 30;
+
+/// Something that makes a task drag the score down, in the order they are
+/// suggested for review.
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum Problem {
+    Overdue,
+    Stale,
+    Unscheduled,
+}
+
+impl Problem {
+    const ALL: [Problem; 3] = [Problem::Overdue, Problem::Stale, Problem::Unscheduled];
+
+    pub fn label(self) -> &'static str {
+        match self {
+            Problem::Overdue => "overdue",
+            Problem::Stale => "stale",
+            Problem::Unscheduled => "no deadline",
+        }
+    }
+
+    fn describe(self, count: usize, stale_days: u32) -> String {
+        let (one, many) = match self {
+            Problem::Overdue => ("task is overdue", "tasks are overdue"),
+            Problem::Stale => ("task hasn't been touched", "tasks haven't been touched"),
+            Problem::Unscheduled => ("task has no deadline", "tasks have no deadline"),
+        };
+        let text = if count == 1 {
+            format!("1 {}", one)
+        } else {
+            format!("{} {}", count, many)
+        };
+        match self {
+            Problem::Stale => format!("{} in {} days", text, stale_days),
+            _ => text,
+        }
+    }
+}
+
+/// How tidy the list is: the share of open tasks that are scheduled, not
+/// overdue and recently touched. Someday tasks are left out.
+pub struct Health {
+    /// The day this was worked out; overdue status changes at midnight.
+    pub date: NaiveDate,
+    open: usize,
+    healthy: usize,
+    stale_days: u32,
+    problems: Vec<(Problem, Vec<u64>)>,
+}
+
+impl Health {
+    /// `activity` holds when each task was last changed, from the journal.
+    pub fn compute(
+        tasks: &[Task],
+        activity: &HashMap<u64, NaiveDateTime>,
+        now: NaiveDateTime,
+        stale_days: u32,
+    ) -> Health {
+        let today = now.date();
+        let cutoff = now - chrono::Duration::days(stale_days as i64);
+        let mut problems: Vec<(Problem, Vec<u64>)> =
+            Problem::ALL.iter().map(|p| (*p, Vec::new())).collect();
+        let (mut open, mut healthy) = (0, 0);
+        for task in tasks.iter().filter(|t| !t.completed && !t.someday) {
+            open += 1;
+            let touched = activity.get(&task.id).copied().max(task.created_at);
+            let found = [
+                task.deadline.is_some_and(|d| d.date() < today),
+                !task.in_progress && touched.is_some_and(|at| at < cutoff),
+                task.deadline.is_none(),
+            ];
+            for ((_, ids), found) in problems.iter_mut().zip(found) {
+                if found {
+                    ids.push(task.id);
+                }
+            }
+            if !found.contains(&true) {
+                healthy += 1;
+            }
+        }
+        problems.retain(|(_, ids)| !ids.is_empty());
+        Health {
+            date: today,
+            open,
+            healthy,
+            stale_days,
+            problems,
+        }
+    }
+
+    /// 0-100; an empty list is perfectly healthy.
+    pub fn score(&self) -> usize {
+        match self.open {
+            0 => 100,
+            open => self.healthy * 100 / open,
+        }
+    }
+
+    /// The problem to review after `current`, wrapping around, or the first
+    /// one when nothing is being reviewed.
+    pub fn next_problem(&self, current: Option<Problem>) -> Option<(Problem, &[u64])> {
+        let start = current
+            .and_then(|c| self.problems.iter().position(|(p, _)| *p == c))
+            .map_or(0, |i| i + 1);
+        let (problem, ids) = self.problems.get(start).or(self.problems.first())?;
+        Some((*problem, ids))
+    }
+
+    /// A nudge about the most pressing problem, naming the review key.
+    pub fn suggestion(&self, review_key: &str) -> Option<String> {
+        let (problem, ids) = self.problems.first()?;
+        Some(format!(
+            "{} - press '{}' to review",
+            problem.describe(ids.len(), self.stale_days),
+            review_key
+        ))
+    }
+}
@@ -0,0 +1,163 @@
+use chrono::{Datelike, Duration, NaiveDate};
+use std::collections::BTreeMap;
+use tui::{
+    backend::Backend,
+    layout::Rect,
+    style::{Color, Style},
+    text::{Span, Spans},
+    widgets::{Block, Borders, Paragraph},
+    Frame,
+};
+
+use crate::clock;
+use crate::config::WeekStart;
+use crate::estimate;
+use crate::{AppState, Task};
+
+const LABEL_WIDTH: usize = 4;
+const MAX_WEEKS: usize = 26;
+/// Most focused tasks listed under the week's focus time.
+const TOP_TASKS: usize = 3;
+
+/// Number of tasks completed on each day.
+pub fn completions_per_day<'a>(
+    tasks: impl Iterator<Item = &'a Task>,
+) -> BTreeMap<NaiveDate, usize> {
+    let mut days = BTreeMap::new();
+    for date in tasks.filter_map(|t| t.completed_at).map(|at| at.date()) {
+        *days.entry(date).or_insert(0) += 1;
+    }
+    days
+}
+
+fn cell_color(count: usize) -> Color {
+    match count {
+        0 => Color::Indexed(238),
+        1 => Color::Indexed(22),
+        2..=3 => Color::Indexed(28),
+        4..=5 => Color::Indexed(34),
+        _ => Color::Indexed(40),
+    }
+}
+
+pub fn week_start_of(date: NaiveDate, week_start: WeekStart) -> NaiveDate {
+    let offset = match week_start {
+        WeekStart::Sunday => date.weekday().num_days_from_sunday(),
+        WeekStart::Monday => date.weekday().num_days_from_monday(),
+    };
+    date - Duration::days(offset as i64)
+}
+
+/// Consecutive days with at least one completion, ending today (or yesterday,
+/// so a streak isn't lost just because today's work hasn't happened yet).
+fn current_streak(days: &BTreeMap<NaiveDate, usize>, today: NaiveDate) -> usize {
+    let mut day = if days.contains_key(&today) {
+        today
+    } else {
+        today - Duration::days(1)
+    };
+    let mut streak = 0;
+    while days.contains_key(&day) {
+        streak += 1;
+        day -= Duration::days(1);
+    }
+    streak
+}
+
+pub fn render_heatmap<B: Backend>(f: &mut Frame<B>, app_state: &AppState, chunk: Rect) {
+    let days = &app_state.heatmap;
+    let today = clock::today();
+    let inner_width = chunk.width.saturating_sub(2) as usize;
+    let weeks = (inner_width.saturating_sub(LABEL_WIDTH) / 2).clamp(1, MAX_WEEKS);
+    let first_day =
+        week_start_of(today, app_state.config.week_start()) - Duration::weeks(weeks as i64 - 1);
+
+    let mut month_row = " ".repeat(LABEL_WIDTH);
+    let mut last_month = None;
+    for week in 0..weeks {
+        let date = first_day + Duration::weeks(week as i64);
+        let column = LABEL_WIDTH + week * 2;
+        if last_month != Some(date.month()) && month_row.len() <= column {
+            month_row.push_str(&" ".repeat(column - month_row.len()));
+            month_row.push_str(&date.format("%b").to_string());
+            last_month = Some(date.month());
+        }
+    }
+    let mut lines = vec![Spans::from(Span::styled(
+        month_row,
+        Style::default().fg(Color::DarkGray),
+    ))];
+
+    for row in 0..7 {
+        let label_date = first_day + Duration::days(row);
+        let label = if row % 2 == 1 {
+            label_date.format("%a").to_string()
+        } else {
+            String::new()
+        };
+        let mut spans = vec![Span::styled(
+            format!("{:<width$}", label, width = LABEL_WIDTH),
+            Style::default().fg(Color::DarkGray),
+        )];
+        for week in 0..weeks {
+            let date = first_day + Duration::weeks(week as i64) + Duration::days(row);
+            if date > today {
+                spans.push(Span::raw("  "));
+                continue;
+            }
+            let count = days.get(&date).copied().unwrap_or(0);
+            spans.push(Span::styled("■ ", Style::default().fg(cell_color(count))));
+        }
+        lines.push(Spans::from(spans));
+    }
+
+    let in_range = days.range(first_day..=today);
+    let total: usize = in_range.clone().map(|(_, n)| n).sum();
+    lines.push(Spans::from(""));
+    lines.push(Spans::from(format!(
+        "{} completed in the last {} weeks, current streak: {} days",
+        total,
+        weeks,
+        current_streak(days, today)
+    )));
+    if let Some((date, count)) = in_range.max_by_key(|(_, n)| **n) {
+        lines.push(Spans::from(format!(
+            "Busiest day: {} ({} completed)",
+            date.format(&app_state.config.date_format),
+            count
+        )));
+    }
+
+    if let Some((day, week)) = &app_state.focus {
+        lines.push(Spans::from(""));
+        lines.push(Spans::from(format!(
+            "Focus today: {}, this week: {}",
+            day.summary(),
+            week.summary()
+        )));
+        lines.push(Spans::from(format!(
+            "Focus streak: {} days, best {} days",
+            week.current_streak, week.best_streak
+        )));
+        for (task, count, minutes) in week.tasks.iter().take(TOP_TASKS) {
+            lines.push(Spans::from(vec![
+                Span::styled(
+                    format!("  {:>6} ", estimate::format_minutes(*minutes)),
+                    Style::default().fg(Color::DarkGray),
+                ),
+                Span::raw(task.clone()),
+                Span::styled(
+                    match count {
+                        1 => " (1 session)".to_string(),
+                        n => format!(" ({} sessions)", n),
+                    },
+                    Style::default().fg(Color::DarkGray),
+                ),
+            ]));
+        }
+    }
+
+    let paragraph =
+        Paragraph::new(lines).block(Block::default().borders(Borders::ALL).title("Completions"));
+    f.render_widget(paragraph, chunk);
+}
@@ -0,0 +1,134 @@
+use unicode_width::UnicodeWidthStr;
+
+use crate::keymap::{self, Action};
+use crate::menu;
+use crate::{AppState, Mode};
+
+/// The most used Normal mode actions, with what the hint calls them.
+const NORMAL: [(Action, &str); 8] = [
+    (Action::NewTask, "add"),
+    (Action::EditTask, "edit"),
+    (Action::ToggleComplete, "done"),
+    (Action::DeleteTask, "delete"),
+    (Action::StartTask, "start"),
+    (Action::Search, "search"),
+    (Action::ShowMenu, "actions"),
+    (Action::Quit, "quit"),
+];
+
+/// Keys the current mode takes and what they do, for the status bar, with
+/// keys of actions as the keymap binds them.
+pub fn hints(app_state: &AppState) -> Vec<(String, &'static str)> {
+    let key = |action: Action| keymap::format_key(app_state.keymap.key_for(action));
+    let fixed = |hints: &[(&str, &'static str)]| -> Vec<(String, &'static str)> {
+        (hints.iter())
+            .map(|(key, what)| (key.to_string(), *what))
+            .collect()
+    };
+    let chords = app_state.config.accessibility.chords;
+    match app_state.mode {
+        Mode::Normal => (NORMAL.iter())
+            .filter(|(action, _)| chords || !menu::is_chord(app_state.keymap.key_for(*action)))
+            .map(|(action, what)| (key(*action), *what))
+            .collect(),
+        Mode::Input | Mode::Edit => fixed(&[("Enter", "set deadline"), ("Tab", "complete")]),
+        Mode::DeleteConfirm => vec![
+            (key(Action::DeleteTask), "confirm"),
+            ("any other key".to_string(), "cancel"),
+        ],
+        Mode::ActionConfirm | Mode::BulkConfirm => {
+            fixed(&[("y", "yes"), ("any other key", "cancel")])
+        }
+        Mode::Recover => fixed(&[
+            ("y", "restore"),
+            ("d", "discard"),
+            ("any other key", "later"),
+        ]),
+        Mode::CompleteConfirm => fixed(&[
+            ("1", "with subtasks"),
+            ("2", "detach subtasks"),
+            ("any other key", "cancel"),
+        ]),
+        Mode::DeadlineInput => fixed(&[
+            ("1-9", "preset"),
+            ("Tab", "type a date"),
+            ("Enter", "set"),
+            ("Esc", "cancel"),
+        ]),
+        Mode::DeadlineEntry => fixed(&[("Enter", "set"), ("Esc", "presets")]),
+        Mode::Postpone => fixed(&[("1-5", "choose"), ("any other key", "cancel")]),
+        Mode::Share => fixed(&[("1-2", "choose"), ("any other key", "cancel")]),
+        Mode::Template | Mode::TagPick => fixed(&[("1-9", "choose"), ("Esc", "cancel")]),
+        Mode::Search => fixed(&[
+            ("Enter", "apply"),
+            ("Tab", "switch syntax"),
+            ("Esc", "cancel"),
+        ]),
+        Mode::CommandInput | Mode::AttachInput | Mode::Placeholder => {
+            fixed(&[("Enter", "confirm"), ("Esc", "cancel")])
+        }
+        Mode::Menu => fixed(&[("Enter", "run"), ("Esc", "close")]),
+        Mode::Kanban => vec![
+            ("Left/Right".to_string(), "column"),
+            (key(Action::StartTask), "start/stop"),
+            (key(Action::ToggleComplete), "done"),
+            ("Esc".to_string(), "return"),
+        ],
+        Mode::Scratch if !app_state.scratch.adding => vec![
+            (key(Action::NewTask), "add"),
+            (key(Action::ToggleComplete), "done"),
+            (key(Action::DeleteTask), "drop"),
+            ("Esc".to_string(), "return"),
+        ],
+        Mode::Scratch => fixed(&[("Enter", "add")]),
+        Mode::Timeline => fixed(&[
+            ("Left/Right", "pick task"),
+            ("Enter", "put here"),
+            ("Backspace", "unplace"),
+            ("Esc", "return"),
+        ]),
+        Mode::Agenda => fixed(&[("Up/Down", "day"), ("Esc", "return")]),
+        Mode::Plan => fixed(&[
+            ("Space", "commit/drop"),
+            ("Enter", "done"),
+            ("Esc", "cancel"),
+        ]),
+        Mode::MergePick => fixed(&[
+            ("Space", "mark"),
+            ("Enter", "pick fields"),
+            ("Esc", "cancel"),
+        ]),
+        Mode::Merge => fixed(&[("Left/Right", "pick"), ("Enter", "merge"), ("Esc", "back")]),
+        Mode::Snapshots => fixed(&[
+            ("Enter", "open/restore"),
+            ("a", "roll back"),
+            ("Esc", "back"),
+        ]),
+        Mode::Storage => fixed(&[
+            ("c", "compact journal"),
+            ("p", "prune snapshots"),
+            ("Esc", "back"),
+        ]),
+        Mode::Conflict => fixed(&[("Left/Right", "pick side"), ("Enter", "apply")]),
+        Mode::Keybindings => fixed(&[("Enter", "rebind"), ("r", "reset"), ("Esc", "close")]),
+        Mode::Zoom => fixed(&[("Up/Down", "task"), ("any other key", "return")]),
+        Mode::History | Mode::WhatsNew | Mode::Heatmap => fixed(&[("any key", "return")]),
+        Mode::Setup | Mode::Locked => Vec::new(),
+    }
+}
+
+/// As many `hints` as fit in `width` columns, e.g. `d: confirm  any other
+/// key: cancel`.
+pub fn line(hints: &[(String, &str)], width: usize) -> String {
+    let mut line = String::new();
+    for (key, what) in hints {
+        let hint = format!("{}: {}", key, what);
+        let separator = if line.is_empty() { "" } else { "  " };
+        if line.width() + separator.len() + hint.width() > width {
+            break;
+        }
+        line.push_str(separator);
+        line.push_str(&hint);
+    }
+    line
+}
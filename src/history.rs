@@ -0,0 +1,176 @@
+use tui::{
+    backend::Backend,
+    layout::Rect,
+    style::{Color, Modifier, Style},
+    text::{Span, Spans},
+    widgets::{Block, Borders, List, ListItem},
+    Frame,
+};
+
+use crate::journal::{Event, EventKind};
+use crate::AppState;
+
+enum Change<'a> {
+    Same(&'a str),
+    Removed(&'a str),
+    Added(&'a str),
+}
+
+/// Word-level diff of two descriptions, based on their longest common subsequence.
+fn word_diff<'a>(from: &'a str, to: &'a str) -> Vec<Change<'a>> {
+    let old: Vec<&str> = from.split_whitespace().collect();
+    let new: Vec<&str> = to.split_whitespace().collect();
+
+    let mut lcs = vec![vec![0usize; new.len() + 1]; old.len() + 1];
+    for i in (0..old.len()).rev() {
+        for j in (0..new.len()).rev() {
+            lcs[i][j] = if old[i] == new[j] {
+                lcs[i + 1][j + 1] + 1
+            } else {
+                lcs[i + 1][j].max(lcs[i][j + 1])
+            };
+        }
+    }
+
+    let (mut i, mut j) = (0, 0);
+    let mut changes = Vec::new();
+    while i < old.len() && j < new.len() {
+        if old[i] == new[j] {
+            changes.push(Change::Same(old[i]));
+            i += 1;
+            j += 1;
+        } else if lcs[i + 1][j] >= lcs[i][j + 1] {
+            changes.push(Change::Removed(old[i]));
+            i += 1;
+        } else {
+            changes.push(Change::Added(new[j]));
+            j += 1;
+        }
+    }
+    changes.extend(old[i..].iter().map(|w| Change::Removed(w)));
+    changes.extend(new[j..].iter().map(|w| Change::Added(w)));
+    changes
+}
+
+fn format_deadline(deadline: &Option<chrono::NaiveDateTime>, date_format: &str) -> String {
+    match deadline {
+        Some(d) => d.format(date_format).to_string(),
+        None => "none".to_string(),
+    }
+}
+
+fn event_line<'a>(event: &'a Event, date_format: &str) -> Spans<'a> {
+    let mut spans = vec![Span::styled(
+        format!("{}  ", event.at.format("%Y-%m-%d %H:%M")),
+        Style::default().fg(Color::DarkGray),
+    )];
+
+    match &event.kind {
+        EventKind::Created {
+            description,
+            deadline,
+            ..
+        } => {
+            spans.push(Span::raw(format!(
+                "Created \"{}\" (due {})",
+                description,
+                format_deadline(deadline, date_format)
+            )));
+        }
+        EventKind::Edited { from, to } => {
+            spans.push(Span::raw("Edited: "));
+            for change in word_diff(from, to) {
+                match change {
+                    Change::Same(w) => spans.push(Span::raw(format!("{} ", w))),
+                    Change::Removed(w) => spans.push(Span::styled(
+                        format!("{} ", w),
+                        Style::default()
+                            .fg(Color::Red)
+                            .add_modifier(Modifier::CROSSED_OUT),
+                    )),
+                    Change::Added(w) => spans.push(Span::styled(
+                        format!("{} ", w),
+                        Style::default().fg(Color::Green),
+                    )),
+                }
+            }
+        }
+        EventKind::Rescheduled { from, to } => {
+            spans.push(Span::raw(format!(
+                "Rescheduled {} -> {}",
+                format_deadline(from, date_format),
+                format_deadline(to, date_format)
+            )));
+        }
+        EventKind::Completed => spans.push(Span::raw("Completed")),
+        EventKind::Reopened => spans.push(Span::raw("Reopened")),
+        EventKind::Started => spans.push(Span::raw("Started")),
+        EventKind::Stopped => spans.push(Span::raw("Stopped")),
+        EventKind::Deferred => spans.push(Span::raw("Moved to Someday")),
+        EventKind::Resumed => spans.push(Span::raw("Back from Someday")),
+        EventKind::MadePrivate => spans.push(Span::raw("Marked private")),
+        EventKind::MadePublic => spans.push(Span::raw("No longer private")),
+        EventKind::Reparented { to, .. } => spans.push(Span::raw(match to {
+            Some(parent) => format!("Moved under task {}", parent),
+            None => "Moved to the top level".to_string(),
+        })),
+        EventKind::CommandChanged { to, .. } => spans.push(Span::raw(match to {
+            Some(command) => format!("Command set to `{}`", command),
+            None => "Command removed".to_string(),
+        })),
+        EventKind::Attached { path } => spans.push(Span::raw(format!("Attached {}", path))),
+        EventKind::Detached { path } => {
+            spans.push(Span::raw(format!("Removed attachment {}", path)))
+        }
+        EventKind::Deleted => spans.push(Span::raw("Deleted")),
+        EventKind::ConflictResolved { kept, taken } => {
+            let mut parts = Vec::new();
+            if !kept.is_empty() {
+                parts.push(format!("kept this session's {}", kept.join(", ")));
+            }
+            if !taken.is_empty() {
+                parts.push(format!("took the other instance's {}", taken.join(", ")));
+            }
+            spans.push(Span::raw(format!(
+                "Resolved a conflict: {}",
+                parts.join("; ")
+            )));
+        }
+        EventKind::Merged { from } => {
+            let tasks: Vec<String> = (from.iter())
+                .map(|(id, description)| format!("task {} \"{}\"", id, description))
+                .collect();
+            spans.push(Span::raw(format!("Merged in {}", tasks.join(", "))));
+        }
+    }
+    if let Some(author) = &event.author {
+        spans.push(Span::styled(
+            format!("  ({})", author),
+            Style::default().fg(Color::DarkGray),
+        ));
+    }
+
+    Spans::from(spans)
+}
+
+pub fn render_history<B: Backend>(f: &mut Frame<B>, app_state: &AppState, chunk: Rect) {
+    let items: Vec<ListItem> = if app_state.history.is_empty() {
+        vec![ListItem::new("No recorded history for this task")]
+    } else {
+        app_state
+            .history
+            .iter()
+            .map(|e| ListItem::new(event_line(e, &app_state.config.date_format)))
+            .collect()
+    };
+
+    let reschedules = app_state
+        .history
+        .iter()
+        .filter(|e| matches!(e.kind, EventKind::Rescheduled { .. }))
+        .count();
+    let title = format!("History (rescheduled {} times)", reschedules);
+
+    let list = List::new(items).block(Block::default().borders(Borders::ALL).title(title));
+    f.render_widget(list, chunk);
+}
@@ -0,0 +1,369 @@
+use chrono::NaiveDate;
+use std::collections::BTreeSet;
+
+use crate::calendar;
+use crate::Task;
+
+/// Tag given to flagged and high-priority tasks, which have no field of
+/// their own here.
+const FLAGGED: &str = "#flagged";
+
+/// A task read from another app's export.
+pub struct Item {
+    /// The title, with the list as a `+project` and `#flagged` when flagged.
+    pub description: String,
+    pub deadline: Option<NaiveDate>,
+    pub completed: bool,
+    /// The id the other app gives it (`UID` in iCalendar).
+    pub uid: Option<String>,
+}
+
+impl Item {
+    fn new(title: &str, list: Option<&str>, flagged: bool) -> Item {
+        let words = |text: &str| text.split_whitespace().collect::<Vec<_>>().join(" ");
+        // Notes-style titles can span lines; a task is one.
+        let mut description = words(title);
+        let project = list.map(|l| words(l).replace(' ', "-"));
+        if let Some(project) = project.filter(|p| !p.is_empty()) {
+            description.push_str(&format!(" +{}", project));
+        }
+        if flagged {
+            description.push_str(&format!(" {}", FLAGGED));
+        }
+        Item {
+            description,
+            deadline: None,
+            completed: false,
+            uid: None,
+        }
+    }
+}
+
+/// Reads an Apple Reminders or Microsoft To Do export: iCalendar (`.ics`)
+/// or CSV, as Outlook writes its tasks. `None` when the text is neither.
+pub fn parse(text: &str) -> Option<Vec<Item>> {
+    let text = text.trim_start_matches('\u{feff}');
+    if text.trim_start().starts_with("BEGIN:VCALENDAR") {
+        Some(parse_ics(text))
+    } else {
+        parse_csv(text)
+    }
+}
+
+/// Undoes the escaping of an iCalendar text value (RFC 5545, 3.3.11).
+fn unescape_ics(text: &str) -> String {
+    let mut result = String::new();
+    let mut chars = text.chars();
+    while let Some(c) = chars.next() {
+        if c != '\\' {
+            result.push(c);
+            continue;
+        }
+        match chars.next() {
+            Some('n' | 'N') => result.push(' '),
+            Some(escaped) => result.push(escaped),
+            None => {}
+        }
+    }
+    result
+}
+
+/// VTODOs, with the calendar's name (`X-WR-CALNAME`) as their list.
+fn parse_ics(text: &str) -> Vec<Item> {
+    let lines = calendar::unfold(text);
+    let mut items = Vec::new();
+    let mut list = None;
+    let mut todo: Option<(String, Option<NaiveDate>, bool, bool)> = None;
+    let mut uid = None;
+    for line in &lines {
+        let Some((name, value)) = line.split_once(':') else {
+            continue;
+        };
+        let property = name.split(';').next().unwrap_or_default().to_uppercase();
+        if property == "X-WR-CALNAME" {
+            list = Some(unescape_ics(value));
+            continue;
+        }
+        if property == "BEGIN" && value.eq_ignore_ascii_case("VTODO") {
+            todo = Some((String::new(), None, false, false));
+            uid = None;
+            continue;
+        }
+        let Some((title, due, flagged, completed)) = todo.as_mut() else {
+            continue;
+        };
+        match property.as_str() {
+            "SUMMARY" => *title = unescape_ics(value),
+            "UID" => uid = Some(unescape_ics(value)),
+            "DUE" => *due = value.get(..8).and_then(|d| date(d, "%Y%m%d")),
+            // 1 to 4 is high priority; Reminders writes flags as 1 too.
+            "PRIORITY" => *flagged = value.trim().parse().is_ok_and(|p: u8| (1..=4).contains(&p)),
+            "STATUS" => *completed = value.trim().eq_ignore_ascii_case("COMPLETED"),
+            "COMPLETED" => *completed = true,
+            "END" if value.eq_ignore_ascii_case("VTODO") => {
+                let (title, due, flagged, completed) = todo.take().unwrap();
+                if !title.trim().is_empty() {
+                    let mut item = Item::new(&title, list.as_deref(), flagged);
+                    item.deadline = due;
+                    item.completed = completed;
+                    item.uid = uid.take();
+                    items.push(item);
+                }
+            }
+            _ => {}
+        }
+    }
+    items
+}
+
+/// Splits CSV into records, honouring quoted fields with `""` escapes and
+/// line breaks inside them.
+fn csv_records(text: &str) -> Vec<Vec<String>> {
+    let mut records = Vec::new();
+    let mut record = Vec::new();
+    let mut field = String::new();
+    let mut quoted = false;
+    let mut chars = text.chars().peekable();
+    while let Some(c) = chars.next() {
+        match c {
+            '"' if quoted && chars.peek() == Some(&'"') => {
+                field.push('"');
+                chars.next();
+            }
+            '"' => quoted = !quoted,
+            ',' if !quoted => record.push(std::mem::take(&mut field)),
+            '\r' if !quoted => {}
+            '\n' if !quoted => {
+                record.push(std::mem::take(&mut field));
+                records.push(std::mem::take(&mut record));
+            }
+            c => field.push(c),
+        }
+    }
+    if !field.is_empty() || !record.is_empty() {
+        record.push(field);
+        records.push(record);
+    }
+    records
+}
+
+fn date(text: &str, format: &str) -> Option<NaiveDate> {
+    NaiveDate::parse_from_str(text, format).ok()
+}
+
+/// Dates as the exports write them, ignoring any time after the date.
+fn csv_date(text: &str) -> Option<NaiveDate> {
+    let text = text.split_whitespace().next()?;
+    ["%Y-%m-%d", "%m/%d/%Y", "%d.%m.%Y", "%Y%m%d"]
+        .iter()
+        .find_map(|format| date(text, format))
+}
+
+/// Rows of a CSV whose header names a title column (`Subject` in Outlook,
+/// `Title` from Reminders shortcuts) and at least one other known column.
+fn parse_csv(text: &str) -> Option<Vec<Item>> {
+    let mut records = csv_records(text).into_iter();
+    let header: Vec<String> = records
+        .next()?
+        .iter()
+        .map(|h| h.trim().to_lowercase())
+        .collect();
+    let column = |names: &[&str]| header.iter().position(|h| names.contains(&h.as_str()));
+    let title = column(&["subject", "title", "name", "task"])?;
+    let list = column(&["list", "folder", "categories"]);
+    let due = column(&["due date", "due", "duedate"]);
+    let priority = column(&["priority", "importance"]);
+    let flagged = column(&["flagged", "flag"]);
+    let completed = column(&["complete", "completed", "status", "date completed"]);
+    let uid = column(&["id", "uid"]);
+    if [list, due, priority, flagged, completed]
+        .iter()
+        .all(Option::is_none)
+    {
+        return None;
+    }
+
+    let items = records
+        .filter_map(|record| {
+            let field = |index: Option<usize>| {
+                let value = index.and_then(|i| record.get(i)).map(|v| v.trim());
+                value.filter(|v| !v.is_empty() && !v.eq_ignore_ascii_case("none"))
+            };
+            let title = field(Some(title))?;
+            let yes = |v: &str| ["true", "yes", "1"].contains(&v.to_lowercase().as_str());
+            let is_flagged = field(priority).is_some_and(|p| {
+                p.eq_ignore_ascii_case("high") || p.parse().is_ok_and(|p: u8| (1..=4).contains(&p))
+            }) || field(flagged).is_some_and(yes);
+            let mut item = Item::new(title, field(list), is_flagged);
+            item.deadline = field(due).and_then(csv_date);
+            // `Complete` is a boolean, `Status` a word, `Date Completed` a date.
+            item.completed = field(completed).is_some_and(|v| {
+                yes(v) || v.eq_ignore_ascii_case("completed") || csv_date(v).is_some()
+            });
+            item.uid = field(uid).map(str::to_string);
+            Some(item)
+        })
+        .collect();
+    Some(items)
+}
+
+/// What to do with an item the list already has.
+#[derive(Clone, Copy, PartialEq)]
+pub enum Existing {
+    Skip,
+    /// Takes the item's description and deadline.
+    Update,
+    /// Adds it again anyway.
+    Duplicate,
+}
+
+impl Existing {
+    /// From `skip`, `update` or `duplicate`, or their first letter.
+    pub fn parse(text: &str) -> Option<Existing> {
+        match text.trim().to_lowercase().as_str() {
+            "s" | "skip" => Some(Existing::Skip),
+            "u" | "update" => Some(Existing::Update),
+            "d" | "duplicate" => Some(Existing::Duplicate),
+            _ => None,
+        }
+    }
+}
+
+/// How alike two descriptions must be, as the share of their words in
+/// common, to count as the same task.
+const SIMILAR: f64 = 0.8;
+
+/// The words of a description, lowercased and without the tags, contexts
+/// and projects an import adds, which the list may name differently.
+fn words(description: &str) -> BTreeSet<String> {
+    (description.split_whitespace())
+        .filter(|w| !w.starts_with(['#', '@', '+', '~', '*']))
+        .map(|w| w.trim_matches(|c: char| !c.is_alphanumeric()))
+        .filter(|w| !w.is_empty())
+        .map(str::to_lowercase)
+        .collect()
+}
+
+/// The task in the list an item was already imported as: the one with its
+/// id, or else one due the same day whose description is nearly the same.
+pub fn existing<'a>(item: &Item, tasks: &'a [Task]) -> Option<&'a Task> {
+    let mut tasks = tasks.iter().filter(|t| t.source.is_none());
+    if let Some(uid) = &item.uid {
+        if let Some(task) = tasks.clone().find(|t| t.external_id.as_ref() == Some(uid)) {
+            return Some(task);
+        }
+    }
+    let item_words = words(&item.description);
+    tasks.find(|task| {
+        // Ids that differ are different tasks, however alike.
+        let other_id = item.uid.is_some() && task.external_id.is_some();
+        if other_id || task.deadline.map(|d| d.date()) != item.deadline {
+            return false;
+        }
+        let task_words = words(&task.description);
+        let shared = item_words.intersection(&task_words).count();
+        let all = item_words.union(&task_words).count();
+        all > 0 && shared as f64 / all as f64 >= SIMILAR
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const ICS: &str = "BEGIN:VCALENDAR\r\n\
+        VERSION:2.0\r\n\
+        X-WR-CALNAME:Home stuff\r\n\
+        BEGIN:VTODO\r\n\
+        UID:abc-1\r\n\
+        SUMMARY:Water the plants\\, then\r\n  the lawn\r\n\
+        DUE;VALUE=DATE:20261020\r\n\
+        PRIORITY:1\r\n\
+        END:VTODO\r\n\
+        BEGIN:VTODO\r\n\
+        SUMMARY:Pay rent\r\n\
+        PRIORITY:9\r\n\
+        STATUS:COMPLETED\r\n\
+        END:VTODO\r\n\
+        BEGIN:VTODO\r\n\
+        SUMMARY:\r\n\
+        END:VTODO\r\n\
+        END:VCALENDAR\r\n";
+
+    #[test]
+    fn reads_ics_todos() {
+        let items = parse(ICS).unwrap();
+        assert_eq!(items.len(), 2);
+        let plants = &items[0];
+        assert_eq!(
+            plants.description,
+            "Water the plants, then the lawn +Home-stuff #flagged"
+        );
+        assert_eq!(plants.deadline, NaiveDate::from_ymd_opt(2026, 10, 20));
+        assert_eq!(plants.uid.as_deref(), Some("abc-1"));
+        assert!(!plants.completed);
+        let rent = &items[1];
+        assert_eq!(rent.description, "Pay rent +Home-stuff");
+        assert!(rent.completed);
+        assert_eq!(rent.uid, None);
+    }
+
+    #[test]
+    fn reads_outlook_csv() {
+        let text = "\u{feff}Subject,Due Date,Priority,Complete,Categories\r\n\
+            \"Call \"\"Bob\"\", about\nthe invoice\",10/20/2026,High,False,Work\r\n\
+            Renew passport,2026-11-01 09:00,Normal,True,\r\n\
+            ,2026-11-02,Low,False,Work\r\n";
+        let items = parse(text).unwrap();
+        assert_eq!(items.len(), 2);
+        assert_eq!(
+            items[0].description,
+            "Call \"Bob\", about the invoice +Work #flagged"
+        );
+        assert_eq!(items[0].deadline, NaiveDate::from_ymd_opt(2026, 10, 20));
+        assert!(!items[0].completed);
+        assert_eq!(items[1].description, "Renew passport");
+        assert_eq!(items[1].deadline, NaiveDate::from_ymd_opt(2026, 11, 1));
+        assert!(items[1].completed);
+    }
+
+    #[test]
+    fn reads_completion_dates_and_flags_in_csv() {
+        let text = "Title,List,Flagged,Date Completed,ID\n\
+            Buy milk,Groceries,yes,15.10.2026,r-1\n\
+            Buy eggs,Groceries,no,,r-2\n";
+        let items = parse(text).unwrap();
+        assert_eq!(items[0].description, "Buy milk +Groceries #flagged");
+        assert!(items[0].completed);
+        assert_eq!(items[0].uid.as_deref(), Some("r-1"));
+        assert_eq!(items[1].description, "Buy eggs +Groceries");
+        assert!(!items[1].completed);
+    }
+
+    #[test]
+    fn rejects_other_text() {
+        assert!(parse("just some notes\nand more").is_none());
+        // A title column alone could be any spreadsheet.
+        assert!(parse("Title\nBuy milk\n").is_none());
+    }
+
+    #[test]
+    fn finds_tasks_imported_before() {
+        let mut by_id = Task::new(1, "Something else".to_string(), None);
+        by_id.external_id = Some("abc-1".to_string());
+        let due = NaiveDate::from_ymd_opt(2026, 10, 20).unwrap();
+        let description = "water the plants #home".to_string();
+        let alike = Task::new(2, description, due.and_hms_opt(0, 0, 0));
+        let tasks = vec![by_id, alike];
+
+        let mut item = Item::new("Water the plants", Some("Home"), false);
+        item.uid = Some("abc-1".to_string());
+        assert_eq!(existing(&item, &tasks).map(|t| t.id), Some(1));
+        item.uid = None;
+        assert_eq!(existing(&item, &tasks).map(|t| t.id), None);
+        item.deadline = Some(due);
+        assert_eq!(existing(&item, &tasks).map(|t| t.id), Some(2));
+        item.uid = Some("other".to_string());
+        assert_eq!(existing(&item, &tasks).map(|t| t.id), Some(2));
+    }
+}
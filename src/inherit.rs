@@ -0,0 +1,56 @@
+use crate::journal::EventKind;
+use crate::tags;
+use crate::urgency::UrgencyConfig;
+use crate::{AppState, Task};
+
+/// A new subtask's description with the `+projects` and priority tags (the
+/// tags `urgency.tags` weighs) of its parent that it doesn't have yet.
+/// Editing them out afterwards is how a subtask opts out.
+pub fn description(parent: &Task, description: &str, urgency: &UrgencyConfig) -> String {
+    let mut result = description.to_string();
+    for project in tags::projects(&parent.description) {
+        if !tags::projects(description).any(|p| p.eq_ignore_ascii_case(project)) {
+            result.push_str(&format!(" +{}", project));
+        }
+    }
+    let priorities = (tags::tags(&parent.description))
+        .filter(|tag| urgency.tags.get(*tag).is_some_and(|weight| *weight > 0.0));
+    for tag in priorities {
+        if !tags::tags(description).any(|t| t.eq_ignore_ascii_case(tag)) {
+            result.push_str(&format!(" #{}", tag));
+        }
+    }
+    result
+}
+
+/// Keeps inherited deadlines in step as `kind` is recorded for `task`: a
+/// subtask given a deadline of its own, or moved to another parent, stops
+/// inheriting, and a new deadline is passed on to the open subtasks still
+/// inheriting, and on down from them.
+pub fn follow(app_state: &mut AppState, task: u64, kind: &EventKind) {
+    let Some(i) = app_state.tasks.iter().position(|t| t.id == task) else {
+        return;
+    };
+    let to = match kind {
+        EventKind::Rescheduled { to, .. } => *to,
+        EventKind::Reparented { .. } => {
+            app_state.tasks[i].inherits_deadline = false;
+            return;
+        }
+        _ => return,
+    };
+    let parent = app_state.tasks[i].parent;
+    let parent_deadline = (app_state.tasks.iter())
+        .find(|t| Some(t.id) == parent)
+        .map(|t| t.deadline);
+    if parent_deadline != Some(to) {
+        app_state.tasks[i].inherits_deadline = false;
+    }
+    let inheriting: Vec<u64> = (app_state.tasks.iter())
+        .filter(|t| t.parent == Some(task) && t.inherits_deadline && !t.completed)
+        .map(|t| t.id)
+        .collect();
+    for child in inheriting {
+        app_state.reschedule(child, to);
+    }
+}
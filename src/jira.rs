@@ -0,0 +1,179 @@
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fs;
+use std::io::{self, Write};
+use std::path::Path;
+use std::process::{Command, Stdio};
+use std::time::SystemTime;
+
+use crate::share;
+
+const ISSUES_FILE: &str = "jira.json";
+
+/// Where linked issues live; tasks link one with a `jira:KEY-123` word.
+#[derive(Serialize, Deserialize, Clone)]
+pub struct JiraConfig {
+    /// Site root, e.g. `https://example.atlassian.net`.
+    pub url: String,
+    /// Account email for Jira Cloud; without it the token is sent as a
+    /// bearer token, as Jira Server's personal access tokens expect.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub user: Option<String>,
+    /// API token, best kept as `keyring:<name>`; asked for when unset.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub token: Option<String>,
+    /// Complete a task when its issue reaches a Done status.
+    #[serde(default)]
+    pub complete_on_done: bool,
+}
+
+impl JiraConfig {
+    pub fn validate(&self) -> Result<(), String> {
+        if !self.url.starts_with("https://") && !self.url.starts_with("http://") {
+            return Err(format!("url '{}' must start with https://", self.url));
+        }
+        Ok(())
+    }
+}
+
+/// An issue as last fetched.
+#[derive(Serialize, Deserialize, Clone)]
+pub struct Issue {
+    pub summary: String,
+    pub status: String,
+    /// Whether the status is in Jira's Done category.
+    pub done: bool,
+}
+
+/// Issues fetched by `todo-rs jira sync`, kept next to the task data.
+#[derive(Serialize, Deserialize, Default)]
+pub struct Issues {
+    issues: BTreeMap<String, Issue>,
+    #[serde(skip)]
+    modified: Option<SystemTime>,
+}
+
+impl Issues {
+    /// Missing or unreadable files just mean nothing was fetched yet.
+    pub fn load(data_dir: &Path) -> Issues {
+        let path = data_dir.join(ISSUES_FILE);
+        let mut issues: Issues = fs::read_to_string(&path)
+            .ok()
+            .and_then(|text| serde_json::from_str(&text).ok())
+            .unwrap_or_default();
+        issues.modified = fs::metadata(path).and_then(|m| m.modified()).ok();
+        issues
+    }
+
+    /// Picks up a sync run from the command line while the app is open.
+    pub fn refresh(&mut self, data_dir: &Path) {
+        let modified = fs::metadata(data_dir.join(ISSUES_FILE)).and_then(|m| m.modified());
+        if modified.ok() != self.modified {
+            *self = Issues::load(data_dir);
+        }
+    }
+
+    pub fn save(&self, data_dir: &Path) -> Result<(), io::Error> {
+        let text = serde_json::to_string_pretty(self)?;
+        fs::write(data_dir.join(ISSUES_FILE), text)
+    }
+
+    pub fn get(&self, key: &str) -> Option<&Issue> {
+        self.issues.get(key)
+    }
+
+    pub fn insert(&mut self, key: &str, issue: Issue) {
+        self.issues.insert(key.to_string(), issue);
+    }
+
+    pub fn remove(&mut self, key: &str) {
+        self.issues.remove(key);
+    }
+
+    pub fn keys(&self) -> impl Iterator<Item = &str> {
+        self.issues.keys().map(String::as_str)
+    }
+}
+
+/// The issue key a description links to with a `jira:KEY-123` word.
+pub fn key(description: &str) -> Option<&str> {
+    description.split_whitespace().find_map(|word| {
+        let key = word.strip_prefix("jira:")?;
+        let (project, number) = key.split_once('-')?;
+        let valid = project.starts_with(|c: char| c.is_ascii_uppercase())
+            && project
+                .chars()
+                .all(|c| c.is_ascii_uppercase() || c.is_ascii_digit() || c == '_')
+            && !number.is_empty()
+            && number.chars().all(|c| c.is_ascii_digit());
+        valid.then_some(key)
+    })
+}
+
+#[derive(Deserialize)]
+struct Response {
+    fields: Fields,
+}
+
+#[derive(Deserialize)]
+struct Fields {
+    summary: String,
+    status: Status,
+}
+
+#[derive(Deserialize)]
+struct Status {
+    name: String,
+    #[serde(rename = "statusCategory")]
+    category: Category,
+}
+
+#[derive(Deserialize)]
+struct Category {
+    key: String,
+}
+
+/// Fetches one issue through the REST API with curl, like `selfupdate`.
+/// The credentials go in on stdin so they never show up in the process list.
+pub fn fetch(config: &JiraConfig, token: &str, key: &str) -> Result<Issue, io::Error> {
+    let url = format!(
+        "{}/rest/api/2/issue/{}?fields=summary,status",
+        config.url.trim_end_matches('/'),
+        key
+    );
+    let credentials = match &config.user {
+        Some(user) => format!(
+            "user = {}\n",
+            share::curl_quote(&format!("{}:{}", user, token))
+        ),
+        None => format!(
+            "header = {}\n",
+            share::curl_quote(&format!("Authorization: Bearer {}", token))
+        ),
+    };
+    let mut child = Command::new("curl")
+        .args(["--fail", "--silent", "--show-error", "--config", "-"])
+        .args(["--header", "Accept: application/json"])
+        .args(["--user-agent", "todo-rs"])
+        .arg(url)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .map_err(|e| io::Error::new(e.kind(), format!("could not run curl: {}", e)))?;
+    if let Some(mut stdin) = child.stdin.take() {
+        stdin.write_all(credentials.as_bytes())?;
+    }
+    let output = child.wait_with_output()?;
+    if !output.status.success() {
+        let message = String::from_utf8_lossy(&output.stderr);
+        return Err(io::Error::other(format!("{}: {}", key, message.trim())));
+    }
+    let response: Response = serde_json::from_slice(&output.stdout)
+        .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, format!("{}: {}", key, e)))?;
+    Ok(Issue {
+        summary: response.fields.summary,
+        status: response.fields.status.name,
+        done: response.fields.status.category.key == "done",
+    })
+}
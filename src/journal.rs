@@ -0,0 +1,365 @@
+use chrono::NaiveDateTime;
+use serde::{Deserialize, Serialize};
+use std::fs::{File, OpenOptions};
+use std::io::{self, BufRead, BufReader, Read, Seek, SeekFrom, Write};
+use std::path::{Path, PathBuf};
+
+use crate::clock::{
+    self, deserialize_date, deserialize_timestamp, serialize_date, serialize_timestamp,
+};
+
+#[derive(Serialize, Deserialize, Clone)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum EventKind {
+    Created {
+        description: String,
+        #[serde(
+            serialize_with = "serialize_date",
+            deserialize_with = "deserialize_date",
+            default
+        )]
+        deadline: Option<NaiveDateTime>,
+        #[serde(default)]
+        parent: Option<u64>,
+    },
+    Edited {
+        from: String,
+        to: String,
+    },
+    Rescheduled {
+        #[serde(
+            serialize_with = "serialize_date",
+            deserialize_with = "deserialize_date",
+            default
+        )]
+        from: Option<NaiveDateTime>,
+        #[serde(
+            serialize_with = "serialize_date",
+            deserialize_with = "deserialize_date",
+            default
+        )]
+        to: Option<NaiveDateTime>,
+    },
+    Completed,
+    Reopened,
+    Started,
+    Stopped,
+    /// Moved to the Someday bucket.
+    Deferred,
+    /// Brought back from Someday.
+    Resumed,
+    /// Marked private: the description stays hidden unless revealed.
+    MadePrivate,
+    MadePublic,
+    /// Moved under another task, or to the top level when `to` is `None`.
+    Reparented {
+        from: Option<u64>,
+        to: Option<u64>,
+    },
+    /// The command the task launches was set, changed or cleared.
+    CommandChanged {
+        from: Option<String>,
+        to: Option<String>,
+    },
+    /// A file was attached, by its absolute path.
+    Attached {
+        path: String,
+    },
+    Detached {
+        path: String,
+    },
+    Deleted,
+    /// Concurrent changes from another instance were settled; lists the
+    /// fields that kept this session's value and those that took theirs.
+    ConflictResolved {
+        kept: Vec<String>,
+        taken: Vec<String>,
+    },
+    /// Duplicates were merged into this task and deleted; lists their ids
+    /// and descriptions.
+    Merged {
+        from: Vec<(u64, String)>,
+    },
+}
+
+#[derive(Serialize, Deserialize, Clone)]
+pub struct Event {
+    #[serde(
+        serialize_with = "serialize_timestamp",
+        deserialize_with = "deserialize_timestamp"
+    )]
+    pub at: NaiveDateTime,
+    pub task: u64,
+    /// Process that recorded the event, so a session can skip its own changes.
+    #[serde(default)]
+    pub session: u32,
+    /// Who made the change, from the `author` setting, when the list is
+    /// shared between people.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub author: Option<String>,
+    #[serde(flatten)]
+    pub kind: EventKind,
+}
+
+impl Event {
+    pub fn now(task: u64, kind: EventKind) -> Event {
+        Event {
+            at: clock::now(),
+            task,
+            session: std::process::id(),
+            author: None,
+            kind,
+        }
+    }
+}
+
+/// First line of a journal that was compacted. Offsets carry on from the
+/// old journal's rather than starting over, so a session that was watching
+/// it resumes where it was instead of reading everything again.
+#[derive(Serialize, Deserialize, Default)]
+struct Compacted {
+    /// The offset of the file's first byte.
+    base: i64,
+    /// Where the old journal ended, and the events kept from it stop.
+    end: u64,
+    /// Where each event kept from the old journal was in it.
+    offsets: Vec<u64>,
+}
+
+#[derive(Serialize, Deserialize)]
+struct Header {
+    compacted: Compacted,
+}
+
+/// Append-only log of task changes, one JSON event per line; the JSON
+/// store's journal. Offsets are byte positions, past a compacted journal's
+/// header counted from where the old journal left off.
+pub struct Journal {
+    path: PathBuf,
+}
+
+impl Journal {
+    pub fn new(path: impl AsRef<Path>) -> Journal {
+        Journal {
+            path: path.as_ref().to_path_buf(),
+        }
+    }
+
+    pub fn append(&self, event: &Event) -> Result<(), io::Error> {
+        let mut file = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&self.path)?;
+        let line = serde_json::to_string(event)?;
+        writeln!(file, "{}", line)
+    }
+
+    pub fn read_all(&self) -> Result<Vec<Event>, io::Error> {
+        let file = match File::open(&self.path) {
+            Ok(f) => f,
+            Err(ref e) if e.kind() == io::ErrorKind::NotFound => return Ok(Vec::new()),
+            Err(e) => return Err(e),
+        };
+
+        let mut events = Vec::new();
+        for line in BufReader::new(file).lines() {
+            let line = line?;
+            if line.trim().is_empty() {
+                continue;
+            }
+            // A torn final line from an interrupted write shouldn't hide the
+            // rest; the header isn't an event either.
+            if let Ok(event) = serde_json::from_str(&line) {
+                events.push(event);
+            }
+        }
+        Ok(events)
+    }
+
+    /// Reads the complete events appended after `offset`, returning them along
+    /// with the offset to resume from next time.
+    pub fn read_from(&self, offset: u64) -> Result<(Vec<Event>, u64), io::Error> {
+        let mut file = match File::open(&self.path) {
+            Ok(f) => f,
+            Err(ref e) if e.kind() == io::ErrorKind::NotFound => return Ok((Vec::new(), 0)),
+            Err(e) => return Err(e),
+        };
+        let (header, header_len) = read_header(&mut file)?;
+        let len = file.metadata()?.len();
+        if offset as i64 > header.base + len as i64 {
+            // The journal was replaced; start over rather than seeking past the end.
+            return Ok((Vec::new(), 0));
+        }
+        let kept_end = (header.end as i64 - header.base) as u64;
+        let mut events = Vec::new();
+        let start = if offset < header.end {
+            // Kept events it hadn't read yet, then on from the old end.
+            file.seek(SeekFrom::Start(header_len))?;
+            let mut kept = vec![0; (kept_end - header_len) as usize];
+            file.read_exact(&mut kept)?;
+            let lines = String::from_utf8_lossy(&kept).into_owned();
+            for (line, at) in lines.lines().zip(&header.offsets) {
+                if *at >= offset {
+                    events.extend(serde_json::from_str(line).ok());
+                }
+            }
+            kept_end
+        } else {
+            (offset as i64 - header.base) as u64
+        };
+        file.seek(SeekFrom::Start(start))?;
+
+        let mut buf = String::new();
+        file.read_to_string(&mut buf)?;
+        // Leave a partially written last line for the next read.
+        let complete = buf.rfind('\n').map_or(0, |i| i + 1);
+        events.extend(
+            buf[..complete]
+                .lines()
+                .filter_map(|line| serde_json::from_str::<Event>(line).ok()),
+        );
+        let end = header.base + (start + complete as u64) as i64;
+        Ok((events, end as u64))
+    }
+
+    /// Drops the events `keep` marks `false`, given them all in order, and
+    /// returns how many went. The log is replaced through a temporary file
+    /// so an interruption leaves the old one whole, and starts with a
+    /// header that keeps the offsets of other sessions good.
+    pub fn retain(&self, keep: &dyn Fn(&[Event]) -> Vec<bool>) -> Result<usize, io::Error> {
+        let text = match std::fs::read_to_string(&self.path) {
+            Ok(text) => text,
+            Err(ref e) if e.kind() == io::ErrorKind::NotFound => return Ok(0),
+            Err(e) => return Err(e),
+        };
+        let (old, header_len) = parse_header(&text);
+        // Every event with the offset it was at, as `read_from` counts them.
+        let mut entries: Vec<(u64, Event)> = Vec::new();
+        let mut kept_offsets = old.offsets.iter();
+        let kept_end = (old.end as i64 - old.base) as usize;
+        let mut position = header_len as usize;
+        for line in text[position..].split_inclusive('\n') {
+            let at = match kept_offsets.next() {
+                Some(at) if position < kept_end => *at,
+                _ => (old.base + position as i64) as u64,
+            };
+            position += line.len();
+            if let Ok(event) = serde_json::from_str(line.trim_end()) {
+                entries.push((at, event));
+            }
+        }
+        let end = (old.base + text.len() as i64) as u64;
+
+        let events: Vec<Event> = entries.iter().map(|(_, event)| event.clone()).collect();
+        let marks = keep(&events);
+        let kept: Vec<(u64, Event)> = (entries.into_iter().zip(marks))
+            .filter_map(|(entry, keep)| keep.then_some(entry))
+            .collect();
+        let dropped = events.len() - kept.len();
+
+        let mut body = String::new();
+        for (_, event) in &kept {
+            body.push_str(&serde_json::to_string(event)?);
+            body.push('\n');
+        }
+        let mut header = Header {
+            compacted: Compacted {
+                base: 0,
+                end,
+                offsets: kept.iter().map(|(at, _)| *at).collect(),
+            },
+        };
+        // The base is part of the header it counts from, so the header is
+        // padded to a width that fits any base.
+        let width = serde_json::to_string(&header)?.len() + i64::MIN.to_string().len();
+        header.compacted.base = end as i64 - (width + 1 + body.len()) as i64;
+        let header_line = format!("{:width$}", serde_json::to_string(&header)?);
+
+        let temp = self.path.with_extension("jsonl.tmp");
+        let mut file = File::create(&temp)?;
+        writeln!(file, "{}", header_line)?;
+        file.write_all(body.as_bytes())?;
+        file.sync_all()?;
+        std::fs::rename(temp, &self.path)?;
+        Ok(dropped)
+    }
+
+    /// The offset just past the last event, to watch from.
+    pub fn end(&self) -> u64 {
+        let Ok(mut file) = File::open(&self.path) else {
+            return 0;
+        };
+        let base = read_header(&mut file).map_or(0, |(header, _)| header.base);
+        let len = file.metadata().map_or(0, |m| m.len());
+        (base + len as i64) as u64
+    }
+}
+
+/// Whether `line` is a compacted journal's header rather than an event.
+pub fn is_header(line: &str) -> bool {
+    serde_json::from_str::<Header>(line.trim_end()).is_ok()
+}
+
+/// The header of a compacted journal, or an empty one for a journal never
+/// compacted, and the header's length in bytes.
+fn parse_header(text: &str) -> (Compacted, u64) {
+    let first = text.split_inclusive('\n').next().unwrap_or_default();
+    match serde_json::from_str::<Header>(first.trim_end()) {
+        Ok(header) => (header.compacted, first.len() as u64),
+        Err(_) => (Compacted::default(), 0),
+    }
+}
+
+fn read_header(file: &mut File) -> Result<(Compacted, u64), io::Error> {
+    let mut first = String::new();
+    BufReader::new(&mut *file).read_line(&mut first)?;
+    Ok(parse_header(&first))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn tasks(events: &[Event]) -> Vec<u64> {
+        events.iter().map(|e| e.task).collect()
+    }
+
+    #[test]
+    fn watchers_carry_on_across_compaction() {
+        let path =
+            std::env::temp_dir().join(format!("todo-rs-journal-{}.jsonl", std::process::id()));
+        let journal = Journal::new(&path);
+        journal.append(&Event::now(1, EventKind::Started)).unwrap();
+        let behind = journal.end();
+        journal
+            .append(&Event::now(2, EventKind::Completed))
+            .unwrap();
+        journal.append(&Event::now(2, EventKind::Deleted)).unwrap();
+        let (_, caught_up) = journal.read_from(0).unwrap();
+
+        // Task 2's history goes, but for its last event.
+        let keep = |events: &[Event]| {
+            (events.iter().enumerate())
+                .map(|(i, e)| e.task == 1 || i + 1 == events.len())
+                .collect()
+        };
+        assert_eq!(journal.retain(&keep).unwrap(), 1);
+        journal.append(&Event::now(3, EventKind::Started)).unwrap();
+
+        let (news, end) = journal.read_from(caught_up).unwrap();
+        assert_eq!(tasks(&news), [3]);
+        assert_eq!(end, journal.end());
+        assert_eq!(tasks(&journal.read_from(behind).unwrap().0), [2, 3]);
+        assert_eq!(tasks(&journal.read_from(0).unwrap().0), [1, 2, 3]);
+        assert_eq!(tasks(&journal.read_all().unwrap()), [1, 2, 3]);
+
+        // Compacting again keeps counting from the same place.
+        assert_eq!(
+            journal.retain(&|events| vec![true; events.len()]).unwrap(),
+            0
+        );
+        assert!(journal.read_from(end).unwrap().0.is_empty());
+        assert_eq!(tasks(&journal.read_from(behind).unwrap().0), [2, 3]);
+        std::fs::remove_file(&path).unwrap();
+    }
+}
@@ -0,0 +1,174 @@
+use tui::{
+    backend::Backend,
+    layout::{Constraint, Direction, Layout, Rect},
+    style::{Color, Modifier, Style},
+    text::{Span, Spans},
+    widgets::{Block, Borders, List, ListItem, Paragraph},
+    Frame,
+};
+
+use crate::config::WipLimits;
+use crate::tags;
+use crate::tasks::Tasks;
+use crate::{AppState, Task};
+
+#[derive(Clone, Copy, PartialEq)]
+pub enum Column {
+    Todo,
+    InProgress,
+    Done,
+}
+
+impl Column {
+    pub const ALL: [Column; 3] = [Column::Todo, Column::InProgress, Column::Done];
+
+    pub fn of(task: &Task) -> Column {
+        if task.completed {
+            Column::Done
+        } else if task.in_progress {
+            Column::InProgress
+        } else {
+            Column::Todo
+        }
+    }
+
+    fn title(self) -> &'static str {
+        match self {
+            Column::Todo => "To Do",
+            Column::InProgress => "In Progress",
+            Column::Done => "Done",
+        }
+    }
+
+    fn index(self) -> usize {
+        Column::ALL.iter().position(|c| *c == self).unwrap_or(0)
+    }
+
+    /// The neighbouring column, or `self` at either edge.
+    pub fn step(self, forward: bool) -> Column {
+        let index = if forward {
+            (self.index() + 1).min(Column::ALL.len() - 1)
+        } else {
+            self.index().saturating_sub(1)
+        };
+        Column::ALL[index]
+    }
+}
+
+/// Indices of the tasks in `column`, in list order.
+pub fn column_tasks(tasks: &[Task], column: Column) -> Vec<usize> {
+    (0..tasks.len())
+        .filter(|i| Column::of(&tasks[*i]) == column)
+        .collect()
+}
+
+/// Each configured limit with how many in-progress tasks count against it.
+fn usage(tasks: &[Task], limits: &WipLimits) -> Vec<(String, usize, usize)> {
+    let started = || Tasks::new(tasks).query().started();
+    let mut usage = Vec::new();
+    if let Some(limit) = limits.in_progress {
+        usage.push(("In Progress".to_string(), started().count(), limit));
+    }
+    for (tag, limit) in &limits.tags {
+        let count = started().tag(tag).count();
+        usage.push((format!("#{}", tag), count, *limit));
+    }
+    for (project, limit) in &limits.projects {
+        let count = started().project(project).count();
+        usage.push((format!("+{}", project), count, *limit));
+    }
+    usage
+}
+
+/// Limits currently exceeded, e.g. `#work 3/2`.
+pub fn violations(tasks: &[Task], limits: &WipLimits) -> Vec<String> {
+    usage(tasks, limits)
+        .into_iter()
+        .filter(|(_, count, limit)| count > limit)
+        .map(|(name, count, limit)| format!("{} {}/{}", name, count, limit))
+        .collect()
+}
+
+/// The first limit that starting `tasks[index]` would push past its maximum.
+pub fn limit_reached(tasks: &[Task], index: usize, limits: &WipLimits) -> Option<String> {
+    let description = &tasks[index].description;
+    let applies = |name: &str| match name.chars().next() {
+        Some('#') => tags::tags(description).any(|t| t == &name[1..]),
+        Some('+') => tags::projects(description).any(|p| p == &name[1..]),
+        _ => true,
+    };
+    usage(tasks, limits)
+        .into_iter()
+        .find(|(name, count, limit)| applies(name) && count >= limit)
+        .map(|(name, count, limit)| format!("{} ({}/{})", name, count, limit))
+}
+
+pub fn render_kanban<B: Backend>(f: &mut Frame<B>, app_state: &AppState, chunk: Rect) {
+    let violations = violations(&app_state.tasks, &app_state.config.wip);
+    let rows = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints(
+            [
+                Constraint::Min(0),
+                Constraint::Length(if violations.is_empty() { 0 } else { 1 }),
+            ]
+            .as_ref(),
+        )
+        .split(chunk);
+    let columns = Layout::default()
+        .direction(Direction::Horizontal)
+        .constraints([Constraint::Ratio(1, 3); 3].as_ref())
+        .split(rows[0]);
+
+    for (column, area) in Column::ALL.into_iter().zip(columns) {
+        let indices = column_tasks(&app_state.tasks, column);
+        let focused = column == app_state.kanban_column;
+        let items: Vec<ListItem> = indices
+            .iter()
+            .map(|i| {
+                let style = if focused && Some(*i) == app_state.selected_task {
+                    app_state.styles.selected
+                } else if column == Column::Done {
+                    app_state.styles.completed
+                } else {
+                    Style::default()
+                };
+                ListItem::new(Span::styled(
+                    app_state.description(&app_state.tasks[*i]),
+                    style,
+                ))
+            })
+            .collect();
+
+        let mut title_style = Style::default();
+        let mut title = format!("{} ({})", column.title(), indices.len());
+        if column == Column::InProgress {
+            if let Some(limit) = app_state.config.wip.in_progress {
+                title = format!("{} ({}/{})", column.title(), indices.len(), limit);
+                if indices.len() > limit {
+                    title_style = title_style.fg(Color::Red).add_modifier(Modifier::BOLD);
+                }
+            }
+        }
+        let border_style = if focused {
+            app_state.styles.selected
+        } else {
+            Style::default()
+        };
+        let list = List::new(items).block(
+            Block::default()
+                .borders(Borders::ALL)
+                .border_style(border_style)
+                .title(Span::styled(title, title_style)),
+        );
+        f.render_widget(list, area);
+    }
+
+    if !violations.is_empty() {
+        let warning = Paragraph::new(Spans::from(Span::styled(
+            format!("Over WIP limit: {}", violations.join(", ")),
+            Style::default().fg(Color::Red),
+        )));
+        f.render_widget(warning, rows[1]);
+    }
+}
@@ -0,0 +1,375 @@
+use crossterm::event::{KeyCode, KeyEvent, KeyEventKind, KeyModifiers};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+/// A key press, independent of the terminal backend.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Key {
+    Char(char),
+    Ctrl(char),
+    Alt(char),
+    F(u8),
+    Up,
+    Down,
+    Left,
+    Right,
+    Home,
+    End,
+    PageUp,
+    PageDown,
+    BackTab,
+    Backspace,
+    Delete,
+    Insert,
+    Esc,
+}
+
+impl Key {
+    /// Converts a crossterm key event; releases and unmapped keys yield `None`.
+    pub fn from_event(event: KeyEvent) -> Option<Key> {
+        // Windows consoles report releases as well as presses.
+        if event.kind == KeyEventKind::Release {
+            return None;
+        }
+        let key = match event.code {
+            KeyCode::Char(c) if event.modifiers.contains(KeyModifiers::CONTROL) => {
+                Key::Ctrl(c.to_ascii_lowercase())
+            }
+            KeyCode::Char(c) if event.modifiers.contains(KeyModifiers::ALT) => Key::Alt(c),
+            KeyCode::Char(c) => Key::Char(c),
+            KeyCode::Enter => Key::Char('\n'),
+            KeyCode::Tab => Key::Char('\t'),
+            KeyCode::BackTab => Key::BackTab,
+            KeyCode::F(n) => Key::F(n),
+            KeyCode::Up => Key::Up,
+            KeyCode::Down => Key::Down,
+            KeyCode::Left => Key::Left,
+            KeyCode::Right => Key::Right,
+            KeyCode::Home => Key::Home,
+            KeyCode::End => Key::End,
+            KeyCode::PageUp => Key::PageUp,
+            KeyCode::PageDown => Key::PageDown,
+            KeyCode::Backspace => Key::Backspace,
+            KeyCode::Delete => Key::Delete,
+            KeyCode::Insert => Key::Insert,
+            KeyCode::Esc => Key::Esc,
+            _ => return None,
+        };
+        Some(key)
+    }
+}
+
+/// Everything a key can be bound to in Normal mode.
+#[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Debug, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Action {
+    Quit,
+    NewTask,
+    NewSubtask,
+    NewFromTemplate,
+    EditTask,
+    DeleteTask,
+    ToggleComplete,
+    ShowHistory,
+    ShowHeatmap,
+    ShowAgenda,
+    ShowKanban,
+    ShowScratch,
+    Zoom,
+    StartTask,
+    Postpone,
+    ShareTask,
+    BlockTime,
+    SetCommand,
+    RunCommand,
+    Attach,
+    OpenAttachments,
+    ToggleSomeday,
+    ReviewHealth,
+    Undo,
+    Search,
+    FilterByTag,
+    SortNext,
+    SortReverse,
+    SortByDue,
+    PlanDay,
+    ShowSnapshots,
+    ShowTimeline,
+    ShowStorage,
+    ToggleScope,
+    MergeTasks,
+    TogglePrivate,
+    PrivacyScreen,
+    Reveal,
+    NextTab,
+    PrevTab,
+    MoveUp,
+    MoveDown,
+    EditKeybindings,
+    ShowMenu,
+    CommandPalette,
+    Suspend,
+}
+
+impl Action {
+    pub const ALL: [Action; 46] = [
+        Action::Quit,
+        Action::NewTask,
+        Action::NewSubtask,
+        Action::NewFromTemplate,
+        Action::EditTask,
+        Action::DeleteTask,
+        Action::ToggleComplete,
+        Action::ShowHistory,
+        Action::ShowHeatmap,
+        Action::ShowAgenda,
+        Action::ShowKanban,
+        Action::ShowScratch,
+        Action::Zoom,
+        Action::StartTask,
+        Action::Postpone,
+        Action::ShareTask,
+        Action::BlockTime,
+        Action::SetCommand,
+        Action::RunCommand,
+        Action::Attach,
+        Action::OpenAttachments,
+        Action::ToggleSomeday,
+        Action::ReviewHealth,
+        Action::Undo,
+        Action::Search,
+        Action::FilterByTag,
+        Action::SortNext,
+        Action::SortReverse,
+        Action::SortByDue,
+        Action::PlanDay,
+        Action::ShowSnapshots,
+        Action::ShowTimeline,
+        Action::ShowStorage,
+        Action::ToggleScope,
+        Action::MergeTasks,
+        Action::TogglePrivate,
+        Action::PrivacyScreen,
+        Action::Reveal,
+        Action::NextTab,
+        Action::PrevTab,
+        Action::MoveUp,
+        Action::MoveDown,
+        Action::EditKeybindings,
+        Action::ShowMenu,
+        Action::CommandPalette,
+        Action::Suspend,
+    ];
+
+    pub fn default_key(self) -> &'static str {
+        match self {
+            Action::Quit => "q",
+            Action::NewTask => "n",
+            Action::NewSubtask => "N",
+            Action::NewFromTemplate => "T",
+            Action::EditTask => "e",
+            Action::DeleteTask => "d",
+            Action::ToggleComplete => "c",
+            Action::ShowHistory => "h",
+            Action::ShowHeatmap => "H",
+            Action::ShowAgenda => "a",
+            Action::ShowKanban => "b",
+            Action::ShowScratch => "t",
+            Action::Zoom => "z",
+            Action::StartTask => "s",
+            Action::Postpone => "p",
+            Action::ShareTask => "S",
+            Action::BlockTime => "B",
+            Action::SetCommand => "X",
+            Action::RunCommand => "o",
+            Action::Attach => "A",
+            Action::OpenAttachments => "O",
+            Action::ToggleSomeday => "m",
+            Action::ReviewHealth => "R",
+            Action::Undo => "u",
+            Action::Search => "/",
+            Action::FilterByTag => "#",
+            Action::SortNext => ">",
+            Action::SortReverse => "<",
+            Action::SortByDue => "D",
+            Action::PlanDay => "P",
+            Action::ShowSnapshots => "M",
+            Action::ShowTimeline => "C",
+            Action::ShowStorage => "F",
+            Action::ToggleScope => "g",
+            Action::MergeTasks => "J",
+            Action::TogglePrivate => "v",
+            Action::PrivacyScreen => "V",
+            Action::Reveal => "Ctrl+r",
+            Action::NextTab => "Tab",
+            Action::PrevTab => "BackTab",
+            Action::MoveUp => "Up",
+            Action::MoveDown => "Down",
+            Action::EditKeybindings => "K",
+            Action::ShowMenu => "Space",
+            Action::CommandPalette => "Ctrl+p",
+            Action::Suspend => "Ctrl+z",
+        }
+    }
+
+    pub fn description(self) -> &'static str {
+        match self {
+            Action::Quit => "Quit",
+            Action::NewTask => "Add a task",
+            Action::NewSubtask => "Add a subtask to the selected task",
+            Action::NewFromTemplate => "Add a task from a template",
+            Action::EditTask => "Edit the selected task",
+            Action::DeleteTask => "Delete the selected task",
+            Action::ToggleComplete => "Toggle completion",
+            Action::ShowHistory => "Show task history",
+            Action::ShowHeatmap => "Show completion heatmap",
+            Action::ShowAgenda => "Show agenda by deadline",
+            Action::ShowKanban => "Show kanban board",
+            Action::ShowScratch => "Focus the scratch list of session-only tasks",
+            Action::Zoom => "Show the selected task in large text",
+            Action::StartTask => "Start or stop working on the task",
+            Action::Postpone => "Postpone the selected task",
+            Action::ShareTask => "Share the task as .ics or email",
+            Action::BlockTime => "Block time for the task in the calendar",
+            Action::SetCommand => "Set the command the task opens",
+            Action::RunCommand => "Run the task's command, or open its first link",
+            Action::Attach => "Attach a file to the task",
+            Action::OpenAttachments => "Open the task's attachments",
+            Action::ToggleSomeday => "Move the task to or from Someday",
+            Action::ReviewHealth => "Review overdue, stale or unscheduled tasks",
+            Action::Undo => "Undo moving stale tasks to Someday",
+            Action::Search => "Filter tasks with a search query",
+            Action::FilterByTag => "Filter by one of the task's tags",
+            Action::SortNext => "Sort by the next column",
+            Action::SortReverse => "Reverse the sort order",
+            Action::SortByDue => "Keep the list sorted by deadline, or back to manual",
+            Action::PlanDay => "Plan the day within capacity",
+            Action::ShowSnapshots => "Look back through snapshots and restore tasks from one",
+            Action::ShowTimeline => "Lay today's tasks and events out hour by hour",
+            Action::ShowStorage => "Show what the data files take, and tidy them up",
+            Action::ToggleScope => "Narrow to the current project, or back to all projects",
+            Action::MergeTasks => "Combine duplicate tasks into one, field by field",
+            Action::TogglePrivate => "Mark the selected task private, or not",
+            Action::PrivacyScreen => "Hide all descriptions but their tags, for screen sharing",
+            Action::Reveal => "Show the selected task's hidden description, or hide it again",
+            Action::NextTab => "Next filter tab",
+            Action::PrevTab => "Previous filter tab",
+            Action::MoveUp => "Move selection up",
+            Action::MoveDown => "Move selection down",
+            Action::EditKeybindings => "Edit keybindings",
+            Action::ShowMenu => "Pick an action from a menu, without key combinations",
+            Action::CommandPalette => "Search for an action and run it",
+            Action::Suspend => "Suspend to the shell",
+        }
+    }
+}
+
+pub struct Keymap {
+    bindings: BTreeMap<Action, Key>,
+}
+
+impl Keymap {
+    /// Builds the keymap from config entries, falling back to the default key
+    /// for any action the config leaves out.
+    pub fn from_config(entries: &BTreeMap<Action, String>) -> Result<Keymap, String> {
+        let mut bindings = BTreeMap::new();
+        for action in Action::ALL {
+            let spec = entries
+                .get(&action)
+                .map(String::as_str)
+                .unwrap_or(action.default_key());
+            let key = parse_key(spec)
+                .ok_or_else(|| format!("invalid key '{}' for {:?}", spec, action))?;
+            bindings.insert(action, key);
+        }
+        Ok(Keymap { bindings })
+    }
+
+    pub fn key_for(&self, action: Action) -> Key {
+        self.bindings[&action]
+    }
+
+    pub fn action_for(&self, key: Key) -> Option<Action> {
+        self.bindings
+            .iter()
+            .find(|(_, bound)| **bound == key)
+            .map(|(action, _)| *action)
+    }
+}
+
+impl Default for Keymap {
+    fn default() -> Keymap {
+        Keymap::from_config(&BTreeMap::new()).expect("default keys are valid")
+    }
+}
+
+/// Parses key specs such as `q`, `Enter`, `Up`, `Ctrl+p`, `Alt+x` or `F5`.
+pub fn parse_key(spec: &str) -> Option<Key> {
+    if let Some(rest) = spec.strip_prefix("Ctrl+") {
+        return single_char(rest).map(|c| Key::Ctrl(c.to_ascii_lowercase()));
+    }
+    if let Some(rest) = spec.strip_prefix("Alt+") {
+        return single_char(rest).map(Key::Alt);
+    }
+    if let Some(c) = single_char(spec) {
+        return Some(Key::Char(c));
+    }
+
+    let key = match spec {
+        "Up" => Key::Up,
+        "Down" => Key::Down,
+        "Left" => Key::Left,
+        "Right" => Key::Right,
+        "Home" => Key::Home,
+        "End" => Key::End,
+        "PageUp" => Key::PageUp,
+        "PageDown" => Key::PageDown,
+        "Enter" => Key::Char('\n'),
+        "Tab" => Key::Char('\t'),
+        "BackTab" => Key::BackTab,
+        "Space" => Key::Char(' '),
+        "Backspace" => Key::Backspace,
+        "Delete" => Key::Delete,
+        "Insert" => Key::Insert,
+        "Esc" => Key::Esc,
+        _ => {
+            let n: u8 = spec.strip_prefix('F')?.parse().ok()?;
+            Key::F(n)
+        }
+    };
+    Some(key)
+}
+
+/// Inverse of [`parse_key`], used when showing bindings to the user.
+pub fn format_key(key: Key) -> String {
+    match key {
+        Key::Char('\n') => "Enter".to_string(),
+        Key::Char('\t') => "Tab".to_string(),
+        Key::Char(' ') => "Space".to_string(),
+        Key::Char(c) => c.to_string(),
+        Key::Ctrl(c) => format!("Ctrl+{}", c),
+        Key::Alt(c) => format!("Alt+{}", c),
+        Key::F(n) => format!("F{}", n),
+        Key::Up => "Up".to_string(),
+        Key::Down => "Down".to_string(),
+        Key::Left => "Left".to_string(),
+        Key::Right => "Right".to_string(),
+        Key::Home => "Home".to_string(),
+        Key::End => "End".to_string(),
+        Key::PageUp => "PageUp".to_string(),
+        Key::PageDown => "PageDown".to_string(),
+        Key::Backspace => "Backspace".to_string(),
+        Key::Delete => "Delete".to_string(),
+        Key::Insert => "Insert".to_string(),
+        Key::Esc => "Esc".to_string(),
+        Key::BackTab => "BackTab".to_string(),
+    }
+}
+
+fn single_char(s: &str) -> Option<char> {
+    let mut chars = s.chars();
+    match (chars.next(), chars.next()) {
+        (Some(c), None) => Some(c),
+        _ => None,
+    }
+}
@@ -0,0 +1,34 @@
+//! Tasks, the search language and queries over a list of them, and the
+//! batches of operations that change one, for tools that work with a
+//! todo-rs list. The app itself is the `todo-rs` binary.
+//!
+//! ```no_run
+//! use todo_rs::tasks::SortKey;
+//! use todo_rs::{query, Task, Tasks};
+//!
+//! let tasks: Vec<Task> = serde_json::from_str(&std::fs::read_to_string("tasks.json")?)?;
+//! let search = query::parse("#work OR +website", &Default::default())?;
+//! let found = Tasks::new(&tasks)
+//!     .query()
+//!     .open()
+//!     .matching(&search)
+//!     .sorted_by(SortKey::Urgency, false);
+//! for task in found.tasks() {
+//!     println!("{} {}", task.id, task.description);
+//! }
+//! # Ok::<(), Box<dyn std::error::Error>>(())
+//! ```
+
+pub mod clock;
+pub mod estimate;
+pub mod journal;
+pub mod ops;
+pub mod privacy;
+pub mod query;
+pub mod tags;
+pub mod task;
+pub mod tasks;
+pub mod urgency;
+
+pub use task::Task;
+pub use tasks::Tasks;
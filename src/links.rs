@@ -0,0 +1,46 @@
+use tui::{style::Style, text::Span};
+
+/// Trailing characters that end a sentence rather than the link.
+const TRAILING: [char; 8] = ['.', ',', ';', ':', '!', '?', ')', '\''];
+
+fn is_link(word: &str) -> bool {
+    word.starts_with("https://") || word.starts_with("http://")
+}
+
+/// The `http(s)://` links written in a description, in order.
+pub fn find(description: &str) -> impl Iterator<Item = &str> {
+    description
+        .split_whitespace()
+        .filter(|word| is_link(word))
+        .map(|word| word.trim_end_matches(TRAILING))
+}
+
+/// The host a link points at, e.g. `github.com` for
+/// `https://github.com/Rlyehan/todo-rs/issues/1`.
+fn host(link: &str) -> &str {
+    let rest = link.split_once("://").map_or(link, |(_, rest)| rest);
+    let host = rest.split(['/', '?', '#']).next().unwrap_or(rest);
+    host.strip_prefix("www.").unwrap_or(host)
+}
+
+/// The description with each link shortened to its host in brackets and
+/// dimmed, so long URLs don't crowd out the task text.
+pub fn shorten(description: &str, style: Style, dim: Style) -> Vec<Span<'static>> {
+    let mut spans = Vec::new();
+    let mut text = String::new();
+    for (i, word) in description.split(' ').enumerate() {
+        if i > 0 {
+            text.push(' ');
+        }
+        if !is_link(word) {
+            text.push_str(word);
+            continue;
+        }
+        let link = word.trim_end_matches(TRAILING);
+        spans.push(Span::styled(std::mem::take(&mut text), style));
+        spans.push(Span::styled(format!("[{}]", host(link)), dim));
+        text.push_str(&word[link.len()..]);
+    }
+    spans.push(Span::styled(text, style));
+    spans
+}
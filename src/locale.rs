@@ -0,0 +1,159 @@
+use chrono::Weekday;
+use serde::{Deserialize, Serialize};
+
+use crate::config::WeekStart;
+
+/// The language deadline presets are shown in and typed dates are read in.
+/// English presets are understood whatever the locale.
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum Locale {
+    #[default]
+    En,
+    De,
+}
+
+/// A locale's words for the presets, as labels write them.
+struct Words {
+    today: &'static str,
+    tomorrow: &'static str,
+    this_week: &'static str,
+    /// The forms of "next" before a weekday, the first being the label's.
+    next: &'static [&'static str],
+    /// Monday first.
+    weekdays: [&'static str; 7],
+    /// Short forms, Monday first.
+    short_weekdays: [&'static str; 7],
+}
+
+const GERMAN: Words = Words {
+    today: "Heute",
+    tomorrow: "Morgen",
+    this_week: "Diese Woche",
+    next: &[
+        "Nächsten",
+        "Nächster",
+        "Nächste",
+        "Naechsten",
+        "Naechster",
+        "Naechste",
+    ],
+    weekdays: [
+        "Montag",
+        "Dienstag",
+        "Mittwoch",
+        "Donnerstag",
+        "Freitag",
+        "Samstag",
+        "Sonntag",
+    ],
+    short_weekdays: ["Mo", "Di", "Mi", "Do", "Fr", "Sa", "So"],
+};
+
+const ENGLISH_WEEKDAYS: [&str; 7] = [
+    "monday",
+    "tuesday",
+    "wednesday",
+    "thursday",
+    "friday",
+    "saturday",
+    "sunday",
+];
+
+impl Locale {
+    fn words(self) -> Option<&'static Words> {
+        match self {
+            Locale::En => None,
+            Locale::De => Some(&GERMAN),
+        }
+    }
+
+    /// The first day of the week where the config doesn't set one.
+    pub fn week_start(self) -> WeekStart {
+        match self {
+            Locale::En => WeekStart::Sunday,
+            Locale::De => WeekStart::Monday,
+        }
+    }
+}
+
+/// `text` as the English preset `calculate_deadline` reads, e.g.
+/// `next friday` for `nächsten Freitag`; anything else comes back
+/// lowercased.
+pub fn to_english(text: &str, locale: Locale) -> String {
+    let text = text.trim().to_lowercase();
+    let Some(words) = locale.words() else {
+        return text;
+    };
+    let is = |word: &str, text: &str| word.to_lowercase() == text;
+    if is(words.today, &text) {
+        return "today".to_string();
+    }
+    if is(words.tomorrow, &text) {
+        return "tomorrow".to_string();
+    }
+    if is(words.this_week, &text) {
+        return "this week".to_string();
+    }
+    let weekday = text.split_once(' ').and_then(|(next, day)| {
+        words.next.iter().find(|word| is(word, next))?;
+        (words.weekdays.iter().chain(&words.short_weekdays))
+            .position(|name| is(name, day.trim()))
+            .map(|i| i % 7)
+    });
+    match weekday {
+        Some(i) => format!("next {}", ENGLISH_WEEKDAYS[i]),
+        None => text,
+    }
+}
+
+/// How `preset` is shown in `locale`, e.g. `Nächsten Freitag` for
+/// `Next Friday` in German. Presets it has no words for are shown as
+/// written.
+pub fn label(preset: &str, locale: Locale) -> String {
+    let Some(words) = locale.words() else {
+        return preset.to_string();
+    };
+    let english = to_english(preset, locale);
+    match english.as_str() {
+        "today" => words.today.to_string(),
+        "tomorrow" => words.tomorrow.to_string(),
+        "this week" => words.this_week.to_string(),
+        other => {
+            let weekday =
+                (other.strip_prefix("next ")).and_then(|day| day.trim().parse::<Weekday>().ok());
+            let Some(weekday) = weekday else {
+                return preset.to_string();
+            };
+            let day = words.weekdays[weekday.num_days_from_monday() as usize];
+            format!("{} {}", words.next[0], day)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn reads_german_presets() {
+        assert_eq!(to_english(" Heute ", Locale::De), "today");
+        assert_eq!(to_english("morgen", Locale::De), "tomorrow");
+        assert_eq!(to_english("diese woche", Locale::De), "this week");
+        assert_eq!(to_english("Nächsten Freitag", Locale::De), "next friday");
+        assert_eq!(to_english("naechster mo", Locale::De), "next monday");
+        assert_eq!(to_english("Next Friday", Locale::De), "next friday");
+        assert_eq!(
+            to_english("nächsten Freitag", Locale::En),
+            "nächsten freitag"
+        );
+    }
+
+    #[test]
+    fn labels_presets_in_the_locale() {
+        assert_eq!(label("Tomorrow", Locale::De), "Morgen");
+        assert_eq!(label("Next Sunday", Locale::De), "Nächsten Sonntag");
+        assert_eq!(label("Next Sunday", Locale::En), "Next Sunday");
+        assert_eq!(label("In 2 weeks", Locale::De), "In 2 weeks");
+    }
+}
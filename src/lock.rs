@@ -0,0 +1,115 @@
+use std::time::{Duration, Instant};
+use tui::{
+    backend::Backend,
+    layout::{Alignment, Rect},
+    style::{Modifier, Style},
+    text::Spans,
+    widgets::{Block, Borders, Paragraph},
+    Frame,
+};
+
+use crate::keymap::Key;
+use crate::secrets;
+use crate::{AppState, Mode};
+
+/// The screen while nobody is at the keyboard: nothing of the list shows
+/// until it is unlocked, with the passphrase when one is set.
+pub struct Lock {
+    /// Where to go back to once unlocked.
+    previous: Mode,
+    /// The passphrase typed so far.
+    typed: String,
+    error: Option<String>,
+}
+
+/// Locks the screen once there has been no input for `lock.after_minutes`.
+pub fn lock_when_idle(app_state: &mut AppState) {
+    let Some(minutes) = app_state.config.lock.after_minutes else {
+        return;
+    };
+    let Some(last_input) = app_state.last_input else {
+        return;
+    };
+    let idle = Instant::now().duration_since(last_input);
+    if app_state.mode == Mode::Locked || idle < Duration::from_secs(minutes as u64 * 60) {
+        return;
+    }
+    let previous = std::mem::replace(&mut app_state.mode, Mode::Locked);
+    app_state.lock = Some(Lock {
+        previous,
+        typed: String::new(),
+        error: None,
+    });
+}
+
+/// Takes every key while locked, so none reaches the list underneath.
+pub fn handle_key(app_state: &mut AppState, key: Key) {
+    let Some(lock) = app_state.lock.as_mut() else {
+        app_state.mode = Mode::Normal;
+        return;
+    };
+    if let Some(passphrase) = &app_state.config.lock.passphrase {
+        match key {
+            Key::Char('\n') => {}
+            Key::Char(c) => {
+                lock.typed.push(c);
+                return;
+            }
+            Key::Backspace => {
+                lock.typed.pop();
+                return;
+            }
+            Key::Esc => {
+                lock.typed.clear();
+                return;
+            }
+            _ => return,
+        }
+        let typed = std::mem::take(&mut lock.typed);
+        match secrets::lookup(passphrase) {
+            Some(expected) if expected == typed => {}
+            Some(_) => {
+                lock.error = Some("Wrong passphrase".to_string());
+                return;
+            }
+            None => {
+                lock.error = Some("Couldn't read the passphrase from the keyring".to_string());
+                return;
+            }
+        }
+    }
+    if let Some(lock) = app_state.lock.take() {
+        app_state.mode = lock.previous;
+    }
+}
+
+pub fn render_lock<B: Backend>(f: &mut Frame<B>, app_state: &AppState, size: Rect) {
+    let Some(lock) = &app_state.lock else {
+        return;
+    };
+    let prompt = if app_state.config.lock.passphrase.is_some() {
+        format!("Passphrase: {}", "*".repeat(lock.typed.chars().count()))
+    } else {
+        "Press any key to unlock".to_string()
+    };
+    let mut text: Vec<Spans> = vec![prompt.into()];
+    if let Some(error) = &lock.error {
+        text.push(error.clone().into());
+    }
+    let height = text.len() as u16 + 2;
+    let width = 50.min(size.width);
+    let area = Rect {
+        x: size.x + (size.width - width) / 2,
+        y: size.y + size.height.saturating_sub(height) / 2,
+        width,
+        height: height.min(size.height),
+    };
+    let block = Block::default()
+        .borders(Borders::ALL)
+        .title("Locked")
+        .border_style(Style::default().add_modifier(Modifier::BOLD));
+    let paragraph = Paragraph::new(text)
+        .block(block)
+        .alignment(Alignment::Center);
+    f.render_widget(paragraph, area);
+}
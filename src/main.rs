@@ -1,44 +1,123 @@
+mod agenda;
+mod archive;
+mod attachments;
+mod bulk;
+mod calendar;
+mod capture;
+mod cleanup;
+mod cli;
+mod columns;
+mod completion;
+mod config;
+mod conflict;
+mod delegate;
+mod demo;
+mod detail;
+mod doctor;
+mod due;
+mod emoji;
+mod eventlog;
+mod events;
+mod export;
+mod focus;
+mod health;
+mod heatmap;
+mod hints;
+mod history;
+mod importers;
+mod inherit;
+mod jira;
+mod kanban;
+mod keymap;
+mod links;
+mod locale;
+mod lock;
+mod maintenance;
+mod menu;
+mod merge;
+mod milestones;
+mod notify;
+mod outline;
+mod palette;
+mod plan;
+mod recovery;
+mod recurrence;
+mod rrule;
+mod rules;
+mod schedule;
+mod scope;
+mod scratch;
+mod script;
+mod search;
+mod secrets;
+mod selfupdate;
+mod session;
+mod settings;
+mod share;
+mod snapshots;
+mod statusbar;
+mod store;
+mod subtasks;
+mod tabs;
+mod templates;
+mod terminal;
+mod timeline;
+mod tutorial;
+mod whatsnew;
+mod wizard;
+mod workdays;
+mod workspace;
+mod zoom;
+
 use chrono::Datelike;
-use chrono::NaiveDateTime;
-use serde::{Deserialize, Serialize};
-use std::fs::File;
-use std::io::{self};
-use std::io::{BufReader, Stdout};
-use termion::{event::Key, input::TermRead, raw::IntoRawMode, raw::RawTerminal};
+use chrono::{NaiveDate, NaiveDateTime};
+use std::collections::{BTreeMap, BTreeSet};
+use std::io::{self, IsTerminal};
+use std::path::PathBuf;
+use std::time::{Duration, Instant, SystemTime};
+use unicode_width::UnicodeWidthStr;
+
+use columns::RowInfo;
+use completion::Completion;
+use config::{Config, Styles, WeekStart};
+use conflict::{Field, Value};
+use crossterm::event::{MouseButton, MouseEvent, MouseEventKind};
+use emoji::EmojiPicker;
+use health::{Health, Problem};
+use journal::{Event, EventKind};
+use kanban::Column;
+use keymap::{Action, Key, Keymap};
+use locale::Locale;
+use ops::Operation;
+use rules::Rules;
+use scratch::Scratch;
+use session::SessionState;
+use settings::{EditorCommand, KeybindingEditor};
+use templates::TemplateFill;
+use todo_rs::tasks::{SortKey, Tasks};
+use todo_rs::{clock, estimate, journal, ops, privacy, query, tags, tasks, urgency, Task};
 use tui::{
-    backend::{Backend, TermionBackend},
-    layout::{Constraint, Direction, Layout, Rect},
+    backend::Backend,
+    layout::{Alignment, Constraint, Direction, Layout, Rect},
     style::{Color, Modifier, Style},
-    text::Text,
-    widgets::{Block, Borders, List, ListItem, Paragraph},
-    Frame, Terminal,
+    text::Span,
+    widgets::{Block, Borders, Paragraph, Row, Table, TableState, Wrap},
+    Frame,
 };
+use wizard::Wizard;
+use workdays::Workdays;
 
-#[derive(Serialize, Deserialize)]
-struct Task {
-    description: String,
-    completed: bool,
-
-    #[serde(
-        serialize_with = "serialize_date",
-        deserialize_with = "deserialize_date",
-        default
-    )]
-    deadline: Option<NaiveDateTime>,
-}
+const TASKS_FILE: &str = "tasks.json";
+const JOURNAL_FILE: &str = "journal.jsonl";
+const DATABASE_FILE: &str = "todo.db";
 
-impl Task {
-    fn new(description: String, deadline: Option<NaiveDateTime>) -> Task {
-        Task {
-            description,
-            completed: false,
-            deadline,
-        }
-    }
+/// Below this size the layout can't fit its borders and input line.
+const MIN_WIDTH: u16 = 40;
+const MIN_HEIGHT: u16 = 10;
 
-    fn toggle_completed(&mut self) {
-        self.completed = !self.completed;
-    }
+struct StatusMessage {
+    text: String,
+    is_error: bool,
 }
 
 #[derive(PartialEq)]
@@ -48,8 +127,46 @@ enum Mode {
     Edit,
     DeleteConfirm,
     DeadlineInput,
+    DeadlineEntry,
+    History,
+    Setup,
+    Keybindings,
+    Heatmap,
+    Agenda,
+    Kanban,
+    Postpone,
+    CompleteConfirm,
+    Share,
+    Search,
+    Scratch,
+    Template,
+    TagPick,
+    Placeholder,
+    WhatsNew,
+    Zoom,
+    CommandInput,
+    AttachInput,
+    ActionConfirm,
+    /// A bulk change is listed, waiting for `y`.
+    BulkConfirm,
+    Menu,
+    Conflict,
+    Plan,
+    Snapshots,
+    /// What the data files take; see `maintenance`.
+    Storage,
+    Timeline,
+    Recover,
+    /// Marking duplicates, then picking what they keep; see `merge`.
+    MergePick,
+    Merge,
+    /// Blanked after `lock.after_minutes` without input.
+    Locked,
 }
 
+/// Makes a chord of a plain key, as `Key::Ctrl` does.
+type Chord = fn(char) -> Key;
+
 struct AppState {
     tasks: Vec<Task>,
     input: String,
@@ -57,10 +174,127 @@ struct AppState {
     selected_task: Option<usize>,
     temp_description: String,
     setting_deadline: bool,
+    /// Why the typed deadline was refused, or what to confirm about it.
+    deadline_notice: Option<StatusMessage>,
+    editing: bool,
+    /// Parent of the task being typed, when adding a subtask.
+    new_parent: Option<u64>,
+    /// The main list's task file and journal; in memory until
+    /// `load_tasks` opens the configured store.
+    store: Box<dyn store::TaskStore>,
+    journal_offset: u64,
+    /// Highest task id in the journal: read once at load, then kept up to
+    /// date as events are appended and merged in.
+    journal_max_id: u64,
+    /// Changes from other instances that clash with this session's, oldest
+    /// first, waiting on the resolution screen.
+    conflicts: Vec<conflict::Conflict>,
+    history: Vec<Event>,
+    heatmap: BTreeMap<NaiveDate, usize>,
+    /// Today's and this week's focus time, shown under the heatmap.
+    focus: Option<(focus::Report, focus::Report)>,
+    agenda_selected: usize,
+    /// Hides every description but its tags, for screen sharing.
+    privacy_screen: bool,
+    /// Task whose description shows despite being private or behind the
+    /// privacy screen.
+    revealed: Option<u64>,
+    /// The day being planned, while in `Mode::Plan`.
+    planner: Option<plan::Planner>,
+    timeline: Option<timeline::Timeline>,
+    /// The day date-derived state was last worked out for; see
+    /// `roll_over_day`.
+    day: Option<NaiveDate>,
+    /// Open while looking back through snapshots, in `Mode::Snapshots`.
+    snapshots: Option<snapshots::Browser>,
+    /// Use of the app since the last snapshot, and when the last key came.
+    active_time: Duration,
+    last_input: Option<Instant>,
+    menu_selected: usize,
+    /// What has been typed into the action palette.
+    menu_query: String,
+    /// The action waiting for `y` in ActionConfirm mode.
+    confirming: Option<Action>,
+    /// The bulk change waiting for `y` in BulkConfirm mode.
+    pending: Option<bulk::Pending>,
+    /// The last key and when it came, for dropping repeats.
+    last_key: Option<(Key, Instant)>,
+    /// The chord `sticky_key` is holding for the next key, and since when.
+    held: Option<(Chord, Instant)>,
+    kanban_column: Column,
+    config: Config,
+    keymap: Keymap,
+    styles: Styles,
+    /// What the terminal can show; without colors, the theme falls back
+    /// to text attributes and the list marks states with symbols.
+    palette: palette::Support,
+    workdays: Workdays,
+    rules: Rules,
+    config_modified: Option<SystemTime>,
+    status: Option<StatusMessage>,
+    data_dir: PathBuf,
+    dirty: bool,
+    wizard: Option<Wizard>,
+    keybinding_editor: KeybindingEditor,
+    suspend_requested: bool,
+    session: SessionState,
+    /// Linked Jira issues as last synced.
+    jira: jira::Issues,
+    /// Archived task ids per project, for the project tabs' progress.
+    archived_projects: BTreeMap<String, BTreeSet<u64>>,
+    body_area: Rect,
+    dragging_split: bool,
+    /// Tasks just moved to Someday automatically, while that can be undone.
+    auto_deferred: Option<(Instant, Vec<u64>)>,
+    /// Active search, as typed and parsed.
+    filter: Option<(String, query::Query)>,
+    sort: SortKey,
+    sort_descending: bool,
+    /// Set while the filter lists the tasks behind a health problem.
+    reviewing: Option<Problem>,
+    /// Worked out between frames, since staleness needs the journal.
+    health: Option<Health>,
+    /// The search prompt takes a regex instead of a query.
+    search_regex: bool,
+    /// Results of the query in the search prompt, while it's open.
+    search: Option<search::Search>,
+    /// The attach prompt copies the file into the data directory.
+    attach_copy: bool,
+    /// Tags of the selected task, offered as filters by `FilterByTag`.
+    tag_choices: Vec<String>,
+    /// Selected filter tab; see [`tabs::tabs`].
+    tab: usize,
+    scratch: Scratch,
+    /// Tab completion of the token being typed in the input line.
+    completion: Option<Completion>,
+    /// Open over Input and Edit mode while picking a symbol.
+    emoji_picker: Option<EmojiPicker>,
+    /// Template whose placeholders are being asked for.
+    template_fill: Option<TemplateFill>,
+    /// Release notes to show after an upgrade.
+    whats_new: Vec<whatsnew::Release>,
+    /// Command to run once the main loop has handed over the terminal.
+    pending_command: Option<String>,
+    /// Tasks an earlier run couldn't save, offered back in Recover mode.
+    recovery: Option<(PathBuf, recovery::Recovery)>,
+    /// Where this run kept its tasks after a failed save; removed once
+    /// saving works again.
+    recovery_file: Option<PathBuf>,
+    /// The guided steps of `todo-rs tutorial`, shown above the list.
+    tutorial: Option<tutorial::Tutorial>,
+    /// Set while Locked.
+    lock: Option<lock::Lock>,
+    /// Worked out when the storage screen opens and after each action on it.
+    maintenance: Option<maintenance::Report>,
+    /// The project or file everything is narrowed to, if any.
+    scope: Option<scope::Scope>,
+    /// Tasks marked for merging, in MergePick and Merge mode.
+    merge: Option<merge::Merge>,
 }
 
 impl AppState {
     fn new() -> AppState {
+        let defaults = config::Loaded::default();
         AppState {
             tasks: Vec::new(),
             input: String::new(),
@@ -68,294 +302,3346 @@ impl AppState {
             selected_task: Some(0),
             temp_description: String::new(),
             setting_deadline: false,
+            deadline_notice: None,
+            editing: false,
+            new_parent: None,
+            store: Box::new(store::MemoryStore::default()),
+            journal_offset: 0,
+            journal_max_id: 0,
+            conflicts: Vec::new(),
+            history: Vec::new(),
+            heatmap: BTreeMap::new(),
+            focus: None,
+            agenda_selected: 0,
+            privacy_screen: false,
+            revealed: None,
+            planner: None,
+            timeline: None,
+            day: None,
+            snapshots: None,
+            active_time: Duration::ZERO,
+            last_input: Some(Instant::now()),
+            menu_selected: 0,
+            menu_query: String::new(),
+            confirming: None,
+            pending: None,
+            last_key: None,
+            held: None,
+            kanban_column: Column::Todo,
+            config: defaults.config,
+            keymap: defaults.keymap,
+            styles: palette::fit(palette::detect(), defaults.styles),
+            palette: palette::detect(),
+            workdays: defaults.workdays,
+            rules: defaults.rules,
+            config_modified: None,
+            status: None,
+            data_dir: PathBuf::from("."),
+            dirty: false,
+            wizard: None,
+            keybinding_editor: KeybindingEditor::new(),
+            suspend_requested: false,
+            session: SessionState::default(),
+            jira: jira::Issues::default(),
+            archived_projects: BTreeMap::new(),
+            body_area: Rect::default(),
+            dragging_split: false,
+            auto_deferred: None,
+            filter: None,
+            sort: SortKey::Manual,
+            sort_descending: false,
+            reviewing: None,
+            health: None,
+            search_regex: false,
+            search: None,
+            tag_choices: Vec::new(),
+            attach_copy: false,
+            tab: 0,
+            scratch: Scratch::default(),
+            completion: None,
+            emoji_picker: None,
+            template_fill: None,
+            whats_new: Vec::new(),
+            pending_command: None,
+            recovery: None,
+            recovery_file: None,
+            tutorial: None,
+            lock: None,
+            maintenance: None,
+            scope: None,
+            merge: None,
         }
     }
 
-    fn add_task(&mut self, description: String, deadline: Option<NaiveDateTime>) {
-        let task = Task::new(description, deadline);
-        self.tasks.push(task);
+    /// Saves the session, unless the store keeps nothing. A failed save
+    /// only loses layout and reminders, so it isn't reported.
+    fn save_session(&self) {
+        if self.store.persists() {
+            let _ = self.session.save(&self.data_dir);
+        }
     }
 
-    fn update_task(&mut self, description: String, deadline: Option<NaiveDateTime>) {
-        if let Some(index) = self.selected_task {
-            if let Some(task) = self.tasks.get_mut(index) {
-                task.description = description;
-                task.deadline = deadline;
-            }
-        }
+    fn set_status(&mut self, text: String, is_error: bool) {
+        self.status = Some(StatusMessage { text, is_error });
     }
 
-    fn delete_task(&mut self) {
-        if let Some(index) = self.selected_task {
-            if index < self.tasks.len() {
-                self.tasks.remove(index);
+    /// Loads the config file, keeping the current settings if it fails to parse.
+    fn reload_config(&mut self) -> bool {
+        self.config_modified = Config::modified();
+        match Config::load() {
+            Ok(loaded) => {
+                self.apply_config(loaded);
+                true
+            }
+            Err(e) => {
+                self.set_status(format!("Config error: {}", e), true);
+                false
             }
         }
     }
 
-    fn load_tasks(&mut self, file_path: &str) -> Result<(), io::Error> {
-        let file = match File::open(file_path) {
-            Ok(f) => f,
-            Err(ref e) if e.kind() == io::ErrorKind::NotFound => {
-                let file = File::create(file_path)?;
-                serde_json::to_writer(&file, &Vec::<Task>::new())?;
-                return Ok(());
-            }
-            Err(e) => return Err(e),
+    fn apply_config(&mut self, loaded: config::Loaded) {
+        self.config = loaded.config;
+        self.keymap = loaded.keymap;
+        self.styles = palette::fit(self.palette, loaded.styles);
+        clock::set_zone(loaded.zone);
+        clock::set_home_zone(loaded.home_zone);
+        self.workdays = loaded.workdays;
+        self.rules = loaded.rules;
+    }
+
+    fn start_setup(&mut self) {
+        self.wizard = Some(Wizard::new());
+        self.mode = Mode::Setup;
+    }
+
+    /// Saves and applies the wizard's answers, then opens the chosen data directory.
+    fn finish_setup(&mut self) {
+        let Some(wizard) = self.wizard.take() else {
+            return;
         };
+        self.mode = Mode::Normal;
 
-        let reader = BufReader::new(file);
-        match serde_json::from_reader(reader) {
-            Ok(tasks) => {
-                self.tasks = tasks;
-                Ok(())
+        let saved = wizard.config.save();
+        self.config_modified = Config::modified();
+        match wizard.config.validate() {
+            Ok(loaded) => self.apply_config(loaded),
+            Err(e) => self.set_status(format!("Config error: {}", e), true),
+        }
+
+        if let Err(e) = self.load_tasks() {
+            self.set_status(format!("Error loading tasks: {}", e), true);
+        } else if let Err(e) = saved {
+            self.set_status(format!("Could not save config: {}", e), true);
+        } else {
+            self.set_status("Setup complete, settings saved".to_string(), false);
+        }
+        self.check_upgrade();
+    }
+
+    /// Binds `key` to `action` in the config file, refusing keys that are
+    /// already taken by another action.
+    fn rebind(&mut self, action: Action, key: Option<Key>) -> Result<(), String> {
+        let mut config = self.config.clone();
+        match key {
+            Some(key) => {
+                if let Some(other) = self.keymap.action_for(key).filter(|a| *a != action) {
+                    return Err(format!(
+                        "'{}' is already bound to \"{}\"",
+                        keymap::format_key(key),
+                        other.description()
+                    ));
+                }
+                config.keybindings.insert(action, keymap::format_key(key));
             }
-            Err(e) => Err(io::Error::new(io::ErrorKind::Other, e)),
+            None => {
+                config.keybindings.remove(&action);
+            }
+        }
+
+        let loaded = config.validate()?;
+        if let Some(other) = Action::ALL
+            .iter()
+            .find(|a| **a != action && loaded.keymap.key_for(**a) == loaded.keymap.key_for(action))
+        {
+            return Err(format!(
+                "default key conflicts with \"{}\"",
+                other.description()
+            ));
         }
+        self.apply_config(loaded);
+        self.config
+            .save()
+            .map_err(|e| format!("Could not save config: {}", e))?;
+        self.config_modified = Config::modified();
+        Ok(())
     }
 
-    fn save_tasks(&self, file_path: &str) -> Result<(), io::Error> {
-        let file = File::create(file_path)?;
+    fn watch_config(&mut self) {
+        if Config::modified() != self.config_modified && self.reload_config() {
+            self.set_status("Config reloaded".to_string(), false);
+        }
+    }
 
-        let active_tasks: Vec<&Task> = self.tasks.iter().filter(|t| !t.completed).collect();
-        serde_json::to_writer(file, &active_tasks)?;
+    /// Ids also account for the journal, since another session may have
+    /// created tasks that haven't reached the task file yet.
+    fn next_id(&self) -> u64 {
+        let max_task = self.tasks.iter().map(|t| t.id).max().unwrap_or(0);
+        max_task.max(self.journal_max_id) + 1
+    }
 
-        Ok(())
+    fn record(&mut self, task: u64, kind: EventKind) {
+        self.dirty = true;
+        self.health = None;
+        let author = self.config.author.clone();
+        self.attribute(task, &kind, &author);
+        inherit::follow(self, task, &kind);
+        // The journal belongs to the main list; other files' ids change
+        // every time they're loaded.
+        let task_ref = self.tasks.iter().find(|t| t.id == task);
+        if task_ref.is_some_and(|t| t.source.is_some()) {
+            return;
+        }
+        let mut event = Event::now(task, kind);
+        event.author = author;
+        // History is best-effort: a failed append must never block editing.
+        let _ = self.store.append_event(&event);
+        self.journal_max_id = self.journal_max_id.max(task);
     }
-}
 
-fn calculate_deadline(option: &str) -> Option<NaiveDateTime> {
-    let today = chrono::Local::now().date_naive();
-    match option {
-        "Today" => Some(today.and_hms_opt(0, 0, 0).unwrap()),
-        "Tomorrow" => Some(
-            (today + chrono::Duration::days(1))
-                .and_hms_opt(0, 0, 0)
-                .unwrap(),
-        ),
-        "This Week" => {
-            let days_until_end_of_week = today.weekday().num_days_from_sunday() as i64;
-            Some(
-                (today + chrono::Duration::days(days_until_end_of_week))
-                    .and_hms_opt(0, 0, 0)
-                    .unwrap(),
-            )
+    /// Keeps track of who completed a task, for shared lists.
+    fn attribute(&mut self, task: u64, kind: &EventKind, author: &Option<String>) {
+        let Some(task) = self.tasks.iter_mut().find(|t| t.id == task) else {
+            return;
+        };
+        match kind {
+            EventKind::Completed => task.completed_by = author.clone(),
+            EventKind::Reopened => task.completed_by = None,
+            _ => {}
         }
-        _ => None,
     }
-}
 
-fn main() -> Result<(), io::Error> {
-    let mut terminal = initialize_terminal()?;
+    /// Adds a task, returning its id, or `None` when it couldn't be added.
+    fn add_task(
+        &mut self,
+        description: String,
+        deadline: Option<NaiveDateTime>,
+        parent: Option<u64>,
+    ) -> Option<u64> {
+        let changelog = self.apply(vec![Operation::Add {
+            description,
+            deadline,
+            parent,
+        }])?;
+        changelog.first().map(|change| change.task)
+    }
 
-    let mut app_state = AppState::new();
-    if let Err(e) = app_state.load_tasks("tasks.json") {
-        eprintln!("Error loading tasks: {}", e);
-    };
-    println!("Loaded {} tasks", app_state.tasks.len());
-    let mut keys = io::stdin().keys();
+    /// Makes the changes through `ops::apply`, showing why when it can't.
+    fn apply(&mut self, operations: Vec<Operation>) -> Option<ops::Changelog> {
+        match ops::apply(self, operations) {
+            Ok(changelog) => Some(changelog),
+            Err(e) => {
+                self.set_status(e, true);
+                None
+            }
+        }
+    }
 
-    loop {
-        terminal.draw(|f| {
-            let size = f.size();
-            let chunks = create_layout(size);
+    /// Adds a task without recording it yet, returning the event to record.
+    fn insert_new_task(
+        &mut self,
+        description: String,
+        deadline: Option<NaiveDateTime>,
+        parent: Option<u64>,
+    ) -> (u64, EventKind) {
+        let description = milestones::resolve(&description, clock::today());
+        let mut description = self.rules.apply(&description);
+        let parent_task = parent.and_then(|p| self.tasks.iter().find(|t| t.id == p));
+        let mut deadline = deadline;
+        let mut inherits_deadline = false;
+        if let Some(parent_task) = parent_task {
+            description = inherit::description(parent_task, &description, &self.config.urgency);
+            if deadline.is_none() {
+                deadline = parent_task.deadline;
+                inherits_deadline = true;
+            }
+        }
+        let mut task = Task::new(self.next_id(), description, deadline);
+        task.parent = parent;
+        task.inherits_deadline = inherits_deadline;
+        task.created_by = self.config.author.clone();
+        // Subtasks go to their parent's file.
+        if let Some(source) = parent_task.and_then(|t| t.source.as_ref()) {
+            let path = (self.config.workspace.get(&source.name)).map_or("", String::as_str);
+            task.source = Some(workspace::Source {
+                name: source.name.clone(),
+                id: workspace::next_file_id(&self.tasks, &source.name, path),
+            });
+        }
+        let (id, kind) = (
+            task.id,
+            EventKind::Created {
+                description: task.description.clone(),
+                deadline,
+                parent,
+            },
+        );
+        self.insert_task(task);
+        (id, kind)
+    }
+
+    /// Adds a task per outline line, nesting each under the closest less
+    /// indented line above it. Returns how many tasks were added.
+    fn import_outline(&mut self, text: &str, parent: Option<u64>) -> usize {
+        let items = outline::parse(text);
+        // Open ancestors of the next line, as (depth, task id).
+        let mut stack: Vec<(usize, u64)> = Vec::new();
+        let mut added = 0;
+        for item in &items {
+            while stack.last().is_some_and(|(depth, _)| *depth >= item.depth) {
+                stack.pop();
+            }
+            let item_parent = stack.last().map(|(_, id)| *id).or(parent);
+            // A line that can't be added leaves its children to the line above.
+            if let Some(id) = self.add_task(item.description.clone(), None, item_parent) {
+                stack.push((item.depth, id));
+                added += 1;
+            }
+        }
+        added
+    }
 
-            render_input_paragraph(f, &app_state, chunks[0]);
-            render_tasks(f, &app_state, chunks[1]);
-        })?;
+    /// Moves an archived task back into the list, returning its position.
+    /// It stays completed; the caller reopens it. When another instance
+    /// reopened it, it may have left the archive for the task file already.
+    fn unarchive(&mut self, id: u64) -> Option<usize> {
+        let archived = archive::load(&self.data_dir).unwrap_or_default();
+        let saved = || self.store.load().ok().flatten();
+        let task = (archived.into_iter().find(|t| t.id == id))
+            .or_else(|| saved()?.into_iter().find(|t| t.id == id))?;
+        self.insert_task(task);
+        self.tasks.iter().position(|t| t.id == id)
+    }
 
-        if let Some(Ok(key)) = keys.next() {
-            if !process_key_event(key, &mut app_state) {
-                break;
-            };
+    /// Subtasks go right after their parent's subtree; everything else at the end.
+    fn insert_task(&mut self, task: Task) {
+        let parent = task
+            .parent
+            .and_then(|id| self.tasks.iter().position(|t| t.id == id));
+        match parent {
+            Some(index) => {
+                let position = subtasks::insert_position(&self.tasks, index);
+                self.tasks.insert(position, task);
+            }
+            None => self.tasks.push(task),
         }
     }
 
-    app_state.save_tasks("tasks.json")?;
-    terminal.clear()?;
-    terminal.set_cursor(0, 0)?;
-    terminal.show_cursor()?;
-    Ok(())
-}
+    /// Saves the task typed before the deadline prompt, as a new task or
+    /// over the one being edited.
+    fn finish_task_input(&mut self, deadline: Option<NaiveDateTime>) {
+        let description = std::mem::take(&mut self.temp_description);
+        if self.editing {
+            self.update_task(description, deadline);
+        } else {
+            let parent = self.new_parent.take();
+            self.add_task(description, deadline, parent);
+        }
+        self.mode = Mode::Normal;
+    }
 
-fn render_tasks<B: Backend>(f: &mut Frame<B>, app_state: &AppState, chunk: Rect) {
-    let today = chrono::Local::now().naive_local();
-    let tasks: Vec<ListItem> = app_state
-        .tasks
-        .iter()
-        .enumerate()
-        .map(|(i, task)| {
-            let is_selected = Some(i) == app_state.selected_task;
-            let is_overdue = task
-                .deadline
-                .map_or(false, |d| d < today.into() && !task.completed);
+    fn update_task(&mut self, description: String, deadline: Option<NaiveDateTime>) {
+        let Some(task) = self.selected_task.and_then(|i| self.tasks.get(i)) else {
+            return;
+        };
+        let task = task.id;
+        let description = milestones::resolve(&description, clock::today());
+        self.apply(vec![
+            Operation::Edit { task, description },
+            Operation::Reschedule { task, deadline },
+        ]);
+    }
 
-            let base_style = if is_overdue {
-                Style::default().fg(Color::Red)
-            } else if task.completed && !is_selected {
-                Style::default()
-                    .fg(Color::LightGreen)
-                    .add_modifier(Modifier::CROSSED_OUT)
-            } else if is_selected {
-                Style::default().fg(Color::Yellow)
+    /// Moves a task's deadline, recording the change.
+    fn reschedule(&mut self, id: u64, deadline: Option<NaiveDateTime>) {
+        if self.tasks.iter().any(|t| t.id == id) {
+            self.apply(vec![Operation::Reschedule { task: id, deadline }]);
+        }
+    }
+
+    fn toggle_selected(&mut self) {
+        if let Some(task) = self.selected_task.and_then(|i| self.tasks.get(i)) {
+            let operation = if task.completed {
+                Operation::Reopen(task.id)
             } else {
-                Style::default()
+                Operation::Complete(task.id)
             };
+            self.apply(vec![operation]);
+        }
+    }
 
-            let content = Text::styled(task.description.as_str(), base_style);
-            ListItem::new(content)
-        })
-        .collect();
+    /// Completes the selected task, first asking what to do with its open
+    /// subtasks if it has any.
+    fn complete_selected(&mut self) {
+        let Some(task) = self.selected_task.and_then(|i| self.tasks.get(i)) else {
+            return;
+        };
+        let open_subtasks = subtasks::descendants(&self.tasks, task.id)
+            .into_iter()
+            .any(|i| !self.tasks[i].completed);
+        if !task.completed && open_subtasks {
+            self.mode = Mode::CompleteConfirm;
+        } else {
+            self.toggle_selected();
+        }
+    }
 
-    let tasks_list = List::new(tasks).block(Block::default().borders(Borders::ALL).title("Tasks"));
-    f.render_widget(tasks_list, chunk);
-}
+    /// Completes the selected task after completing its open subtasks, or
+    /// after moving them up to its own parent.
+    fn finish_with_subtasks(&mut self, complete_subtasks: bool) {
+        let Some(task) = self.selected_task.and_then(|i| self.tasks.get(i)) else {
+            return;
+        };
+        let (id, parent) = (task.id, task.parent);
 
-fn render_input_paragraph<B: Backend>(f: &mut Frame<B>, app_state: &AppState, chunk: Rect) {
-    let (title, input_text) = match app_state.mode {
-        Mode::Input => ("Input", format!("Input Mode: {}", app_state.input)),
-        Mode::Edit => ("Edit", format!("Editing: {}", app_state.input)),
-        Mode::DeleteConfirm => (
-            "Delete",
-            "Press 'd' again to confirm deletion, or any other key to cancel.".to_string(),
-        ),
-        Mode::DeadlineInput => {
-            let deadline_options = "1: Today, 2: Tomorrow, 3: This Week";
-            ("Select Deadline", deadline_options.to_string())
+        let mut operations: Vec<Operation> = if complete_subtasks {
+            (subtasks::descendants(&self.tasks, id).into_iter())
+                .filter(|i| !self.tasks[*i].completed)
+                .map(|i| Operation::Complete(self.tasks[i].id))
+                .collect()
+        } else {
+            // Grandchildren stay with their own parent and move up along with it.
+            (self.tasks.iter())
+                .filter(|t| t.parent == Some(id) && !t.completed)
+                .map(|t| Operation::Reparent { task: t.id, parent })
+                .collect()
+        };
+        operations.push(Operation::Complete(id));
+        self.apply(operations);
+    }
+
+    /// Whether the project scope, if any, takes in `task`.
+    fn in_scope(&self, task: &Task) -> bool {
+        self.scope.as_ref().is_none_or(|scope| scope.includes(task))
+    }
+
+    /// Indices of the tasks the active search lets through.
+    fn visible_tasks(&self) -> Vec<usize> {
+        let mut query = (Tasks::new(&self.tasks).query())
+            .filter(|t| self.in_scope(t))
+            .sorted_by(self.sort, self.sort_descending)
+            .weighted_by(&self.config.urgency);
+        if let Some((_, filter)) = &self.filter {
+            query = query.matching(filter);
         }
-        _ => ("Input", "Press 'n' to add a task".to_string()),
-    };
+        query.indices()
+    }
 
-    let input_paragraph =
-        Paragraph::new(input_text).block(Block::default().borders(Borders::ALL).title(title));
-    f.render_widget(input_paragraph, chunk);
-}
+    /// Moves to the previous or next visible task.
+    fn move_selection(&mut self, up: bool) {
+        let visible = self.visible_tasks();
+        // By position in the list, which differs from file order when sorted.
+        let position = self
+            .selected_task
+            .and_then(|selected| visible.iter().position(|i| *i == selected));
+        let next = match (position, self.selected_task) {
+            (Some(p), _) if up => p.checked_sub(1).map(|p| &visible[p]),
+            (Some(p), _) => visible.get(p + 1),
+            (None, Some(selected)) if up => visible.iter().rev().find(|i| **i < selected),
+            (None, Some(selected)) => visible.iter().find(|i| **i > selected),
+            (None, None) => visible.first(),
+        };
+        if let Some(next) = next {
+            self.selected_task = Some(*next);
+        }
+    }
 
-fn initialize_terminal() -> Result<Terminal<TermionBackend<RawTerminal<Stdout>>>, io::Error> {
-    let stdout = io::stdout().into_raw_mode()?;
-    let backend = TermionBackend::new(stdout);
-    let mut terminal = Terminal::new(backend)?;
-    terminal.clear()?;
-    Ok(terminal)
-}
+    /// Applies a search query; an empty one clears the filter.
+    fn apply_search(&mut self, text: &str) -> Result<(), String> {
+        self.reviewing = None;
+        if text.trim().is_empty() {
+            self.filter = None;
+            return Ok(());
+        }
+        let (label, query) = search::parse(self, text)?;
+        self.filter = Some((label, query));
+        self.selected_task = self.visible_tasks().first().copied();
+        Ok(())
+    }
 
-fn create_layout(size: Rect) -> Vec<Rect> {
-    Layout::default()
-        .direction(Direction::Vertical)
-        .margin(1)
-        .constraints([Constraint::Length(3), Constraint::Min(0)].as_ref())
-        .split(size)
-}
+    /// Counts a key press towards the next snapshot, and takes it once the
+    /// app has been in use for `snapshots::INTERVAL`.
+    fn count_use(&mut self) {
+        let now = Instant::now();
+        let gap = self.last_input.map(|last| now.duration_since(last));
+        self.last_input = Some(now);
+        if let Some(gap) = gap.filter(|gap| *gap < snapshots::IDLE) {
+            self.active_time += gap;
+        }
+        if self.active_time < snapshots::INTERVAL {
+            return;
+        }
+        self.active_time = Duration::ZERO;
+        if !self.store.persists() {
+            return;
+        }
+        let main: Vec<&Task> = self.tasks.iter().filter(|t| t.source.is_none()).collect();
+        if let Err(e) = snapshots::take(&self.data_dir, &main, None) {
+            self.set_status(format!("Snapshot failed: {}", e), true);
+        }
+    }
 
-fn process_key_event(key: Key, app_state: &mut AppState) -> bool {
-    match app_state.mode {
-        Mode::Normal => match key {
-            Key::Char('q') => {
-                return false;
-            }
-            Key::Char('n') => {
-                app_state.mode = Mode::Input;
-                app_state.input.clear();
-            }
-            Key::Char('d') if app_state.selected_task.is_some() => {
-                app_state.mode = Mode::DeleteConfirm;
-            }
-            Key::Char('e') if app_state.selected_task.is_some() => {
-                app_state.mode = Mode::Edit;
-                app_state.input = app_state.tasks[app_state.selected_task.unwrap()]
-                    .description
-                    .clone();
-            }
-            Key::Char('c') if app_state.selected_task.is_some() => {
-                if let Some(index) = app_state.selected_task {
-                    if let Some(task) = app_state.tasks.get_mut(index) {
-                        task.toggle_completed();
-                    }
-                }
-            }
-            Key::Up => {
-                if let Some(selected) = app_state.selected_task {
-                    app_state.selected_task = Some(selected.saturating_sub(1));
-                }
-            }
-            Key::Down => {
-                if let Some(selected) = app_state.selected_task {
-                    app_state.selected_task =
-                        Some((selected + 1).min(app_state.tasks.len().saturating_sub(1)));
-                }
-            }
-            _ => {}
-        },
-        Mode::DeleteConfirm => match key {
-            Key::Char('d') => {
-                app_state.delete_task();
-                app_state.mode = Mode::Normal;
-            }
-            _ => {
-                app_state.mode = Mode::Normal;
-            }
-        },
-        Mode::Input | Mode::Edit => match key {
-            Key::Char('\n') => {
-                if !app_state.setting_deadline {
-                    app_state.temp_description = app_state.input.clone();
-                    app_state.input.clear();
-                    app_state.mode = Mode::DeadlineInput;
-                }
+    /// Takes a snapshot named after the change about to be made, e.g.
+    /// "pre-import", so it can be rolled back as a whole. Nothing is taken
+    /// when the store keeps nothing.
+    fn snapshot_before(&self, name: &str) -> Result<(), io::Error> {
+        if !self.store.persists() {
+            return Ok(());
+        }
+        let main: Vec<&Task> = self.tasks.iter().filter(|t| t.source.is_none()).collect();
+        snapshots::take(&self.data_dir, &main, Some(name))
+    }
+
+    fn open_snapshots(&mut self) {
+        let browser = snapshots::Browser::new(&self.data_dir);
+        if browser.snapshots.is_empty() {
+            self.set_status(
+                "No snapshots yet; one is taken every 15 minutes of use".to_string(),
+                false,
+            );
+            return;
+        }
+        self.snapshots = Some(browser);
+        self.mode = Mode::Snapshots;
+    }
+
+    /// Brings tasks back as a snapshot has them: deleted ones return under
+    /// their old id, the others get their old description, deadline and
+    /// completion back.
+    fn restore_tasks(&mut self, tasks: Vec<Task>) -> usize {
+        let (operations, restored) = self.restore_operations(tasks, &[]);
+        self.apply(operations).map_or(0, |_| restored)
+    }
+
+    /// The operations restoring `tasks`, and how many tasks they change;
+    /// tasks in `deleting` count as gone.
+    fn restore_operations(&self, tasks: Vec<Task>, deleting: &[u64]) -> (Vec<Operation>, usize) {
+        let mut operations = Vec::new();
+        let mut restored = 0;
+        for old in tasks {
+            let current = (self.tasks.iter()).find(|t| t.id == old.id && !deleting.contains(&t.id));
+            let Some(task) = current else {
+                operations.push(Operation::Restore(old));
+                restored += 1;
+                continue;
+            };
+            let before = operations.len();
+            let id = old.id;
+            if task.description != old.description {
+                let description = old.description;
+                operations.push(Operation::Edit {
+                    task: id,
+                    description,
+                });
             }
-            Key::Char(c) => {
-                app_state.input.push(c);
+            if task.deadline != old.deadline {
+                let deadline = old.deadline;
+                operations.push(Operation::Reschedule { task: id, deadline });
             }
-            Key::Backspace => {
-                app_state.input.pop();
+            if task.completed != old.completed {
+                operations.push(if old.completed {
+                    Operation::Complete(id)
+                } else {
+                    Operation::Reopen(id)
+                });
             }
-            _ => {}
-        },
-        Mode::DeadlineInput => match key {
-            Key::Char('1') => app_state.input = "Today".to_string(),
-            Key::Char('2') => app_state.input = "Tomorrow".to_string(),
-            Key::Char('3') => app_state.input = "This Week".to_string(),
-            Key::Char('q') | Key::Esc => {
-                app_state.mode = Mode::Normal;
+            if operations.len() > before {
+                restored += 1;
             }
-            Key::Char('\n') => {
-                let deadline_option = app_state.input.clone();
-                let deadline = calculate_deadline(&deadline_option);
+        }
+        (operations, restored)
+    }
 
-                let description = std::mem::take(&mut app_state.temp_description);
-                if let Mode::Edit = app_state.mode {
-                    app_state.update_task(description, deadline);
-                } else {
-                    app_state.add_task(description, deadline);
-                }
+    /// The batch putting the whole list back as a snapshot taken at `taken`
+    /// has it, and how many tasks it changes: its tasks are restored, and
+    /// tasks added since are deleted.
+    fn roll_back(&self, tasks: Vec<Task>, taken: NaiveDateTime) -> (Vec<Operation>, usize) {
+        let added: Vec<u64> = (self.tasks.iter())
+            .filter(|t| t.source.is_none() && !tasks.iter().any(|s| s.id == t.id))
+            .filter(|t| t.created_at.is_some_and(|at| at >= taken))
+            .map(|t| t.id)
+            .collect();
+        self.delete_and_restore(added, tasks)
+    }
 
-                app_state.mode = Mode::Normal;
+    /// The batch deleting `deleted` and restoring `tasks`, and how many
+    /// tasks it changes.
+    fn delete_and_restore(&self, deleted: Vec<u64>, tasks: Vec<Task>) -> (Vec<Operation>, usize) {
+        let (restore, restored) = self.restore_operations(tasks, &deleted);
+        let mut operations: Vec<Operation> =
+            deleted.iter().map(|id| Operation::Delete(*id)).collect();
+        operations.extend(restore);
+        (operations, restored + deleted.len())
+    }
+
+    /// The key press to act on, if any: repeats of the last key within
+    /// `repeat_delay_ms`, and chords when they are turned off, are dropped,
+    /// and in Normal mode the key after `sticky_key` becomes a chord.
+    fn accept_key(&mut self, key: Key) -> Option<Key> {
+        let options = &self.config.accessibility;
+        let delay = Duration::from_millis(options.repeat_delay_ms);
+        let timeout = Duration::from_millis(options.chord_timeout_ms);
+        let now = Instant::now();
+        let repeat = self
+            .last_key
+            .is_some_and(|(last, at)| last == key && now.duration_since(at) < delay);
+        self.last_key = Some((key, now));
+        let normal = self.mode == Mode::Normal;
+        let chord = !options.chords && normal && menu::is_chord(key);
+        if repeat || chord {
+            return None;
+        }
+        let sticky = options.sticky_key.as_deref().and_then(keymap::parse_key);
+        let held = (self.held.take())
+            .filter(|(_, at)| now.duration_since(*at) <= timeout)
+            .map(|(chord, _)| chord);
+        if normal && sticky == Some(key) {
+            let (chord, name): (Chord, _) = match held {
+                Some(_) => (Key::Alt, "Alt"),
+                None => (Key::Ctrl, "Ctrl"),
+            };
+            self.held = Some((chord, now));
+            self.set_status(format!("{} held for the next key", name), false);
+            return None;
+        }
+        match (held, key) {
+            (Some(chord), Key::Char(c)) if normal => match chord(c) {
+                Key::Ctrl(c) => Some(Key::Ctrl(c.to_ascii_lowercase())),
+                chord => Some(chord),
+            },
+            _ => Some(key),
+        }
+    }
+
+    fn toggle_someday_selected(&mut self) {
+        if let Some(index) = self.selected_task.filter(|i| *i < self.tasks.len()) {
+            let someday = !self.tasks[index].someday;
+            self.set_someday(index, someday);
+        }
+    }
+
+    /// Catches up with a new day for a list left open overnight: adds the
+    /// day's scheduled tasks and works out again the views that were worked
+    /// out for the old day. Overdue flags, the agenda and the Today tab are
+    /// worked out every frame and need nothing.
+    fn roll_over_day(&mut self) {
+        let today = clock::today();
+        let previous = self.day.replace(today);
+        if previous.is_none_or(|day| day == today) || self.mode == Mode::Setup {
+            return;
+        }
+        self.add_scheduled_tasks();
+        if self.mode == Mode::Heatmap {
+            self.open_heatmap();
+        }
+        if self.timeline.is_some() {
+            self.timeline = Some(timeline::Timeline::new(&self.tasks, &self.config.calendar));
+        }
+    }
+
+    /// Recomputes the health score when tasks changed or the day rolled over.
+    fn refresh_health(&mut self) {
+        let now = clock::now();
+        if self.health.as_ref().is_some_and(|h| h.date == now.date()) {
+            return;
+        }
+        let activity = self.store.last_activity().unwrap_or_default();
+        let stale_days = self.config.someday_after_days.unwrap_or(health::STALE_DAYS);
+        self.health = Some(Health::compute(&self.tasks, &activity, now, stale_days));
+    }
+
+    /// Sorts by the next column the view shows, back to the manual order
+    /// after the last.
+    fn sort_next(&mut self) {
+        let shown = columns::for_view(self).show;
+        let position = shown.iter().position(|c| c.sort_key() == self.sort);
+        let next = match position {
+            Some(p) => shown.get(p + 1),
+            None if self.sort == SortKey::Manual => shown.first(),
+            None => None,
+        };
+        self.sort = next.map_or(SortKey::Manual, |c| c.sort_key());
+        self.sort_descending = false;
+        self.session.sort_by_due = self.sort == SortKey::Due;
+        let text = match next {
+            Some(column) => format!("Sorted by {}", column.title().to_lowercase()),
+            None => "Manual order".to_string(),
+        };
+        self.set_status(text, false);
+    }
+
+    /// Switches between keeping the list sorted by deadline and the manual
+    /// order. The list is sorted as it is drawn, so a changed deadline moves
+    /// its task straight away, and the selection stays on it.
+    fn toggle_sort_by_due(&mut self) {
+        let by_due = self.sort != SortKey::Due;
+        self.sort = if by_due {
+            SortKey::Due
+        } else {
+            SortKey::Manual
+        };
+        self.sort_descending = false;
+        self.session.sort_by_due = by_due;
+        let text = if by_due {
+            "Sorted by deadline"
+        } else {
+            "Manual order"
+        };
+        self.set_status(text.to_string(), false);
+    }
+
+    /// Runs the config's `on_start` commands, then those given with `--exec`.
+    fn run_startup_commands(&mut self, exec: &[script::Command]) {
+        // Checked when the config was loaded, so parsing can't fail here.
+        let mut commands: Vec<script::Command> = (self.config.on_start.iter())
+            .flat_map(|text| script::parse(text).unwrap_or_default())
+            .collect();
+        commands.extend(exec.iter().cloned());
+        if let Err(e) = script::run(self, &commands) {
+            self.set_status(format!("Startup command failed: {}", e), true);
+        }
+    }
+
+    /// Filters the list down to the tasks behind the next health problem.
+    fn review_health(&mut self) {
+        self.refresh_health();
+        let next = self
+            .health
+            .as_ref()
+            .and_then(|h| h.next_problem(self.reviewing))
+            .map(|(problem, ids)| (problem, ids.to_vec()));
+        let Some((problem, ids)) = next else {
+            self.set_status("Nothing to review, the list is healthy".to_string(), false);
+            return;
+        };
+        self.filter = Some((
+            format!("review: {}", problem.label()),
+            query::Query::Ids(ids),
+        ));
+        self.reviewing = Some(problem);
+        self.selected_task = self.visible_tasks().first().copied();
+    }
+
+    /// Switches to the neighbouring filter tab, wrapping around.
+    fn cycle_tab(&mut self, forward: bool) {
+        let count = tabs::tabs(self).len();
+        let tab = if forward {
+            (self.tab + 1) % count
+        } else {
+            (self.tab + count - 1) % count
+        };
+        self.select_tab(tab);
+    }
+
+    fn select_tab(&mut self, tab: usize) {
+        let tabs = tabs::tabs(self);
+        self.tab = tab.min(tabs.len() - 1);
+        if let Some(ids) = tabs[self.tab].ids.clone() {
+            self.reviewing = None;
+            self.filter = Some(("today".to_string(), query::Query::Ids(ids)));
+            self.selected_task = self.visible_tasks().first().copied();
+            return;
+        }
+        let query = tabs[self.tab].query.clone().unwrap_or_default();
+        self.search_regex = false;
+        if let Err(e) = self.apply_search(&query) {
+            self.set_status(format!("Invalid filter: {}", e), true);
+        }
+    }
+
+    /// The tasks committed to today, if the day has been planned.
+    fn todays_plan(&self) -> Option<Vec<u64>> {
+        let today = clock::today().format("%Y-%m-%d").to_string();
+        let planned = self.session.planned_on.as_deref() == Some(today.as_str());
+        (planned && !self.session.planned.is_empty()).then(|| self.session.planned.clone())
+    }
+
+    /// Makes the planner's choice today's plan and shows it in the Today tab.
+    fn commit_plan(&mut self) {
+        let Some(planner) = self.planner.take() else {
+            return;
+        };
+        self.mode = Mode::Normal;
+        let minutes = planner.minutes(&self.tasks);
+        let count = planner.committed.len();
+        self.session.planned_on = Some(clock::today().format("%Y-%m-%d").to_string());
+        self.session.planned = planner.committed;
+        self.save_session();
+        // The Today tab, or All when nothing was committed.
+        let today = tabs::tabs(self).iter().position(|t| t.ids.is_some());
+        self.select_tab(today.unwrap_or(0));
+        self.set_status(
+            format!(
+                "Planned {} tasks, {} of {}",
+                count,
+                estimate::format_minutes(minutes),
+                estimate::format_minutes(self.config.capacity_minutes())
+            ),
+            false,
+        );
+    }
+
+    fn set_command(&mut self, command: Option<String>) {
+        let Some(task) = self.selected_task.and_then(|i| self.tasks.get(i)) else {
+            return;
+        };
+        let task = task.id;
+        self.apply(vec![Operation::SetCommand { task, command }]);
+    }
+
+    /// Attaches the file at `path` to the task at `index`, copying it into
+    /// the data directory when `copy` is set and the store persists.
+    fn attach(&mut self, index: usize, path: &str, copy: bool) -> Result<(), io::Error> {
+        let Some(task) = self.tasks.get(index) else {
+            return Ok(());
+        };
+        let id = task.id;
+        let copy = copy && self.store.persists();
+        let path = attachments::store(path, copy, &self.data_dir, id)?;
+        self.apply(vec![Operation::Attach { task: id, path }]);
+        Ok(())
+    }
+
+    /// Drops the reference; a copy in the data directory stays on disk.
+    fn detach(&mut self, index: usize, path: &str) -> bool {
+        let Some(task) = self.tasks.get(index) else {
+            return false;
+        };
+        if !task.attachments.iter().any(|p| p == path) {
+            return false;
+        }
+        let (task, path) = (task.id, path.to_string());
+        self.apply(vec![Operation::Detach { task, path }]).is_some()
+    }
+
+    /// Opens the what's-new screen when this version hasn't run here before.
+    /// Fresh installs skip it: everything is new to them.
+    fn check_upgrade(&mut self) {
+        let current = env!("CARGO_PKG_VERSION");
+        let upgraded = SessionState::exists(&self.data_dir)
+            && self.session.last_version.as_deref() != Some(current);
+        if upgraded {
+            self.whats_new = whatsnew::releases_since(self.session.last_version.as_deref());
+            if !self.whats_new.is_empty() {
+                self.mode = Mode::WhatsNew;
             }
-            _ => {}
-        },
+        }
+        self.session.last_version = Some(current.to_string());
     }
-    true
-}
 
-fn serialize_date<S>(date: &Option<NaiveDateTime>, serializer: S) -> Result<S::Ok, S::Error>
-where
-    S: serde::Serializer,
-{
-    match date {
-        Some(d) => serializer.serialize_str(&d.format("%Y-%m-%d %H:%M:%S").to_string()),
-        None => serializer.serialize_none(),
+    /// Asks for the next placeholder, or hands the filled-in text to the
+    /// input line once all are known so it can still be tweaked.
+    fn advance_template(&mut self) {
+        let Some(fill) = &self.template_fill else {
+            return;
+        };
+        self.input.clear();
+        if fill.next_name().is_some() {
+            self.mode = Mode::Placeholder;
+            return;
+        }
+        self.input = fill.text();
+        self.template_fill = None;
+        self.editing = false;
+        self.new_parent = None;
+        self.mode = Mode::Input;
     }
-}
 
-fn deserialize_date<'de, D>(deserializer: D) -> Result<Option<NaiveDateTime>, D::Error>
-where
-    D: serde::Deserializer<'de>,
-{
-    let s: Option<String> = Option::deserialize(deserializer)?;
-    match s {
-        Some(str) => NaiveDateTime::parse_from_str(&str, "%Y-%m-%d %H:%M:%S")
-            .map(Some)
-            .map_err(serde::de::Error::custom),
-        None => Ok(None),
+    /// Moves the selected scratch item into the real task list.
+    fn promote_scratch(&mut self) {
+        let Some(item) = self.scratch.remove() else {
+            return;
+        };
+        let Some(id) = self.add_task(item.description, None, None) else {
+            return;
+        };
+        if item.done {
+            self.apply(vec![Operation::Complete(id)]);
+        }
+        self.set_status("Moved to the task list".to_string(), false);
+    }
+
+    /// Whether `task`'s description is hidden: private tasks always, all of
+    /// them behind the privacy screen, unless revealed.
+    fn masked(&self, task: &Task) -> bool {
+        (task.private || self.privacy_screen) && self.revealed != Some(task.id)
+    }
+
+    /// `task`'s description as it may be shown right now.
+    fn description(&self, task: &Task) -> String {
+        if self.masked(task) {
+            privacy::mask(&task.description)
+        } else {
+            task.description.clone()
+        }
+    }
+
+    fn toggle_private_selected(&mut self) {
+        let Some(task) = self.selected_task.and_then(|i| self.tasks.get(i)) else {
+            return;
+        };
+        let (operation, text) = if task.private {
+            (Operation::MakePublic(task.id), "No longer private")
+        } else {
+            (Operation::MakePrivate(task.id), "Marked private")
+        };
+        if self.apply(vec![operation]).is_none() {
+            return;
+        }
+        self.revealed = None;
+        self.set_status(text.to_string(), false);
+    }
+
+    fn set_someday(&mut self, index: usize, someday: bool) {
+        let id = self.tasks[index].id;
+        let operation = if someday {
+            Operation::Defer(id)
+        } else {
+            Operation::Resume(id)
+        };
+        self.apply(vec![operation]);
+    }
+
+    /// Offers to move open tasks nobody has touched in `someday_after_days`
+    /// to Someday, listing them first. Another screen opening at startup
+    /// leaves them for the next start.
+    fn defer_stale_tasks(&mut self) {
+        let Some(days) = self.config.someday_after_days else {
+            return;
+        };
+        let cutoff = clock::now() - chrono::Duration::days(days as i64);
+        let activity = self.store.last_activity().unwrap_or_default();
+        let stale: Vec<u64> = (self.tasks.iter())
+            .filter(|task| {
+                let touched = activity.get(&task.id).copied().max(task.created_at);
+                !task.completed
+                    && !task.in_progress
+                    && !task.someday
+                    && touched.is_some_and(|at| at < cutoff)
+            })
+            .map(|task| task.id)
+            .collect();
+        if !stale.is_empty() && self.mode == Mode::Normal {
+            bulk::defer_stale(self, stale);
+        }
+    }
+
+    /// Moves `ids` to Someday, keeping them so the move can be undone for
+    /// a while.
+    fn defer_tasks(&mut self, ids: Vec<u64>) {
+        let days = self.config.someday_after_days.unwrap_or_default();
+        let operations = ids.iter().map(|id| Operation::Defer(*id)).collect();
+        if self.apply(operations).is_none() {
+            return;
+        }
+        let summary = format!("Moved {} stale tasks to Someday", ids.len());
+        self.auto_deferred = Some((Instant::now(), ids));
+        self.set_status(
+            format!(
+                "{}, press '{}' to undo",
+                summary,
+                keymap::format_key(self.keymap.key_for(Action::Undo))
+            ),
+            false,
+        );
+        if self.config.notifications {
+            notify::deliver(
+                &self.config.channels,
+                notify::Reminder::Someday,
+                &summary,
+                &format!("Untouched for {} days", days),
+                notify::Urgency::Normal,
+            );
+        }
+    }
+
+    fn undo_auto_defer(&mut self) {
+        let Some((at, ids)) = self.auto_deferred.take() else {
+            self.set_status("Nothing to undo".to_string(), false);
+            return;
+        };
+        let window = Duration::from_secs(self.config.accessibility.undo_seconds);
+        if at.elapsed() > window {
+            self.set_status("Too late to undo the move to Someday".to_string(), true);
+            return;
+        }
+        let operations = (ids.iter())
+            .filter(|id| self.tasks.iter().any(|t| t.id == **id))
+            .map(|id| Operation::Resume(*id))
+            .collect();
+        if self.apply(operations).is_none() {
+            return;
+        }
+        self.set_status(format!("Restored {} tasks from Someday", ids.len()), false);
+    }
+
+    /// Moves the selected task in or out of In Progress, checking WIP limits
+    /// before starting it.
+    fn toggle_started(&mut self) {
+        let Some(index) = self.selected_task.filter(|i| *i < self.tasks.len()) else {
+            return;
+        };
+        if self.tasks[index].completed {
+            return;
+        }
+        if !self.tasks[index].in_progress {
+            if let Some(limit) = kanban::limit_reached(&self.tasks, index, &self.config.wip) {
+                if self.config.wip.block {
+                    let text = format!("WIP limit reached for {}, finish something first", limit);
+                    self.set_status(text, true);
+                    return;
+                }
+                self.set_status(format!("Started past the WIP limit for {}", limit), true);
+            }
+        }
+
+        let task = &self.tasks[index];
+        let operation = if task.in_progress {
+            Operation::Stop(task.id)
+        } else {
+            Operation::Start(task.id)
+        };
+        self.apply(vec![operation]);
+    }
+
+    /// Keeps the selection inside the focused kanban column.
+    fn select_in_column(&mut self, column: Column) {
+        self.kanban_column = column;
+        let indices = kanban::column_tasks(&self.tasks, column);
+        if !self.selected_task.is_some_and(|i| indices.contains(&i)) {
+            self.selected_task = indices.first().copied();
+        }
+    }
+
+    fn delete_task(&mut self) {
+        if let Some(task) = self.selected_task.and_then(|i| self.tasks.get(i)) {
+            self.apply(vec![Operation::Delete(task.id)]);
+        }
+    }
+
+    /// Applies changes other sessions (e.g. the CLI) appended to the journal
+    /// since the last merge, so saving doesn't overwrite them.
+    fn merge_journal(&mut self) {
+        let Ok((events, offset)) = self.store.watch(self.journal_offset) else {
+            return;
+        };
+        self.journal_offset = offset;
+        let max_id = events.iter().map(|e| e.task).max().unwrap_or(0);
+        self.journal_max_id = self.journal_max_id.max(max_id);
+
+        let session = std::process::id();
+        for event in events.into_iter().filter(|e| e.session != session) {
+            self.apply_event(event);
+        }
+        if !self.conflicts.is_empty() && self.mode == Mode::Normal {
+            self.mode = Mode::Conflict;
+            if let Err(e) = self.snapshot_before("pre-merge") {
+                self.set_status(format!("Snapshot failed: {}", e), true);
+            }
+        }
+    }
+
+    /// Applies the choices for the first conflict, recording them so every
+    /// instance ends up with the same values.
+    fn resolve_conflict(&mut self) {
+        if self.conflicts.is_empty() {
+            return;
+        }
+        let conflict = self.conflicts.remove(0);
+        self.mode = if self.conflicts.is_empty() {
+            Mode::Normal
+        } else {
+            Mode::Conflict
+        };
+        let (mut kept, mut taken) = (Vec::new(), Vec::new());
+        let (mut description, mut deadline) = (None, None);
+        for field in conflict.fields {
+            let (from, to) = if field.take_remote {
+                taken.push(field.field.name().to_string());
+                (field.local, field.remote)
+            } else {
+                kept.push(field.field.name().to_string());
+                (field.remote, field.local)
+            };
+            // Written even when keeping ours: the other instance still has theirs.
+            match (from, to) {
+                (Value::Text(from), Value::Text(to)) => description = Some((from, to)),
+                (Value::Date(from), Value::Date(to)) => deadline = Some((from, to)),
+                _ => {}
+            }
+        }
+        if self.tasks.iter().any(|t| t.id == conflict.task) {
+            self.apply(vec![Operation::Resolve {
+                task: conflict.task,
+                kept,
+                taken,
+                description,
+                deadline,
+            }]);
+        }
+    }
+
+    fn apply_event(&mut self, event: Event) {
+        self.health = None;
+        let position = self.tasks.iter().position(|t| t.id == event.task);
+        // Journal events are about the main list, never another file's tasks.
+        if position.is_some_and(|i| self.tasks[i].source.is_some()) {
+            return;
+        }
+        self.attribute(event.task, &event.kind, &event.author);
+        let author = event.author.clone();
+        match (event.kind, position) {
+            (
+                EventKind::Created {
+                    description,
+                    deadline,
+                    parent,
+                },
+                None,
+            ) => {
+                let mut task = Task::new(event.task, description, deadline);
+                task.created_at = Some(event.at);
+                task.parent = parent;
+                task.created_by = author;
+                self.insert_task(task);
+            }
+            // A change made from a value this session no longer has clashes
+            // with a change made here, unless both arrived at the same value.
+            (EventKind::Edited { from, to }, Some(i)) => {
+                let local = &self.tasks[i].description;
+                if *local != from && *local != to {
+                    let (local, remote) = (Value::Text(local.clone()), Value::Text(to));
+                    let field = Field::Description;
+                    conflict::add(
+                        &mut self.conflicts,
+                        event.task,
+                        author,
+                        field,
+                        local,
+                        remote,
+                    );
+                } else {
+                    self.tasks[i].description = to;
+                }
+            }
+            (EventKind::Rescheduled { from, to }, Some(i)) => {
+                let local = self.tasks[i].deadline;
+                if local != from && local != to {
+                    let (local, remote) = (Value::Date(local), Value::Date(to));
+                    let field = Field::Deadline;
+                    conflict::add(
+                        &mut self.conflicts,
+                        event.task,
+                        author,
+                        field,
+                        local,
+                        remote,
+                    );
+                } else {
+                    self.tasks[i].deadline = to;
+                }
+            }
+            (EventKind::Completed, Some(i)) => self.tasks[i].set_completed(true, event.at),
+            (EventKind::Reopened, Some(i)) => self.tasks[i].set_completed(false, event.at),
+            // Reopened from the archive by another instance.
+            (EventKind::Reopened, None) => {
+                if let Some(i) = self.unarchive(event.task) {
+                    self.tasks[i].set_completed(false, event.at);
+                    self.tasks[i].completed_by = None;
+                }
+            }
+            (EventKind::Started, Some(i)) => self.tasks[i].in_progress = true,
+            (EventKind::Stopped, Some(i)) => self.tasks[i].in_progress = false,
+            (EventKind::Deferred, Some(i)) => self.tasks[i].someday = true,
+            (EventKind::Resumed, Some(i)) => self.tasks[i].someday = false,
+            (EventKind::MadePrivate, Some(i)) => self.tasks[i].private = true,
+            (EventKind::MadePublic, Some(i)) => self.tasks[i].private = false,
+            (EventKind::Reparented { to, .. }, Some(i)) => self.tasks[i].parent = to,
+            (EventKind::CommandChanged { to, .. }, Some(i)) => self.tasks[i].command = to,
+            (EventKind::Attached { path }, Some(i))
+                if !self.tasks[i].attachments.contains(&path) =>
+            {
+                self.tasks[i].attachments.push(path);
+            }
+            (EventKind::Detached { path }, Some(i)) => {
+                self.tasks[i].attachments.retain(|p| *p != path);
+            }
+            (EventKind::Deleted, Some(i)) => {
+                self.tasks.remove(i);
+                if let Some(selected) = self.selected_task {
+                    self.selected_task = Some(selected.min(self.tasks.len().saturating_sub(1)));
+                }
+            }
+            _ => {}
+        }
+    }
+
+    /// Sends one desktop notification listing tasks due today or overdue.
+    fn notify_due_today(&self) {
+        if !self.config.notifications {
+            return;
+        }
+        let tomorrow = clock::today() + chrono::Duration::days(1);
+        let end_of_today = tomorrow.and_hms_opt(0, 0, 0).unwrap();
+        let due: Vec<String> = self
+            .tasks
+            .iter()
+            .filter(|t| !t.completed && t.deadline.is_some_and(|d| d < end_of_today))
+            .filter(|t| self.in_scope(t))
+            .map(Task::shared_description)
+            .collect();
+        if !due.is_empty() {
+            let summary = format!("{} tasks due today", due.len());
+            notify::deliver(
+                &self.config.channels,
+                notify::Reminder::DueToday,
+                &summary,
+                &due.join("\n"),
+                notify::Urgency::Normal,
+            );
+        }
+    }
+
+    /// Adds the `[[scheduled]]` tasks whose date has come. The last date each
+    /// was added for is remembered, so restarting adds nothing twice; dates
+    /// missed while the app was closed add one task, for the latest.
+    fn add_scheduled_tasks(&mut self) {
+        let today = clock::today();
+        let mut added = Vec::new();
+        for scheduled in self.config.scheduled.clone() {
+            let Some(date) = scheduled.latest(today) else {
+                continue;
+            };
+            let stamp = date.format("%Y-%m-%d").to_string();
+            let last = self.session.scheduled.get(&scheduled.description);
+            // Dates in this format compare in calendar order.
+            if last.is_some_and(|last| *last >= stamp) {
+                continue;
+            }
+            self.add_task(
+                scheduled.description.clone(),
+                date.and_hms_opt(0, 0, 0),
+                None,
+            );
+            self.session
+                .scheduled
+                .insert(scheduled.description.clone(), stamp);
+            added.push(scheduled.description);
+        }
+        if added.is_empty() {
+            return;
+        }
+        // Saved straight away so a crash can't add them again.
+        let _ = self.save_or_recover();
+        self.save_session();
+        self.set_status(format!("Added scheduled: {}", added.join(", ")), false);
+    }
+
+    /// Sends one urgent notification a day for tasks overdue by more than
+    /// `escalate_after_days`, apart from the due-today reminders.
+    fn escalate_overdue(&mut self) {
+        let Some(days) = self.config.escalate_after_days else {
+            return;
+        };
+        let today = clock::today();
+        let stamp = today.format("%Y-%m-%d").to_string();
+        if self.session.escalated_on.as_deref() == Some(stamp.as_str()) {
+            return;
+        }
+        let cutoff = today - chrono::Duration::days(days as i64);
+        let late: Vec<String> = self
+            .tasks
+            .iter()
+            .filter(|t| !t.completed && !t.someday && self.in_scope(t))
+            .filter_map(|t| {
+                let deadline = t.deadline?.date();
+                (deadline < cutoff).then(|| {
+                    let overdue = (today - deadline).num_days();
+                    format!("{} ({} days overdue)", t.shared_description(), overdue)
+                })
+            })
+            .collect();
+        if late.is_empty() {
+            return;
+        }
+        let summary = match late.len() {
+            1 => "1 task is long overdue".to_string(),
+            n => format!("{} tasks are long overdue", n),
+        };
+        // Failed deliveries aren't retried: the day still counts as done so
+        // it doesn't go out again every tick.
+        notify::deliver(
+            &self.config.channels,
+            notify::Reminder::Overdue,
+            &summary,
+            &late.join("\n"),
+            notify::Urgency::Critical,
+        );
+        self.session.escalated_on = Some(stamp);
+        self.save_session();
+    }
+
+    fn open_heatmap(&mut self) {
+        let archived = match archive::load(&self.data_dir) {
+            Ok(archived) => archived,
+            Err(e) => {
+                self.set_status(format!("Could not read archive: {}", e), true);
+                Vec::new()
+            }
+        };
+        // Completed tasks still in memory may not have been archived yet.
+        let pending = self
+            .tasks
+            .iter()
+            .filter(|t| !archived.iter().any(|a| a.id == t.id));
+        self.heatmap = heatmap::completions_per_day(archived.iter().chain(pending.clone()));
+        let events = self.store.events().unwrap_or_default();
+        let sessions = focus::sessions(&events, clock::now());
+        let describe = |id: u64| {
+            let task = pending.clone().chain(&archived).find(|t| t.id == id);
+            task.map_or(format!("task {}", id), |t| self.description(t))
+        };
+        let today = clock::today();
+        let monday = heatmap::week_start_of(today, self.config.week_start());
+        let reports = (
+            focus::report(&sessions, today, today, today, describe),
+            focus::report(&sessions, monday, today, today, describe),
+        );
+        self.focus = Some(reports);
+        self.mode = Mode::Heatmap;
+    }
+
+    fn open_history(&mut self) {
+        let Some(task) = self.selected_task.and_then(|i| self.tasks.get(i)) else {
+            return;
+        };
+        if self.masked(task) {
+            let key = keymap::format_key(self.keymap.key_for(Action::Reveal));
+            self.set_status(format!("Press '{}' to reveal the task first", key), false);
+            return;
+        }
+        self.history = self.store.history(task.id).unwrap_or_default();
+        self.mode = Mode::History;
+    }
+
+    fn load_tasks(&mut self) -> Result<(), io::Error> {
+        self.health = None;
+        self.data_dir = self.config.data_path();
+        if self.config.storage != store::Storage::Memory {
+            std::fs::create_dir_all(&self.data_dir)?;
+        }
+        self.store = store::open(self.config.storage, &self.data_dir)?;
+        self.journal_max_id = self.store.max_task_id();
+        self.session = SessionState::load(&self.data_dir);
+        self.jira = jira::Issues::load(&self.data_dir);
+        self.archived_projects = tabs::archived_projects(&self.data_dir);
+
+        match self.store.load()? {
+            Some(tasks) => {
+                self.tasks = tasks;
+                self.journal_offset = self.store.offset();
+                self.assign_missing_ids();
+            }
+            // With no task file, the whole journal is merged in.
+            None => self.journal_offset = 0,
+        }
+        self.load_workspace()
+    }
+
+    /// Adds the tasks of the workspace's other files after the main list's.
+    fn load_workspace(&mut self) -> Result<(), io::Error> {
+        self.tasks.retain(|t| t.source.is_none());
+        for (name, path) in &self.config.workspace {
+            let tasks = workspace::load(name, path, self.next_id())
+                .map_err(|e| io::Error::other(format!("workspace file {}: {}", name, e)))?;
+            self.tasks.extend(tasks);
+        }
+        Ok(())
+    }
+
+    /// Tasks saved before ids existed are numbered after the highest known id.
+    fn assign_missing_ids(&mut self) {
+        let ids = self.next_id()..;
+        for (id, task) in ids.zip(self.tasks.iter_mut().filter(|t| t.id == 0)) {
+            task.id = id;
+        }
+    }
+
+    /// Saves the tasks, or when that fails, e.g. on a full or read-only
+    /// disk, keeps them in a recovery file outside the data directory that
+    /// the next launch offers back. The error says where they went.
+    fn save_or_recover(&mut self) -> Result<(), String> {
+        let error = match self.save_tasks() {
+            Ok(()) => {
+                if let Some(path) = self.recovery_file.take() {
+                    let _ = std::fs::remove_file(path);
+                }
+                return Ok(());
+            }
+            Err(e) => e.to_string(),
+        };
+        let main: Vec<Task> = (self.tasks.iter())
+            .filter(|t| t.source.is_none())
+            .cloned()
+            .collect();
+        match recovery::write(&self.data_dir, &main, &error) {
+            Ok(path) => {
+                let message = format!("{}; changes kept in {}", error, path.display());
+                self.recovery_file = Some(path);
+                Err(message)
+            }
+            Err(e) => Err(format!(
+                "{}; couldn't keep a recovery file either: {}",
+                error, e
+            )),
+        }
+    }
+
+    /// Switches to Recover mode when an earlier run left unsaved tasks.
+    fn offer_recovery(&mut self) {
+        if !self.store.persists() {
+            return;
+        }
+        self.recovery = recovery::find(&self.data_dir);
+        if self.recovery.is_some() {
+            self.mode = Mode::Recover;
+        }
+    }
+
+    /// Brings back the tasks a failed save kept, as restoring a snapshot
+    /// does; tasks missing from them that are older than the failure were
+    /// deleted then, and are deleted again.
+    fn restore_recovered(&mut self) {
+        let Some((path, recovery)) = self.recovery.take() else {
+            return;
+        };
+        if let Err(e) = self.snapshot_before("pre-recovery") {
+            self.set_status(format!("Snapshot failed, nothing restored: {}", e), true);
+            return;
+        }
+        let failed_at = NaiveDateTime::parse_from_str(&recovery.saved_at, "%Y-%m-%d %H:%M:%S").ok();
+        let deleted: Vec<u64> = (self.tasks.iter())
+            .filter(|t| t.source.is_none() && !recovery.tasks.iter().any(|r| r.id == t.id))
+            .filter(|t| {
+                t.created_at
+                    .zip(failed_at)
+                    .is_some_and(|(at, failed)| at < failed)
+            })
+            .map(|t| t.id)
+            .collect();
+        let (operations, count) = self.delete_and_restore(deleted, recovery.tasks);
+        let count = self.apply(operations).map_or(0, |_| count);
+        self.selected_task = self.visible_tasks().first().copied();
+        match self.save_or_recover() {
+            Ok(()) => {
+                let _ = std::fs::remove_file(path);
+                self.set_status(format!("Restored {} unsaved changes", count), false);
+            }
+            Err(e) => self.set_status(format!("Saving failed again: {}", e), true),
+        }
+    }
+
+    fn save_tasks(&mut self) -> Result<(), io::Error> {
+        self.dirty = false;
+
+        let main: Vec<Task> = (self.tasks.iter())
+            .filter(|t| t.source.is_none())
+            .cloned()
+            .collect();
+        let active_tasks: Vec<&Task> = main.iter().filter(|t| !t.completed).collect();
+        self.store.save(&active_tasks)?;
+        if !self.store.persists() {
+            return Ok(());
+        }
+        for (name, path) in &self.config.workspace {
+            workspace::save(name, path, &self.tasks)?;
+        }
+
+        // The tasks are safe by now; a failed export shouldn't fail the save.
+        let config = &self.config;
+        if let Err(e) = export::write_all(
+            &config.exports,
+            &self.tasks,
+            &config.filters,
+            &config.date_format,
+        ) {
+            self.set_status(format!("Export failed: {}", e), true);
+        }
+        archive::update(&self.data_dir, &main)?;
+        self.archived_projects = tabs::archived_projects(&self.data_dir);
+        Ok(())
+    }
+}
+
+/// The TUI's list: new tasks get milestones, rules and what they inherit,
+/// and changes go to the journal.
+impl ops::TaskList for AppState {
+    fn tasks(&mut self) -> &mut Vec<Task> {
+        &mut self.tasks
+    }
+
+    fn create(
+        &mut self,
+        description: String,
+        deadline: Option<NaiveDateTime>,
+        parent: Option<u64>,
+    ) -> (u64, EventKind) {
+        self.insert_new_task(description, deadline, parent)
+    }
+
+    fn insert(&mut self, task: Task) {
+        self.insert_task(task);
+    }
+
+    fn unarchive(&mut self, task: u64) -> Option<usize> {
+        AppState::unarchive(self, task)
+    }
+
+    fn record(&mut self, task: u64, kind: EventKind) {
+        AppState::record(self, task, kind);
+    }
+}
+
+/// Resolves a deadline preset: `today`, `tomorrow`, `this week` or
+/// `next <weekday>`, in any case, or the same in `locale`'s words. With
+/// workdays on, the result moves to a workday; `this week` moves back to
+/// the week's last one.
+fn calculate_deadline(
+    option: &str,
+    week_start: WeekStart,
+    locale: Locale,
+    workdays: &Workdays,
+) -> Option<NaiveDateTime> {
+    let today = clock::today();
+    let option = locale::to_english(option, locale);
+    let date = match option.as_str() {
+        "today" => workdays.on_or_after(today),
+        "tomorrow" => workdays.add_days(today, 1),
+        "this week" => {
+            let days_into_week = match week_start {
+                WeekStart::Sunday => today.weekday().num_days_from_sunday(),
+                WeekStart::Monday => today.weekday().num_days_from_monday(),
+            };
+            let days_until_end_of_week = 6 - days_into_week as i64;
+            let end_of_week = today + chrono::Duration::days(days_until_end_of_week);
+            workdays.on_or_before(end_of_week, today)
+        }
+        other => {
+            let weekday: chrono::Weekday = other.strip_prefix("next ")?.trim().parse().ok()?;
+            // Always in the future: "next Monday" on a Monday is a week away.
+            let days_ahead = (weekday.num_days_from_monday() as i64
+                - today.weekday().num_days_from_monday() as i64)
+                .rem_euclid(7);
+            let days_ahead = if days_ahead == 0 { 7 } else { days_ahead };
+            workdays.on_or_after(today + chrono::Duration::days(days_ahead))
+        }
+    };
+    Some(date.and_hms_opt(0, 0, 0).unwrap())
+}
+
+/// Applies a postpone menu choice. Overdue and missing deadlines are pushed
+/// back from today rather than from the past. With workdays on, days are
+/// counted in workdays and weeks or months land on the next workday.
+fn postpone_deadline(
+    deadline: Option<NaiveDateTime>,
+    choice: char,
+    workdays: &Workdays,
+) -> Option<NaiveDateTime> {
+    let today = clock::today().and_hms_opt(0, 0, 0).unwrap();
+    let from = deadline.filter(|d| *d >= today).unwrap_or(today);
+    let date = match choice {
+        '1' => workdays.add_days(from.date(), 1),
+        '2' => workdays.add_days(from.date(), 3),
+        '3' => workdays.on_or_after(from.date() + chrono::Duration::weeks(1)),
+        '4' => workdays.on_or_after(from.date().checked_add_months(chrono::Months::new(1))?),
+        _ => return None,
+    };
+    Some(date.and_time(from.time()))
+}
+
+fn main() -> Result<(), io::Error> {
+    let mut args: Vec<String> = std::env::args().skip(1).collect();
+    // The tutorial runs on the demo's sample data.
+    let tutorial = args.first().is_some_and(|a| a == "tutorial");
+    // Kept until exit, when dropping it deletes the sample data.
+    let _demo = if tutorial || args.first().is_some_and(|a| a == "--demo") {
+        args.remove(0);
+        match demo::Sandbox::new() {
+            Ok(sandbox) => Some(sandbox),
+            Err(e) => {
+                eprintln!("--demo: {}", e);
+                std::process::exit(1);
+            }
+        }
+    } else {
+        None
+    };
+    let exec = if args.first().is_some_and(|a| a == "--exec") {
+        let parsed = args.get(1).map(|text| script::parse(text));
+        match parsed {
+            Some(Ok(commands)) => {
+                args.drain(..2);
+                commands
+            }
+            Some(Err(e)) => {
+                eprintln!("--exec: {}", e);
+                std::process::exit(1);
+            }
+            None => {
+                eprintln!("--exec needs a command list, e.g. \"filter #work; view agenda\"");
+                std::process::exit(1);
+            }
+        }
+    } else {
+        Vec::new()
+    };
+    if args.is_empty() && !(io::stdout().is_terminal() && io::stdin().is_terminal()) {
+        // Piped or redirected: print the list rather than escape sequences.
+        args.push("list".to_string());
+    }
+    if !args.is_empty() {
+        if let Err(e) = cli::run(&args) {
+            eprintln!("{}", e);
+            std::process::exit(1);
+        }
+        return Ok(());
+    }
+
+    let mut app_state = AppState::new();
+    let first_run = Config::is_first_run();
+    if !first_run {
+        app_state.reload_config();
+    }
+    let mut terminal = terminal::initialize(app_state.config.mouse)?;
+
+    if first_run {
+        app_state.start_setup();
+    } else {
+        if let Err(e) = app_state.load_tasks() {
+            eprintln!("Error loading tasks: {}", e);
+        };
+        println!("Loaded {} tasks", app_state.tasks.len());
+        app_state.notify_due_today();
+        app_state.add_scheduled_tasks();
+        app_state.scope = (app_state.session.scope.as_deref()).and_then(scope::Scope::parse);
+        if app_state.session.sort_by_due {
+            app_state.sort = SortKey::Due;
+        }
+        app_state.run_startup_commands(&exec);
+        app_state.check_upgrade();
+        // After the upgrade check, which the session file it saves would fool.
+        app_state.escalate_overdue();
+        app_state.offer_recovery();
+        // Asks, so only once nothing else opened at startup.
+        app_state.defer_stale_tasks();
+        if tutorial {
+            app_state.tutorial = Some(tutorial::Tutorial::new(&app_state.tasks));
+        }
+    }
+    let mut events = events::Events::new(Duration::from_millis(250));
+    // Whether the screen is behind, and the minute of the last frame, for
+    // `low_refresh`; otherwise every event redraws.
+    let mut changed = true;
+    let mut drawn_minute = None;
+
+    loop {
+        app_state.refresh_health();
+        let minute = clock::now().format("%H:%M").to_string();
+        let behind = changed || drawn_minute.as_ref() != Some(&minute);
+        if !app_state.config.low_refresh || (behind && !events.input_pending()) {
+            app_state.body_area = draw(&mut terminal, &app_state)?;
+            drawn_minute = Some(minute);
+        }
+
+        let event = events.next();
+        // Ticks change what's shown only through what they pick up.
+        changed = !matches!(event, Ok(events::Event::Tick));
+        match event {
+            Ok(events::Event::Input(key)) => {
+                app_state.count_use();
+                let key = app_state.accept_key(key);
+                if key.is_some_and(|key| !process_key_event(key, &mut app_state)) {
+                    break;
+                };
+                tutorial::advance(&mut app_state);
+            }
+            Ok(events::Event::Mouse(mouse)) => process_mouse_event(mouse, &mut app_state),
+            Ok(events::Event::Paste(text)) => process_paste(&text, &mut app_state),
+            // Drawing picks up the new size; the event only wakes the loop.
+            Ok(events::Event::Resize) => {}
+            Ok(events::Event::Suspend) => app_state.suspend_requested = true,
+            Ok(events::Event::Tick) => {
+                let escalated = app_state.session.escalated_on.clone();
+                let before = (app_state.journal_offset, app_state.config_modified);
+                app_state.merge_journal();
+                app_state.watch_config();
+                app_state.jira.refresh(&app_state.data_dir);
+                app_state.escalate_overdue();
+                lock::lock_when_idle(&mut app_state);
+                let day = app_state.day;
+                app_state.roll_over_day();
+                let found = search::poll(&mut app_state);
+                changed = app_state.mode == Mode::Locked
+                    || found
+                    || day != app_state.day
+                    || before != (app_state.journal_offset, app_state.config_modified)
+                    || escalated != app_state.session.escalated_on;
+            }
+            Err(_) => break,
+        }
+
+        if app_state.suspend_requested {
+            app_state.suspend_requested = false;
+            terminal::suspend(&mut terminal, app_state.config.mouse)?;
+        }
+
+        if let Some(command) = app_state.pending_command.take() {
+            events.pause();
+            let result = terminal::run_command(&mut terminal, app_state.config.mouse, &command);
+            events.resume();
+            match result {
+                Ok(status) if status.success() => {}
+                Ok(status) => app_state.set_status(format!("`{}` {}", command, status), true),
+                Err(e) => app_state.set_status(format!("Could not run `{}`: {}", command, e), true),
+            }
+        }
+
+        if app_state.config.autosave && app_state.dirty {
+            if let Err(e) = app_state.save_or_recover() {
+                app_state.set_status(format!("Autosave failed: {}", e), true);
+            }
+        }
+    }
+
+    if app_state.mode == Mode::Setup {
+        // Quitting mid-setup leaves the first run for next time.
+        return terminal::restore(&mut terminal);
+    }
+    app_state.merge_journal();
+    let saved = app_state.save_or_recover();
+    app_state.save_session();
+    terminal::restore(&mut terminal)?;
+    // Reported once the terminal is back, where it can be read.
+    if let Err(e) = saved {
+        eprintln!("Saving failed: {}", e);
+        std::process::exit(1);
+    }
+    Ok(())
+}
+
+fn render_tasks<B: Backend>(f: &mut Frame<B>, app_state: &AppState, chunk: Rect) {
+    let today = clock::now();
+    let date_format = &app_state.config.date_format;
+    let columns = columns::for_view(app_state);
+    let visible = app_state.visible_tasks();
+    let scores = urgency::scores(&app_state.tasks, &app_state.config.urgency, today);
+    // Scrolls to the selection wherever sorting moved it.
+    let mut state = TableState::default();
+    state.select(
+        app_state
+            .selected_task
+            .and_then(|selected| visible.iter().position(|i| *i == selected)),
+    );
+    let rows: Vec<Row> = visible
+        .into_iter()
+        .map(|i| (i, &app_state.tasks[i]))
+        .map(|(i, task)| {
+            let is_selected = Some(i) == app_state.selected_task;
+            let is_overdue = task.deadline.is_some_and(|d| d < today && !task.completed);
+
+            let soon = today.date() + chrono::Duration::days(plan::SOON_DAYS);
+            let is_due_soon = task
+                .deadline
+                .is_some_and(|d| d.date() <= soon && !task.completed);
+
+            let styles = &app_state.styles;
+            let base_style = if is_overdue {
+                styles.overdue
+            } else if task.completed && !is_selected {
+                styles.completed
+            } else if is_selected {
+                styles.selected
+            } else if subtasks::is_blocked(&app_state.tasks, task) {
+                styles.blocked
+            } else if is_due_soon {
+                styles.due_soon
+            } else if task.someday {
+                styles.someday
+            } else {
+                Style::default()
+            };
+
+            let mut lead = "  ".repeat(subtasks::depth(&app_state.tasks, i));
+            if app_state.palette == palette::Support::None {
+                let selection = if is_selected { '>' } else { ' ' };
+                let state = if task.completed {
+                    'x'
+                } else if is_overdue {
+                    '!'
+                } else if is_due_soon {
+                    '~'
+                } else {
+                    ' '
+                };
+                lead = format!("{}{} {}", selection, state, lead);
+            }
+            if let Some(merge) = &app_state.merge {
+                let mark = if merge.tasks.contains(&task.id) {
+                    "[x] "
+                } else {
+                    "[ ] "
+                };
+                lead = format!("{}{}", mark, lead);
+            }
+            let issue = jira::key(&task.description).and_then(|key| app_state.jira.get(key));
+            let row = RowInfo {
+                lead,
+                style: base_style,
+                status: issue.map(|issue| issue.status.as_str()),
+                masked: app_state.masked(task),
+                urgency: scores[i],
+            };
+            Row::new(
+                (columns.show.iter())
+                    .map(|column| column.cell(task, &row, date_format))
+                    .collect::<Vec<_>>(),
+            )
+        })
+        .collect();
+
+    let title = match &app_state.filter {
+        Some((text, _)) => format!("Tasks matching {}", text),
+        None => "Tasks".to_string(),
+    };
+    let title = format!("{} - {}", title, scope::label(app_state));
+    let arrow = if app_state.sort_descending {
+        " ▼"
+    } else {
+        " ▲"
+    };
+    let titles = columns.show.iter().map(|c| {
+        if c.sort_key() == app_state.sort {
+            format!("{}{}", c.title(), arrow)
+        } else {
+            c.title().to_string()
+        }
+    });
+    let header = Row::new(titles.collect::<Vec<_>>()).style(
+        Style::default()
+            .fg(Color::DarkGray)
+            .add_modifier(Modifier::BOLD),
+    );
+    let widths = columns.constraints(date_format, chunk.width.saturating_sub(2), 2);
+    let table = Table::new(rows)
+        .header(header)
+        .widths(&widths)
+        .column_spacing(2)
+        .block(Block::default().borders(Borders::ALL).title(title));
+    f.render_stateful_widget(table, chunk, &mut state);
+}
+
+fn render_input_paragraph<B: Backend>(f: &mut Frame<B>, app_state: &AppState, chunk: Rect) {
+    let (title, input_text) = match app_state.mode {
+        Mode::Input if app_state.new_parent.is_some() => {
+            ("New Subtask", format!("Input Mode: {}", app_state.input))
+        }
+        Mode::Input if !app_state.config.accessibility.chords => (
+            "Input (F2: symbols)",
+            format!("Input Mode: {}", app_state.input),
+        ),
+        Mode::Input => (
+            "Input (Ctrl+e: symbols)",
+            format!("Input Mode: {}", app_state.input),
+        ),
+        Mode::CompleteConfirm => {
+            let open = app_state
+                .selected_task
+                .and_then(|i| app_state.tasks.get(i))
+                .map_or(0, |task| {
+                    subtasks::descendants(&app_state.tasks, task.id)
+                        .into_iter()
+                        .filter(|i| !app_state.tasks[*i].completed)
+                        .count()
+                });
+            (
+                "Complete",
+                format!(
+                    "{} open subtasks. 1: Complete them too, 2: Detach them, any other key: Cancel",
+                    open
+                ),
+            )
+        }
+        Mode::Edit if !app_state.config.accessibility.chords => (
+            "Edit (F2: symbols)",
+            format!("Editing: {}", app_state.input),
+        ),
+        Mode::Edit => (
+            "Edit (Ctrl+e: symbols)",
+            format!("Editing: {}", app_state.input),
+        ),
+        Mode::ActionConfirm => {
+            let question = match app_state.confirming {
+                Some(Action::DeleteTask) => "Delete the selected task?",
+                Some(Action::ToggleComplete) => "Toggle completion of the selected task?",
+                _ => "Move the selected task to or from Someday?",
+            };
+            ("Confirm", format!("{} y: yes, any other key: cancel", question))
+        }
+        Mode::BulkConfirm => {
+            let question = app_state.pending.as_ref().map_or("", |p| p.question.as_str());
+            ("Confirm", format!("{} y: yes, any other key: cancel", question))
+        }
+        Mode::Menu => (
+            "Actions",
+            "Type to search  Up/Down: choose  Enter: run  Esc: close".to_string(),
+        ),
+        Mode::DeleteConfirm => (
+            "Delete",
+            format!(
+                "Press '{}' again to confirm deletion, or any other key to cancel.",
+                keymap::format_key(app_state.keymap.key_for(Action::DeleteTask))
+            ),
+        ),
+        Mode::DeadlineInput => {
+            let deadline_options: Vec<String> = app_state
+                .config
+                .deadline_presets
+                .iter()
+                .enumerate()
+                .map(|(i, preset)| {
+                    format!("{}: {}", i + 1, locale::label(preset, app_state.config.locale))
+                })
+                .collect();
+            (
+                "Select Deadline (Tab: type a date)",
+                deadline_options.join(", "),
+            )
+        }
+        Mode::DeadlineEntry => (
+            "Deadline (YYYY-MM-DD or a preset, Esc: presets)",
+            format!("Due: {}", app_state.input),
+        ),
+        Mode::History => ("History", "Press any key to return".to_string()),
+        Mode::WhatsNew => ("What's new", "Press any key to continue".to_string()),
+        Mode::Recover => {
+            let (tasks, when, error) = match &app_state.recovery {
+                Some((_, r)) => (r.tasks.len(), r.saved_at.as_str(), r.error.as_str()),
+                None => (0, "", ""),
+            };
+            (
+                "Recover unsaved changes",
+                format!(
+                    "Saving {} tasks failed at {} ({}). y: restore them, d: discard, \
+                     any other key: ask next time",
+                    tasks, when, error
+                ),
+            )
+        }
+        Mode::Conflict => (
+            "Resolve conflict",
+            "Up/Down: field  Left/Right: pick side  l/r: all mine/theirs  Enter: apply"
+                .to_string(),
+        ),
+        Mode::CommandInput => (
+            "Command (empty: remove, Esc: cancel)",
+            format!("$ {}", app_state.input),
+        ),
+        Mode::AttachInput if app_state.attach_copy => (
+            "Attach a copy in the data folder (Tab: link the file instead, Esc: cancel)",
+            format!("File: {}", app_state.input),
+        ),
+        Mode::AttachInput => (
+            "Attach file (Tab: store a copy instead, Esc: cancel)",
+            format!("File: {}", app_state.input),
+        ),
+        Mode::Zoom => (
+            "Zoom",
+            "Up/Down: previous/next task  any other key: return".to_string(),
+        ),
+        Mode::Heatmap => ("Completions", "Press any key to return".to_string()),
+        Mode::Agenda => (
+            "Agenda",
+            "Up/Down: pick a day to see its tasks  Esc: return".to_string(),
+        ),
+        Mode::Plan => (
+            "Plan the day",
+            "Up/Down: move  Space: commit or drop  Enter: done  Esc: cancel".to_string(),
+        ),
+        Mode::Snapshots if app_state.snapshots.as_ref().is_some_and(|b| b.tasks.is_some()) => (
+            "Snapshot",
+            "Up/Down: move  Space: mark  Enter: restore marked or selected  a: roll back to it all  \
+             Esc: back"
+                .to_string(),
+        ),
+        Mode::Timeline => (
+            "Day planner",
+            "Up/Down: time  Left/Right: pick a task  Enter: put it here  Backspace: unplace  \
+             Esc: return"
+                .to_string(),
+        ),
+        Mode::MergePick => (
+            "Merge tasks",
+            "Up/Down: move  Space: mark  Enter: pick fields  Esc: cancel".to_string(),
+        ),
+        Mode::Merge => (
+            "Merge tasks",
+            "Up/Down: field  Left/Right: pick a task's value  Enter: merge  Esc: back".to_string(),
+        ),
+        Mode::Snapshots => (
+            "Snapshots",
+            "Up/Down: move  Enter: open  a: roll back to it  Esc: return".to_string(),
+        ),
+        Mode::Storage => (
+            "Storage",
+            format!(
+                "c: compact the journal  p: prune snapshots to the newest {}  Esc: return",
+                maintenance::PRUNE_KEEP
+            ),
+        ),
+        Mode::Search if app_state.search_regex => (
+            "Regex search (Tab: query)",
+            format!("/{}", app_state.input),
+        ),
+        Mode::Search => ("Search (Tab: regex)", format!("/{}", app_state.input)),
+        Mode::Share => (
+            "Share",
+            "1: Save as .ics, 2: Draft an email, any other key: Cancel".to_string(),
+        ),
+        Mode::Postpone => {
+            let days = if app_state.workdays.enabled() {
+                "1: +1 workday, 2: +3 workdays"
+            } else {
+                "1: +1 day, 2: +3 days"
+            };
+            (
+                "Postpone",
+                format!("{}, 3: Next week, 4: Next month, 5: Remove deadline", days),
+            )
+        }
+        Mode::Kanban => (
+            "Board",
+            format!(
+                "Left/Right: column  '{}': start/stop  '{}': done  Esc: return",
+                keymap::format_key(app_state.keymap.key_for(Action::StartTask)),
+                keymap::format_key(app_state.keymap.key_for(Action::ToggleComplete))
+            ),
+        ),
+        Mode::Template => {
+            let names: Vec<String> = app_state
+                .config
+                .templates
+                .keys()
+                .enumerate()
+                .map(|(i, name)| format!("{}: {}", i + 1, name))
+                .collect();
+            ("Template", names.join(", "))
+        }
+        Mode::TagPick => {
+            let tags: Vec<String> = (app_state.tag_choices.iter().enumerate())
+                .map(|(i, tag)| format!("{}: #{}", i + 1, tag))
+                .collect();
+            ("Show tasks tagged (Esc: cancel)", tags.join(", "))
+        }
+        Mode::Placeholder => {
+            let name = app_state
+                .template_fill
+                .as_ref()
+                .and_then(|fill| fill.next_name())
+                .unwrap_or_default();
+            (
+                "Fill in template (Esc: cancel)",
+                format!("{}: {}", name, app_state.input),
+            )
+        }
+        Mode::Scratch if app_state.scratch.adding => {
+            ("Scratch", format!("Input Mode: {}", app_state.input))
+        }
+        Mode::Scratch => (
+            "Scratch",
+            format!(
+                "'{}': add  '{}': done  '{}': drop  Enter: move to task list  Esc: return",
+                keymap::format_key(app_state.keymap.key_for(Action::NewTask)),
+                keymap::format_key(app_state.keymap.key_for(Action::ToggleComplete)),
+                keymap::format_key(app_state.keymap.key_for(Action::DeleteTask))
+            ),
+        ),
+        Mode::Keybindings => (
+            "Keybindings",
+            "Select an action and press Enter, then the new key".to_string(),
+        ),
+        _ => ("Input", "Press 'n' to add a task".to_string()),
+    };
+
+    let mut block = Block::default().borders(Borders::ALL).title(title);
+    // Report a bad pattern while it's being typed, not only on Enter.
+    if app_state.mode == Mode::Search && app_state.search_regex {
+        if let Err(e) = query::parse_regex(&app_state.input) {
+            let title = format!("{} - {}", title, e);
+            block = block.title(Span::styled(title, Style::default().fg(Color::Red)));
+        }
+    }
+    // Typed deadlines are checked on Enter; the verdict stays until the next edit.
+    let notice = app_state.deadline_notice.as_ref();
+    if let Some(notice) = notice.filter(|_| app_state.mode == Mode::DeadlineEntry) {
+        let (text, color) = if notice.is_error {
+            (notice.text.clone(), Color::Red)
+        } else {
+            (
+                format!("{} - Enter again to keep it", notice.text),
+                Color::Yellow,
+            )
+        };
+        block = block.title(Span::styled(text, Style::default().fg(color)));
+    }
+    // A running count, turning red past `description_limit`.
+    if matches!(app_state.mode, Mode::Input | Mode::Edit) {
+        let words = app_state.input.split_whitespace().count();
+        let chars = app_state.input.chars().count();
+        let (count, style) = match app_state.config.description_limit {
+            Some(limit) if chars > limit => (
+                format!("{}/{} characters, {} over", chars, limit, chars - limit),
+                Style::default().fg(Color::Red),
+            ),
+            Some(limit) => (format!("{}/{} characters", chars, limit), Style::default()),
+            None => (format!("{} characters", chars), Style::default()),
+        };
+        let text = format!("{} - {} words, {}", title, words, count);
+        block = block.title(Span::styled(text, style));
+    }
+    let input_paragraph = Paragraph::new(input_text).block(block);
+    f.render_widget(input_paragraph, chunk);
+}
+
+fn render_status_bar<B: Backend>(f: &mut Frame<B>, app_state: &AppState, chunk: Rect) {
+    let dim = Style::default().fg(Color::DarkGray);
+    let (text, style) = match (&app_state.status, &app_state.config.status_format) {
+        (Some(message), _) => {
+            let color = if message.is_error {
+                Color::Red
+            } else {
+                Color::Green
+            };
+            (message.text.clone(), Style::default().fg(color))
+        }
+        (None, Some(format)) => (statusbar::render(format, app_state), dim),
+        (None, None) => {
+            let tasks = app_state.tasks.iter().filter(|t| app_state.in_scope(t));
+            let active = tasks.clone().filter(|t| !t.completed).count();
+            let mut text = format!("{} active / {} tasks", active, tasks.count());
+            if app_state.filter.is_some() {
+                text.push_str(&format!(", {} shown", app_state.visible_tasks().len()));
+            }
+            if let Some(health) = &app_state.health {
+                text.push_str(&format!(", health {}%", health.score()));
+                let key = keymap::format_key(app_state.keymap.key_for(Action::ReviewHealth));
+                if let Some(suggestion) = health.suggestion(&key) {
+                    text.push_str(&format!(" - {}", suggestion));
+                }
+            }
+            (text, dim)
+        }
+    };
+    // Hints take what the status leaves, dropping the last ones first.
+    let room = (chunk.width as usize).saturating_sub(text.width() + 2);
+    let hints = if app_state.config.key_hints {
+        hints::line(&hints::hints(app_state), room)
+    } else {
+        String::new()
+    };
+    let chunks = Layout::default()
+        .direction(Direction::Horizontal)
+        .constraints([Constraint::Min(0), Constraint::Length(hints.width() as u16)].as_ref())
+        .split(chunk);
+    f.render_widget(Paragraph::new(text).style(style), chunks[0]);
+    f.render_widget(Paragraph::new(hints).style(dim), chunks[1]);
+}
+
+/// Draws a frame, returning where the task list and details went; tui
+/// only writes the cells that changed since the last frame.
+fn draw(
+    terminal: &mut tui::Terminal<terminal::Backend>,
+    app_state: &AppState,
+) -> Result<Rect, io::Error> {
+    let mut body_area = app_state.body_area;
+    terminal.draw(|f| {
+        let size = f.size();
+        if size.width < MIN_WIDTH || size.height < MIN_HEIGHT {
+            render_too_small(f, size);
+            return;
+        }
+        let mut chunks = create_layout(size);
+
+        if app_state.mode == Mode::Locked {
+            lock::render_lock(f, app_state, size);
+            return;
+        }
+
+        if let Some(wizard) = &app_state.wizard {
+            wizard::render_wizard(f, wizard, size);
+            return;
+        }
+
+        render_input_paragraph(f, app_state, chunks[0]);
+        if app_state.tutorial.is_some() {
+            let rows = Layout::default()
+                .direction(Direction::Vertical)
+                .constraints([Constraint::Length(tutorial::HEIGHT), Constraint::Min(0)].as_ref())
+                .split(chunks[1]);
+            tutorial::render_tutorial(f, app_state, rows[0]);
+            chunks[1] = rows[1];
+        }
+        if app_state.mode == Mode::History {
+            history::render_history(f, app_state, chunks[1]);
+        } else if app_state.mode == Mode::Zoom {
+            zoom::render_zoom(f, app_state, chunks[1]);
+        } else if app_state.mode == Mode::Conflict {
+            conflict::render_conflict(f, app_state, chunks[1]);
+        } else if app_state.mode == Mode::WhatsNew {
+            whatsnew::render_whats_new(f, app_state, chunks[1]);
+        } else if app_state.mode == Mode::Heatmap {
+            heatmap::render_heatmap(f, app_state, chunks[1]);
+        } else if app_state.mode == Mode::Agenda {
+            agenda::render_agenda(f, app_state, chunks[1]);
+        } else if app_state.mode == Mode::Plan {
+            plan::render_plan(f, app_state, chunks[1]);
+        } else if app_state.mode == Mode::Snapshots {
+            snapshots::render_snapshots(f, app_state, chunks[1]);
+        } else if app_state.mode == Mode::Storage {
+            maintenance::render_maintenance(f, app_state, chunks[1]);
+        } else if app_state.mode == Mode::BulkConfirm {
+            bulk::render_bulk(f, app_state, chunks[1]);
+        } else if app_state.mode == Mode::Merge {
+            merge::render_merge(f, app_state, chunks[1]);
+        } else if app_state.mode == Mode::Timeline {
+            timeline::render_timeline(f, app_state, chunks[1]);
+        } else if app_state.mode == Mode::Kanban {
+            kanban::render_kanban(f, app_state, chunks[1]);
+        } else if app_state.mode == Mode::Keybindings {
+            settings::render_keybindings(f, app_state, &app_state.keybinding_editor, chunks[1]);
+        } else {
+            body_area = chunks[1];
+            if tabs::tabs(app_state).len() > 1 {
+                let rows = Layout::default()
+                    .direction(Direction::Vertical)
+                    .constraints([Constraint::Length(1), Constraint::Min(0)].as_ref())
+                    .split(chunks[1]);
+                tabs::render_tabs(f, app_state, rows[0]);
+                body_area = rows[1];
+            }
+            let panes = split_body(body_area, app_state.session.split_percent);
+            if app_state.mode == Mode::Search {
+                search::render_results(f, app_state, panes[0]);
+            } else if app_state.mode == Mode::Scratch || !app_state.scratch.items.is_empty() {
+                let rows = Layout::default()
+                    .direction(Direction::Vertical)
+                    .constraints(
+                        [
+                            Constraint::Min(0),
+                            Constraint::Length(app_state.scratch.height()),
+                        ]
+                        .as_ref(),
+                    )
+                    .split(panes[0]);
+                render_tasks(f, app_state, rows[0]);
+                scratch::render_scratch(f, app_state, rows[1]);
+            } else {
+                render_tasks(f, app_state, panes[0]);
+            }
+            detail::render_detail(f, app_state, panes[1]);
+        }
+        render_status_bar(f, app_state, chunks[2]);
+        let label = match app_state.mode {
+            Mode::Input => "Input Mode: ",
+            Mode::Edit => "Editing: ",
+            _ => "",
+        };
+        if !label.is_empty() {
+            let text_x = chunks[0].x + 1 + label.len() as u16;
+            completion::render_popup(f, app_state, text_x, chunks[0].bottom());
+        }
+        if let Some(picker) = &app_state.emoji_picker {
+            emoji::render_picker(f, app_state, picker);
+        }
+        if app_state.mode == Mode::Menu {
+            menu::render_menu(f, app_state);
+        }
+    })?;
+    Ok(body_area)
+}
+
+fn render_too_small<B: Backend>(f: &mut Frame<B>, size: Rect) {
+    let message = format!(
+        "Terminal too small ({}x{}).\nPlease enlarge it to at least {}x{}.",
+        size.width, size.height, MIN_WIDTH, MIN_HEIGHT
+    );
+    let top = size.height.saturating_sub(2) / 2;
+    let area = Rect::new(size.x, size.y + top, size.width, size.height - top);
+    let paragraph = Paragraph::new(message)
+        .alignment(Alignment::Center)
+        .wrap(Wrap { trim: true })
+        .style(Style::default().fg(Color::Yellow));
+    f.render_widget(paragraph, area);
+}
+
+fn create_layout(size: Rect) -> Vec<Rect> {
+    Layout::default()
+        .direction(Direction::Vertical)
+        .margin(1)
+        .constraints(
+            [
+                Constraint::Length(3),
+                Constraint::Min(0),
+                Constraint::Length(1),
+            ]
+            .as_ref(),
+        )
+        .split(size)
+}
+
+/// Splits the body into the task list and the detail pane.
+fn split_body(area: Rect, list_percent: u16) -> Vec<Rect> {
+    Layout::default()
+        .direction(Direction::Horizontal)
+        .constraints(
+            [
+                Constraint::Percentage(list_percent),
+                Constraint::Percentage(100 - list_percent),
+            ]
+            .as_ref(),
+        )
+        .split(area)
+}
+
+/// Dragging the border between the list and the detail pane resizes them.
+fn process_mouse_event(mouse: MouseEvent, app_state: &mut AppState) {
+    let body = app_state.body_area;
+    if app_state.mode != Mode::Normal || body.width == 0 {
+        return;
+    }
+
+    let (x, y) = (mouse.column, mouse.row);
+    match mouse.kind {
+        MouseEventKind::Down(MouseButton::Left) => {
+            let divider = split_body(body, app_state.session.split_percent)[1].x;
+            let on_divider = x + 1 >= divider && x <= divider;
+            app_state.dragging_split = on_divider && y >= body.y && y < body.y + body.height;
+        }
+        MouseEventKind::Drag(MouseButton::Left) if app_state.dragging_split => {
+            let offset = x.saturating_sub(body.x) as u32;
+            let percent = (offset * 100 / body.width as u32) as u16;
+            app_state.session.split_percent =
+                percent.clamp(SessionState::MIN_SPLIT, SessionState::MAX_SPLIT);
+        }
+        MouseEventKind::Up(_) if app_state.dragging_split => {
+            app_state.dragging_split = false;
+            app_state.save_session();
+        }
+        _ => {}
+    }
+}
+
+/// Multi-line pastes become tasks (nested by indentation); a single line is
+/// typed into the input line.
+fn process_paste(text: &str, app_state: &mut AppState) {
+    // Terminals paste line breaks as carriage returns.
+    let text = &text.replace("\r\n", "\n").replace('\r', "\n");
+    let typing = matches!(app_state.mode, Mode::Input | Mode::Edit) && !app_state.setting_deadline;
+    if typing && !text.trim_end().contains('\n') {
+        app_state.input.push_str(text.trim_end());
+        return;
+    }
+    if !matches!(app_state.mode, Mode::Normal | Mode::Input) {
+        return;
+    }
+
+    let parent = if app_state.mode == Mode::Input {
+        app_state.new_parent.take()
+    } else {
+        None
+    };
+    if let Err(e) = app_state.snapshot_before("pre-import") {
+        app_state.set_status(format!("Snapshot failed, nothing imported: {}", e), true);
+        return;
+    }
+    let count = app_state.import_outline(text, parent);
+    app_state.mode = Mode::Normal;
+    app_state.set_status(format!("Imported {} tasks", count), false);
+}
+
+fn process_key_event(key: Key, app_state: &mut AppState) -> bool {
+    app_state.status = None;
+    // Not even suspending, which would hand the shell to whoever is at
+    // the keyboard.
+    if app_state.mode == Mode::Locked {
+        lock::handle_key(app_state, key);
+        return true;
+    }
+    // Suspending works from any mode, except while capturing a new binding.
+    let suspend = app_state.keymap.action_for(key) == Some(Action::Suspend);
+    if suspend && app_state.mode != Mode::Keybindings {
+        app_state.suspend_requested = true;
+        return true;
+    }
+
+    if let Some(picker) = app_state.emoji_picker.as_mut() {
+        match key {
+            Key::Char('\n') => {
+                if let Some(symbol) = picker.selection() {
+                    app_state.input.push_str(symbol);
+                }
+                app_state.emoji_picker = None;
+            }
+            Key::Esc => app_state.emoji_picker = None,
+            Key::Left => picker.step(-1),
+            Key::Right => picker.step(1),
+            Key::Up => picker.row_step(false),
+            Key::Down => picker.row_step(true),
+            Key::Char(c) => {
+                picker.query.push(c);
+                picker.selected = 0;
+            }
+            Key::Backspace => {
+                picker.query.pop();
+                picker.selected = 0;
+            }
+            _ => {}
+        }
+        return true;
+    }
+
+    match app_state.mode {
+        Mode::Normal => match app_state.keymap.action_for(key) {
+            Some(Action::Quit) => {
+                return false;
+            }
+            Some(
+                action @ (Action::DeleteTask | Action::ToggleComplete | Action::ToggleSomeday),
+            ) if app_state.config.accessibility.confirm_actions
+                && app_state.selected_task.is_some() =>
+            {
+                app_state.confirming = Some(action);
+                app_state.mode = Mode::ActionConfirm;
+            }
+            Some(Action::ShowMenu | Action::CommandPalette) => {
+                app_state.menu_selected = 0;
+                app_state.menu_query.clear();
+                app_state.mode = Mode::Menu;
+            }
+            Some(Action::NewTask) => {
+                app_state.mode = Mode::Input;
+                app_state.editing = false;
+                app_state.new_parent = None;
+                app_state.input.clear();
+            }
+            Some(Action::NewSubtask) if app_state.selected_task.is_some() => {
+                app_state.mode = Mode::Input;
+                app_state.editing = false;
+                app_state.new_parent = app_state
+                    .selected_task
+                    .and_then(|i| app_state.tasks.get(i))
+                    .map(|t| t.id);
+                app_state.input.clear();
+            }
+            Some(Action::NewFromTemplate) => {
+                if app_state.config.templates.is_empty() {
+                    app_state.set_status(
+                        "No templates yet; add them under [templates] in the config".to_string(),
+                        true,
+                    );
+                } else {
+                    app_state.mode = Mode::Template;
+                }
+            }
+            Some(Action::DeleteTask) if app_state.selected_task.is_some() => {
+                app_state.mode = Mode::DeleteConfirm;
+            }
+            Some(Action::FilterByTag) => {
+                let task = app_state.selected_task.and_then(|i| app_state.tasks.get(i));
+                let mut choices: Vec<String> = Vec::new();
+                for tag in task.into_iter().flat_map(|t| tags::tags(&t.description)) {
+                    if !choices.iter().any(|c| c == tag) && choices.len() < 9 {
+                        choices.push(tag.to_string());
+                    }
+                }
+                if choices.is_empty() {
+                    app_state.set_status("The task has no #tags".to_string(), true);
+                } else {
+                    app_state.tag_choices = choices;
+                    app_state.mode = Mode::TagPick;
+                }
+            }
+            Some(Action::EditTask) if app_state.selected_task.is_some() => {
+                app_state.mode = Mode::Edit;
+                app_state.editing = true;
+                app_state.input = app_state.tasks[app_state.selected_task.unwrap()]
+                    .description
+                    .clone();
+            }
+            Some(Action::ToggleComplete) if app_state.selected_task.is_some() => {
+                app_state.complete_selected();
+            }
+            Some(Action::ShowHistory) if app_state.selected_task.is_some() => {
+                app_state.open_history();
+            }
+            Some(Action::ShowHeatmap) => app_state.open_heatmap(),
+            Some(Action::ShowAgenda) => {
+                app_state.agenda_selected = 0;
+                app_state.mode = Mode::Agenda;
+            }
+            Some(Action::ShowKanban) => {
+                let column = app_state
+                    .selected_task
+                    .and_then(|i| app_state.tasks.get(i))
+                    .map_or(Column::Todo, Column::of);
+                app_state.select_in_column(column);
+                app_state.mode = Mode::Kanban;
+            }
+            Some(Action::ShowScratch) => app_state.mode = Mode::Scratch,
+            Some(Action::Zoom) => app_state.mode = Mode::Zoom,
+            Some(Action::ReviewHealth) => app_state.review_health(),
+            Some(Action::SortNext) => app_state.sort_next(),
+            Some(Action::SortByDue) => app_state.toggle_sort_by_due(),
+            Some(Action::TogglePrivate) => app_state.toggle_private_selected(),
+            Some(Action::PrivacyScreen) => {
+                app_state.privacy_screen = !app_state.privacy_screen;
+                app_state.revealed = None;
+                let text = if app_state.privacy_screen {
+                    "Privacy screen on"
+                } else {
+                    "Privacy screen off"
+                };
+                app_state.set_status(text.to_string(), false);
+            }
+            Some(Action::Reveal) => {
+                let task = app_state.selected_task.and_then(|i| app_state.tasks.get(i));
+                let id = task.map(|t| t.id);
+                app_state.revealed = if app_state.revealed == id { None } else { id };
+            }
+            Some(Action::PlanDay) => {
+                app_state.planner = Some(plan::Planner::new(app_state));
+                app_state.mode = Mode::Plan;
+            }
+            Some(Action::ShowSnapshots) => app_state.open_snapshots(),
+            Some(Action::ToggleScope) => scope::toggle(app_state),
+            Some(Action::MergeTasks) => merge::start(app_state),
+            Some(Action::ShowStorage) => {
+                app_state.maintenance = Some(maintenance::Report::new(app_state));
+                app_state.mode = Mode::Storage;
+            }
+            Some(Action::ShowTimeline) => {
+                let calendar = &app_state.config.calendar;
+                app_state.timeline = Some(timeline::Timeline::new(&app_state.tasks, calendar));
+                app_state.mode = Mode::Timeline;
+            }
+            Some(Action::SortReverse) if app_state.sort != SortKey::Manual => {
+                app_state.sort_descending = !app_state.sort_descending;
+            }
+            Some(Action::SetCommand) => {
+                if let Some(task) = app_state.selected_task.and_then(|i| app_state.tasks.get(i)) {
+                    app_state.input = task.command.clone().unwrap_or_default();
+                    app_state.mode = Mode::CommandInput;
+                }
+            }
+            Some(Action::RunCommand) => {
+                let task = app_state.selected_task.and_then(|i| app_state.tasks.get(i));
+                let link = |t: &Task| links::find(&t.description).next().map(str::to_string);
+                match task.map(|t| (t.command.clone(), link(t))) {
+                    Some((Some(command), _)) => app_state.pending_command = Some(command),
+                    // Without a command, the task's first link is its work.
+                    Some((None, Some(link))) => {
+                        if let Err(e) = share::open_url(&link) {
+                            app_state.set_status(format!("Could not open link: {}", e), true);
+                        }
+                    }
+                    Some((None, None)) => {
+                        let key = app_state.keymap.key_for(Action::SetCommand);
+                        app_state.set_status(
+                            format!(
+                                "No command yet; press '{}' to set one",
+                                keymap::format_key(key)
+                            ),
+                            true,
+                        );
+                    }
+                    None => {}
+                }
+            }
+            Some(Action::Attach) if app_state.selected_task.is_some() => {
+                app_state.input.clear();
+                app_state.attach_copy = false;
+                app_state.mode = Mode::AttachInput;
+            }
+            Some(Action::OpenAttachments) => {
+                let task = app_state.selected_task.and_then(|i| app_state.tasks.get(i));
+                match task.map(|t| t.attachments.clone()) {
+                    Some(paths) if paths.is_empty() => {
+                        let key = keymap::format_key(app_state.keymap.key_for(Action::Attach));
+                        let text = format!("No attachments; press '{}' to add one", key);
+                        app_state.set_status(text, true);
+                    }
+                    Some(paths) => {
+                        if let Err(e) = attachments::open(&paths) {
+                            app_state.set_status(format!("Could not open: {}", e), true);
+                        }
+                    }
+                    None => {}
+                }
+            }
+            Some(Action::StartTask) => app_state.toggle_started(),
+            Some(Action::Postpone) if app_state.selected_task.is_some() => {
+                app_state.mode = Mode::Postpone;
+            }
+            Some(Action::ShareTask) if app_state.selected_task.is_some() => {
+                app_state.mode = Mode::Share;
+            }
+            Some(Action::ToggleSomeday) => app_state.toggle_someday_selected(),
+            Some(Action::Undo) => app_state.undo_auto_defer(),
+            Some(Action::BlockTime) => {
+                if let Some(task) = app_state.selected_task.and_then(|i| app_state.tasks.get(i)) {
+                    match calendar::block_time(task, &app_state.config.calendar) {
+                        Ok(text) => app_state.set_status(text, false),
+                        Err(e) => {
+                            app_state.set_status(format!("Could not block time: {}", e), true)
+                        }
+                    }
+                }
+            }
+            Some(Action::EditKeybindings) => {
+                app_state.keybinding_editor = KeybindingEditor::new();
+                app_state.mode = Mode::Keybindings;
+            }
+            Some(Action::NextTab) => app_state.cycle_tab(true),
+            Some(Action::PrevTab) => app_state.cycle_tab(false),
+            Some(Action::MoveUp) => app_state.move_selection(true),
+            Some(Action::MoveDown) => app_state.move_selection(false),
+            Some(Action::Search) => search::open(app_state),
+            _ => {}
+        },
+        // Taken at the top, before suspending or the emoji picker.
+        Mode::Locked => {}
+        Mode::Setup => {
+            if key == Key::Ctrl('c') {
+                return false;
+            }
+            if let Some(wizard) = app_state.wizard.as_mut() {
+                if wizard.handle_key(key) {
+                    app_state.finish_setup();
+                }
+            }
+        }
+        Mode::Keybindings => {
+            let result = match app_state.keybinding_editor.handle_key(key) {
+                EditorCommand::None => Ok(()),
+                EditorCommand::Close => {
+                    app_state.mode = Mode::Normal;
+                    Ok(())
+                }
+                EditorCommand::Bind(action, key) => app_state.rebind(action, Some(key)),
+                EditorCommand::Reset(action) => app_state.rebind(action, None),
+            };
+            if let Err(e) = result {
+                app_state.set_status(e, true);
+            }
+        }
+        Mode::Heatmap => app_state.mode = Mode::Normal,
+        Mode::CompleteConfirm => {
+            app_state.mode = Mode::Normal;
+            match key {
+                Key::Char('1') => app_state.finish_with_subtasks(true),
+                Key::Char('2') => app_state.finish_with_subtasks(false),
+                _ => {}
+            }
+        }
+        Mode::Search => match key {
+            Key::Char('\n') => {
+                let text = std::mem::take(&mut app_state.input);
+                match app_state.apply_search(&text) {
+                    Ok(()) => {
+                        // A typed search replaces whatever tab was picked.
+                        app_state.tab = 0;
+                        app_state.mode = Mode::Normal;
+                        app_state.search = None;
+                    }
+                    Err(e) => {
+                        app_state.input = text;
+                        app_state.set_status(format!("Invalid search: {}", e), true);
+                    }
+                }
+            }
+            Key::Char('\t') => {
+                app_state.search_regex = !app_state.search_regex;
+                search::restart(app_state);
+            }
+            Key::Char(c) => {
+                app_state.input.push(c);
+                search::restart(app_state);
+            }
+            Key::Backspace => {
+                app_state.input.pop();
+                search::restart(app_state);
+            }
+            Key::Esc => {
+                app_state.input.clear();
+                app_state.mode = Mode::Normal;
+                app_state.search = None;
+            }
+            _ => {}
+        },
+        Mode::Share => {
+            app_state.mode = Mode::Normal;
+            let Some(task) = app_state.selected_task.and_then(|i| app_state.tasks.get(i)) else {
+                return true;
+            };
+            let result = match key {
+                Key::Char('1') => {
+                    let dir = if app_state.store.persists() {
+                        app_state.data_dir.clone()
+                    } else {
+                        std::env::temp_dir()
+                    };
+                    share::write_ics(task, &dir).map(|path| format!("Saved {}", path.display()))
+                }
+                Key::Char('2') => {
+                    share::open_url(&share::mailto_url(task, &app_state.config.date_format))
+                        .map(|_| "Opened an email draft".to_string())
+                }
+                _ => return true,
+            };
+            match result {
+                Ok(text) => app_state.set_status(text, false),
+                Err(e) => app_state.set_status(format!("Could not share task: {}", e), true),
+            }
+        }
+        Mode::Postpone => {
+            app_state.mode = Mode::Normal;
+            let task = app_state.selected_task.and_then(|i| app_state.tasks.get(i));
+            if let (Key::Char(choice @ '1'..='5'), Some(task)) = (key, task) {
+                let deadline = postpone_deadline(task.deadline, choice, &app_state.workdays);
+                let description = task.description.clone();
+                app_state.update_task(description, deadline);
+                let text = match deadline {
+                    Some(d) => format!("Postponed to {}", d.format(&app_state.config.date_format)),
+                    None => "Deadline removed".to_string(),
+                };
+                app_state.set_status(text, false);
+            }
+        }
+        Mode::Kanban => match (app_state.keymap.action_for(key), key) {
+            (Some(Action::Quit) | Some(Action::ShowKanban), _) | (_, Key::Esc) => {
+                app_state.mode = Mode::Normal;
+            }
+            (_, Key::Left) | (_, Key::Right) => {
+                let column = app_state.kanban_column.step(key == Key::Right);
+                app_state.select_in_column(column);
+            }
+            (Some(action @ (Action::MoveUp | Action::MoveDown)), _) => {
+                let indices = kanban::column_tasks(&app_state.tasks, app_state.kanban_column);
+                let position = indices
+                    .iter()
+                    .position(|i| Some(*i) == app_state.selected_task);
+                let next = match (position, action) {
+                    (Some(p), Action::MoveUp) => p.saturating_sub(1),
+                    (Some(p), _) => (p + 1).min(indices.len().saturating_sub(1)),
+                    (None, _) => 0,
+                };
+                app_state.selected_task = indices.get(next).copied();
+            }
+            (Some(action @ (Action::StartTask | Action::ToggleComplete)), _) => {
+                if action == Action::StartTask {
+                    app_state.toggle_started();
+                } else {
+                    app_state.complete_selected();
+                }
+                // Follow the task into its new column.
+                if let Some(task) = app_state.selected_task.and_then(|i| app_state.tasks.get(i)) {
+                    app_state.kanban_column = Column::of(task);
+                }
+            }
+            _ => {}
+        },
+        Mode::Template => {
+            app_state.mode = Mode::Normal;
+            if let Key::Char(c @ '1'..='9') = key {
+                let index = c.to_digit(10).unwrap() as usize - 1;
+                if let Some(template) = app_state.config.templates.values().nth(index) {
+                    app_state.template_fill = Some(TemplateFill::new(template));
+                    app_state.advance_template();
+                }
+            }
+        }
+        Mode::TagPick => {
+            app_state.mode = Mode::Normal;
+            if let Key::Char(c @ '1'..='9') = key {
+                let index = c.to_digit(10).unwrap() as usize - 1;
+                if let Some(tag) = app_state.tag_choices.get(index).cloned() {
+                    app_state.search_regex = false;
+                    if let Err(e) = app_state.apply_search(&format!("#{}", tag)) {
+                        app_state.set_status(format!("Invalid filter: {}", e), true);
+                    }
+                }
+            }
+        }
+        Mode::Placeholder => match key {
+            Key::Char('\n') => {
+                let value = std::mem::take(&mut app_state.input);
+                if let Some(fill) = app_state.template_fill.as_mut() {
+                    if let Some(name) = fill.next_name().map(str::to_string) {
+                        fill.values.insert(name, value);
+                    }
+                }
+                app_state.advance_template();
+            }
+            Key::Char(c) => app_state.input.push(c),
+            Key::Backspace => {
+                app_state.input.pop();
+            }
+            Key::Esc => {
+                app_state.input.clear();
+                app_state.template_fill = None;
+                app_state.mode = Mode::Normal;
+            }
+            _ => {}
+        },
+        Mode::Scratch if app_state.scratch.adding => match key {
+            Key::Char('\n') => {
+                let description = std::mem::take(&mut app_state.input);
+                if !description.trim().is_empty() {
+                    app_state.scratch.add(description);
+                }
+                app_state.scratch.adding = false;
+            }
+            Key::Char(c) => app_state.input.push(c),
+            Key::Backspace => {
+                app_state.input.pop();
+            }
+            Key::Esc => {
+                app_state.input.clear();
+                app_state.scratch.adding = false;
+            }
+            _ => {}
+        },
+        Mode::Scratch => match (app_state.keymap.action_for(key), key) {
+            (Some(Action::Quit) | Some(Action::ShowScratch), _) | (_, Key::Esc) => {
+                app_state.mode = Mode::Normal;
+            }
+            (Some(Action::NewTask), _) => {
+                app_state.input.clear();
+                app_state.scratch.adding = true;
+            }
+            (Some(Action::ToggleComplete), _) => app_state.scratch.toggle(),
+            (Some(Action::DeleteTask), _) => {
+                app_state.scratch.remove();
+            }
+            (Some(Action::MoveUp), _) => app_state.scratch.move_selection(true),
+            (Some(Action::MoveDown), _) => app_state.scratch.move_selection(false),
+            (_, Key::Char('\n')) => app_state.promote_scratch(),
+            _ => {}
+        },
+        Mode::Agenda => match app_state.keymap.action_for(key) {
+            Some(Action::MoveUp) => {
+                app_state.agenda_selected = app_state.agenda_selected.saturating_sub(1);
+            }
+            Some(Action::MoveDown) => {
+                let days = agenda::build(&app_state.tasks, |t| app_state.in_scope(t)).len();
+                app_state.agenda_selected =
+                    (app_state.agenda_selected + 1).min(days.saturating_sub(1));
+            }
+            Some(Action::Quit) | Some(Action::ShowAgenda) => app_state.mode = Mode::Normal,
+            _ if key == Key::Esc => app_state.mode = Mode::Normal,
+            _ => {}
+        },
+        Mode::Plan => match (app_state.keymap.action_for(key), key) {
+            (Some(Action::MoveUp), _) => {
+                if let Some(planner) = &mut app_state.planner {
+                    planner.move_selection(true);
+                }
+            }
+            (Some(Action::MoveDown), _) => {
+                if let Some(planner) = &mut app_state.planner {
+                    planner.move_selection(false);
+                }
+            }
+            (_, Key::Char(' ')) => {
+                let capacity = app_state.config.capacity_minutes();
+                let toggled = (app_state.planner.as_mut())
+                    .map(|planner| planner.toggle(&app_state.tasks, capacity));
+                if let Some(Err(e)) = toggled {
+                    app_state.set_status(e, true);
+                }
+            }
+            (_, Key::Char('\n')) => app_state.commit_plan(),
+            (_, Key::Esc) => {
+                app_state.planner = None;
+                app_state.mode = Mode::Normal;
+            }
+            _ => {}
+        },
+        Mode::Timeline => {
+            let Some(timeline) = &mut app_state.timeline else {
+                app_state.mode = Mode::Normal;
+                return true;
+            };
+            match (app_state.keymap.action_for(key), key) {
+                (Some(Action::MoveUp), _) => timeline.move_cursor(true),
+                (Some(Action::MoveDown), _) => timeline.move_cursor(false),
+                (_, Key::Left) => timeline.cycle(&app_state.tasks, false),
+                (_, Key::Right) => timeline.cycle(&app_state.tasks, true),
+                (_, Key::Char('\n')) => {
+                    let picked = timeline.picked;
+                    let Some(task) = app_state.tasks.iter().find(|t| Some(t.id) == picked) else {
+                        return true;
+                    };
+                    let Some(timeline) = &app_state.timeline else {
+                        return true;
+                    };
+                    match timeline.place(app_state, task) {
+                        Ok(deadline) => app_state.reschedule(task.id, Some(deadline)),
+                        Err(e) => app_state.set_status(e, true),
+                    }
+                }
+                (_, Key::Backspace) => {
+                    let (id, day) = (timeline.picked, timeline.day);
+                    if let Some(id) = id {
+                        app_state.reschedule(id, day.and_hms_opt(0, 0, 0));
+                    }
+                }
+                (Some(Action::Quit) | Some(Action::ShowTimeline), _) | (_, Key::Esc) => {
+                    app_state.timeline = None;
+                    app_state.mode = Mode::Normal;
+                }
+                _ => {}
+            }
+        }
+        Mode::MergePick => merge::handle_pick_key(app_state, key),
+        Mode::Merge => merge::handle_key(app_state, key),
+        Mode::Storage => match (app_state.keymap.action_for(key), key) {
+            (_, Key::Char('c')) => bulk::compact_journal(app_state),
+            (_, Key::Char('p')) if !app_state.store.persists() => {
+                let text = "Snapshots aren't kept in memory mode".to_string();
+                app_state.set_status(text, true);
+            }
+            (_, Key::Char('p')) => bulk::prune_snapshots(app_state),
+            (Some(Action::Quit) | Some(Action::ShowStorage), _) | (_, Key::Esc) => {
+                app_state.maintenance = None;
+                app_state.mode = Mode::Normal;
+            }
+            _ => {}
+        },
+        Mode::BulkConfirm => bulk::handle_key(app_state, key),
+        Mode::Snapshots => {
+            let Some(browser) = &mut app_state.snapshots else {
+                app_state.mode = Mode::Normal;
+                return true;
+            };
+            match (app_state.keymap.action_for(key), key) {
+                (Some(Action::MoveUp), _) => browser.move_selection(true),
+                (Some(Action::MoveDown), _) => browser.move_selection(false),
+                (_, Key::Char(' ')) => browser.toggle_mark(),
+                (_, Key::Char('\n')) if browser.tasks.is_none() => {
+                    if let Err(e) = browser.open() {
+                        app_state.set_status(format!("Could not open snapshot: {}", e), true);
+                    }
+                }
+                (_, Key::Char('\n')) => {
+                    let (picked, taken) = (browser.picked(), browser.taken());
+                    let count = app_state.restore_tasks(picked);
+                    let format = format!("{} %H:%M", app_state.config.date_format);
+                    let taken = taken.map(|t| t.format(&format).to_string());
+                    let tasks = match count {
+                        1 => "1 task".to_string(),
+                        n => format!("{} tasks", n),
+                    };
+                    app_state.snapshots = None;
+                    app_state.mode = Mode::Normal;
+                    app_state.set_status(
+                        format!("Restored {} from {}", tasks, taken.unwrap_or_default()),
+                        false,
+                    );
+                }
+                (_, Key::Char('a')) => {
+                    let opened = match browser.tasks {
+                        Some(_) => Ok(()),
+                        None => browser.open(),
+                    };
+                    if let Err(e) = opened {
+                        app_state.set_status(format!("Could not open snapshot: {}", e), true);
+                        return true;
+                    }
+                    let format = format!("{} %H:%M", app_state.config.date_format);
+                    let label = browser.label(&format);
+                    let taken = browser.taken().unwrap_or_default();
+                    let tasks = browser.tasks.take().unwrap_or_default();
+                    app_state.snapshots = None;
+                    app_state.mode = Mode::Normal;
+                    bulk::roll_back(app_state, tasks, taken, label);
+                }
+                (_, Key::Esc) if browser.tasks.is_some() => browser.close(),
+                (_, Key::Esc) => {
+                    app_state.snapshots = None;
+                    app_state.mode = Mode::Normal;
+                }
+                _ => {}
+            }
+        }
+        Mode::CommandInput => match key {
+            Key::Char('\n') => {
+                let command = std::mem::take(&mut app_state.input);
+                let command = command.trim();
+                app_state.set_command((!command.is_empty()).then(|| command.to_string()));
+                app_state.mode = Mode::Normal;
+            }
+            Key::Char(c) => app_state.input.push(c),
+            Key::Backspace => {
+                app_state.input.pop();
+            }
+            Key::Esc => {
+                app_state.input.clear();
+                app_state.mode = Mode::Normal;
+            }
+            _ => {}
+        },
+        Mode::AttachInput => match key {
+            Key::Char('\n') => {
+                let path = std::mem::take(&mut app_state.input);
+                app_state.mode = Mode::Normal;
+                let index = app_state.selected_task.filter(|_| !path.trim().is_empty());
+                if let Some(index) = index {
+                    match app_state.attach(index, &path, app_state.attach_copy) {
+                        Ok(()) => app_state.set_status("Attached".to_string(), false),
+                        Err(e) => app_state.set_status(format!("Could not attach {}", e), true),
+                    }
+                }
+            }
+            Key::Char('\t') => app_state.attach_copy = !app_state.attach_copy,
+            Key::Char(c) => app_state.input.push(c),
+            Key::Backspace => {
+                app_state.input.pop();
+            }
+            Key::Esc => {
+                app_state.input.clear();
+                app_state.mode = Mode::Normal;
+            }
+            _ => {}
+        },
+        Mode::Zoom => match app_state.keymap.action_for(key) {
+            Some(Action::MoveUp) => app_state.move_selection(true),
+            Some(Action::MoveDown) => app_state.move_selection(false),
+            _ => app_state.mode = Mode::Normal,
+        },
+        Mode::WhatsNew => {
+            app_state.whats_new.clear();
+            app_state.mode = Mode::Normal;
+        }
+        Mode::Recover => {
+            // The release notes the recovery question went ahead of.
+            app_state.mode = if app_state.whats_new.is_empty() {
+                Mode::Normal
+            } else {
+                Mode::WhatsNew
+            };
+            match key {
+                Key::Char('y') => app_state.restore_recovered(),
+                Key::Char('d') => {
+                    if let Some((path, _)) = app_state.recovery.take() {
+                        let _ = std::fs::remove_file(path);
+                    }
+                    app_state.set_status("Discarded the unsaved changes".to_string(), false);
+                }
+                _ => {
+                    app_state.recovery = None;
+                    let text = "Kept the unsaved changes for next time".to_string();
+                    app_state.set_status(text, false);
+                }
+            }
+        }
+        Mode::History => {
+            app_state.history.clear();
+            app_state.mode = Mode::Normal;
+        }
+        Mode::Conflict => {
+            let Some(conflict) = app_state.conflicts.first_mut() else {
+                app_state.mode = Mode::Normal;
+                return true;
+            };
+            let last = conflict.fields.len().saturating_sub(1);
+            match key {
+                Key::Up => conflict.selected = conflict.selected.saturating_sub(1),
+                Key::Down => conflict.selected = (conflict.selected + 1).min(last),
+                Key::Left | Key::Right => {
+                    if let Some(field) = conflict.fields.get_mut(conflict.selected) {
+                        field.take_remote = key == Key::Right;
+                    }
+                }
+                Key::Char('l') | Key::Char('r') => {
+                    for field in conflict.fields.iter_mut() {
+                        field.take_remote = key == Key::Char('r');
+                    }
+                    app_state.resolve_conflict();
+                }
+                Key::Char('\n') => app_state.resolve_conflict(),
+                _ => {}
+            }
+        }
+        Mode::ActionConfirm => {
+            app_state.mode = Mode::Normal;
+            let confirmed = key == Key::Char('y');
+            match app_state.confirming.take().filter(|_| confirmed) {
+                Some(Action::DeleteTask) => app_state.delete_task(),
+                Some(Action::ToggleComplete) => app_state.complete_selected(),
+                Some(_) => app_state.toggle_someday_selected(),
+                None => app_state.set_status("Cancelled".to_string(), false),
+            }
+        }
+        Mode::Menu => {
+            let found = menu::matches(&app_state.menu_query);
+            match key {
+                Key::Up => app_state.menu_selected = app_state.menu_selected.saturating_sub(1),
+                Key::Down => {
+                    let last = found.len().saturating_sub(1);
+                    app_state.menu_selected = (app_state.menu_selected + 1).min(last);
+                }
+                Key::Char('\n') => {
+                    app_state.mode = Mode::Normal;
+                    if let Some(action) = found.get(app_state.menu_selected) {
+                        let key = app_state.keymap.key_for(*action);
+                        // Runs as if the action's key was pressed, bypassing the chord filter.
+                        return process_key_event(key, app_state);
+                    }
+                }
+                Key::Char(c) => {
+                    app_state.menu_query.push(c);
+                    app_state.menu_selected = 0;
+                }
+                Key::Backspace => {
+                    app_state.menu_query.pop();
+                    app_state.menu_selected = 0;
+                }
+                _ => app_state.mode = Mode::Normal,
+            }
+        }
+        Mode::DeleteConfirm => match app_state.keymap.action_for(key) {
+            Some(Action::DeleteTask) => {
+                app_state.delete_task();
+                app_state.mode = Mode::Normal;
+            }
+            _ => {
+                app_state.mode = Mode::Normal;
+            }
+        },
+        Mode::Input | Mode::Edit => match key {
+            Key::Char('\t') | Key::BackTab => {
+                let forward = key != Key::BackTab;
+                completion::cycle(
+                    &mut app_state.input,
+                    &mut app_state.completion,
+                    &app_state.tasks,
+                    forward,
+                );
+            }
+            Key::Ctrl('e') | Key::F(2) => {
+                app_state.completion = None;
+                app_state.emoji_picker = Some(EmojiPicker::default());
+            }
+            Key::Char('\n') if !app_state.setting_deadline => {
+                app_state.completion = None;
+                app_state.temp_description = app_state.input.clone();
+                app_state.input.clear();
+                app_state.mode = Mode::DeadlineInput;
+            }
+            Key::Char(c) => {
+                app_state.completion = None;
+                app_state.input.push(c);
+            }
+            Key::Backspace => {
+                app_state.completion = None;
+                app_state.input.pop();
+            }
+            _ => {}
+        },
+        Mode::DeadlineInput => match key {
+            Key::Char(c @ '1'..='9') => {
+                let index = c.to_digit(10).unwrap() as usize - 1;
+                if let Some(preset) = app_state.config.deadline_presets.get(index) {
+                    app_state.input = preset.clone();
+                }
+            }
+            Key::Char('q') | Key::Esc => {
+                app_state.mode = Mode::Normal;
+            }
+            Key::Char('\t') => {
+                app_state.input.clear();
+                app_state.deadline_notice = None;
+                app_state.mode = Mode::DeadlineEntry;
+            }
+            Key::Char('\n') => {
+                let deadline_option = app_state.input.clone();
+                let deadline = calculate_deadline(
+                    &deadline_option,
+                    app_state.config.week_start(),
+                    app_state.config.locale,
+                    &app_state.workdays,
+                );
+                // Confirming without picking an option keeps the current deadline.
+                let deadline = if app_state.editing && deadline_option.is_empty() {
+                    app_state
+                        .selected_task
+                        .and_then(|i| app_state.tasks.get(i))
+                        .and_then(|t| t.deadline)
+                } else {
+                    deadline
+                };
+                app_state.finish_task_input(deadline);
+            }
+            _ => {}
+        },
+        Mode::DeadlineEntry => match key {
+            Key::Esc => {
+                app_state.input.clear();
+                app_state.mode = Mode::DeadlineInput;
+            }
+            Key::Char('\n') => {
+                match due::parse(&app_state.input, &app_state.config, &app_state.workdays) {
+                    Ok(deadline) => {
+                        let warning = due::warning(deadline, &app_state.config.date_format);
+                        // A warning already shown for this same input counts as confirmed.
+                        let confirmed = app_state
+                            .deadline_notice
+                            .as_ref()
+                            .is_some_and(|n| !n.is_error && Some(&n.text) == warning.as_ref());
+                        match warning {
+                            Some(text) if !confirmed => {
+                                app_state.deadline_notice = Some(StatusMessage {
+                                    text,
+                                    is_error: false,
+                                });
+                            }
+                            _ => {
+                                app_state.input.clear();
+                                app_state.deadline_notice = None;
+                                app_state.finish_task_input(Some(deadline));
+                            }
+                        }
+                    }
+                    Err(text) => {
+                        app_state.deadline_notice = Some(StatusMessage {
+                            text,
+                            is_error: true,
+                        });
+                    }
+                }
+            }
+            Key::Char(c) => {
+                app_state.deadline_notice = None;
+                app_state.input.push(c);
+            }
+            Key::Backspace => {
+                app_state.deadline_notice = None;
+                app_state.input.pop();
+            }
+            _ => {}
+        },
+    }
+    true
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn toggling_in_the_tui_goes_through_apply() {
+        let mut app_state = AppState::new();
+        app_state.add_task("Write".to_string(), None, None);
+        app_state.selected_task = Some(0);
+        app_state.toggle_selected();
+        app_state.toggle_selected();
+        app_state.update_task("Write".to_string(), None);
+        let kinds: Vec<EventKind> = (app_state.store.events().unwrap().into_iter())
+            .map(|e| e.kind)
+            .collect();
+        assert!(matches!(
+            kinds[..],
+            [
+                EventKind::Created { .. },
+                EventKind::Completed,
+                EventKind::Reopened
+            ]
+        ));
+    }
+
+    #[test]
+    fn promoting_completes_the_promoted_task_only() {
+        let mut app_state = AppState::new();
+        app_state.add_task("Already there".to_string(), None, None);
+        app_state.scratch.add(" ".to_string());
+        app_state.scratch.toggle();
+        app_state.promote_scratch();
+        assert!(!app_state.tasks[0].completed);
+
+        app_state.scratch.add("Done already".to_string());
+        app_state.scratch.toggle();
+        app_state.promote_scratch();
+        let completed: Vec<u64> = (app_state.tasks.iter())
+            .filter(|t| t.completed)
+            .map(|t| t.id)
+            .collect();
+        assert_eq!(completed, [2]);
+    }
+
+    #[test]
+    fn an_outline_nests_under_the_ids_it_was_given() {
+        let mut app_state = AppState::new();
+        let outline = "- Trip\n  - Book flights\n  - Pack";
+        assert_eq!(app_state.import_outline(outline, Some(9)), 0);
+        assert!(app_state.tasks.is_empty());
+
+        assert_eq!(app_state.import_outline(outline, None), 3);
+        let trip = app_state.tasks[0].id;
+        assert!(app_state.tasks[1..].iter().all(|t| t.parent == Some(trip)));
+    }
+
+    #[test]
+    fn rolling_back_deletes_and_restores_in_one_batch() {
+        let mut app_state = AppState::new();
+        app_state.add_task("Kept".to_string(), None, None);
+        let taken = clock::now() - chrono::Duration::seconds(1);
+        let snapshot = app_state.tasks.clone();
+        app_state.add_task("Added since".to_string(), None, None);
+        app_state.selected_task = Some(0);
+        app_state.delete_task();
+
+        let (operations, count) = app_state.roll_back(snapshot, taken);
+        assert_eq!(count, 2);
+        app_state.apply(operations).unwrap();
+        let ids: Vec<u64> = app_state.tasks.iter().map(|t| t.id).collect();
+        assert_eq!(ids, [1]);
+    }
+
+    #[test]
+    fn the_sticky_key_holds_ctrl_then_alt_for_the_next_key() {
+        let mut app_state = AppState::new();
+        app_state.config.accessibility.sticky_key = Some("\\".to_string());
+        assert_eq!(app_state.accept_key(Key::Char('\\')), None);
+        assert_eq!(app_state.accept_key(Key::Char('R')), Some(Key::Ctrl('r')));
+        assert_eq!(app_state.accept_key(Key::Char('r')), Some(Key::Char('r')));
+
+        app_state.accept_key(Key::Char('\\'));
+        app_state.accept_key(Key::Char('\\'));
+        assert_eq!(app_state.accept_key(Key::Char('x')), Some(Key::Alt('x')));
+
+        app_state.config.accessibility.chord_timeout_ms = 0;
+        app_state.accept_key(Key::Char('\\'));
+        std::thread::sleep(Duration::from_millis(5));
+        assert_eq!(app_state.accept_key(Key::Char('r')), Some(Key::Char('r')));
     }
 }
@@ -0,0 +1,240 @@
+use std::collections::{BTreeSet, HashMap};
+use std::fs;
+use std::io;
+use std::path::Path;
+use tui::{
+    backend::Backend,
+    layout::Rect,
+    style::{Color, Modifier, Style},
+    text::{Span, Spans},
+    widgets::{Block, Borders, Paragraph, Wrap},
+    Frame,
+};
+
+use crate::archive::{self, ARCHIVE_FILE};
+use crate::attachments::ATTACHMENTS_DIR;
+use crate::journal::Event;
+use crate::snapshots;
+use crate::store::Storage;
+use crate::{AppState, DATABASE_FILE, JOURNAL_FILE, TASKS_FILE};
+
+/// Snapshots pruning leaves, newest first.
+pub const PRUNE_KEEP: usize = 10;
+
+/// A file or folder in the data directory and the room it takes.
+struct Entry {
+    name: &'static str,
+    bytes: u64,
+    detail: String,
+}
+
+/// What the data directory holds, for the storage screen.
+pub struct Report {
+    entries: Vec<Entry>,
+}
+
+/// Bytes taken by a file, or everything under a folder.
+fn size(path: &Path) -> u64 {
+    let Ok(metadata) = fs::metadata(path) else {
+        return 0;
+    };
+    if !metadata.is_dir() {
+        return metadata.len();
+    }
+    let entries = fs::read_dir(path).into_iter().flatten().flatten();
+    entries.map(|entry| size(&entry.path())).sum()
+}
+
+fn format_size(bytes: u64) -> String {
+    match bytes {
+        0..=1023 => format!("{} B", bytes),
+        1024..=1_048_575 => format!("{:.1} KB", bytes as f64 / 1024.0),
+        _ => format!("{:.1} MB", bytes as f64 / 1_048_576.0),
+    }
+}
+
+/// Which events stay once the history of tasks that are gone is dropped:
+/// of each, only the last event (its deletion) stays, so its id is never
+/// handed out again.
+fn compacted(events: &[Event], kept: &BTreeSet<u64>) -> Vec<bool> {
+    let mut last: HashMap<u64, usize> = HashMap::new();
+    for (i, event) in events.iter().enumerate() {
+        last.insert(event.task, i);
+    }
+    (events.iter().enumerate())
+        .map(|(i, event)| kept.contains(&event.task) || last[&event.task] == i)
+        .collect()
+}
+
+/// Ids of the tasks still in the list or the archive.
+fn kept_ids(app_state: &AppState) -> BTreeSet<u64> {
+    let archived = archive::load(&app_state.data_dir).unwrap_or_default();
+    let main = app_state.tasks.iter().filter(|t| t.source.is_none());
+    main.chain(&archived).map(|t| t.id).collect()
+}
+
+impl Report {
+    pub fn new(app_state: &AppState) -> Report {
+        let dir = &app_state.data_dir;
+        let open = app_state.tasks.iter().filter(|t| !t.completed).count();
+        let archived = archive::load(dir).map_or(0, |tasks| tasks.len());
+        let events = app_state.store.events().unwrap_or_default();
+        let total = events.len();
+        let marks = compacted(&events, &kept_ids(app_state));
+        let dropped = marks.iter().filter(|keep| !**keep).count();
+        let shots = snapshots::list(dir);
+        let oldest = (shots.last()).map_or(String::new(), |s| {
+            format!(", oldest {}", s.taken.format(&app_state.config.date_format))
+        });
+        let journal = format!("{} events, {} about deleted tasks", total, dropped);
+        let mut entries = match app_state.config.storage {
+            Storage::Sqlite => vec![Entry {
+                name: "Database",
+                bytes: size(&dir.join(DATABASE_FILE)),
+                detail: format!("{} open tasks, {}", open, journal),
+            }],
+            _ => vec![
+                Entry {
+                    name: "Task file",
+                    bytes: size(&dir.join(TASKS_FILE)),
+                    detail: format!("{} open tasks", open),
+                },
+                Entry {
+                    name: "Journal",
+                    bytes: size(&dir.join(JOURNAL_FILE)),
+                    detail: journal,
+                },
+            ],
+        };
+        entries.extend([
+            Entry {
+                name: "Archive",
+                bytes: size(&dir.join(ARCHIVE_FILE)),
+                detail: format!("{} completed tasks", archived),
+            },
+            Entry {
+                name: "Snapshots",
+                bytes: size(&dir.join(snapshots::DIR)),
+                detail: format!("{} snapshots{}", shots.len(), oldest),
+            },
+            Entry {
+                name: "Attachments",
+                bytes: size(&dir.join(ATTACHMENTS_DIR)),
+                detail: "copies kept with --copy".to_string(),
+            },
+        ]);
+        Report { entries }
+    }
+}
+
+/// The task of each event compacting would drop, in journal order.
+pub fn compaction_preview(app_state: &AppState) -> Vec<u64> {
+    let events = app_state.store.events().unwrap_or_default();
+    let marks = compacted(&events, &kept_ids(app_state));
+    (events.iter().zip(marks))
+        .filter(|(_, keep)| !keep)
+        .map(|(event, _)| event.task)
+        .collect()
+}
+
+/// Shows the storage screen again after `done`, with its outcome.
+pub fn refresh(app_state: &mut AppState, done: Result<String, String>) {
+    app_state.maintenance = Some(Report::new(app_state));
+    match done {
+        Ok(text) => app_state.set_status(text, false),
+        Err(text) => app_state.set_status(text, true),
+    }
+}
+
+/// Squashes the history of deleted tasks in the journal, returning how
+/// many events went.
+pub fn compact_journal(app_state: &mut AppState) -> Result<usize, io::Error> {
+    // Takes in what other sessions appended first: their tasks would look
+    // deleted, and moving the offset past them would lose them.
+    app_state.merge_journal();
+    let kept = kept_ids(app_state);
+    let dropped = (app_state.store).retain_events(&|events| compacted(events, &kept))?;
+    // Past what this copy wrote, so it isn't read back as news.
+    app_state.journal_offset = app_state.store.offset();
+    Ok(dropped)
+}
+
+pub fn render_maintenance<B: Backend>(f: &mut Frame<B>, app_state: &AppState, chunk: Rect) {
+    let Some(report) = &app_state.maintenance else {
+        return;
+    };
+    let dim = Style::default()
+        .fg(Color::DarkGray)
+        .add_modifier(Modifier::ITALIC);
+    let row = |name: &str, bytes: u64| format!("{:<12}{:>10}  ", name, format_size(bytes));
+    let mut lines: Vec<Spans> = (report.entries.iter())
+        .map(|entry| {
+            Spans::from(vec![
+                Span::raw(row(entry.name, entry.bytes)),
+                Span::styled(entry.detail.clone(), dim),
+            ])
+        })
+        .collect();
+    let total: u64 = report.entries.iter().map(|e| e.bytes).sum();
+    lines.push(Spans::from(row("Total", total)));
+    let title = format!("Storage - {}", app_state.data_dir.display());
+    let block = Block::default().borders(Borders::ALL).title(title);
+    let paragraph = Paragraph::new(lines)
+        .block(block)
+        .wrap(Wrap { trim: false });
+    f.render_widget(paragraph, chunk);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::journal::EventKind;
+
+    fn event(task: u64, kind: EventKind) -> Event {
+        Event {
+            // Another session's, as the CLI's would be.
+            session: 0,
+            ..Event::now(task, kind)
+        }
+    }
+
+    fn created(task: u64, description: &str) -> Event {
+        let kind = EventKind::Created {
+            description: description.to_string(),
+            deadline: None,
+            parent: None,
+        };
+        event(task, kind)
+    }
+
+    #[test]
+    fn compacting_keeps_tasks_added_by_other_sessions() {
+        let mut app_state = AppState::new();
+        app_state.data_dir = std::env::temp_dir().join("todo-rs-compact-test");
+        let merged = [
+            created(1, "Kept"),
+            created(2, "Gone"),
+            event(2, EventKind::Completed),
+            event(2, EventKind::Deleted),
+        ];
+        // Appended after the last tick, so not merged yet.
+        let unmerged = [created(3, "From the CLI"), event(3, EventKind::Started)];
+        for event in &merged {
+            app_state.store.append_event(event).unwrap();
+        }
+        app_state.journal_offset = app_state.store.offset();
+        for event in &unmerged {
+            app_state.store.append_event(event).unwrap();
+        }
+        let kept = crate::Task::new(1, "Kept".to_string(), None);
+        app_state.tasks.push(kept);
+
+        assert_eq!(compact_journal(&mut app_state).unwrap(), 2);
+        let ids: Vec<u64> = app_state.tasks.iter().map(|t| t.id).collect();
+        assert_eq!(ids, vec![1, 3]);
+        let events = app_state.store.events().unwrap();
+        let tasks: Vec<u64> = events.iter().map(|e| e.task).collect();
+        assert_eq!(tasks, vec![1, 2, 3, 3]);
+        assert!(matches!(events[1].kind, EventKind::Deleted));
+    }
+}
@@ -0,0 +1,123 @@
+use tui::{
+    backend::Backend,
+    layout::{Constraint, Direction, Layout, Rect},
+    style::{Color, Style},
+    text::{Span, Spans},
+    widgets::{Block, Borders, Clear, List, ListItem, ListState, Paragraph},
+    Frame,
+};
+
+use crate::keymap::{self, Action, Key};
+use crate::AppState;
+
+/// Actions offered in the palette: everything but opening it and moving
+/// the selection, which the palette itself uses.
+fn actions() -> impl Iterator<Item = Action> {
+    Action::ALL.into_iter().filter(|a| {
+        !matches!(
+            a,
+            Action::ShowMenu | Action::CommandPalette | Action::MoveUp | Action::MoveDown
+        )
+    })
+}
+
+pub fn is_chord(key: Key) -> bool {
+    matches!(key, Key::Ctrl(_) | Key::Alt(_))
+}
+
+/// How well `query` matches `text` as a subsequence, ignoring case; higher
+/// is better. Runs of consecutive letters and word starts score extra, so
+/// `ag` ranks "Show agenda" above "Toggle something".
+fn fuzzy_score(query: &str, text: &str) -> Option<i32> {
+    let text: Vec<char> = text.to_lowercase().chars().collect();
+    let mut score = 0;
+    let mut position = 0;
+    let mut previous: Option<usize> = None;
+    for c in query.to_lowercase().chars().filter(|c| !c.is_whitespace()) {
+        let found = position + text[position..].iter().position(|t| *t == c)?;
+        score += 1;
+        if previous.is_some_and(|p| p + 1 == found) {
+            score += 3;
+        }
+        if found == 0 || text[found - 1] == ' ' {
+            score += 2;
+        }
+        previous = Some(found);
+        position = found + 1;
+    }
+    Some(score)
+}
+
+/// The text an action is searched by: what it does and its config name.
+fn search_text(action: Action) -> String {
+    format!("{} {:?}", action.description(), action)
+}
+
+/// Actions matching the typed query, best first; all of them in keymap
+/// order when nothing is typed.
+pub fn matches(query: &str) -> Vec<Action> {
+    let mut scored: Vec<(i32, Action)> = actions()
+        .filter_map(|a| fuzzy_score(query, &search_text(a)).map(|s| (s, a)))
+        .collect();
+    // Stable, so equal scores keep the keymap order.
+    scored.sort_by_key(|(score, _)| -score);
+    scored.into_iter().map(|(_, action)| action).collect()
+}
+
+/// Every action with its key, searchable and run with Enter, so nothing
+/// needs a key combination or remembering a binding.
+pub fn render_menu<B: Backend>(f: &mut Frame<B>, app_state: &AppState) {
+    let chords = app_state.config.accessibility.chords;
+    let found = matches(&app_state.menu_query);
+    let items: Vec<ListItem> = found
+        .iter()
+        .map(|action| {
+            let key = app_state.keymap.key_for(*action);
+            let key = if is_chord(key) && !chords {
+                "-".to_string()
+            } else {
+                keymap::format_key(key)
+            };
+            ListItem::new(format!("{:<8}{}", key, action.description()))
+        })
+        .collect();
+
+    let size = f.size();
+    let width = 60.min(size.width);
+    let height = (actions().count() as u16 + 3).min(size.height);
+    let area = Rect::new(
+        size.x + (size.width - width) / 2,
+        size.y + (size.height - height) / 2,
+        width,
+        height,
+    );
+    let block = Block::default()
+        .borders(Borders::ALL)
+        .title("Actions (type to search, Enter: run, Esc: close)");
+    let inner = block.inner(area);
+    f.render_widget(Clear, area);
+    f.render_widget(block, area);
+    let rows = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([Constraint::Length(1), Constraint::Min(0)].as_ref())
+        .split(inner);
+
+    let prompt = Spans::from(vec![
+        Span::styled("> ", Style::default().fg(Color::DarkGray)),
+        Span::raw(app_state.menu_query.as_str()),
+    ]);
+    f.render_widget(Paragraph::new(prompt), rows[0]);
+    if items.is_empty() {
+        let none = Span::styled("No matching actions", Style::default().fg(Color::DarkGray));
+        f.render_widget(Paragraph::new(none), rows[1]);
+        return;
+    }
+    let list = List::new(items).highlight_style(
+        Style::default()
+            .fg(Color::Black)
+            .bg(app_state.styles.highlight()),
+    );
+    let mut state = ListState::default();
+    state.select(Some(app_state.menu_selected));
+    f.render_stateful_widget(list, rows[1], &mut state);
+}
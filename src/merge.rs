@@ -0,0 +1,396 @@
+use chrono::NaiveDateTime;
+use tui::{
+    backend::Backend,
+    layout::Rect,
+    style::{Color, Modifier, Style},
+    text::{Span, Spans},
+    widgets::{Block, Borders, Paragraph, Wrap},
+    Frame,
+};
+
+use crate::keymap::{Action, Key};
+use crate::ops::Operation;
+use crate::privacy;
+use crate::tags;
+use crate::{AppState, Mode, Task};
+
+#[derive(Clone, Copy, PartialEq)]
+enum Field {
+    Description,
+    Deadline,
+    Tags,
+}
+
+const FIELDS: [Field; 3] = [Field::Description, Field::Deadline, Field::Tags];
+
+impl Field {
+    fn name(self) -> &'static str {
+        match self {
+            Field::Description => "description",
+            Field::Deadline => "deadline",
+            Field::Tags => "tags",
+        }
+    }
+}
+
+/// Duplicates being combined into one task: marked in the list in
+/// `Mode::MergePick`, then taken field by field in `Mode::Merge`. The task
+/// marked first is kept; the others are deleted once their attachments
+/// and subtasks have moved over to it.
+pub struct Merge {
+    /// Ids, in the order marked.
+    pub tasks: Vec<u64>,
+    /// Per field of `FIELDS`, the position in `tasks` of the task whose
+    /// value is taken; for tags, one past the last takes everyone's.
+    picks: [usize; 3],
+    /// Row picked on the merge screen.
+    selected: usize,
+}
+
+/// Whether `word` is a `#tag` or `+project`, which merge alike.
+fn is_tag(word: &str) -> bool {
+    tags::tags(word).next().is_some() || tags::projects(word).next().is_some()
+}
+
+/// A description without its tags and projects.
+fn text(description: &str) -> String {
+    let words: Vec<&str> = (description.split_whitespace())
+        .filter(|word| !is_tag(word))
+        .collect();
+    words.join(" ")
+}
+
+fn tag_words(description: &str) -> Vec<&str> {
+    (description.split_whitespace())
+        .filter(|word| is_tag(word))
+        .collect()
+}
+
+impl Merge {
+    fn choices(&self, field: Field) -> usize {
+        match field {
+            Field::Tags => self.tasks.len() + 1,
+            _ => self.tasks.len(),
+        }
+    }
+
+    fn cycle(&mut self, forward: bool) {
+        let count = self.choices(FIELDS[self.selected]);
+        let pick = &mut self.picks[self.selected];
+        *pick = if forward {
+            (*pick + 1) % count
+        } else {
+            (*pick + count - 1) % count
+        };
+    }
+
+    /// The tags the merged task gets, each once.
+    fn tags<'a>(&self, tasks: &[&'a Task]) -> Vec<&'a str> {
+        let from = match tasks.get(self.picks[2]) {
+            Some(task) => vec![*task],
+            None => tasks.to_vec(),
+        };
+        let mut tags: Vec<&str> = Vec::new();
+        for tag in from.into_iter().flat_map(|t| tag_words(&t.description)) {
+            if !tags.iter().any(|t| t.eq_ignore_ascii_case(tag)) {
+                tags.push(tag);
+            }
+        }
+        tags
+    }
+
+    /// The merged task's description and deadline; `tasks` are the marked
+    /// ones, in the order of `self.tasks`.
+    fn result(&self, tasks: &[&Task]) -> (String, Option<NaiveDateTime>) {
+        let mut words = vec![text(&tasks[self.picks[0]].description)];
+        words.extend(self.tags(tasks).into_iter().map(str::to_string));
+        words.retain(|word| !word.is_empty());
+        (words.join(" "), tasks[self.picks[1]].deadline)
+    }
+}
+
+/// The marked tasks, unless one has gone since, e.g. deleted by another
+/// session.
+fn marked<'a>(app_state: &'a AppState, merge: &Merge) -> Option<Vec<&'a Task>> {
+    (merge.tasks.iter())
+        .map(|id| app_state.tasks.iter().find(|t| t.id == *id))
+        .collect()
+}
+
+/// Starts marking tasks to merge, the selected one first.
+pub fn start(app_state: &mut AppState) {
+    let Some(task) = app_state.selected_task.and_then(|i| app_state.tasks.get(i)) else {
+        return;
+    };
+    app_state.merge = Some(Merge {
+        tasks: vec![task.id],
+        picks: [0; 3],
+        selected: 0,
+    });
+    app_state.mode = Mode::MergePick;
+}
+
+fn toggle_mark(app_state: &mut AppState) {
+    let Some(task) = app_state.selected_task.and_then(|i| app_state.tasks.get(i)) else {
+        return;
+    };
+    let id = task.id;
+    let file = task.file().to_string();
+    let first = (app_state.merge.as_ref())
+        .and_then(|merge| merge.tasks.first())
+        .and_then(|first| app_state.tasks.iter().find(|t| t.id == *first));
+    // Merging moves subtasks and history, which can't cross files.
+    if first.is_some_and(|first| first.id != id && first.file() != file) {
+        let text = format!("Only tasks from {} can be merged with these", file);
+        app_state.set_status(text, true);
+        return;
+    }
+    let Some(merge) = app_state.merge.as_mut() else {
+        return;
+    };
+    match merge.tasks.iter().position(|t| *t == id) {
+        Some(i) => {
+            merge.tasks.remove(i);
+        }
+        None => merge.tasks.push(id),
+    }
+}
+
+/// Goes on to pick the fields, from the earliest deadline and everyone's
+/// tags.
+fn open(app_state: &mut AppState) {
+    let Some(merge) = &app_state.merge else {
+        return;
+    };
+    if merge.tasks.len() < 2 {
+        app_state.set_status("Mark at least two tasks to merge".to_string(), true);
+        return;
+    }
+    let Some(tasks) = marked(app_state, merge) else {
+        return;
+    };
+    let earliest = (tasks.iter().enumerate())
+        .filter_map(|(i, task)| task.deadline.map(|deadline| (deadline, i)))
+        .min()
+        .map_or(0, |(_, i)| i);
+    let all = tasks.len();
+    let Some(merge) = app_state.merge.as_mut() else {
+        return;
+    };
+    merge.picks = [0, earliest, all];
+    merge.selected = 0;
+    app_state.mode = Mode::Merge;
+}
+
+/// Marks tasks in the list.
+pub fn handle_pick_key(app_state: &mut AppState, key: Key) {
+    match (app_state.keymap.action_for(key), key) {
+        (Some(Action::MoveUp), _) => app_state.move_selection(true),
+        (Some(Action::MoveDown), _) => app_state.move_selection(false),
+        (_, Key::Char(' ')) => toggle_mark(app_state),
+        (_, Key::Char('\n')) => open(app_state),
+        (_, Key::Esc) => {
+            app_state.merge = None;
+            app_state.mode = Mode::Normal;
+        }
+        _ => {}
+    }
+}
+
+/// Picks what each field keeps.
+pub fn handle_key(app_state: &mut AppState, key: Key) {
+    let Some(merge) = &mut app_state.merge else {
+        app_state.mode = Mode::Normal;
+        return;
+    };
+    match (app_state.keymap.action_for(key), key) {
+        (Some(Action::MoveUp), _) => merge.selected = merge.selected.saturating_sub(1),
+        (Some(Action::MoveDown), _) => merge.selected = (merge.selected + 1).min(FIELDS.len() - 1),
+        (_, Key::Left) => merge.cycle(false),
+        (_, Key::Right) => merge.cycle(true),
+        (_, Key::Char('\n')) => apply(app_state),
+        (_, Key::Esc) => app_state.mode = Mode::MergePick,
+        _ => {}
+    }
+}
+
+/// Rewrites the first marked task as picked and merges the others into
+/// it, in one batch.
+fn apply(app_state: &mut AppState) {
+    let Some(merge) = app_state.merge.take() else {
+        return;
+    };
+    app_state.mode = Mode::Normal;
+    let Some(tasks) = marked(app_state, &merge) else {
+        let text = "A marked task is gone; nothing was merged".to_string();
+        app_state.set_status(text, true);
+        return;
+    };
+    let (description, deadline) = merge.result(&tasks);
+    let kept = tasks[0].id;
+    let from: Vec<u64> = tasks[1..].iter().map(|t| t.id).collect();
+    let count = tasks.len();
+    if let Err(e) = app_state.snapshot_before("pre-merge") {
+        let text = format!("Snapshot failed, nothing merged: {}", e);
+        app_state.set_status(text, true);
+        return;
+    }
+    let operations = vec![
+        Operation::Edit {
+            task: kept,
+            description,
+        },
+        Operation::Reschedule {
+            task: kept,
+            deadline,
+        },
+        Operation::Merge { into: kept, from },
+    ];
+    if app_state.apply(operations).is_none() {
+        return;
+    }
+    app_state.selected_task = app_state.tasks.iter().position(|t| t.id == kept);
+    let text = format!("Merged {} tasks into task {}", count, kept);
+    app_state.set_status(text, false);
+}
+
+/// Each field with the value it keeps and where it comes from, then the
+/// merged task as it will read.
+pub fn render_merge<B: Backend>(f: &mut Frame<B>, app_state: &AppState, chunk: Rect) {
+    let Some(merge) = &app_state.merge else {
+        return;
+    };
+    let Some(tasks) = marked(app_state, merge) else {
+        return;
+    };
+    let dim = Style::default()
+        .fg(Color::DarkGray)
+        .add_modifier(Modifier::ITALIC);
+    let selected = app_state.styles.selected.add_modifier(Modifier::BOLD);
+    let date_format = &app_state.config.date_format;
+    let show_date = |deadline: Option<NaiveDateTime>| {
+        deadline.map_or("none".to_string(), |d| d.format(date_format).to_string())
+    };
+    let shown = |task: &Task, text: String| {
+        if app_state.masked(task) {
+            privacy::mask(&text)
+        } else {
+            text
+        }
+    };
+
+    let mut lines = Vec::new();
+    for (row, field) in FIELDS.iter().enumerate() {
+        let pick = merge.picks[row];
+        let (value, from) = match (field, tasks.get(pick)) {
+            (Field::Description, Some(task)) => (shown(task, text(&task.description)), pick),
+            (Field::Deadline, Some(task)) => (show_date(task.deadline), pick),
+            (Field::Tags, Some(task)) => (tag_words(&task.description).join(" "), pick),
+            _ => (merge.tags(&tasks).join(" "), tasks.len()),
+        };
+        let source = match tasks.get(from) {
+            Some(task) => format!("  from task {}", task.id),
+            None => "  from all of them".to_string(),
+        };
+        let marker = if row == merge.selected { ">" } else { " " };
+        let style = if row == merge.selected {
+            selected
+        } else {
+            Style::default()
+        };
+        lines.push(Spans::from(vec![
+            Span::raw(format!("{} {:<13}", marker, field.name())),
+            Span::styled(format!("< {} >", value), style),
+            Span::styled(source, dim),
+        ]));
+    }
+
+    let (description, deadline) = merge.result(&tasks);
+    let description = shown(tasks[0], description);
+    lines.push(Spans::default());
+    lines.push(Spans::from(vec![
+        Span::raw(format!("  {:<13}", "result")),
+        Span::raw(format!("{}  due {}", description, show_date(deadline))),
+    ]));
+    lines.push(Spans::default());
+    lines.push(Spans::from(Span::styled(
+        format!("  Kept: task {}; deleted after merging:", tasks[0].id),
+        dim,
+    )));
+    for task in &tasks[1..] {
+        let description = app_state.description(task);
+        let line = format!("    task {}  {}", task.id, description);
+        lines.push(Spans::from(Span::styled(line, dim)));
+    }
+
+    let title = format!("Merge {} tasks", tasks.len());
+    let block = Block::default().borders(Borders::ALL).title(title);
+    let paragraph = Paragraph::new(lines)
+        .block(block)
+        .wrap(Wrap { trim: false });
+    f.render_widget(paragraph, chunk);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn date(text: &str) -> Option<NaiveDateTime> {
+        chrono::NaiveDate::parse_from_str(text, "%Y-%m-%d")
+            .ok()?
+            .and_hms_opt(0, 0, 0)
+    }
+
+    fn task(id: u64, description: &str, deadline: Option<NaiveDateTime>) -> Task {
+        Task::new(id, description.to_string(), deadline)
+    }
+
+    #[test]
+    fn takes_picked_fields_and_everyones_tags() {
+        let one = task(1, "Call the bank #money", date("2026-10-30"));
+        let two = task(2, "Phone bank about card #Money +admin", date("2026-10-20"));
+        let mut merge = Merge {
+            tasks: vec![1, 2],
+            picks: [1, 0, 2],
+            selected: 0,
+        };
+        assert_eq!(
+            merge.result(&[&one, &two]),
+            (
+                "Phone bank about card #money +admin".to_string(),
+                date("2026-10-30")
+            )
+        );
+        merge.selected = 2;
+        merge.cycle(true);
+        assert_eq!(merge.picks[2], 0);
+        merge.cycle(false);
+        merge.cycle(false);
+        assert_eq!(
+            merge.result(&[&one, &two]).0,
+            "Phone bank about card #Money +admin"
+        );
+    }
+
+    #[test]
+    fn merging_keeps_the_first_marked_task() {
+        let mut app_state = AppState::new();
+        app_state.add_task("Call the bank #money".to_string(), date("2026-10-30"), None);
+        app_state.add_task("Phone bank #admin".to_string(), date("2026-10-20"), None);
+        let sub = app_state.add_task("Find card".to_string(), None, Some(2));
+        app_state.selected_task = (app_state.tasks.iter()).position(|t| t.id == 1);
+        start(&mut app_state);
+        app_state.selected_task = (app_state.tasks.iter()).position(|t| t.id == 2);
+        handle_pick_key(&mut app_state, Key::Char(' '));
+        handle_pick_key(&mut app_state, Key::Char('\n'));
+        assert!(app_state.mode == Mode::Merge);
+        handle_key(&mut app_state, Key::Char('\n'));
+
+        let ids: Vec<u64> = app_state.tasks.iter().map(|t| t.id).collect();
+        assert_eq!(ids, [1, sub.unwrap()]);
+        let kept = &app_state.tasks[0];
+        assert_eq!(kept.description, "Call the bank #money #admin");
+        assert_eq!(kept.deadline, date("2026-10-20"));
+        assert_eq!(app_state.tasks[1].parent, Some(1));
+    }
+}
@@ -0,0 +1,81 @@
+use chrono::{Datelike, Duration, NaiveDate, Weekday};
+
+/// A checkpoint inside a larger task, written into its description as a
+/// `^name:YYYY-MM-DD` word, e.g. `Essay ^draft:2026-10-19 ^final:2026-10-23`.
+/// Writing `done` in place of the date takes it off the agenda.
+pub struct Milestone<'a> {
+    pub name: &'a str,
+    pub date: NaiveDate,
+}
+
+fn split(word: &str) -> Option<(&str, &str)> {
+    let (name, when) = word.strip_prefix('^')?.split_once(':')?;
+    (!name.is_empty() && !when.is_empty()).then_some((name, when))
+}
+
+/// The milestones in a description that still have a date, in order.
+pub fn milestones(description: &str) -> impl Iterator<Item = Milestone<'_>> {
+    description.split_whitespace().filter_map(|word| {
+        let (name, when) = split(word)?;
+        let date = NaiveDate::parse_from_str(when, "%Y-%m-%d").ok()?;
+        Some(Milestone { name, date })
+    })
+}
+
+/// The date a relative `when` means as of `today`: `today`, `tomorrow`, or
+/// a weekday such as `mon` or `friday`, taken as the next one from today.
+fn resolve_when(when: &str, today: NaiveDate) -> Option<NaiveDate> {
+    match when.to_lowercase().as_str() {
+        "today" => return Some(today),
+        "tomorrow" => return Some(today + Duration::days(1)),
+        _ => {}
+    }
+    let weekday: Weekday = when.parse().ok()?;
+    let ahead = (7 + weekday.num_days_from_monday() - today.weekday().num_days_from_monday()) % 7;
+    Some(today + Duration::days(ahead as i64))
+}
+
+/// Pins relative milestone dates such as `^review:wed` to the date they
+/// mean now, so the milestone doesn't drift to the following week.
+pub fn resolve(description: &str, today: NaiveDate) -> String {
+    let words = description.split(' ').map(|word| {
+        let date = split(word).and_then(|(name, when)| Some((name, resolve_when(when, today)?)));
+        match date {
+            Some((name, date)) => format!("^{}:{}", name, date.format("%Y-%m-%d")),
+            None => word.to_string(),
+        }
+    });
+    words.collect::<Vec<_>>().join(" ")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn date(text: &str) -> NaiveDate {
+        NaiveDate::parse_from_str(text, "%Y-%m-%d").unwrap()
+    }
+
+    #[test]
+    fn reads_dated_milestones_in_order() {
+        let description = "Essay ^draft:2026-10-19 ^outline:done ^:2026-10-01 ^final:2026-10-23";
+        let found: Vec<_> = milestones(description).map(|m| (m.name, m.date)).collect();
+        assert_eq!(
+            found,
+            [("draft", date("2026-10-19")), ("final", date("2026-10-23"))]
+        );
+    }
+
+    #[test]
+    fn pins_relative_dates() {
+        // A Friday.
+        let today = date("2026-10-16");
+        assert_eq!(
+            resolve(
+                "Talk ^slides:tomorrow ^review:wed ^dry-run:fri ^done:done",
+                today
+            ),
+            "Talk ^slides:2026-10-17 ^review:2026-10-21 ^dry-run:2026-10-16 ^done:done"
+        );
+    }
+}
@@ -0,0 +1,247 @@
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::io::{self, Write};
+use std::process::{Command, Stdio};
+use std::thread;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use crate::secrets;
+use crate::share;
+
+#[derive(Clone, Copy, PartialEq)]
+pub enum Urgency {
+    Normal,
+    /// Stays on screen until dismissed where the platform supports it, and
+    /// looks or sounds like a warning.
+    Critical,
+}
+
+/// The kinds of notification, each routed to its own channels.
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+#[serde(rename_all = "snake_case")]
+pub enum Reminder {
+    /// Tasks due today or overdue, at startup.
+    DueToday,
+    /// Tasks past `escalate_after_days`.
+    Overdue,
+    /// Stale tasks moved to Someday.
+    Someday,
+}
+
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq, Debug)]
+#[serde(rename_all = "lowercase")]
+pub enum Channel {
+    Desktop,
+    Telegram,
+    Matrix,
+}
+
+#[derive(Serialize, Deserialize, Clone)]
+pub struct TelegramConfig {
+    /// Bot token from @BotFather, best kept as `keyring:<name>`.
+    pub token: String,
+    pub chat_id: String,
+}
+
+#[derive(Serialize, Deserialize, Clone)]
+pub struct MatrixConfig {
+    /// e.g. `https://matrix.org`.
+    pub homeserver: String,
+    /// Room id, e.g. `!abc123:matrix.org`, that the bot has joined.
+    pub room: String,
+    /// The bot's access token, best kept as `keyring:<name>`.
+    pub token: String,
+}
+
+/// Where notifications go besides the desktop.
+#[derive(Serialize, Deserialize, Clone, Default)]
+#[serde(default)]
+pub struct ChannelsConfig {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub telegram: Option<TelegramConfig>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub matrix: Option<MatrixConfig>,
+    /// Channels per kind of reminder, e.g. `overdue = ["desktop", "telegram"]`;
+    /// kinds left out go to the desktop only.
+    pub reminders: BTreeMap<Reminder, Vec<Channel>>,
+}
+
+impl ChannelsConfig {
+    pub fn validate(&self) -> Result<(), String> {
+        let channels = self.reminders.values().flatten();
+        for channel in channels {
+            let configured = match channel {
+                Channel::Desktop => true,
+                Channel::Telegram => self.telegram.is_some(),
+                Channel::Matrix => self.matrix.is_some(),
+            };
+            if !configured {
+                let name = format!("{:?}", channel).to_lowercase();
+                return Err(format!(
+                    "reminders use {} but [channels.{}] is missing",
+                    name, name
+                ));
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Sends a notification to every channel `reminder` is routed to. The
+/// desktop one shows right away; bots are reached in the background so a
+/// slow network never stalls the UI. Failures are dropped, as with a
+/// missing desktop notifier. `summary` and `body` leave the machine, so
+/// task descriptions in them go through `Task::shared_description`.
+pub fn deliver(
+    channels: &ChannelsConfig,
+    reminder: Reminder,
+    summary: &str,
+    body: &str,
+    urgency: Urgency,
+) {
+    let routes = channels.reminders.get(&reminder);
+    let routes = routes.map_or(&[Channel::Desktop][..], Vec::as_slice);
+    if routes.contains(&Channel::Desktop) {
+        let _ = send(summary, body, urgency);
+    }
+    let text = format!("{}\n{}", summary, body);
+    if let Some(telegram) = channels
+        .telegram
+        .clone()
+        .filter(|_| routes.contains(&Channel::Telegram))
+    {
+        let text = text.clone();
+        thread::spawn(move || send_telegram(&telegram, &text));
+    }
+    if let Some(matrix) = channels
+        .matrix
+        .clone()
+        .filter(|_| routes.contains(&Channel::Matrix))
+    {
+        thread::spawn(move || send_matrix(&matrix, &text));
+    }
+}
+
+/// Runs curl with `secret_config` on stdin, so tokens stay out of the
+/// process list.
+fn curl(secret_config: &str, args: &[&str]) -> Result<(), io::Error> {
+    let mut child = Command::new("curl")
+        .args(["--fail", "--silent", "--show-error", "--config", "-"])
+        .args(args)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::null())
+        .stderr(Stdio::piped())
+        .spawn()?;
+    if let Some(mut stdin) = child.stdin.take() {
+        stdin.write_all(secret_config.as_bytes())?;
+    }
+    let output = child.wait_with_output()?;
+    if output.status.success() {
+        Ok(())
+    } else {
+        let message = String::from_utf8_lossy(&output.stderr);
+        Err(io::Error::other(message.trim().to_string()))
+    }
+}
+
+/// Posts through the Bot API's `sendMessage`.
+fn send_telegram(config: &TelegramConfig, text: &str) -> Result<(), io::Error> {
+    let token = secrets::lookup(&config.token)
+        .ok_or_else(|| io::Error::new(io::ErrorKind::NotFound, "no Telegram token"))?;
+    let url = format!("https://api.telegram.org/bot{}/sendMessage", token);
+    curl(
+        &format!("url = {}\n", share::curl_quote(&url)),
+        &[
+            "--data-urlencode",
+            &format!("chat_id={}", config.chat_id),
+            "--data-urlencode",
+            &format!("text={}", text),
+        ],
+    )
+}
+
+/// Sends an `m.text` message with the client-server API.
+fn send_matrix(config: &MatrixConfig, text: &str) -> Result<(), io::Error> {
+    let token = secrets::lookup(&config.token)
+        .ok_or_else(|| io::Error::new(io::ErrorKind::NotFound, "no Matrix token"))?;
+    // Each message needs a transaction id of its own.
+    let now = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default();
+    let url = format!(
+        "{}/_matrix/client/v3/rooms/{}/send/m.room.message/todo-rs-{}-{}",
+        config.homeserver.trim_end_matches('/'),
+        share::percent_encode(&config.room),
+        now.as_millis(),
+        std::process::id()
+    );
+    let body = serde_json::json!({ "msgtype": "m.text", "body": text }).to_string();
+    curl(
+        &format!(
+            "header = {}\n",
+            share::curl_quote(&format!("Authorization: Bearer {}", token))
+        ),
+        &[
+            "--request",
+            "PUT",
+            "--header",
+            "Content-Type: application/json",
+            "--data-binary",
+            &body,
+            &url,
+        ],
+    )
+}
+
+/// Shows a desktop notification through the platform's command-line notifier.
+pub fn send(summary: &str, body: &str, urgency: Urgency) -> Result<(), io::Error> {
+    let critical = urgency == Urgency::Critical;
+    let mut command = if cfg!(windows) {
+        // Text goes through the environment so PowerShell never parses it as code.
+        let script = "Add-Type -AssemblyName System.Windows.Forms; \
+            $n = New-Object System.Windows.Forms.NotifyIcon; \
+            $n.Icon = [System.Drawing.SystemIcons]::$env:TODO_RS_ICON; \
+            $n.Visible = $true; \
+            $n.ShowBalloonTip(10000, $env:TODO_RS_SUMMARY, $env:TODO_RS_BODY, $env:TODO_RS_LEVEL); \
+            Start-Sleep -Seconds 10; $n.Dispose()";
+        let (icon, level) = if critical {
+            ("Warning", "Warning")
+        } else {
+            ("Information", "Info")
+        };
+        let mut command = Command::new("powershell");
+        command
+            .args(["-NoProfile", "-WindowStyle", "Hidden", "-Command", script])
+            .env("TODO_RS_SUMMARY", summary)
+            .env("TODO_RS_BODY", body)
+            .env("TODO_RS_ICON", icon)
+            .env("TODO_RS_LEVEL", level);
+        command
+    } else if cfg!(target_os = "macos") {
+        let mut script = format!("display notification {:?} with title {:?}", body, summary);
+        if critical {
+            script.push_str(" sound name \"Basso\"");
+        }
+        let mut command = Command::new("osascript");
+        command.arg("-e").arg(script);
+        command
+    } else {
+        let mut command = Command::new("notify-send");
+        command.arg("--app-name=todo-rs");
+        if critical {
+            command.arg("--urgency=critical");
+        }
+        command.arg(summary).arg(body);
+        command
+    };
+
+    let status = command
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .status()?;
+    if status.success() {
+        Ok(())
+    } else {
+        Err(io::Error::other(format!("notifier exited with {}", status)))
+    }
+}
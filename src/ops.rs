@@ -0,0 +1,560 @@
+use chrono::NaiveDateTime;
+
+use crate::clock;
+use crate::journal::{Event, EventKind};
+use crate::Task;
+
+/// One change to a list, as the command line, the TUI or another tool
+/// asks for it.
+pub enum Operation {
+    Add {
+        description: String,
+        deadline: Option<NaiveDateTime>,
+        parent: Option<u64>,
+    },
+    Edit {
+        task: u64,
+        description: String,
+    },
+    Complete(u64),
+    /// Takes the task out of the archive first if it was filed there.
+    Reopen(u64),
+    Reschedule {
+        task: u64,
+        deadline: Option<NaiveDateTime>,
+    },
+    /// Leaves the list once the whole batch went through, so the rest of
+    /// it can no longer refer to the task.
+    Delete(u64),
+    /// Brings back a deleted task under its own id, as it was; a parent
+    /// that is gone is dropped.
+    Restore(Task),
+    Start(u64),
+    Stop(u64),
+    /// Moves the task to Someday.
+    Defer(u64),
+    Resume(u64),
+    MakePrivate(u64),
+    MakePublic(u64),
+    /// Moves the task under another one, or to the top level.
+    Reparent {
+        task: u64,
+        parent: Option<u64>,
+    },
+    SetCommand {
+        task: u64,
+        command: Option<String>,
+    },
+    Attach {
+        task: u64,
+        path: String,
+    },
+    Detach {
+        task: u64,
+        path: String,
+    },
+    /// Settles a conflict with another instance. Each field given is set
+    /// to its second value and recorded as a change from the first, the
+    /// other instance's, even when this list has it already.
+    Resolve {
+        task: u64,
+        kept: Vec<String>,
+        taken: Vec<String>,
+        description: Option<(String, String)>,
+        deadline: Option<(Option<NaiveDateTime>, Option<NaiveDateTime>)>,
+    },
+    /// Deletes the `from` tasks into `into`, which takes over their
+    /// attachments and subtasks, and their privacy.
+    Merge {
+        into: u64,
+        from: Vec<u64>,
+    },
+}
+
+/// A change `apply` made, as recorded in the journal.
+pub struct Change {
+    pub task: u64,
+    pub kind: EventKind,
+}
+
+pub type Changelog = Vec<Change>;
+
+/// What `apply` changes: the tasks, how new ones are made and where
+/// changes are recorded.
+pub trait TaskList {
+    fn tasks(&mut self) -> &mut Vec<Task>;
+
+    /// Makes a task for `Operation::Add` and puts it in the list,
+    /// returning its id and the event recording it.
+    fn create(
+        &mut self,
+        description: String,
+        deadline: Option<NaiveDateTime>,
+        parent: Option<u64>,
+    ) -> (u64, EventKind);
+
+    /// Puts a task back in the list, for `Operation::Restore`.
+    fn insert(&mut self, task: Task) {
+        self.tasks().push(task);
+    }
+
+    /// Brings an archived task back into the list for `Operation::Reopen`,
+    /// returning its position.
+    fn unarchive(&mut self, _task: u64) -> Option<usize> {
+        None
+    }
+
+    /// Called for each change once the whole batch went through.
+    fn record(&mut self, task: u64, kind: EventKind);
+}
+
+/// Tasks held in memory, with the events recording their changes.
+#[derive(Default)]
+pub struct List {
+    pub tasks: Vec<Task>,
+    pub events: Vec<Event>,
+}
+
+impl TaskList for List {
+    fn tasks(&mut self) -> &mut Vec<Task> {
+        &mut self.tasks
+    }
+
+    fn create(
+        &mut self,
+        description: String,
+        deadline: Option<NaiveDateTime>,
+        parent: Option<u64>,
+    ) -> (u64, EventKind) {
+        let id = self.tasks.iter().map(|t| t.id).max().unwrap_or(0) + 1;
+        let mut task = Task::new(id, description.clone(), deadline);
+        task.parent = parent;
+        self.tasks.push(task);
+        let kind = EventKind::Created {
+            description,
+            deadline,
+            parent,
+        };
+        (id, kind)
+    }
+
+    fn record(&mut self, task: u64, kind: EventKind) {
+        self.events.push(Event::now(task, kind));
+    }
+}
+
+/// Makes every change or none: the first one that can't be made puts the
+/// list back as it was, before anything is recorded. The changes that
+/// were made are returned in order; one that leaves a task as it was is
+/// left out.
+pub fn apply<L: TaskList + ?Sized>(
+    list: &mut L,
+    operations: Vec<Operation>,
+) -> Result<Changelog, String> {
+    let before = list.tasks().clone();
+    let mut batch = Batch {
+        list,
+        deleted: Vec::new(),
+    };
+    let mut changelog = Vec::new();
+    for operation in operations {
+        match batch.apply_one(operation) {
+            Ok(changes) => changelog.extend(changes),
+            Err(e) => {
+                *batch.list.tasks() = before;
+                return Err(e);
+            }
+        }
+    }
+    let Batch { list, deleted } = batch;
+    // Deleted tasks are still there while they're recorded, so the list
+    // can tell where they came from.
+    for change in &changelog {
+        list.record(change.task, change.kind.clone());
+    }
+    list.tasks().retain(|t| !deleted.contains(&t.id));
+    Ok(changelog)
+}
+
+struct Batch<'a, L: TaskList + ?Sized> {
+    list: &'a mut L,
+    deleted: Vec<u64>,
+}
+
+impl<L: TaskList + ?Sized> Batch<'_, L> {
+    fn index(&mut self, task: u64) -> Result<usize, String> {
+        let deleted = &self.deleted;
+        (self.list.tasks().iter())
+            .position(|t| t.id == task && !deleted.contains(&t.id))
+            .ok_or(format!("no task {}", task))
+    }
+
+    fn task(&mut self, task: u64) -> Result<&mut Task, String> {
+        let i = self.index(task)?;
+        Ok(&mut self.list.tasks()[i])
+    }
+
+    fn apply_one(&mut self, operation: Operation) -> Result<Changelog, String> {
+        let change = |task: u64, kind: EventKind| Ok(vec![Change { task, kind }]);
+        let unchanged = || Ok(Vec::new());
+        match operation {
+            Operation::Add {
+                description,
+                deadline,
+                parent,
+            } => {
+                if description.trim().is_empty() {
+                    return Err("a task needs a description".to_string());
+                }
+                if let Some(parent) = parent {
+                    self.index(parent)?;
+                }
+                let (task, kind) = self.list.create(description, deadline, parent);
+                change(task, kind)
+            }
+            Operation::Edit { task, description } => {
+                if description.trim().is_empty() {
+                    return Err("a task needs a description".to_string());
+                }
+                let t = self.task(task)?;
+                if t.description == description {
+                    return unchanged();
+                }
+                let from = std::mem::replace(&mut t.description, description.clone());
+                change(
+                    task,
+                    EventKind::Edited {
+                        from,
+                        to: description,
+                    },
+                )
+            }
+            Operation::Complete(task) => {
+                let t = self.task(task)?;
+                if t.completed {
+                    return Err(format!("task {} is already completed", task));
+                }
+                t.set_completed(true, clock::now());
+                change(task, EventKind::Completed)
+            }
+            Operation::Reopen(task) => {
+                let i = (self.index(task).ok())
+                    .or_else(|| self.list.unarchive(task))
+                    .ok_or(format!("no task {}", task))?;
+                let t = &mut self.list.tasks()[i];
+                if !t.completed {
+                    return Err(format!("task {} is not completed", task));
+                }
+                t.set_completed(false, clock::now());
+                change(task, EventKind::Reopened)
+            }
+            Operation::Reschedule { task, deadline } => {
+                let t = self.task(task)?;
+                let from = std::mem::replace(&mut t.deadline, deadline);
+                if from == deadline {
+                    return unchanged();
+                }
+                change(task, EventKind::Rescheduled { from, to: deadline })
+            }
+            Operation::Delete(task) => {
+                self.index(task)?;
+                self.deleted.push(task);
+                change(task, EventKind::Deleted)
+            }
+            Operation::Restore(mut task) => {
+                let id = task.id;
+                if self.list.tasks().iter().any(|t| t.id == id) {
+                    return Err(format!("task {} already exists", id));
+                }
+                task.parent = task.parent.filter(|p| self.index(*p).is_ok());
+                let mut changes = vec![Change {
+                    task: id,
+                    kind: EventKind::Created {
+                        description: task.description.clone(),
+                        deadline: task.deadline,
+                        parent: task.parent,
+                    },
+                }];
+                if task.completed {
+                    let kind = EventKind::Completed;
+                    changes.push(Change { task: id, kind });
+                }
+                self.list.insert(task);
+                Ok(changes)
+            }
+            Operation::Start(task) => {
+                let t = self.task(task)?;
+                if t.completed {
+                    return Err(format!("task {} is completed", task));
+                }
+                if t.in_progress {
+                    return unchanged();
+                }
+                t.in_progress = true;
+                change(task, EventKind::Started)
+            }
+            Operation::Stop(task) => {
+                let t = self.task(task)?;
+                if !t.in_progress {
+                    return unchanged();
+                }
+                t.in_progress = false;
+                change(task, EventKind::Stopped)
+            }
+            Operation::Defer(task) | Operation::Resume(task) => {
+                let someday = matches!(operation, Operation::Defer(_));
+                let t = self.task(task)?;
+                if t.someday == someday {
+                    return unchanged();
+                }
+                t.someday = someday;
+                let kind = if someday {
+                    EventKind::Deferred
+                } else {
+                    EventKind::Resumed
+                };
+                change(task, kind)
+            }
+            Operation::MakePrivate(task) | Operation::MakePublic(task) => {
+                let private = matches!(operation, Operation::MakePrivate(_));
+                let t = self.task(task)?;
+                if t.private == private {
+                    return unchanged();
+                }
+                t.private = private;
+                let kind = if private {
+                    EventKind::MadePrivate
+                } else {
+                    EventKind::MadePublic
+                };
+                change(task, kind)
+            }
+            Operation::Reparent { task, parent } => {
+                let i = self.index(task)?;
+                // Walking up from the new parent must not come back here.
+                let mut above = parent;
+                while let Some(p) = above {
+                    if p == task {
+                        return Err(format!("task {} can't go under its own subtask", task));
+                    }
+                    let j = self.index(p)?;
+                    above = self.list.tasks()[j].parent;
+                }
+                let t = &mut self.list.tasks()[i];
+                let from = std::mem::replace(&mut t.parent, parent);
+                if from == parent {
+                    return unchanged();
+                }
+                change(task, EventKind::Reparented { from, to: parent })
+            }
+            Operation::SetCommand { task, command } => {
+                let t = self.task(task)?;
+                if t.command == command {
+                    return unchanged();
+                }
+                let from = std::mem::replace(&mut t.command, command.clone());
+                change(task, EventKind::CommandChanged { from, to: command })
+            }
+            Operation::Attach { task, path } => {
+                let t = self.task(task)?;
+                if t.attachments.contains(&path) {
+                    return unchanged();
+                }
+                t.attachments.push(path.clone());
+                change(task, EventKind::Attached { path })
+            }
+            Operation::Detach { task, path } => {
+                let t = self.task(task)?;
+                let Some(position) = t.attachments.iter().position(|p| *p == path) else {
+                    return Err(format!("task {} has no attachment {}", task, path));
+                };
+                let path = t.attachments.remove(position);
+                change(task, EventKind::Detached { path })
+            }
+            Operation::Resolve {
+                task,
+                kept,
+                taken,
+                description,
+                deadline,
+            } => {
+                let t = self.task(task)?;
+                let mut kinds = vec![EventKind::ConflictResolved { kept, taken }];
+                if let Some((from, to)) = description {
+                    t.description = to.clone();
+                    kinds.push(EventKind::Edited { from, to });
+                }
+                if let Some((from, to)) = deadline {
+                    t.deadline = to;
+                    kinds.push(EventKind::Rescheduled { from, to });
+                }
+                let changes = kinds.into_iter().map(|kind| Change { task, kind });
+                Ok(changes.collect())
+            }
+            Operation::Merge { into, from } => self.merge(into, from),
+        }
+    }
+
+    fn merge(&mut self, into: u64, from: Vec<u64>) -> Result<Changelog, String> {
+        let mut gone = Vec::new();
+        for id in &from {
+            if *id == into {
+                return Err(format!("task {} can't be merged into itself", into));
+            }
+            let i = self.index(*id)?;
+            gone.push(self.list.tasks()[i].clone());
+        }
+        let i = self.index(into)?;
+        let is_gone = |id: u64| from.contains(&id);
+
+        let mut changes = Vec::new();
+        let task = &mut self.list.tasks()[i];
+        for path in gone.iter().flat_map(|t| &t.attachments) {
+            if !task.attachments.contains(path) {
+                task.attachments.push(path.clone());
+                let kind = EventKind::Attached { path: path.clone() };
+                changes.push(Change { task: into, kind });
+            }
+        }
+        // A private duplicate keeps the merged task hidden too.
+        if !task.private && gone.iter().any(|t| t.private) {
+            task.private = true;
+            let kind = EventKind::MadePrivate;
+            changes.push(Change { task: into, kind });
+        }
+
+        // Subtasks move to the kept task; the kept task itself, if it was
+        // under one of the others, moves up to the nearest one staying.
+        let deleted = &self.deleted;
+        for task in self.list.tasks().iter_mut() {
+            let Some(parent) = task.parent.filter(|p| is_gone(*p)) else {
+                continue;
+            };
+            if is_gone(task.id) || deleted.contains(&task.id) {
+                continue;
+            }
+            let mut to = Some(into);
+            if task.id == into {
+                to = Some(parent);
+                while let Some(p) = to.filter(|p| is_gone(*p)) {
+                    to = gone.iter().find(|t| t.id == p).and_then(|t| t.parent);
+                }
+            }
+            task.parent = to;
+            let kind = EventKind::Reparented {
+                from: Some(parent),
+                to,
+            };
+            changes.push(Change {
+                task: task.id,
+                kind,
+            });
+        }
+
+        for task in &gone {
+            self.deleted.push(task.id);
+            let kind = EventKind::Deleted;
+            changes.push(Change {
+                task: task.id,
+                kind,
+            });
+        }
+        let from = (gone.into_iter()).map(|t| (t.id, t.description)).collect();
+        let kind = EventKind::Merged { from };
+        changes.push(Change { task: into, kind });
+        Ok(changes)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn add(description: &str) -> Operation {
+        Operation::Add {
+            description: description.to_string(),
+            deadline: None,
+            parent: None,
+        }
+    }
+
+    #[test]
+    fn a_failed_batch_changes_nothing() {
+        let mut list = List::default();
+        apply(&mut list, vec![add("Kept")]).unwrap();
+        let batch = vec![
+            add("Dropped"),
+            Operation::Complete(1),
+            Operation::Complete(9),
+        ];
+        assert_eq!(apply(&mut list, batch).err().unwrap(), "no task 9");
+        assert_eq!(list.tasks.len(), 1);
+        assert!(!list.tasks[0].completed);
+        assert_eq!(list.events.len(), 1);
+    }
+
+    #[test]
+    fn a_deleted_task_is_gone_for_the_rest_of_the_batch() {
+        let mut list = List::default();
+        apply(&mut list, vec![add("One"), add("Two")]).unwrap();
+        let batch = vec![Operation::Delete(1), Operation::Complete(1)];
+        assert_eq!(apply(&mut list, batch).err().unwrap(), "no task 1");
+        assert_eq!(list.tasks.len(), 2);
+
+        apply(&mut list, vec![Operation::Delete(1)]).unwrap();
+        let ids: Vec<u64> = list.tasks.iter().map(|t| t.id).collect();
+        assert_eq!(ids, [2]);
+        assert!(matches!(
+            list.events.last().unwrap().kind,
+            EventKind::Deleted
+        ));
+    }
+
+    #[test]
+    fn merging_moves_subtasks_and_attachments_over() {
+        let mut list = List::default();
+        let child = Operation::Add {
+            description: "Child".to_string(),
+            deadline: None,
+            parent: Some(2),
+        };
+        apply(&mut list, vec![add("Kept"), add("Duplicate"), child]).unwrap();
+        let attach = Operation::Attach {
+            task: 2,
+            path: "/tmp/notes.txt".to_string(),
+        };
+        apply(&mut list, vec![attach, Operation::MakePrivate(2)]).unwrap();
+
+        let merge = Operation::Merge {
+            into: 1,
+            from: vec![2],
+        };
+        let changelog = apply(&mut list, vec![merge]).unwrap();
+        let kept = &list.tasks[0];
+        assert_eq!(list.tasks.len(), 2);
+        assert_eq!(kept.attachments, ["/tmp/notes.txt"]);
+        assert!(kept.private);
+        assert_eq!(list.tasks[1].parent, Some(1));
+        assert!(matches!(
+            &changelog.last().unwrap().kind,
+            EventKind::Merged { from } if from[..] == [(2, "Duplicate".to_string())]
+        ));
+    }
+
+    #[test]
+    fn reparenting_refuses_a_loop() {
+        let mut list = List::default();
+        let child = Operation::Add {
+            description: "Child".to_string(),
+            deadline: None,
+            parent: Some(1),
+        };
+        apply(&mut list, vec![add("Parent"), child]).unwrap();
+        let loop_back = Operation::Reparent {
+            task: 1,
+            parent: Some(2),
+        };
+        assert!(apply(&mut list, vec![loop_back]).is_err());
+        assert_eq!(list.tasks[0].parent, None);
+    }
+}
@@ -0,0 +1,26 @@
+/// One line of an indented outline: its nesting level and the task text.
+pub struct OutlineItem {
+    pub depth: usize,
+    pub description: String,
+}
+
+/// Parses indented text into outline items. A tab or two spaces make one
+/// level; list bullets (`-`, `*`, `•`) are dropped and blank lines skipped.
+pub fn parse(text: &str) -> Vec<OutlineItem> {
+    text.lines()
+        .filter(|line| !line.trim().is_empty())
+        .map(|line| {
+            let content = line.trim_start();
+            let indent = &line[..line.len() - content.len()];
+            let depth = indent.matches('\t').count() + indent.matches(' ').count() / 2;
+            let description = ["- ", "* ", "• "]
+                .iter()
+                .find_map(|bullet| content.strip_prefix(bullet))
+                .unwrap_or(content)
+                .trim_end()
+                .to_string();
+            OutlineItem { depth, description }
+        })
+        .filter(|item| !item.description.is_empty())
+        .collect()
+}
@@ -0,0 +1,197 @@
+use std::env;
+use tui::buffer::Cell;
+use tui::style::{Color, Modifier};
+
+use crate::config::Styles;
+
+/// How many colors the terminal can show, judged from the environment.
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum Support {
+    /// `NO_COLOR` is set or `TERM` is `dumb`: text attributes only.
+    None,
+    /// The 8 basic colors, as over SSH to a console or an old terminal.
+    Basic,
+    /// The 256-color palette.
+    Indexed,
+    /// 24-bit color.
+    TrueColor,
+}
+
+pub fn detect() -> Support {
+    let no_color = env::var_os("NO_COLOR").is_some_and(|v| !v.is_empty());
+    let term = env::var("TERM").unwrap_or_default();
+    let colorterm = env::var("COLORTERM").unwrap_or_default();
+    if no_color || term == "dumb" {
+        Support::None
+    } else if colorterm == "truecolor" || colorterm == "24bit" {
+        Support::TrueColor
+    } else if term.contains("256color") || cfg!(windows) {
+        Support::Indexed
+    } else {
+        Support::Basic
+    }
+}
+
+/// The basic color closest to an RGB value.
+fn basic_rgb(r: u8, g: u8, b: u8) -> Color {
+    match (r > 127, g > 127, b > 127) {
+        (false, false, false) => Color::Black,
+        (true, false, false) => Color::Red,
+        (false, true, false) => Color::Green,
+        (true, true, false) => Color::Yellow,
+        (false, false, true) => Color::Blue,
+        (true, false, true) => Color::Magenta,
+        (false, true, true) => Color::Cyan,
+        (true, true, true) => Color::Gray,
+    }
+}
+
+/// The RGB value of a 256-color palette entry past the 16 named ones.
+fn indexed_rgb(index: u8) -> (u8, u8, u8) {
+    if index >= 232 {
+        let level = 8 + (index - 232) * 10;
+        return (level, level, level);
+    }
+    let cube = index - 16;
+    let level = |n: u8| if n == 0 { 0 } else { 55 + n * 40 };
+    (level(cube / 36), level(cube / 6 % 6), level(cube % 6))
+}
+
+/// `color` as 8-color terminals show it, where the bright variants are
+/// missing and `Gray` is the only white. `None` stands for dark gray,
+/// which is shown dimmed instead.
+fn basic(color: Color) -> Option<Color> {
+    let color = match color {
+        Color::DarkGray | Color::Indexed(8) => return None,
+        Color::White => Color::Gray,
+        Color::LightRed => Color::Red,
+        Color::LightGreen => Color::Green,
+        Color::LightYellow => Color::Yellow,
+        Color::LightBlue => Color::Blue,
+        Color::LightMagenta => Color::Magenta,
+        Color::LightCyan => Color::Cyan,
+        Color::Indexed(index) if index < 16 => return basic(named(index)),
+        Color::Indexed(index) => {
+            let (r, g, b) = indexed_rgb(index);
+            basic_rgb(r, g, b)
+        }
+        Color::Rgb(r, g, b) => basic_rgb(r, g, b),
+        other => other,
+    };
+    Some(color)
+}
+
+fn named(index: u8) -> Color {
+    [
+        Color::Black,
+        Color::Red,
+        Color::Green,
+        Color::Yellow,
+        Color::Blue,
+        Color::Magenta,
+        Color::Cyan,
+        Color::Gray,
+        Color::DarkGray,
+        Color::LightRed,
+        Color::LightGreen,
+        Color::LightYellow,
+        Color::LightBlue,
+        Color::LightMagenta,
+        Color::LightCyan,
+        Color::White,
+    ][index as usize]
+}
+
+/// The escape sequence that switches to `cell`'s style on an 8-color
+/// terminal, once `adapt` made its colors basic ones.
+pub fn basic_sgr(cell: &Cell) -> String {
+    let modifiers = [
+        (Modifier::BOLD, 1),
+        (Modifier::DIM, 2),
+        (Modifier::ITALIC, 3),
+        (Modifier::UNDERLINED, 4),
+        (Modifier::REVERSED, 7),
+        (Modifier::CROSSED_OUT, 9),
+    ];
+    let mut codes = vec![0];
+    codes.extend(
+        (modifiers.iter())
+            .filter(|(modifier, _)| cell.modifier.contains(*modifier))
+            .map(|(_, code)| *code),
+    );
+    let offset = |color: Color| {
+        let basic = [
+            Color::Black,
+            Color::Red,
+            Color::Green,
+            Color::Yellow,
+            Color::Blue,
+            Color::Magenta,
+            Color::Cyan,
+            Color::Gray,
+        ];
+        basic.iter().position(|c| *c == color)
+    };
+    codes.extend(offset(cell.fg).map(|n| 30 + n));
+    codes.extend(offset(cell.bg).map(|n| 40 + n));
+    let codes: Vec<String> = codes.iter().map(|c| c.to_string()).collect();
+    format!("\x1b[{}m", codes.join(";"))
+}
+
+/// The nearest 256-color palette entry, for terminals without 24-bit color.
+fn indexed(r: u8, g: u8, b: u8) -> Color {
+    let step = |c: u8| if c < 48 { 0 } else { (c - 35) / 40 };
+    Color::Indexed(16 + 36 * step(r) + 6 * step(g) + step(b))
+}
+
+/// `cell` restyled for what the terminal supports: colors it can't show
+/// are swapped for ones it can, or for text attributes.
+pub fn adapt(support: Support, cell: &Cell) -> Cell {
+    let mut cell = cell.clone();
+    match support {
+        Support::TrueColor => {}
+        Support::Indexed => {
+            if let Color::Rgb(r, g, b) = cell.fg {
+                cell.fg = indexed(r, g, b);
+            }
+            if let Color::Rgb(r, g, b) = cell.bg {
+                cell.bg = indexed(r, g, b);
+            }
+        }
+        Support::Basic => {
+            cell.fg = basic(cell.fg).unwrap_or_else(|| {
+                cell.modifier.insert(Modifier::DIM);
+                Color::Reset
+            });
+            cell.bg = basic(cell.bg).unwrap_or(Color::Black);
+        }
+        Support::None => {
+            // A colored background marks a highlight; keep it visible.
+            if cell.bg != Color::Reset {
+                cell.modifier.insert(Modifier::REVERSED);
+            }
+            if cell.fg == Color::DarkGray {
+                cell.modifier.insert(Modifier::DIM);
+            }
+            cell.fg = Color::Reset;
+            cell.bg = Color::Reset;
+        }
+    }
+    cell
+}
+
+/// The theme's styles for `support`: without colors, the states it tells
+/// apart by color alone get text attributes instead.
+pub fn fit(support: Support, styles: Styles) -> Styles {
+    if support != Support::None {
+        return styles;
+    }
+    Styles {
+        overdue: styles.overdue.add_modifier(Modifier::BOLD),
+        due_soon: styles.due_soon.add_modifier(Modifier::UNDERLINED),
+        completed: styles.completed.add_modifier(Modifier::CROSSED_OUT),
+        selected: styles.selected.add_modifier(Modifier::REVERSED),
+        someday: styles.someday.add_modifier(Modifier::DIM),
+        blocked: styles.blocked.add_modifier(Modifier::ITALIC),
+    }
+}
@@ -0,0 +1,194 @@
+use chrono::NaiveDate;
+use tui::{
+    backend::Backend,
+    layout::Rect,
+    style::{Color, Modifier, Style},
+    text::{Span, Spans},
+    widgets::{Block, Borders, Paragraph},
+    Frame,
+};
+
+use crate::clock;
+use crate::estimate;
+use crate::{AppState, Task};
+
+/// Deadlines this close are offered as well as today's.
+pub const SOON_DAYS: i64 = 3;
+/// Undated tasks offered as suggestions, those waiting longest first.
+const SUGGESTIONS: usize = 5;
+
+/// Why a task is offered for the day.
+#[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Reason {
+    Overdue,
+    DueToday,
+    Started,
+    DueSoon,
+    Waiting,
+}
+
+impl Reason {
+    fn label(self) -> &'static str {
+        match self {
+            Reason::Overdue => "overdue",
+            Reason::DueToday => "due today",
+            Reason::Started => "in progress",
+            Reason::DueSoon => "due soon",
+            Reason::Waiting => "suggested",
+        }
+    }
+}
+
+/// Open tasks worth doing today, most pressing first.
+fn candidates(tasks: &[Task], today: NaiveDate) -> Vec<(u64, Reason)> {
+    let open: Vec<&Task> = tasks
+        .iter()
+        .filter(|t| !t.completed && !t.someday)
+        .collect();
+    let soon = today + chrono::Duration::days(SOON_DAYS);
+    let reason = |task: &Task| match task.deadline.map(|d| d.date()) {
+        Some(d) if d < today => Some(Reason::Overdue),
+        Some(d) if d == today => Some(Reason::DueToday),
+        _ if task.in_progress => Some(Reason::Started),
+        Some(d) if d <= soon => Some(Reason::DueSoon),
+        _ => None,
+    };
+    let mut offered: Vec<(u64, Reason)> = (open.iter())
+        .filter_map(|t| Some((t.id, reason(t)?)))
+        .collect();
+    // Stable, so each group keeps the list order.
+    offered.sort_by_key(|(_, reason)| *reason);
+    let mut waiting: Vec<&Task> = (open.iter().copied())
+        .filter(|t| t.deadline.is_none() && !t.in_progress)
+        .collect();
+    waiting.sort_by_key(|t| t.created_at);
+    let suggested = waiting.iter().take(SUGGESTIONS);
+    offered.extend(suggested.map(|t| (t.id, Reason::Waiting)));
+    offered
+}
+
+/// A morning planning session: tasks are committed to today one at a
+/// time, as long as their estimates fit the daily capacity.
+pub struct Planner {
+    pub candidates: Vec<(u64, Reason)>,
+    pub committed: Vec<u64>,
+    pub selected: usize,
+}
+
+impl Planner {
+    /// Starts from today's plan when there already is one.
+    pub fn new(app_state: &AppState) -> Planner {
+        let today = clock::today();
+        let mut candidates = candidates(&app_state.tasks, today);
+        let committed: Vec<u64> = (app_state.todays_plan().unwrap_or_default().into_iter())
+            .filter(|id| app_state.tasks.iter().any(|t| t.id == *id))
+            .collect();
+        // Committed earlier but no longer pressing: still listed, to drop.
+        for id in &committed {
+            if !candidates.iter().any(|(c, _)| c == id) {
+                candidates.push((*id, Reason::Waiting));
+            }
+        }
+        Planner {
+            candidates,
+            committed,
+            selected: 0,
+        }
+    }
+
+    /// Combined estimate of the committed tasks; those without one count
+    /// as zero.
+    pub fn minutes(&self, tasks: &[Task]) -> u32 {
+        tasks
+            .iter()
+            .filter(|t| self.committed.contains(&t.id))
+            .filter_map(|t| estimate::from_description(&t.description))
+            .sum()
+    }
+
+    pub fn move_selection(&mut self, up: bool) {
+        self.selected = if up {
+            self.selected.saturating_sub(1)
+        } else {
+            (self.selected + 1).min(self.candidates.len().saturating_sub(1))
+        };
+    }
+
+    /// Commits or drops the selected task. Refuses to commit past
+    /// `capacity` minutes, saying by how much.
+    pub fn toggle(&mut self, tasks: &[Task], capacity: u32) -> Result<(), String> {
+        let Some((id, _)) = self.candidates.get(self.selected) else {
+            return Ok(());
+        };
+        if let Some(position) = self.committed.iter().position(|c| c == id) {
+            self.committed.remove(position);
+            return Ok(());
+        }
+        let task = tasks.iter().find(|t| t.id == *id);
+        let minutes = task.and_then(|t| estimate::from_description(&t.description));
+        let total = self.minutes(tasks) + minutes.unwrap_or(0);
+        if total > capacity {
+            return Err(format!(
+                "That would be {} over today's {}",
+                estimate::format_minutes(total - capacity),
+                estimate::format_minutes(capacity)
+            ));
+        }
+        self.committed.push(*id);
+        Ok(())
+    }
+}
+
+pub fn render_plan<B: Backend>(f: &mut Frame<B>, app_state: &AppState, chunk: Rect) {
+    let Some(planner) = &app_state.planner else {
+        return;
+    };
+    let capacity = app_state.config.capacity_minutes();
+    let title = format!(
+        "Plan today: {} of {} committed",
+        estimate::format_minutes(planner.minutes(&app_state.tasks)),
+        estimate::format_minutes(capacity)
+    );
+    let block = Block::default().borders(Borders::ALL).title(title);
+    if planner.candidates.is_empty() {
+        let text = "Nothing due, started or waiting; enjoy the day";
+        f.render_widget(Paragraph::new(text).block(block), chunk);
+        return;
+    }
+
+    let lines: Vec<Spans> = (planner.candidates.iter().enumerate())
+        .filter_map(|(i, (id, reason))| {
+            let task = app_state.tasks.iter().find(|t| t.id == *id)?;
+            let mark = if planner.committed.contains(id) {
+                "x"
+            } else {
+                " "
+            };
+            let style = if i == planner.selected {
+                app_state.styles.selected
+            } else if *reason == Reason::Overdue {
+                app_state.styles.overdue
+            } else {
+                Style::default()
+            };
+            let estimate = estimate::from_description(&task.description)
+                .map_or("no estimate".to_string(), estimate::format_minutes);
+            Some(Spans::from(vec![
+                Span::styled(format!("[{}] {}", mark, app_state.description(task)), style),
+                Span::styled(
+                    format!("  ({}, {})", reason.label(), estimate),
+                    Style::default()
+                        .fg(Color::DarkGray)
+                        .add_modifier(Modifier::ITALIC),
+                ),
+            ]))
+        })
+        .collect();
+    // Keeps the selection on screen in a long list.
+    let height = chunk.height.saturating_sub(2) as usize;
+    let scroll = planner.selected.saturating_sub(height.saturating_sub(1));
+    let paragraph = Paragraph::new(lines)
+        .block(block)
+        .scroll((scroll as u16, 0));
+    f.render_widget(paragraph, chunk);
+}
@@ -0,0 +1,17 @@
+/// Stands in for a run of hidden words.
+const MASK: &str = "•••";
+
+/// The description with all but its `#tags`, `+projects` and `@contexts`
+/// hidden, so the list can be shown on a shared screen.
+pub fn mask(description: &str) -> String {
+    let mut words: Vec<&str> = Vec::new();
+    for word in description.split_whitespace() {
+        let marker = word.starts_with(['#', '+', '@']) && word.chars().count() > 1;
+        if marker {
+            words.push(word);
+        } else if words.last() != Some(&MASK) {
+            words.push(MASK);
+        }
+    }
+    words.join(" ")
+}
@@ -0,0 +1,546 @@
+use chrono::NaiveDate;
+use regex::{Regex, RegexBuilder};
+use std::cmp::Ordering;
+use std::collections::BTreeMap;
+use std::fmt;
+use unicode_normalization::char::is_combining_mark;
+use unicode_normalization::UnicodeNormalization;
+
+use crate::tags;
+use crate::Task;
+
+const FIELDS: &str = "due, created, tag, project, file, filter";
+/// Saved filters can refer to each other, but not endlessly.
+const MAX_FILTER_DEPTH: usize = 8;
+
+#[derive(Clone, Copy, Debug)]
+pub enum Comparison {
+    Less,
+    LessOrEqual,
+    Equal,
+    GreaterOrEqual,
+    Greater,
+}
+
+impl Comparison {
+    fn holds(self, ordering: Ordering) -> bool {
+        match self {
+            Comparison::Less => ordering == Ordering::Less,
+            Comparison::LessOrEqual => ordering != Ordering::Greater,
+            Comparison::Equal => ordering == Ordering::Equal,
+            Comparison::GreaterOrEqual => ordering != Ordering::Less,
+            Comparison::Greater => ordering == Ordering::Greater,
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug)]
+pub enum DateField {
+    Due,
+    Created,
+}
+
+#[derive(Clone, Copy, Debug)]
+pub enum DateValue {
+    Today,
+    Tomorrow,
+    On(NaiveDate),
+}
+
+impl DateValue {
+    fn resolve(self, today: NaiveDate) -> NaiveDate {
+        match self {
+            DateValue::Today => today,
+            DateValue::Tomorrow => today + chrono::Duration::days(1),
+            DateValue::On(date) => date,
+        }
+    }
+}
+
+#[derive(Clone, Debug)]
+pub enum Query {
+    And(Box<Query>, Box<Query>),
+    Or(Box<Query>, Box<Query>),
+    Not(Box<Query>),
+    Text(String),
+    Regex(Regex),
+    Tag(String),
+    Project(String),
+    Date(DateField, Comparison, DateValue),
+    NoDate(DateField),
+    Done,
+    Open,
+    Overdue,
+    Started,
+    Someday,
+    /// Tasks from one file of the workspace.
+    File(String),
+    /// Tasks picked by the app rather than typed, e.g. for a health review.
+    Ids(Vec<u64>),
+}
+
+/// What went wrong and where, as a 1-based column in the query text.
+#[derive(Debug)]
+pub struct ParseError {
+    pub message: String,
+    pub column: usize,
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{} (column {})", self.message, self.column)
+    }
+}
+
+impl std::error::Error for ParseError {}
+
+#[derive(Debug, PartialEq)]
+enum Token {
+    Open,
+    Close,
+    Word(String),
+    Quoted(String),
+}
+
+fn tokenize(text: &str) -> Result<Vec<(Token, usize)>, ParseError> {
+    let mut tokens = Vec::new();
+    let mut chars = text.char_indices().peekable();
+    while let Some(&(start, c)) = chars.peek() {
+        let column = text[..start].chars().count() + 1;
+        match c {
+            c if c.is_whitespace() => {
+                chars.next();
+            }
+            '(' | ')' => {
+                chars.next();
+                let token = if c == '(' { Token::Open } else { Token::Close };
+                tokens.push((token, column));
+            }
+            '"' => {
+                chars.next();
+                let mut quoted = String::new();
+                loop {
+                    match chars.next() {
+                        Some((_, '"')) => break,
+                        Some((_, c)) => quoted.push(c),
+                        None => {
+                            return Err(ParseError {
+                                message: "unclosed quote".to_string(),
+                                column,
+                            })
+                        }
+                    }
+                }
+                tokens.push((Token::Quoted(quoted), column));
+            }
+            _ => {
+                let mut word = String::new();
+                while let Some(&(_, c)) = chars.peek() {
+                    if c.is_whitespace() || c == '(' || c == ')' || c == '"' {
+                        break;
+                    }
+                    word.push(c);
+                    chars.next();
+                }
+                tokens.push((Token::Word(word), column));
+            }
+        }
+    }
+    Ok(tokens)
+}
+
+struct Parser<'a> {
+    tokens: Vec<(Token, usize)>,
+    position: usize,
+    end_column: usize,
+    filters: &'a BTreeMap<String, String>,
+    depth: usize,
+}
+
+impl Parser<'_> {
+    fn peek(&self) -> Option<&Token> {
+        self.tokens.get(self.position).map(|(token, _)| token)
+    }
+
+    fn column(&self) -> usize {
+        self.tokens
+            .get(self.position)
+            .map_or(self.end_column, |(_, column)| *column)
+    }
+
+    fn error<T>(&self, message: impl Into<String>) -> Result<T, ParseError> {
+        Err(ParseError {
+            message: message.into(),
+            column: self.column(),
+        })
+    }
+
+    fn at_keyword(&self, keyword: &str) -> bool {
+        matches!(self.peek(), Some(Token::Word(w)) if w == keyword)
+    }
+
+    fn parse_or(&mut self) -> Result<Query, ParseError> {
+        let mut query = self.parse_and()?;
+        while self.at_keyword("OR") {
+            self.position += 1;
+            query = Query::Or(Box::new(query), Box::new(self.parse_and()?));
+        }
+        Ok(query)
+    }
+
+    fn parse_and(&mut self) -> Result<Query, ParseError> {
+        let mut query = self.parse_unary()?;
+        loop {
+            if self.at_keyword("AND") {
+                self.position += 1;
+            } else if matches!(self.peek(), None | Some(Token::Close)) || self.at_keyword("OR") {
+                return Ok(query);
+            }
+            query = Query::And(Box::new(query), Box::new(self.parse_unary()?));
+        }
+    }
+
+    fn parse_unary(&mut self) -> Result<Query, ParseError> {
+        let column = self.column();
+        let Some((token, _)) = self.tokens.get(self.position) else {
+            return self.error("expected a search term");
+        };
+        match token {
+            Token::Word(w) if w == "NOT" => {
+                self.position += 1;
+                Ok(Query::Not(Box::new(self.parse_unary()?)))
+            }
+            Token::Word(w) if w == "AND" || w == "OR" => {
+                self.error(format!("expected a search term before {}", w))
+            }
+            Token::Open => {
+                self.position += 1;
+                let query = self.parse_or()?;
+                if self.peek() != Some(&Token::Close) {
+                    return Err(ParseError {
+                        message: "unclosed parenthesis".to_string(),
+                        column,
+                    });
+                }
+                self.position += 1;
+                Ok(query)
+            }
+            Token::Close => self.error("unexpected ')'"),
+            Token::Quoted(text) => {
+                let query = Query::Text(fold(text));
+                self.position += 1;
+                Ok(query)
+            }
+            Token::Word(word) => {
+                let word = word.clone();
+                let query = self.parse_term(&word)?;
+                self.position += 1;
+                Ok(query)
+            }
+        }
+    }
+
+    fn parse_term(&self, word: &str) -> Result<Query, ParseError> {
+        match word {
+            "done" => return Ok(Query::Done),
+            "open" => return Ok(Query::Open),
+            "overdue" => return Ok(Query::Overdue),
+            "started" => return Ok(Query::Started),
+            "someday" => return Ok(Query::Someday),
+            _ => {}
+        }
+        if let Some(tag) = word.strip_prefix('#').filter(|t| !t.is_empty()) {
+            return Ok(Query::Tag(tag.to_string()));
+        }
+        if let Some(project) = word.strip_prefix('+').filter(|p| !p.is_empty()) {
+            return Ok(Query::Project(project.to_string()));
+        }
+
+        let Some(split) = word.find([':', '<', '>', '=']) else {
+            return Ok(Query::Text(fold(word)));
+        };
+        let field = &word[..split];
+        let rest = &word[split..];
+        let (comparison, value) = [
+            ("<=", Comparison::LessOrEqual),
+            (">=", Comparison::GreaterOrEqual),
+            ("<", Comparison::Less),
+            (">", Comparison::Greater),
+            ("=", Comparison::Equal),
+            (":", Comparison::Equal),
+        ]
+        .iter()
+        .find_map(|(op, comparison)| rest.strip_prefix(op).map(|value| (*comparison, value)))
+        .expect("split is at an operator");
+        let is_colon = rest.starts_with(':');
+
+        if value.is_empty() {
+            return self.error(format!("expected a value after '{}'", word));
+        }
+        match field {
+            "due" | "created" => {
+                let field = if field == "due" {
+                    DateField::Due
+                } else {
+                    DateField::Created
+                };
+                if is_colon && value == "none" {
+                    return Ok(Query::NoDate(field));
+                }
+                if is_colon && value == "any" {
+                    return Ok(Query::Not(Box::new(Query::NoDate(field))));
+                }
+                let date = match value {
+                    "today" => DateValue::Today,
+                    "tomorrow" => DateValue::Tomorrow,
+                    _ => match NaiveDate::parse_from_str(value, "%Y-%m-%d") {
+                        Ok(date) => DateValue::On(date),
+                        Err(_) => {
+                            return self.error(format!(
+                                "'{}' is not a date; use YYYY-MM-DD, today or tomorrow",
+                                value
+                            ))
+                        }
+                    },
+                };
+                Ok(Query::Date(field, comparison, date))
+            }
+            "tag" | "project" | "file" | "filter" if !is_colon => self.error(format!(
+                "{} only supports ':', as in {}:{}",
+                field, field, value
+            )),
+            "tag" => Ok(Query::Tag(value.to_string())),
+            "project" => Ok(Query::Project(value.to_string())),
+            "file" => Ok(Query::File(value.to_string())),
+            "filter" => {
+                let Some(saved) = self.filters.get(value) else {
+                    return self.error(format!("no saved filter named '{}'", value));
+                };
+                if self.depth >= MAX_FILTER_DEPTH {
+                    return self.error(format!("saved filter '{}' refers to itself", value));
+                }
+                parse_nested(saved, self.filters, self.depth + 1).map_err(|e| ParseError {
+                    message: format!("in saved filter '{}': {}", value, e),
+                    column: self.column(),
+                })
+            }
+            _ if field.chars().all(|c| c.is_ascii_alphabetic()) && !field.is_empty() => self.error(
+                format!("unknown field '{}', expected one of {}", field, FIELDS),
+            ),
+            _ => Ok(Query::Text(fold(word))),
+        }
+    }
+}
+
+/// Parses a query such as `due<2024-07-01 AND tag:work AND NOT done`.
+///
+/// Terms are free text (`report`, `"two words"`), `#tag` / `tag:x`,
+/// `+project` / `project:x`, dates (`due<today`, `created>=2024-01-01`,
+/// `due:none`), states (`done`, `open`, `overdue`, `started`, `someday`) and
+/// saved filters (`filter:name`, expanded from `filters`). Terms next to each
+/// other are ANDed; `OR`, `NOT` and parentheses work as usual.
+pub fn parse(text: &str, filters: &BTreeMap<String, String>) -> Result<Query, ParseError> {
+    parse_nested(text, filters, 0)
+}
+
+fn parse_nested(
+    text: &str,
+    filters: &BTreeMap<String, String>,
+    depth: usize,
+) -> Result<Query, ParseError> {
+    let mut parser = Parser {
+        tokens: tokenize(text)?,
+        position: 0,
+        end_column: text.chars().count() + 1,
+        filters,
+        depth,
+    };
+    let query = parser.parse_or()?;
+    if parser.position < parser.tokens.len() {
+        return parser.error("unexpected ')'");
+    }
+    Ok(query)
+}
+
+/// Folds case and strips diacritics, so `Café` and `cafe` compare equal.
+/// Decomposing first (NFD) splits accented letters into base letter plus
+/// combining mark, and the marks are dropped.
+pub fn fold(text: &str) -> String {
+    text.nfd()
+        .filter(|c| !is_combining_mark(*c))
+        .flat_map(char::to_lowercase)
+        .collect()
+}
+
+/// A case-insensitive regex over descriptions (also tried on their folded
+/// form, so `cafe` finds `café`), used instead of the query
+/// language when the search prompt is in regex mode.
+pub fn parse_regex(pattern: &str) -> Result<Query, String> {
+    RegexBuilder::new(pattern)
+        .case_insensitive(true)
+        .build()
+        .map(Query::Regex)
+        .map_err(|e| describe_regex_error(&e))
+}
+
+/// Regex syntax errors span several lines with a caret under the problem;
+/// the last line alone says what's wrong.
+fn describe_regex_error(error: &regex::Error) -> String {
+    let text = error.to_string();
+    let last = text
+        .lines()
+        .rev()
+        .find(|l| !l.trim().is_empty())
+        .unwrap_or("");
+    last.trim().trim_start_matches("error: ").to_string()
+}
+
+impl Query {
+    pub fn matches(&self, task: &Task, today: NaiveDate) -> bool {
+        match self {
+            Query::And(a, b) => a.matches(task, today) && b.matches(task, today),
+            Query::Or(a, b) => a.matches(task, today) || b.matches(task, today),
+            Query::Not(query) => !query.matches(task, today),
+            Query::Text(text) => fold(&task.description).contains(text.as_str()),
+            Query::Regex(regex) => {
+                regex.is_match(&task.description) || regex.is_match(&fold(&task.description))
+            }
+            Query::Tag(tag) => tags::tags(&task.description).any(|t| fold(t) == fold(tag)),
+            Query::Project(project) => {
+                tags::projects(&task.description).any(|p| fold(p) == fold(project))
+            }
+            Query::Date(field, comparison, value) => {
+                let date = match field {
+                    DateField::Due => task.deadline,
+                    DateField::Created => task.created_at,
+                };
+                date.is_some_and(|d| comparison.holds(d.date().cmp(&value.resolve(today))))
+            }
+            Query::NoDate(DateField::Due) => task.deadline.is_none(),
+            Query::NoDate(DateField::Created) => task.created_at.is_none(),
+            Query::Done => task.completed,
+            Query::Open => !task.completed,
+            Query::Overdue => !task.completed && task.deadline.is_some_and(|d| d.date() < today),
+            Query::Started => task.in_progress && !task.completed,
+            Query::Someday => task.someday,
+            Query::File(name) => task.file() == name,
+            Query::Ids(ids) => ids.contains(&task.id),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::NaiveDateTime;
+
+    fn date(text: &str) -> NaiveDate {
+        NaiveDate::parse_from_str(text, "%Y-%m-%d").unwrap()
+    }
+
+    fn task(description: &str, due: Option<&str>) -> Task {
+        let deadline = due.map(|d| date(d).and_hms_opt(12, 0, 0).unwrap());
+        Task::new(1, description.to_string(), deadline)
+    }
+
+    /// The descriptions among `tasks` that `query` finds on 2026-10-16.
+    fn find(query: &str, tasks: &[Task]) -> Vec<String> {
+        find_with(query, tasks, &BTreeMap::new())
+    }
+
+    fn find_with(query: &str, tasks: &[Task], filters: &BTreeMap<String, String>) -> Vec<String> {
+        let query = parse(query, filters).unwrap();
+        (tasks.iter())
+            .filter(|t| query.matches(t, date("2026-10-16")))
+            .map(|t| t.description.clone())
+            .collect()
+    }
+
+    fn error(query: &str) -> String {
+        parse(query, &BTreeMap::new()).unwrap_err().to_string()
+    }
+
+    fn list() -> Vec<Task> {
+        let mut done = task("Pay rent #home", Some("2026-10-01"));
+        done.set_completed(true, NaiveDateTime::default());
+        vec![
+            task("Write report #work +q4", Some("2026-10-10")),
+            task("Call the café #home", Some("2026-10-17")),
+            task("Read a book", None),
+            done,
+        ]
+    }
+
+    #[test]
+    fn combines_dates_tags_and_states() {
+        let tasks = list();
+        assert_eq!(
+            find("due<2026-10-20 AND tag:home AND NOT done", &tasks),
+            ["Call the café #home"]
+        );
+        assert_eq!(find("#home OR +q4", &tasks).len(), 3);
+        assert_eq!(find("overdue", &tasks), ["Write report #work +q4"]);
+        assert_eq!(find("due:none", &tasks), ["Read a book"]);
+        assert_eq!(find("due:tomorrow", &tasks), ["Call the café #home"]);
+        assert_eq!(find("due:any open", &tasks).len(), 2);
+    }
+
+    #[test]
+    fn and_binds_tighter_than_or() {
+        let tasks = list();
+        assert_eq!(find("book OR report AND done", &tasks), ["Read a book"]);
+        assert!(find("(book OR report) AND done", &tasks).is_empty());
+    }
+
+    #[test]
+    fn text_ignores_case_and_accents() {
+        let tasks = list();
+        assert_eq!(find("CAFE", &tasks), ["Call the café #home"]);
+        assert_eq!(find("\"a book\"", &tasks), ["Read a book"]);
+        assert_eq!(fold("Crème Brûlée"), "creme brulee");
+    }
+
+    #[test]
+    fn expands_saved_filters() {
+        let tasks = list();
+        let mut filters = BTreeMap::new();
+        filters.insert("chores".to_string(), "#home open".to_string());
+        filters.insert("loop".to_string(), "filter:loop".to_string());
+        assert_eq!(
+            find_with("filter:chores", &tasks, &filters),
+            ["Call the café #home"]
+        );
+        let error = parse("filter:loop", &filters).unwrap_err();
+        assert!(error.message.contains("refers to itself"), "{}", error);
+    }
+
+    #[test]
+    fn errors_point_at_the_column() {
+        assert_eq!(error("report AND"), "expected a search term (column 11)");
+        assert_eq!(error("(report OR book"), "unclosed parenthesis (column 1)");
+        assert_eq!(error("book \"two"), "unclosed quote (column 6)");
+        assert_eq!(error("book)"), "unexpected ')' (column 5)");
+        assert_eq!(
+            error("due<soon"),
+            "'soon' is not a date; use YYYY-MM-DD, today or tomorrow (column 1)"
+        );
+        assert_eq!(
+            error("tag>work"),
+            "tag only supports ':', as in tag:work (column 1)"
+        );
+        assert!(error("colour:red").starts_with("unknown field 'colour'"));
+    }
+
+    #[test]
+    fn regex_errors_keep_the_last_line() {
+        let tasks = list();
+        let query = parse_regex("^(call|read)").unwrap();
+        assert_eq!(
+            (tasks.iter())
+                .filter(|t| query.matches(t, date("2026-10-16")))
+                .count(),
+            2
+        );
+        assert_eq!(parse_regex("(unclosed").unwrap_err(), "unclosed group");
+    }
+}
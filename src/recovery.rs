@@ -0,0 +1,74 @@
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+use crate::clock;
+use crate::Task;
+
+/// Tasks that couldn't be saved, e.g. on a read-only or full disk, kept
+/// outside the data directory until the next launch offers them back.
+#[derive(Serialize, Deserialize)]
+pub struct Recovery {
+    /// The data directory the tasks belong to.
+    pub data_dir: String,
+    /// When saving failed, as `YYYY-MM-DD HH:MM:SS`.
+    pub saved_at: String,
+    pub error: String,
+    pub tasks: Vec<Task>,
+}
+
+/// Where recovery files go, most preferred first; the data directory
+/// itself is left out as the place that just failed.
+fn dirs() -> Vec<PathBuf> {
+    let mut dirs: Vec<PathBuf> = dirs::data_local_dir()
+        .map(|dir| dir.join("todo-rs").join("recovery"))
+        .into_iter()
+        .collect();
+    dirs.push(std::env::temp_dir().join("todo-rs-recovery"));
+    dirs
+}
+
+/// One file per data directory, so two lists can't overwrite each
+/// other's unsaved changes.
+fn file_name(data_dir: &Path) -> String {
+    let digest = Sha256::digest(data_dir.to_string_lossy().as_bytes());
+    let hash: String = digest
+        .iter()
+        .take(6)
+        .map(|b| format!("{:02x}", b))
+        .collect();
+    format!("{}.json", hash)
+}
+
+/// Writes `tasks` to the first recovery folder that takes them, returning
+/// the file.
+pub fn write(data_dir: &Path, tasks: &[Task], error: &str) -> Result<PathBuf, io::Error> {
+    let recovery = Recovery {
+        data_dir: data_dir.to_string_lossy().into_owned(),
+        saved_at: clock::now().format("%Y-%m-%d %H:%M:%S").to_string(),
+        error: error.to_string(),
+        tasks: tasks.to_vec(),
+    };
+    let text = serde_json::to_string(&recovery)?;
+    let mut last_error = io::Error::new(io::ErrorKind::NotFound, "no folder to write to");
+    for dir in dirs() {
+        let path = dir.join(file_name(data_dir));
+        match fs::create_dir_all(&dir).and_then(|_| fs::write(&path, &text)) {
+            Ok(()) => return Ok(path),
+            Err(e) => last_error = e,
+        }
+    }
+    Err(last_error)
+}
+
+/// The unsaved tasks an earlier run left for `data_dir`, and their file.
+pub fn find(data_dir: &Path) -> Option<(PathBuf, Recovery)> {
+    dirs().into_iter().find_map(|dir| {
+        let path = dir.join(file_name(data_dir));
+        let text = fs::read_to_string(&path).ok()?;
+        let recovery: Recovery = serde_json::from_str(&text).ok()?;
+        (Path::new(&recovery.data_dir) == data_dir).then_some((path, recovery))
+    })
+}
@@ -0,0 +1,151 @@
+use chrono::{Months, NaiveDate};
+
+use crate::rrule::Rule;
+use crate::Task;
+
+/// How far ahead repeating tasks are projected in the agenda.
+pub const PROJECTION_DAYS: i64 = 90;
+
+/// A repeat interval, written into a description as a `*interval` word:
+/// `*daily`, `*weekly`, `*monthly`, `*yearly`, or `*3d`, `*2w`, `*6m`.
+/// An RRULE such as `*FREQ=WEEKLY;BYDAY=MO,WE,FR` works too, and can skip
+/// dates with EXDATE.
+#[derive(Clone, PartialEq, Debug)]
+pub enum Recurrence {
+    Days(u32),
+    Weeks(u32),
+    Months(u32),
+    Rule(Rule),
+}
+
+impl Recurrence {
+    pub fn parse(text: &str) -> Option<Recurrence> {
+        let recurrence = match text {
+            "daily" => Recurrence::Days(1),
+            "weekly" => Recurrence::Weeks(1),
+            "monthly" => Recurrence::Months(1),
+            "yearly" => Recurrence::Months(12),
+            _ if text.contains('=') => return Rule::parse(text).ok().map(Recurrence::Rule),
+            _ => {
+                let unit = text.chars().last()?;
+                let count: u32 = text[..text.len() - unit.len_utf8()].parse().ok()?;
+                match unit {
+                    _ if count == 0 => return None,
+                    'd' => Recurrence::Days(count),
+                    'w' => Recurrence::Weeks(count),
+                    'm' => Recurrence::Months(count),
+                    _ => return None,
+                }
+            }
+        };
+        Some(recurrence)
+    }
+
+    pub fn from_description(description: &str) -> Option<Recurrence> {
+        description
+            .split_whitespace()
+            .find_map(|word| word.strip_prefix('*').and_then(Recurrence::parse))
+    }
+
+    pub fn next(&self, date: NaiveDate) -> Option<NaiveDate> {
+        match self {
+            Recurrence::Days(n) => date.checked_add_days(chrono::Days::new(*n as u64)),
+            Recurrence::Weeks(n) => date.checked_add_days(chrono::Days::new(7 * *n as u64)),
+            Recurrence::Months(n) => date.checked_add_months(Months::new(*n)),
+            Recurrence::Rule(rule) => rule.next(date),
+        }
+    }
+
+    /// Whether `date` is left out of the series, though `next` lands on it.
+    pub fn excludes(&self, date: NaiveDate) -> bool {
+        match self {
+            Recurrence::Rule(rule) => rule.excludes(date),
+            _ => false,
+        }
+    }
+
+    pub fn describe(&self) -> String {
+        match self {
+            Recurrence::Days(1) => "every day".to_string(),
+            Recurrence::Weeks(1) => "every week".to_string(),
+            Recurrence::Months(1) => "every month".to_string(),
+            Recurrence::Months(12) => "every year".to_string(),
+            Recurrence::Days(n) => format!("every {} days", n),
+            Recurrence::Weeks(n) => format!("every {} weeks", n),
+            Recurrence::Months(n) => format!("every {} months", n),
+            Recurrence::Rule(rule) => rule.describe(),
+        }
+    }
+}
+
+/// Why an RRULE word in the description was not understood, if one wasn't.
+pub fn rule_error(description: &str) -> Option<String> {
+    description
+        .split_whitespace()
+        .filter_map(|word| word.strip_prefix('*'))
+        .filter(|word| word.contains('='))
+        .find_map(|word| Rule::parse(word).err())
+}
+
+/// Dates from `from` to `until` an open repeating task will fall on after
+/// its current deadline, leaving out its exception dates.
+pub fn occurrences(task: &Task, from: NaiveDate, until: NaiveDate) -> Vec<NaiveDate> {
+    let (Some(recurrence), Some(deadline)) = (
+        Recurrence::from_description(&task.description),
+        task.deadline,
+    ) else {
+        return Vec::new();
+    };
+    if task.completed {
+        return Vec::new();
+    }
+    // A rule's COUNT includes the current deadline.
+    let remaining = match &recurrence {
+        Recurrence::Rule(rule) => rule.count.map_or(u32::MAX, |n| n - 1),
+        _ => u32::MAX,
+    };
+    let mut dates = Vec::new();
+    let mut date = deadline.date();
+    for _ in 0..remaining {
+        let Some(next) = recurrence.next(date).filter(|d| *d <= until) else {
+            break;
+        };
+        if next >= from && !recurrence.excludes(next) {
+            dates.push(next);
+        }
+        date = next;
+    }
+    dates
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn date(text: &str) -> NaiveDate {
+        NaiveDate::parse_from_str(text, "%Y-%m-%d").unwrap()
+    }
+
+    fn dates(description: &str, deadline: &str) -> Vec<NaiveDate> {
+        let deadline = date(deadline).and_hms_opt(0, 0, 0);
+        let task = Task::new(1, description.to_string(), deadline);
+        occurrences(&task, date("2026-01-01"), date("2027-12-31"))
+    }
+
+    #[test]
+    fn count_includes_the_current_deadline() {
+        assert_eq!(
+            dates("Standup *FREQ=DAILY;COUNT=3", "2026-10-16"),
+            [date("2026-10-17"), date("2026-10-18")]
+        );
+        assert_eq!(dates("Once *FREQ=DAILY;COUNT=1", "2026-10-16"), []);
+    }
+
+    #[test]
+    fn excluded_dates_count_towards_count() {
+        assert_eq!(
+            dates("Walk *FREQ=DAILY;COUNT=4;EXDATE=20261018", "2026-10-16"),
+            [date("2026-10-17"), date("2026-10-19")]
+        );
+    }
+}
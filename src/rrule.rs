@@ -0,0 +1,618 @@
+use chrono::{Datelike, Duration, NaiveDate, Weekday};
+
+/// Longest gap searched for the next occurrence; enough for a rule like
+/// every Feb 29 to find its next leap year.
+const SEARCH_DAYS: i64 = 366 * 9;
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+enum Frequency {
+    Daily,
+    Weekly,
+    Monthly,
+    Yearly,
+}
+
+/// An RFC 5545 recurrence rule such as `FREQ=WEEKLY;BYDAY=MO,WE,FR`,
+/// evaluated on dates. Supports FREQ, INTERVAL, BYDAY (with ordinals like
+/// `-1FR`), BYMONTHDAY, BYMONTH, WKST, UNTIL and COUNT, and EXDATE for
+/// dates the series skips, e.g. `EXDATE=20261225,20261228/20270101`.
+#[derive(Clone, PartialEq, Debug)]
+pub struct Rule {
+    frequency: Frequency,
+    interval: u32,
+    by_day: Vec<(Option<i32>, Weekday)>,
+    by_month_day: Vec<i32>,
+    by_month: Vec<u32>,
+    week_start: Weekday,
+    until: Option<NaiveDate>,
+    /// Excluded dates, as inclusive ranges; a single date is a range of one.
+    exceptions: Vec<(NaiveDate, NaiveDate)>,
+    /// Occurrences in all, counting the task's current deadline.
+    pub count: Option<u32>,
+}
+
+fn parse_weekday(text: &str) -> Result<Weekday, String> {
+    let weekday = match text {
+        "MO" => Weekday::Mon,
+        "TU" => Weekday::Tue,
+        "WE" => Weekday::Wed,
+        "TH" => Weekday::Thu,
+        "FR" => Weekday::Fri,
+        "SA" => Weekday::Sat,
+        "SU" => Weekday::Sun,
+        _ => return Err(format!("unknown weekday '{}'", text)),
+    };
+    Ok(weekday)
+}
+
+fn parse_number(name: &str, text: &str) -> Result<i32, String> {
+    text.trim_start_matches('+')
+        .parse()
+        .map_err(|_| format!("{} expects numbers, got '{}'", name, text))
+}
+
+/// Checks a BYMONTHDAY or ordinal BYDAY value: not zero and within `max`
+/// either way.
+fn in_range(name: &str, n: i32, max: i32) -> Result<i32, String> {
+    if n == 0 || n.abs() > max {
+        return Err(format!("{} value {} is out of range", name, n));
+    }
+    Ok(n)
+}
+
+fn days_in_month(date: NaiveDate) -> u32 {
+    let first = date.with_day(1).unwrap();
+    let next = first.checked_add_months(chrono::Months::new(1)).unwrap();
+    (next - first).num_days() as u32
+}
+
+fn days_in_year(date: NaiveDate) -> u32 {
+    if date.leap_year() {
+        366
+    } else {
+        365
+    }
+}
+
+fn ordinal_suffix(n: i32) -> &'static str {
+    match (n % 10, n % 100) {
+        (1, 11) | (2, 12) | (3, 13) => "th",
+        (1, _) => "st",
+        (2, _) => "nd",
+        (3, _) => "rd",
+        _ => "th",
+    }
+}
+
+/// `1st`, `last`, `2nd-last`, ...
+fn ordinal(n: i32) -> String {
+    match n {
+        -1 => "last".to_string(),
+        n if n < 0 => format!("{}{}-last", -n, ordinal_suffix(-n)),
+        n => format!("{}{}", n, ordinal_suffix(n)),
+    }
+}
+
+const MONTHS: [&str; 12] = [
+    "Jan", "Feb", "Mar", "Apr", "May", "Jun", "Jul", "Aug", "Sep", "Oct", "Nov", "Dec",
+];
+
+impl Rule {
+    /// Parses the rule's `NAME=value` parts, with or without an `RRULE:`
+    /// prefix. Names are case-insensitive.
+    pub fn parse(text: &str) -> Result<Rule, String> {
+        if !text.is_ascii() {
+            return Err("rules are plain ASCII".to_string());
+        }
+        let text = text.to_uppercase();
+        let text = text.strip_prefix("RRULE:").unwrap_or(&text);
+        let mut rule = Rule {
+            frequency: Frequency::Daily,
+            interval: 1,
+            by_day: Vec::new(),
+            by_month_day: Vec::new(),
+            by_month: Vec::new(),
+            week_start: Weekday::Mon,
+            until: None,
+            exceptions: Vec::new(),
+            count: None,
+        };
+        let mut frequency = None;
+        for part in text.split(';').filter(|p| !p.is_empty()) {
+            let (name, value) = part
+                .split_once('=')
+                .ok_or_else(|| format!("expected NAME=value, got '{}'", part))?;
+            let values = value.split(',');
+            match name {
+                "FREQ" => {
+                    frequency = Some(match value {
+                        "DAILY" => Frequency::Daily,
+                        "WEEKLY" => Frequency::Weekly,
+                        "MONTHLY" => Frequency::Monthly,
+                        "YEARLY" => Frequency::Yearly,
+                        _ => return Err(format!("unsupported FREQ '{}'", value)),
+                    })
+                }
+                "INTERVAL" => {
+                    rule.interval = value
+                        .parse()
+                        .ok()
+                        .filter(|n| *n > 0)
+                        .ok_or_else(|| format!("invalid INTERVAL '{}'", value))?;
+                }
+                "COUNT" => {
+                    rule.count = Some(
+                        value
+                            .parse()
+                            .ok()
+                            .filter(|n| *n > 0)
+                            .ok_or_else(|| format!("invalid COUNT '{}'", value))?,
+                    );
+                }
+                "UNTIL" => {
+                    let date = value
+                        .get(..8)
+                        .and_then(|d| NaiveDate::parse_from_str(d, "%Y%m%d").ok())
+                        .ok_or_else(|| format!("invalid UNTIL '{}'", value))?;
+                    rule.until = Some(date);
+                }
+                "EXDATE" => {
+                    for range in values {
+                        let (first, last) = range.split_once('/').unwrap_or((range, range));
+                        let date = |text: &str| {
+                            (text.get(..8))
+                                .and_then(|d| NaiveDate::parse_from_str(d, "%Y%m%d").ok())
+                                .ok_or_else(|| format!("invalid EXDATE '{}'", range))
+                        };
+                        let (first, last) = (date(first)?, date(last)?);
+                        if last < first {
+                            return Err(format!("EXDATE range '{}' ends before it starts", range));
+                        }
+                        rule.exceptions.push((first, last));
+                    }
+                }
+                "WKST" => rule.week_start = parse_weekday(value)?,
+                "BYDAY" => {
+                    for day in values {
+                        let split = day.len().saturating_sub(2);
+                        let weekday = parse_weekday(&day[split..])?;
+                        let n = match &day[..split] {
+                            "" => None,
+                            n => Some(in_range("BYDAY", parse_number("BYDAY", n)?, 53)?),
+                        };
+                        rule.by_day.push((n, weekday));
+                    }
+                }
+                "BYMONTHDAY" => {
+                    for day in values {
+                        let n = parse_number("BYMONTHDAY", day)?;
+                        rule.by_month_day.push(in_range("BYMONTHDAY", n, 31)?);
+                    }
+                }
+                "BYMONTH" => {
+                    for month in values {
+                        let n = month
+                            .parse()
+                            .ok()
+                            .filter(|n| (1..=12).contains(n))
+                            .ok_or_else(|| format!("invalid BYMONTH '{}'", month))?;
+                        rule.by_month.push(n);
+                    }
+                }
+                _ => return Err(format!("unsupported rule part '{}'", name)),
+            }
+        }
+        rule.frequency = frequency.ok_or("the rule needs a FREQ")?;
+        if rule.count.is_some() && rule.until.is_some() {
+            return Err("COUNT and UNTIL can't be used together".to_string());
+        }
+        if rule.by_day.iter().any(|(n, _)| n.is_some())
+            && !matches!(rule.frequency, Frequency::Monthly | Frequency::Yearly)
+        {
+            return Err("numbered BYDAY needs FREQ=MONTHLY or YEARLY".to_string());
+        }
+        Ok(rule)
+    }
+
+    fn week_of(&self, date: NaiveDate) -> NaiveDate {
+        let offset = (date.weekday().num_days_from_monday() + 7
+            - self.week_start.num_days_from_monday())
+            % 7;
+        date - Duration::days(offset as i64)
+    }
+
+    /// Whether `date` falls in a period `INTERVAL` periods on from `anchor`'s.
+    fn in_period(&self, anchor: NaiveDate, date: NaiveDate) -> bool {
+        let periods = match self.frequency {
+            Frequency::Daily => (date - anchor).num_days(),
+            Frequency::Weekly => (self.week_of(date) - self.week_of(anchor)).num_days() / 7,
+            Frequency::Monthly => {
+                (date.year() - anchor.year()) as i64 * 12 + date.month() as i64
+                    - anchor.month() as i64
+            }
+            Frequency::Yearly => (date.year() - anchor.year()) as i64,
+        };
+        periods % self.interval as i64 == 0
+    }
+
+    fn matches_day(&self, n: Option<i32>, weekday: Weekday, date: NaiveDate) -> bool {
+        if date.weekday() != weekday {
+            return false;
+        }
+        let Some(n) = n else {
+            return true;
+        };
+        // Counted within the month, or the year for a yearly rule without BYMONTH.
+        let in_year = self.frequency == Frequency::Yearly && self.by_month.is_empty();
+        let (position, length) = if in_year {
+            (date.ordinal(), days_in_year(date))
+        } else {
+            (date.day(), days_in_month(date))
+        };
+        if n > 0 {
+            (position as i32 - 1) / 7 + 1 == n
+        } else {
+            (length as i32 - position as i32) / 7 + 1 == -n
+        }
+    }
+
+    /// Whether `date` is an occurrence of a series whose last occurrence was
+    /// `anchor`. Parts the rule leaves out are taken from `anchor`, as RFC
+    /// 5545 takes them from DTSTART.
+    fn matches(&self, anchor: NaiveDate, date: NaiveDate) -> bool {
+        if !self.in_period(anchor, date) {
+            return false;
+        }
+        if !self.by_month.is_empty() && !self.by_month.contains(&date.month()) {
+            return false;
+        }
+        if !self.by_month_day.is_empty() {
+            let length = days_in_month(date) as i32;
+            let day = date.day() as i32;
+            if !self
+                .by_month_day
+                .iter()
+                .any(|n| *n == day || *n == day - length - 1)
+            {
+                return false;
+            }
+        }
+        if !self.by_day.is_empty() {
+            return self
+                .by_day
+                .iter()
+                .any(|(n, weekday)| self.matches_day(*n, *weekday, date));
+        }
+        if !self.by_month_day.is_empty() {
+            return true;
+        }
+        match self.frequency {
+            Frequency::Daily => true,
+            Frequency::Weekly => date.weekday() == anchor.weekday(),
+            Frequency::Monthly => date.day() == anchor.day(),
+            Frequency::Yearly => {
+                date.day() == anchor.day()
+                    && (!self.by_month.is_empty() || date.month() == anchor.month())
+            }
+        }
+    }
+
+    /// The first occurrence after `date`, which should itself be one.
+    pub fn next(&self, date: NaiveDate) -> Option<NaiveDate> {
+        (1..=SEARCH_DAYS)
+            .map(|i| date + Duration::days(i))
+            .take_while(|d| self.until.is_none_or(|until| *d <= until))
+            .find(|d| self.matches(date, *d))
+    }
+
+    /// Whether EXDATE leaves `date` out of the series. It still counts
+    /// towards COUNT, as in RFC 5545.
+    pub fn excludes(&self, date: NaiveDate) -> bool {
+        (self.exceptions.iter()).any(|(first, last)| (*first..=*last).contains(&date))
+    }
+
+    pub fn describe(&self) -> String {
+        let (unit, units) = match self.frequency {
+            Frequency::Daily => ("day", "days"),
+            Frequency::Weekly => ("week", "weeks"),
+            Frequency::Monthly => ("month", "months"),
+            Frequency::Yearly => ("year", "years"),
+        };
+        let mut text = match self.interval {
+            1 => format!("every {}", unit),
+            n => format!("every {} {}", n, units),
+        };
+        if !self.by_day.is_empty() {
+            let days: Vec<String> = self
+                .by_day
+                .iter()
+                .map(|(n, weekday)| match n {
+                    Some(n) => format!("the {} {}", ordinal(*n), weekday),
+                    None => weekday.to_string(),
+                })
+                .collect();
+            text.push_str(&format!(" on {}", days.join(", ")));
+        }
+        if !self.by_month_day.is_empty() {
+            let days: Vec<String> = self.by_month_day.iter().map(|n| ordinal(*n)).collect();
+            text.push_str(&format!(" on the {} day", days.join(", ")));
+        }
+        if !self.by_month.is_empty() {
+            let months: Vec<&str> = self
+                .by_month
+                .iter()
+                .map(|m| MONTHS[*m as usize - 1])
+                .collect();
+            text.push_str(&format!(" in {}", months.join(", ")));
+        }
+        if let Some(until) = self.until {
+            text.push_str(&format!(" until {}", until.format("%Y-%m-%d")));
+        }
+        if let Some(count) = self.count {
+            text.push_str(&format!(", {} times", count));
+        }
+        if !self.exceptions.is_empty() {
+            let day = |date: &NaiveDate| date.format("%Y-%m-%d").to_string();
+            let dates: Vec<String> = (self.exceptions.iter())
+                .map(|(first, last)| match first == last {
+                    true => day(first),
+                    false => format!("{} to {}", day(first), day(last)),
+                })
+                .collect();
+            text.push_str(&format!(", except {}", dates.join(", ")));
+        }
+        text
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn date(text: &str) -> NaiveDate {
+        NaiveDate::parse_from_str(text, "%Y-%m-%d").unwrap()
+    }
+
+    /// The next `n` occurrences after `start`, each found from the one
+    /// before as a repeating task does.
+    fn series(rule: &str, start: &str, n: usize) -> Vec<String> {
+        let rule = Rule::parse(rule).unwrap();
+        let mut dates = Vec::new();
+        let mut date = date(start);
+        while dates.len() < n {
+            let Some(next) = rule.next(date) else {
+                break;
+            };
+            dates.push(next.format("%Y-%m-%d").to_string());
+            date = next;
+        }
+        dates
+    }
+
+    #[test]
+    fn daily_with_interval() {
+        assert_eq!(
+            series("FREQ=DAILY;INTERVAL=3", "2026-01-30", 4),
+            ["2026-02-02", "2026-02-05", "2026-02-08", "2026-02-11"]
+        );
+    }
+
+    #[test]
+    fn weekly_by_day() {
+        assert_eq!(
+            series("FREQ=WEEKLY;BYDAY=MO,WE,FR", "2026-10-16", 5),
+            [
+                "2026-10-19",
+                "2026-10-21",
+                "2026-10-23",
+                "2026-10-26",
+                "2026-10-28"
+            ]
+        );
+        assert_eq!(
+            series("FREQ=WEEKLY;INTERVAL=2;BYDAY=TU,TH", "2026-10-13", 5),
+            [
+                "2026-10-15",
+                "2026-10-27",
+                "2026-10-29",
+                "2026-11-10",
+                "2026-11-12"
+            ]
+        );
+    }
+
+    #[test]
+    fn week_start_decides_which_week_a_sunday_is_in() {
+        assert_eq!(
+            series(
+                "FREQ=WEEKLY;INTERVAL=2;BYDAY=SU,TU;WKST=SU",
+                "2026-10-11",
+                5
+            ),
+            [
+                "2026-10-13",
+                "2026-10-25",
+                "2026-10-27",
+                "2026-11-08",
+                "2026-11-10"
+            ]
+        );
+        assert_eq!(
+            series(
+                "FREQ=WEEKLY;INTERVAL=2;BYDAY=SU,TU;WKST=MO",
+                "2026-10-11",
+                5
+            ),
+            [
+                "2026-10-20",
+                "2026-10-25",
+                "2026-11-03",
+                "2026-11-08",
+                "2026-11-17"
+            ]
+        );
+    }
+
+    #[test]
+    fn monthly_skips_months_without_the_day() {
+        let expected = ["2026-03-31", "2026-05-31", "2026-07-31", "2026-08-31"];
+        assert_eq!(series("FREQ=MONTHLY", "2026-01-31", 4), expected);
+        assert_eq!(
+            series("FREQ=MONTHLY;BYMONTHDAY=31", "2026-01-31", 4),
+            expected
+        );
+    }
+
+    #[test]
+    fn by_month_day() {
+        assert_eq!(
+            series("FREQ=MONTHLY;BYMONTHDAY=-1", "2026-01-31", 4),
+            ["2026-02-28", "2026-03-31", "2026-04-30", "2026-05-31"]
+        );
+        assert_eq!(
+            series("FREQ=MONTHLY;BYMONTHDAY=1,15", "2026-10-15", 4),
+            ["2026-11-01", "2026-11-15", "2026-12-01", "2026-12-15"]
+        );
+    }
+
+    #[test]
+    fn numbered_by_day_in_the_month() {
+        assert_eq!(
+            series("FREQ=MONTHLY;BYDAY=-1FR", "2026-10-30", 4),
+            ["2026-11-27", "2026-12-25", "2027-01-29", "2027-02-26"]
+        );
+        assert_eq!(
+            series("FREQ=MONTHLY;BYDAY=2TU", "2026-10-13", 3),
+            ["2026-11-10", "2026-12-08", "2027-01-12"]
+        );
+        assert_eq!(
+            series("FREQ=MONTHLY;INTERVAL=3;BYDAY=1MO", "2026-10-05", 3),
+            ["2027-01-04", "2027-04-05", "2027-07-05"]
+        );
+    }
+
+    #[test]
+    fn yearly_on_leap_days() {
+        assert_eq!(
+            series("FREQ=YEARLY", "2024-02-29", 2),
+            ["2028-02-29", "2032-02-29"]
+        );
+        assert_eq!(
+            series("FREQ=YEARLY;BYMONTH=2;BYMONTHDAY=-1", "2024-02-29", 3),
+            ["2025-02-28", "2026-02-28", "2027-02-28"]
+        );
+    }
+
+    #[test]
+    fn numbered_by_day_in_the_year() {
+        assert_eq!(
+            series("FREQ=YEARLY;BYDAY=1MO", "2026-01-05", 3),
+            ["2027-01-04", "2028-01-03", "2029-01-01"]
+        );
+        assert_eq!(
+            series("FREQ=YEARLY;BYDAY=-1SU", "2026-12-27", 2),
+            ["2027-12-26", "2028-12-31"]
+        );
+        // Thanksgiving: counted within November once BYMONTH is given.
+        assert_eq!(
+            series("FREQ=YEARLY;BYMONTH=11;BYDAY=4TH", "2026-11-26", 3),
+            ["2027-11-25", "2028-11-23", "2029-11-22"]
+        );
+    }
+
+    #[test]
+    fn yearly_by_month() {
+        assert_eq!(
+            series("FREQ=YEARLY;BYMONTH=3,9", "2026-03-15", 4),
+            ["2026-09-15", "2027-03-15", "2027-09-15", "2028-03-15"]
+        );
+    }
+
+    #[test]
+    fn until_is_inclusive() {
+        assert_eq!(
+            series("FREQ=DAILY;UNTIL=20261020", "2026-10-17", 10),
+            ["2026-10-18", "2026-10-19", "2026-10-20"]
+        );
+        assert_eq!(
+            series(
+                "RRULE:FREQ=WEEKLY;BYDAY=MO;UNTIL=20261102T000000Z",
+                "2026-10-19",
+                10
+            ),
+            ["2026-10-26", "2026-11-02"]
+        );
+    }
+
+    #[test]
+    fn exdate_leaves_out_dates_and_ranges() {
+        let rule = Rule::parse("FREQ=DAILY;EXDATE=20261225,20261228/20270101").unwrap();
+        let excluded = |text: &str| rule.excludes(date(text));
+        assert!(excluded("2026-12-25"));
+        assert!(!excluded("2026-12-26"));
+        assert!(excluded("2026-12-28"));
+        assert!(excluded("2027-01-01"));
+        assert!(!excluded("2027-01-02"));
+        // Excluded dates are still occurrences, for COUNT.
+        assert_eq!(rule.next(date("2026-12-24")), Some(date("2026-12-25")));
+    }
+
+    #[test]
+    fn parses_count_and_lowercase() {
+        let rule = Rule::parse("rrule:freq=weekly;count=5").unwrap();
+        assert_eq!(rule.count, Some(5));
+        assert_eq!(rule.describe(), "every week, 5 times");
+    }
+
+    #[test]
+    fn rejects_bad_rules() {
+        let error = |text: &str| Rule::parse(text).unwrap_err();
+        assert_eq!(error("INTERVAL=2"), "the rule needs a FREQ");
+        assert_eq!(error("FREQ=HOURLY"), "unsupported FREQ 'HOURLY'");
+        assert_eq!(error("FREQ=DAILY;INTERVAL=0"), "invalid INTERVAL '0'");
+        assert_eq!(error("FREQ=DAILY;COUNT=0"), "invalid COUNT '0'");
+        assert_eq!(
+            error("FREQ=DAILY;COUNT=2;UNTIL=20270101"),
+            "COUNT and UNTIL can't be used together"
+        );
+        assert_eq!(
+            error("FREQ=WEEKLY;BYDAY=1MO"),
+            "numbered BYDAY needs FREQ=MONTHLY or YEARLY"
+        );
+        assert_eq!(
+            error("FREQ=MONTHLY;BYMONTHDAY=0"),
+            "BYMONTHDAY value 0 is out of range"
+        );
+        assert_eq!(
+            error("FREQ=MONTHLY;BYMONTHDAY=32"),
+            "BYMONTHDAY value 32 is out of range"
+        );
+        assert_eq!(error("FREQ=YEARLY;BYMONTH=13"), "invalid BYMONTH '13'");
+        assert_eq!(error("FREQ=WEEKLY;BYDAY=XX"), "unknown weekday 'XX'");
+        assert_eq!(
+            error("FREQ=DAILY;EXDATE=20270102/20270101"),
+            "EXDATE range '20270102/20270101' ends before it starts"
+        );
+        assert_eq!(
+            error("FREQ=DAILY;BYHOUR=9"),
+            "unsupported rule part 'BYHOUR'"
+        );
+    }
+
+    #[test]
+    fn describes_rules() {
+        let describe = |text: &str| Rule::parse(text).unwrap().describe();
+        assert_eq!(
+            describe("FREQ=MONTHLY;INTERVAL=2;BYDAY=-2FR,1MO"),
+            "every 2 months on the 2nd-last Fri, the 1st Mon"
+        );
+        assert_eq!(
+            describe("FREQ=YEARLY;BYMONTH=2;BYMONTHDAY=-1;UNTIL=20300101"),
+            "every year on the last day in Feb until 2030-01-01"
+        );
+        assert_eq!(
+            describe("FREQ=DAILY;EXDATE=20261225,20261228/20270101"),
+            "every day, except 2026-12-25, 2026-12-28 to 2027-01-01"
+        );
+    }
+}
@@ -0,0 +1,106 @@
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+
+use crate::tags;
+
+/// Classifies tasks as they're added, as a `[[rules]]` table: a task whose
+/// description matches `match` gets the rule's tags and project, e.g.
+/// `match = 'PR-\d+'` with `tags = ["code-review"]`.
+#[derive(Serialize, Deserialize, Clone)]
+pub struct RuleConfig {
+    #[serde(rename = "match")]
+    pub pattern: String,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub tags: Vec<String>,
+    /// Added as a tag too, e.g. `high` for `#high`, as priorities are
+    /// written here.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub priority: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub project: Option<String>,
+}
+
+struct Rule {
+    regex: Regex,
+    /// The words the rule adds, with their `#` or `+`.
+    words: Vec<String>,
+}
+
+/// The configured rules, compiled.
+pub struct Rules {
+    rules: Vec<Rule>,
+}
+
+impl Rules {
+    pub fn from_config(config: &[RuleConfig]) -> Result<Rules, String> {
+        let rules = (config.iter())
+            .map(|rule| {
+                let regex = Regex::new(&rule.pattern)
+                    .map_err(|_| format!("rules: invalid match '{}'", rule.pattern))?;
+                let tags = rule.tags.iter().chain(&rule.priority);
+                let mut words: Vec<String> = tags.map(|tag| format!("#{}", tag)).collect();
+                words.extend(rule.project.iter().map(|project| format!("+{}", project)));
+                if words.is_empty() {
+                    return Err(format!("rules: '{}' adds nothing", rule.pattern));
+                }
+                Ok(Rule { regex, words })
+            })
+            .collect::<Result<_, String>>()?;
+        Ok(Rules { rules })
+    }
+
+    /// `description` with the tags and projects of every rule it matches,
+    /// leaving out those it already has.
+    pub fn apply(&self, description: &str) -> String {
+        let mut result = description.to_string();
+        for rule in self.rules.iter().filter(|r| r.regex.is_match(description)) {
+            for word in &rule.words {
+                let (prefix, name) = word.split_at(1);
+                let same = |w: &str| w.eq_ignore_ascii_case(name);
+                let present = match prefix {
+                    "#" => tags::tags(&result).any(same),
+                    _ => tags::projects(&result).any(same),
+                };
+                if !present {
+                    result = format!("{} {}", result, word);
+                }
+            }
+        }
+        result
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn rule(pattern: &str, tags: &[&str], project: Option<&str>) -> RuleConfig {
+        RuleConfig {
+            pattern: pattern.to_string(),
+            tags: tags.iter().map(|tag| tag.to_string()).collect(),
+            priority: None,
+            project: project.map(str::to_string),
+        }
+    }
+
+    #[test]
+    fn adds_what_matching_rules_add_once() {
+        let mut urgent = rule("(?i)asap", &[], None);
+        urgent.priority = Some("high".to_string());
+        let rules =
+            Rules::from_config(&[rule(r"PR-\d+", &["code-review"], Some("work")), urgent]).unwrap();
+        assert_eq!(
+            rules.apply("Look at PR-12 ASAP"),
+            "Look at PR-12 ASAP #code-review +work #high"
+        );
+        assert_eq!(rules.apply("PR-7 #Code-Review"), "PR-7 #Code-Review +work");
+        assert_eq!(rules.apply("Water plants"), "Water plants");
+    }
+
+    #[test]
+    fn rejects_rules_that_cannot_apply() {
+        let error = |config| Rules::from_config(&[config]).err().unwrap();
+        assert_eq!(error(rule("(", &["x"], None)), "rules: invalid match '('");
+        assert_eq!(error(rule("x", &[], None)), "rules: 'x' adds nothing");
+    }
+}
@@ -0,0 +1,56 @@
+use chrono::NaiveDate;
+use serde::{Deserialize, Serialize};
+
+use crate::recurrence::Recurrence;
+
+/// A task added on its own on a schedule, as `[[scheduled]]` tables, e.g.
+/// `Pay rent` from `2026-11-01`, repeating `monthly`.
+#[derive(Serialize, Deserialize, Clone)]
+pub struct Scheduled {
+    pub description: String,
+    /// First date the task is added on, as `YYYY-MM-DD`. Each date it is
+    /// added on is also its deadline.
+    pub start: String,
+    /// How often after that, as in a `*interval` word: `monthly`, `2w`, or
+    /// an RRULE such as `FREQ=MONTHLY;BYMONTHDAY=1`.
+    pub repeat: String,
+}
+
+impl Scheduled {
+    pub fn validate(&self) -> Result<(), String> {
+        if self.description.trim().is_empty() {
+            return Err("description is empty".to_string());
+        }
+        if NaiveDate::parse_from_str(&self.start, "%Y-%m-%d").is_err() {
+            return Err(format!(
+                "invalid start '{}', expected YYYY-MM-DD",
+                self.start
+            ));
+        }
+        if Recurrence::parse(&self.repeat).is_none() {
+            return Err(format!(
+                "unknown repeat '{}', expected e.g. monthly, 2w or an RRULE",
+                self.repeat
+            ));
+        }
+        Ok(())
+    }
+
+    /// The latest date the task falls on, up to and including `today`;
+    /// `None` before `start`, or if every date so far is an exception.
+    pub fn latest(&self, today: NaiveDate) -> Option<NaiveDate> {
+        let mut date = NaiveDate::parse_from_str(&self.start, "%Y-%m-%d").ok()?;
+        let recurrence = Recurrence::parse(&self.repeat)?;
+        if date > today {
+            return None;
+        }
+        let mut latest = Some(date).filter(|d| !recurrence.excludes(*d));
+        while let Some(next) = recurrence.next(date).filter(|d| *d <= today) {
+            date = next;
+            if !recurrence.excludes(date) {
+                latest = Some(date);
+            }
+        }
+        latest
+    }
+}
@@ -0,0 +1,69 @@
+use std::collections::BTreeMap;
+
+use crate::clock;
+use crate::query::{self, Query};
+use crate::tabs;
+use crate::tags;
+use crate::{AppState, Task};
+
+/// One project (`+name`) or workspace file (`file:name`) that counts, the
+/// agenda, search and notifications are narrowed to.
+pub struct Scope {
+    pub label: String,
+    query: Query,
+}
+
+impl Scope {
+    pub fn parse(label: &str) -> Option<Scope> {
+        let query = query::parse(label, &BTreeMap::new()).ok()?;
+        Some(Scope {
+            label: label.to_string(),
+            query,
+        })
+    }
+
+    pub fn includes(&self, task: &Task) -> bool {
+        self.query.matches(task, clock::today())
+    }
+}
+
+/// What "current project" means right now: the project or file tab shown,
+/// else the selected task's first project, else its workspace file.
+fn current(app_state: &AppState) -> Option<String> {
+    let tabs = tabs::tabs(app_state);
+    let tab = tabs.get(app_state.tab).and_then(|tab| tab.query.as_ref());
+    if let Some(query) = tab.filter(|q| q.starts_with('+') || q.starts_with("file:")) {
+        return Some(query.clone());
+    }
+    let task = (app_state.selected_task).and_then(|i| app_state.tasks.get(i))?;
+    if let Some(project) = tags::projects(&task.description).next() {
+        return Some(format!("+{}", project));
+    }
+    let source = task.source.as_ref()?;
+    Some(format!("file:{}", source.name))
+}
+
+/// Narrows to the current project, or widens back to all of them.
+pub fn toggle(app_state: &mut AppState) {
+    if app_state.scope.take().is_none() {
+        match current(app_state).as_deref().and_then(Scope::parse) {
+            Some(scope) => app_state.scope = Some(scope),
+            None => {
+                let text = "Pick a project tab or a task in a project first".to_string();
+                app_state.set_status(text, true);
+                return;
+            }
+        }
+    }
+    app_state.session.scope = app_state.scope.as_ref().map(|s| s.label.clone());
+    app_state.save_session();
+    app_state.set_status(format!("Scope: {}", label(app_state)), false);
+}
+
+/// The scope for titles: the project or file, or "all projects".
+pub fn label(app_state: &AppState) -> &str {
+    app_state
+        .scope
+        .as_ref()
+        .map_or("all projects", |scope| scope.label.as_str())
+}
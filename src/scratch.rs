@@ -0,0 +1,101 @@
+use tui::{
+    backend::Backend,
+    layout::Rect,
+    style::{Color, Style},
+    text::{Span, Spans},
+    widgets::{Block, Borders, List, ListItem},
+    Frame,
+};
+
+use crate::{AppState, Mode};
+
+/// A throwaway task that only lives until the app exits.
+pub struct ScratchItem {
+    pub description: String,
+    pub done: bool,
+}
+
+/// Session-only task list, e.g. action items jotted down during a meeting.
+/// Nothing here is saved or journaled until it is promoted.
+#[derive(Default)]
+pub struct Scratch {
+    pub items: Vec<ScratchItem>,
+    pub selected: usize,
+    /// The input line is collecting a new item.
+    pub adding: bool,
+}
+
+impl Scratch {
+    pub fn add(&mut self, description: String) {
+        self.items.push(ScratchItem {
+            description,
+            done: false,
+        });
+        self.selected = self.items.len() - 1;
+    }
+
+    pub fn toggle(&mut self) {
+        if let Some(item) = self.items.get_mut(self.selected) {
+            item.done = !item.done;
+        }
+    }
+
+    /// Takes the selected item out of the scratch list.
+    pub fn remove(&mut self) -> Option<ScratchItem> {
+        if self.selected >= self.items.len() {
+            return None;
+        }
+        let item = self.items.remove(self.selected);
+        self.selected = self.selected.min(self.items.len().saturating_sub(1));
+        Some(item)
+    }
+
+    pub fn move_selection(&mut self, up: bool) {
+        self.selected = if up {
+            self.selected.saturating_sub(1)
+        } else {
+            (self.selected + 1).min(self.items.len().saturating_sub(1))
+        };
+    }
+
+    /// Rows the pane needs, borders included; kept small so the task list
+    /// stays the main view.
+    pub fn height(&self) -> u16 {
+        (self.items.len().max(1) as u16 + 2).min(10)
+    }
+}
+
+pub fn render_scratch<B: Backend>(f: &mut Frame<B>, app_state: &AppState, chunk: Rect) {
+    let scratch = &app_state.scratch;
+    let focused = app_state.mode == Mode::Scratch;
+    let items: Vec<ListItem> = scratch
+        .items
+        .iter()
+        .enumerate()
+        .map(|(i, item)| {
+            let style = if focused && i == scratch.selected {
+                app_state.styles.selected
+            } else if item.done {
+                app_state.styles.completed
+            } else {
+                Style::default()
+            };
+            let marker = if item.done { "[x] " } else { "[ ] " };
+            ListItem::new(Spans::from(vec![
+                Span::styled(marker, Style::default().fg(Color::DarkGray)),
+                Span::styled(item.description.as_str(), style),
+            ]))
+        })
+        .collect();
+
+    let border = if focused {
+        app_state.styles.selected
+    } else {
+        Style::default().fg(Color::DarkGray)
+    };
+    let block = Block::default()
+        .borders(Borders::ALL)
+        .border_style(border)
+        .title("Scratch (not saved)");
+    f.render_widget(List::new(items).block(block), chunk);
+}
@@ -0,0 +1,79 @@
+use crate::keymap::Action;
+use crate::tasks::SortKey;
+use crate::{AppState, Mode};
+
+/// One step of a startup script.
+#[derive(Clone, Debug)]
+pub enum Command {
+    /// Applies a search query, like `/`; empty clears the filter.
+    Filter(String),
+    Sort(SortKey),
+    /// Opens a view, through the action that opens it; `None` is the list.
+    View(Option<Action>),
+}
+
+const COMMANDS: &str = "filter, sort, view";
+const VIEWS: &str = "list, agenda, board, heatmap, scratch, zoom";
+
+/// Parses `;`-separated commands, such as
+/// `filter #work; sort due; view agenda`.
+pub fn parse(text: &str) -> Result<Vec<Command>, String> {
+    let mut commands = Vec::new();
+    for part in text.split(';').map(str::trim).filter(|p| !p.is_empty()) {
+        let (name, argument) = part.split_once(char::is_whitespace).unwrap_or((part, ""));
+        let argument = argument.trim();
+        let command = match name {
+            "filter" => Command::Filter(argument.to_string()),
+            "sort" => Command::Sort(SortKey::parse(argument)?),
+            "view" => Command::View(match argument {
+                "list" => None,
+                "agenda" => Some(Action::ShowAgenda),
+                "board" | "kanban" => Some(Action::ShowKanban),
+                "heatmap" => Some(Action::ShowHeatmap),
+                "scratch" => Some(Action::ShowScratch),
+                "zoom" => Some(Action::Zoom),
+                _ => {
+                    return Err(format!(
+                        "unknown view '{}', expected one of {}",
+                        argument, VIEWS
+                    ))
+                }
+            }),
+            _ => {
+                return Err(format!(
+                    "unknown command '{}', expected one of {}",
+                    name, COMMANDS
+                ))
+            }
+        };
+        commands.push(command);
+    }
+    Ok(commands)
+}
+
+/// Runs the commands in order, stopping at the first that fails.
+pub fn run(app_state: &mut AppState, commands: &[Command]) -> Result<(), String> {
+    for command in commands {
+        match command {
+            Command::Filter(query) => {
+                app_state.search_regex = false;
+                app_state
+                    .apply_search(query)
+                    .map_err(|e| format!("filter {}: {}", query, e))?;
+            }
+            Command::Sort(key) => {
+                app_state.sort = *key;
+                app_state.sort_descending = false;
+                app_state.selected_task = app_state.visible_tasks().first().copied();
+            }
+            Command::View(action) => {
+                app_state.mode = Mode::Normal;
+                if let Some(action) = action {
+                    let key = app_state.keymap.key_for(*action);
+                    crate::process_key_event(key, app_state);
+                }
+            }
+        }
+    }
+    Ok(())
+}
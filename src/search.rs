@@ -0,0 +1,229 @@
+use std::collections::HashSet;
+use std::path::Path;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{mpsc, Arc, OnceLock};
+use std::thread;
+use tui::{
+    backend::Backend,
+    layout::Rect,
+    style::{Color, Modifier, Style},
+    text::{Span, Spans},
+    widgets::{Block, Borders, Paragraph},
+    Frame,
+};
+
+use crate::archive;
+use crate::clock;
+use crate::query::{self, Query};
+use crate::{AppState, Mode, Task};
+
+/// Tasks searched between two reports to the screen.
+const BATCH: usize = 500;
+
+/// A task the search prompt's query lets through.
+pub struct Hit {
+    pub task: Task,
+    pub archived: bool,
+}
+
+/// What a search thread reports after each batch.
+struct Progress {
+    hits: Vec<Hit>,
+    searched: usize,
+    total: usize,
+}
+
+/// The results of the query being typed, searched for on a thread of their
+/// own over the list and the archive, so a big archive never holds up
+/// typing or drawing. They come in batch by batch on the tick.
+pub struct Search {
+    /// The list as the prompt opened, shared with every search thread.
+    list: Arc<Vec<Task>>,
+    /// Read once, by the first search to need it.
+    archive: Arc<OnceLock<Vec<Task>>>,
+    rx: Option<mpsc::Receiver<Progress>>,
+    /// Tells the thread for the previous query to give up.
+    cancel: Arc<AtomicBool>,
+    hits: Vec<Hit>,
+    searched: usize,
+    total: usize,
+    /// Every task has been looked at.
+    done: bool,
+    error: Option<String>,
+}
+
+impl Drop for Search {
+    fn drop(&mut self) {
+        self.cancel.store(true, Ordering::SeqCst);
+    }
+}
+
+/// Parses the search prompt's text the way its syntax is set, returning
+/// the label the filter shows with the query.
+pub fn parse(app_state: &AppState, text: &str) -> Result<(String, Query), String> {
+    if app_state.search_regex {
+        return Ok((format!("/{}/", text), query::parse_regex(text)?));
+    }
+    let query = query::parse(text, &app_state.config.filters).map_err(|e| e.to_string())?;
+    Ok((text.to_string(), query))
+}
+
+/// Opens the search prompt on the current filter, with its results.
+pub fn open(app_state: &mut AppState) {
+    app_state.input = (app_state.filter.as_ref()).map_or(String::new(), |(text, _)| text.clone());
+    app_state.mode = Mode::Search;
+    app_state.search = Some(Search {
+        list: Arc::new(app_state.tasks.clone()),
+        archive: Arc::new(OnceLock::new()),
+        rx: None,
+        cancel: Arc::new(AtomicBool::new(false)),
+        hits: Vec::new(),
+        searched: 0,
+        total: 0,
+        done: false,
+        error: None,
+    });
+    restart(app_state);
+}
+
+/// Starts over for the text now in the prompt, calling off the search
+/// for what it held before.
+pub fn restart(app_state: &mut AppState) {
+    let text = app_state.input.clone();
+    let parsed = (!text.trim().is_empty()).then(|| parse(app_state, &text));
+    let data_dir = app_state.data_dir.clone();
+    let Some(search) = app_state.search.as_mut() else {
+        return;
+    };
+    search.cancel.store(true, Ordering::SeqCst);
+    search.cancel = Arc::new(AtomicBool::new(false));
+    search.rx = None;
+    search.hits.clear();
+    search.searched = 0;
+    search.total = 0;
+    search.done = false;
+    search.error = None;
+    let query = match parsed {
+        None => return,
+        Some(Ok((_, query))) => query,
+        Some(Err(e)) => {
+            search.error = Some(e);
+            return;
+        }
+    };
+    let (tx, rx) = mpsc::channel();
+    search.rx = Some(rx);
+    let (list, archive) = (search.list.clone(), search.archive.clone());
+    let cancel = search.cancel.clone();
+    thread::spawn(move || run(query, &list, &archive, &data_dir, &cancel, tx));
+}
+
+fn run(
+    query: Query,
+    list: &[Task],
+    archive: &OnceLock<Vec<Task>>,
+    data_dir: &Path,
+    cancel: &AtomicBool,
+    tx: mpsc::Sender<Progress>,
+) {
+    let archive = archive.get_or_init(|| archive::load(data_dir).unwrap_or_default());
+    // Completed tasks can be in both until the next save.
+    let listed: HashSet<u64> = list.iter().map(|t| t.id).collect();
+    let archived = archive.iter().filter(|t| !listed.contains(&t.id));
+    let total = list.len() + archived.clone().count();
+    let tasks = (list.iter().map(|t| (t, false))).chain(archived.map(|t| (t, true)));
+    let today = clock::today();
+    let mut hits = Vec::new();
+    for (searched, (task, archived)) in tasks.enumerate() {
+        if cancel.load(Ordering::SeqCst) {
+            return;
+        }
+        if query.matches(task, today) {
+            hits.push(Hit {
+                task: task.clone(),
+                archived,
+            });
+        }
+        if (searched + 1) % BATCH == 0 {
+            let hits = std::mem::take(&mut hits);
+            let progress = Progress {
+                hits,
+                searched: searched + 1,
+                total,
+            };
+            if tx.send(progress).is_err() {
+                return;
+            }
+        }
+    }
+    let _ = tx.send(Progress {
+        hits,
+        searched: total,
+        total,
+    });
+}
+
+/// Takes in what the search thread has found since the last call,
+/// returning whether anything came.
+pub fn poll(app_state: &mut AppState) -> bool {
+    let Some(search) = app_state.search.as_ref() else {
+        return false;
+    };
+    let Some(rx) = &search.rx else {
+        return false;
+    };
+    let batches: Vec<Progress> = rx.try_iter().collect();
+    let Some(last) = batches.last().map(|p| (p.searched, p.total)) else {
+        return false;
+    };
+    // Narrowed here rather than on the thread, which can't see the scope.
+    let hits: Vec<Hit> = (batches.into_iter())
+        .flat_map(|progress| progress.hits)
+        .filter(|hit| app_state.in_scope(&hit.task))
+        .collect();
+    let Some(search) = app_state.search.as_mut() else {
+        return false;
+    };
+    (search.searched, search.total) = last;
+    search.done = last.0 == last.1;
+    search.hits.extend(hits);
+    true
+}
+
+pub fn render_results<B: Backend>(f: &mut Frame<B>, app_state: &AppState, chunk: Rect) {
+    let Some(search) = &app_state.search else {
+        return;
+    };
+    let dim = Style::default()
+        .fg(Color::DarkGray)
+        .add_modifier(Modifier::ITALIC);
+    let date_format = &app_state.config.date_format;
+    let lines: Vec<Spans> = (search.hits.iter())
+        .take(chunk.height.saturating_sub(2) as usize)
+        .map(|hit| {
+            let mut spans = vec![Span::raw(app_state.description(&hit.task))];
+            if let Some(deadline) = hit.task.deadline {
+                let due = format!("  {}", clock::format_deadline(deadline, date_format));
+                spans.push(Span::styled(due, dim));
+            }
+            if hit.archived {
+                spans.push(Span::styled("  archived", dim));
+            }
+            Spans::from(spans)
+        })
+        .collect();
+    let matches = search.hits.len();
+    let percent = (search.searched * 100).checked_div(search.total);
+    let title = match (&search.error, percent) {
+        (Some(e), _) => Span::styled(format!("Results - {}", e), Style::default().fg(Color::Red)),
+        _ if search.rx.is_none() => Span::raw("Results"),
+        _ if search.done => Span::raw(format!("Results - {} matches", matches)),
+        (None, None) => Span::raw("Results - reading the archive"),
+        (None, Some(percent)) => {
+            let progress = format!("{} matches, searched {}%", matches, percent);
+            Span::raw(format!("Results - {}", progress))
+        }
+    };
+    let block = Block::default().borders(Borders::ALL).title(title);
+    f.render_widget(Paragraph::new(lines).block(block), chunk);
+}
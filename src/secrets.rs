@@ -0,0 +1,217 @@
+use crossterm::event::{self, Event, KeyCode, KeyEventKind, KeyModifiers};
+use crossterm::terminal;
+use std::io::{self, Write};
+use std::process::{Command, Stdio};
+
+/// Service name the secrets are filed under in the keyring.
+const SERVICE: &str = "todo-rs";
+/// Config values starting with this name a keyring entry instead of holding
+/// the secret itself, e.g. `token = "keyring:jira"`.
+pub const PREFIX: &str = "keyring:";
+/// Exit code the Windows script uses for a missing entry.
+const NOT_FOUND: i32 = 2;
+
+/// Credential Manager through PowerShell; the secret and its name travel in
+/// the environment so PowerShell never parses them as code.
+const WINDOWS_SCRIPT: &str = r#"
+$ErrorActionPreference = 'Stop'
+Add-Type -TypeDefinition @'
+using System;
+using System.Runtime.InteropServices;
+public static class TodoCred {
+    [StructLayout(LayoutKind.Sequential, CharSet = CharSet.Unicode)]
+    public struct CREDENTIAL {
+        public int Flags; public int Type; public string TargetName; public string Comment;
+        public System.Runtime.InteropServices.ComTypes.FILETIME LastWritten;
+        public int CredentialBlobSize; public IntPtr CredentialBlob; public int Persist;
+        public int AttributeCount; public IntPtr Attributes; public string TargetAlias;
+        public string UserName;
+    }
+    [DllImport("advapi32.dll", CharSet = CharSet.Unicode, SetLastError = true)]
+    public static extern bool CredRead(string target, int type, int flags, out IntPtr cred);
+    [DllImport("advapi32.dll", CharSet = CharSet.Unicode, SetLastError = true)]
+    public static extern bool CredWrite(ref CREDENTIAL cred, int flags);
+    [DllImport("advapi32.dll", CharSet = CharSet.Unicode, SetLastError = true)]
+    public static extern bool CredDelete(string target, int type, int flags);
+    [DllImport("advapi32.dll")]
+    public static extern void CredFree(IntPtr cred);
+}
+'@
+$target = $env:TODO_RS_TARGET
+switch ($env:TODO_RS_OP) {
+    'get' {
+        $ptr = [IntPtr]::Zero
+        if (-not [TodoCred]::CredRead($target, 1, 0, [ref]$ptr)) { exit 2 }
+        $cred = [Runtime.InteropServices.Marshal]::PtrToStructure($ptr, [type][TodoCred+CREDENTIAL])
+        $secret = [Runtime.InteropServices.Marshal]::PtrToStringUni($cred.CredentialBlob, $cred.CredentialBlobSize / 2)
+        [TodoCred]::CredFree($ptr)
+        [Console]::Out.Write($secret)
+    }
+    'set' {
+        $secret = $env:TODO_RS_SECRET
+        $cred = New-Object TodoCred+CREDENTIAL
+        $cred.Type = 1; $cred.Persist = 2; $cred.TargetName = $target; $cred.UserName = $env:USERNAME
+        $cred.CredentialBlobSize = $secret.Length * 2
+        $cred.CredentialBlob = [Runtime.InteropServices.Marshal]::StringToCoTaskMemUni($secret)
+        $ok = [TodoCred]::CredWrite([ref]$cred, 0)
+        [Runtime.InteropServices.Marshal]::ZeroFreeCoTaskMemUnicode($cred.CredentialBlob)
+        if (-not $ok) { exit 1 }
+    }
+    'delete' {
+        if (-not [TodoCred]::CredDelete($target, 1, 0)) { exit 2 }
+    }
+}
+"#;
+
+enum Op<'a> {
+    Get,
+    Set(&'a str),
+    Delete,
+}
+
+/// Runs the platform's keyring tool. `Ok(None)` means there is no such
+/// entry; an error means the keyring itself couldn't be used.
+fn run(name: &str, op: Op) -> Result<Option<String>, io::Error> {
+    let mut secret_input = None;
+    let mut command = if cfg!(windows) {
+        let mut command = Command::new("powershell");
+        command
+            .args(["-NoProfile", "-NonInteractive", "-Command", WINDOWS_SCRIPT])
+            .env("TODO_RS_TARGET", format!("{}/{}", SERVICE, name));
+        let op = match op {
+            Op::Get => "get",
+            Op::Set(secret) => {
+                command.env("TODO_RS_SECRET", secret);
+                "set"
+            }
+            Op::Delete => "delete",
+        };
+        command.env("TODO_RS_OP", op);
+        command
+    } else if cfg!(target_os = "macos") {
+        let mut command = Command::new("security");
+        match op {
+            Op::Get => command.args(["find-generic-password", "-w"]),
+            Op::Set(_) => command.args(["add-generic-password", "-U"]),
+            Op::Delete => command.arg("delete-generic-password"),
+        };
+        command.args(["-s", SERVICE, "-a", name]);
+        // A `-w` with no value, last, makes it ask for the secret, twice,
+        // on stdin: an argument would show in the process list.
+        if let Op::Set(secret) = op {
+            command.arg("-w");
+            secret_input = Some(format!("{0}\n{0}\n", secret));
+        }
+        command
+    } else {
+        let mut command = Command::new("secret-tool");
+        match op {
+            Op::Get => command.arg("lookup"),
+            Op::Set(secret) => {
+                secret_input = Some(secret.to_string());
+                command.args(["store", &format!("--label={} {}", SERVICE, name)])
+            }
+            Op::Delete => command.arg("clear"),
+        };
+        command.args(["service", SERVICE, "account", name]);
+        command
+    };
+
+    let mut child = command
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .map_err(|e| io::Error::new(e.kind(), format!("no keyring tool found: {}", e)))?;
+    if let (Some(secret), Some(mut stdin)) = (secret_input, child.stdin.take()) {
+        stdin.write_all(secret.as_bytes())?;
+    }
+    let output = child.wait_with_output()?;
+    let stderr = String::from_utf8_lossy(&output.stderr).trim().to_string();
+    // Not-found exits quietly, except on macOS, which says so.
+    let missing = output.status.code() == Some(NOT_FOUND)
+        || (cfg!(target_os = "macos") && stderr.contains("could not be found"))
+        || (!cfg!(windows) && !cfg!(target_os = "macos") && stderr.is_empty());
+    if output.status.success() {
+        let text = String::from_utf8_lossy(&output.stdout);
+        Ok(Some(text.trim_end_matches(['\r', '\n']).to_string()))
+    } else if missing {
+        Ok(None)
+    } else {
+        Err(io::Error::other(format!("keyring error: {}", stderr)))
+    }
+}
+
+/// The secret stored under `name`, if any.
+pub fn get(name: &str) -> Result<Option<String>, io::Error> {
+    run(name, Op::Get)
+}
+
+pub fn set(name: &str, secret: &str) -> Result<(), io::Error> {
+    run(name, Op::Set(secret)).map(|_| ())
+}
+
+/// Removes the entry; `false` when there was none.
+pub fn delete(name: &str) -> Result<bool, io::Error> {
+    run(name, Op::Delete).map(|found| found.is_some())
+}
+
+/// Reads a line from the terminal without echoing it.
+pub fn prompt(label: &str) -> Result<String, io::Error> {
+    eprint!("{}: ", label);
+    io::stderr().flush()?;
+    terminal::enable_raw_mode()?;
+    let mut secret = String::new();
+    let result = loop {
+        let key = match event::read() {
+            Ok(Event::Key(key)) if key.kind != KeyEventKind::Release => key,
+            Ok(_) => continue,
+            Err(e) => break Err(e),
+        };
+        match key.code {
+            KeyCode::Enter => break Ok(()),
+            KeyCode::Esc => break Err(io::Error::new(io::ErrorKind::Interrupted, "cancelled")),
+            KeyCode::Char('c') if key.modifiers.contains(KeyModifiers::CONTROL) => {
+                break Err(io::Error::new(io::ErrorKind::Interrupted, "cancelled"));
+            }
+            KeyCode::Backspace => {
+                secret.pop();
+            }
+            KeyCode::Char(c) => secret.push(c),
+            _ => {}
+        }
+    };
+    terminal::disable_raw_mode()?;
+    eprintln!();
+    result.map(|_| secret)
+}
+
+/// A secret from the config: `keyring:<name>` is looked up in the keyring,
+/// anything else is the secret itself. When there's no value, or the
+/// keyring can't supply it, it is asked for.
+pub fn resolve(value: Option<&str>, label: &str) -> Result<String, io::Error> {
+    let Some(value) = value else {
+        return prompt(label);
+    };
+    let Some(name) = value.strip_prefix(PREFIX) else {
+        return Ok(value.to_string());
+    };
+    match get(name) {
+        Ok(Some(secret)) => Ok(secret),
+        Ok(None) | Err(_) => prompt(label),
+    }
+}
+
+/// Like `resolve`, but never prompts: for when the terminal belongs to the
+/// UI. `None` when the keyring can't supply the secret.
+pub fn lookup(value: &str) -> Option<String> {
+    match value.strip_prefix(PREFIX) {
+        Some(name) => get(name).ok().flatten(),
+        None => Some(value.to_string()),
+    }
+}
+
+/// Whether the keyring answers at all, for `todo-rs doctor`.
+pub fn available() -> bool {
+    get("doctor-probe").is_ok()
+}
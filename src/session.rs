@@ -0,0 +1,68 @@
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fs;
+use std::io;
+use std::path::Path;
+
+const SESSION_FILE: &str = "session.json";
+
+/// UI state remembered between runs, kept next to the task data.
+#[derive(Serialize, Deserialize)]
+#[serde(default)]
+pub struct SessionState {
+    /// Width of the task list as a percentage of the body, the rest is the detail pane.
+    pub split_percent: u16,
+    /// Version that last ran, to tell when an upgrade happened.
+    pub last_version: Option<String>,
+    /// Day (`YYYY-MM-DD`) the last overdue escalation was sent, so it goes
+    /// out once a day however often the app starts.
+    pub escalated_on: Option<String>,
+    /// Whether the list was left sorted by deadline, which it then is again.
+    pub sort_by_due: bool,
+    /// Last date (`YYYY-MM-DD`) each `[[scheduled]]` task was added for, by
+    /// description.
+    pub scheduled: BTreeMap<String, String>,
+    /// Day (`YYYY-MM-DD`) of the last plan, and the tasks committed to it.
+    pub planned_on: Option<String>,
+    pub planned: Vec<u64>,
+    /// The project or file everything was narrowed to; see `scope`.
+    pub scope: Option<String>,
+}
+
+impl Default for SessionState {
+    fn default() -> SessionState {
+        SessionState {
+            split_percent: 60,
+            last_version: None,
+            escalated_on: None,
+            sort_by_due: false,
+            scheduled: BTreeMap::new(),
+            planned_on: None,
+            planned: Vec::new(),
+            scope: None,
+        }
+    }
+}
+
+impl SessionState {
+    pub const MIN_SPLIT: u16 = 20;
+    pub const MAX_SPLIT: u16 = 85;
+
+    /// Missing or unreadable session files just mean starting from defaults.
+    pub fn load(data_dir: &Path) -> SessionState {
+        fs::read_to_string(data_dir.join(SESSION_FILE))
+            .ok()
+            .and_then(|text| serde_json::from_str(&text).ok())
+            .unwrap_or_default()
+    }
+
+    /// Whether an earlier run left a session file behind.
+    pub fn exists(data_dir: &Path) -> bool {
+        data_dir.join(SESSION_FILE).exists()
+    }
+
+    pub fn save(&self, data_dir: &Path) -> Result<(), io::Error> {
+        let text = serde_json::to_string(self)?;
+        fs::write(data_dir.join(SESSION_FILE), text)
+    }
+}
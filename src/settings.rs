@@ -0,0 +1,99 @@
+use tui::{
+    backend::Backend,
+    layout::{Constraint, Rect},
+    style::{Color, Modifier, Style},
+    widgets::{Block, Borders, Cell, Row, Table, TableState},
+    Frame,
+};
+
+use crate::keymap::{format_key, Action, Key};
+use crate::AppState;
+
+/// State of the keybinding editor: which action is highlighted and whether
+/// the next key press should be captured as its new binding.
+pub struct KeybindingEditor {
+    pub selected: usize,
+    pub capturing: bool,
+}
+
+pub enum EditorCommand {
+    None,
+    Close,
+    Bind(Action, Key),
+    Reset(Action),
+}
+
+impl KeybindingEditor {
+    pub fn new() -> KeybindingEditor {
+        KeybindingEditor {
+            selected: 0,
+            capturing: false,
+        }
+    }
+
+    pub fn handle_key(&mut self, key: Key) -> EditorCommand {
+        let action = Action::ALL[self.selected];
+        if self.capturing {
+            self.capturing = false;
+            return match key {
+                Key::Esc => EditorCommand::None,
+                key => EditorCommand::Bind(action, key),
+            };
+        }
+
+        match key {
+            Key::Up => self.selected = self.selected.saturating_sub(1),
+            Key::Down => self.selected = (self.selected + 1).min(Action::ALL.len() - 1),
+            Key::Char('\n') => self.capturing = true,
+            Key::Char('r') => return EditorCommand::Reset(action),
+            Key::Esc | Key::Char('q') => return EditorCommand::Close,
+            _ => {}
+        }
+        EditorCommand::None
+    }
+}
+
+pub fn render_keybindings<B: Backend>(
+    f: &mut Frame<B>,
+    app_state: &AppState,
+    editor: &KeybindingEditor,
+    chunk: Rect,
+) {
+    let rows: Vec<Row> = Action::ALL
+        .iter()
+        .enumerate()
+        .map(|(i, action)| {
+            let key = if editor.capturing && i == editor.selected {
+                "press a key...".to_string()
+            } else {
+                format_key(app_state.keymap.key_for(*action))
+            };
+            let customized = app_state.config.keybindings.contains_key(action);
+            let key_style = if customized {
+                Style::default().fg(Color::Cyan)
+            } else {
+                Style::default()
+            };
+            Row::new(vec![
+                Cell::from(action.description()),
+                Cell::from(key).style(key_style),
+            ])
+        })
+        .collect();
+
+    let table = Table::new(rows)
+        .header(
+            Row::new(vec!["Action", "Key"]).style(Style::default().add_modifier(Modifier::BOLD)),
+        )
+        .widths(&[Constraint::Percentage(60), Constraint::Percentage(40)])
+        .highlight_style(app_state.styles.selected)
+        .block(
+            Block::default()
+                .borders(Borders::ALL)
+                .title("Keybindings (Enter: rebind, r: reset, Esc: close)"),
+        );
+
+    let mut state = TableState::default();
+    state.select(Some(editor.selected));
+    f.render_stateful_widget(table, chunk, &mut state);
+}
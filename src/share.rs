@@ -0,0 +1,106 @@
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
+
+use crate::Task;
+
+/// Escapes text for an iCalendar property value (RFC 5545, 3.3.11).
+pub fn escape_ics(text: &str) -> String {
+    text.replace('\\', "\\\\")
+        .replace(';', "\\;")
+        .replace(',', "\\,")
+        .replace('\n', "\\n")
+}
+
+/// The task as a standalone calendar file holding one VTODO.
+pub fn to_ics(task: &Task) -> String {
+    let now = chrono::Utc::now().format("%Y%m%dT%H%M%SZ");
+    let mut lines = vec![
+        "BEGIN:VCALENDAR".to_string(),
+        "VERSION:2.0".to_string(),
+        "PRODID:-//todo-rs//EN".to_string(),
+        "BEGIN:VTODO".to_string(),
+        format!("UID:todo-rs-{}-{}", task.id, now),
+        format!("DTSTAMP:{}", now),
+        format!("SUMMARY:{}", escape_ics(&task.description)),
+    ];
+    if let Some(deadline) = task.deadline {
+        lines.push(format!("DUE;VALUE=DATE:{}", deadline.format("%Y%m%d")));
+    }
+    let status = if task.completed {
+        "COMPLETED"
+    } else {
+        "NEEDS-ACTION"
+    };
+    lines.push(format!("STATUS:{}", status));
+    lines.push("END:VTODO".to_string());
+    lines.push("END:VCALENDAR".to_string());
+    // iCalendar requires CRLF line endings.
+    lines.join("\r\n") + "\r\n"
+}
+
+/// Writes `task-<id>.ics` into `dir`, returning its path.
+pub fn write_ics(task: &Task, dir: &Path) -> Result<PathBuf, io::Error> {
+    let path = dir.join(format!("task-{}.ics", task.id));
+    fs::write(&path, to_ics(task))?;
+    Ok(path)
+}
+
+/// Quotes a value for a curl config file, which is how tokens reach curl
+/// without showing up in the process list.
+pub fn curl_quote(value: &str) -> String {
+    format!("\"{}\"", value.replace('\\', "\\\\").replace('"', "\\\""))
+}
+
+pub fn percent_encode(text: &str) -> String {
+    text.bytes()
+        .map(|b| match b {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'.' | b'_' | b'~' => {
+                (b as char).to_string()
+            }
+            _ => format!("%{:02X}", b),
+        })
+        .collect()
+}
+
+/// A `mailto:` link drafting an email about the task.
+pub fn mailto_url(task: &Task, date_format: &str) -> String {
+    let mut body = task.description.clone();
+    if let Some(deadline) = task.deadline {
+        body.push_str(&format!("\n\nDue: {}", deadline.format(date_format)));
+    }
+    format!(
+        "mailto:?subject={}&body={}",
+        percent_encode(&task.description),
+        percent_encode(&body)
+    )
+}
+
+/// Hands a URL to the platform's default handler.
+pub fn open_url(url: &str) -> Result<(), io::Error> {
+    let mut command = if cfg!(windows) {
+        // Not `cmd /C start`: cmd would split the URL at its `&`s.
+        let mut command = Command::new("rundll32");
+        command.arg("url.dll,FileProtocolHandler").arg(url);
+        command
+    } else if cfg!(target_os = "macos") {
+        let mut command = Command::new("open");
+        command.arg(url);
+        command
+    } else {
+        let mut command = Command::new("xdg-open");
+        command.arg(url);
+        command
+    };
+
+    let status = command
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .status()?;
+    if status.success() {
+        Ok(())
+    } else {
+        Err(io::Error::other(format!("opener exited with {}", status)))
+    }
+}
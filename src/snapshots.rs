@@ -0,0 +1,255 @@
+use chrono::NaiveDateTime;
+use std::collections::BTreeSet;
+use std::fs::{self, File};
+use std::io::{self, BufReader};
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+use tui::{
+    backend::Backend,
+    layout::Rect,
+    style::{Color, Modifier, Style},
+    text::{Span, Spans},
+    widgets::{Block, Borders, Paragraph},
+    Frame,
+};
+
+use crate::clock;
+use crate::{AppState, Task};
+
+pub const DIR: &str = "snapshots";
+const NAME_FORMAT: &str = "%Y-%m-%dT%H-%M-%S";
+/// Use of the app between two snapshots.
+pub const INTERVAL: Duration = Duration::from_secs(15 * 60);
+/// A pause between keys longer than this doesn't count as use.
+pub const IDLE: Duration = Duration::from_secs(5 * 60);
+/// Older snapshots are removed as new ones are taken.
+const KEEP: usize = 100;
+
+/// A snapshot on disk.
+pub struct Snapshot {
+    pub taken: NaiveDateTime,
+    /// What it was taken ahead of, e.g. "pre-import"; `None` for those
+    /// taken every `INTERVAL`.
+    pub name: Option<String>,
+    pub path: PathBuf,
+}
+
+impl Snapshot {
+    pub fn label(&self, date_format: &str) -> String {
+        let taken = self.taken.format(date_format);
+        match &self.name {
+            Some(name) => format!("{} {}", name, taken),
+            None => taken.to_string(),
+        }
+    }
+}
+
+/// Writes the whole list, completed tasks included, to a new snapshot,
+/// named after what it is taken ahead of if anything.
+pub fn take(data_dir: &Path, tasks: &[&Task], name: Option<&str>) -> Result<(), io::Error> {
+    let dir = data_dir.join(DIR);
+    fs::create_dir_all(&dir)?;
+    let taken = clock::now().format(NAME_FORMAT);
+    let file = match name {
+        Some(name) => format!("{}_{}.json", taken, name),
+        None => format!("{}.json", taken),
+    };
+    serde_json::to_writer(File::create(dir.join(file))?, tasks)?;
+    prune(data_dir, KEEP)?;
+    Ok(())
+}
+
+/// Removes all but the newest `keep` snapshots, returning how many went.
+pub fn prune(data_dir: &Path, keep: usize) -> Result<usize, io::Error> {
+    let old = list(data_dir).into_iter().skip(keep);
+    let mut removed = 0;
+    for snapshot in old {
+        fs::remove_file(snapshot.path)?;
+        removed += 1;
+    }
+    Ok(removed)
+}
+
+/// The snapshots taken, newest first.
+pub fn list(data_dir: &Path) -> Vec<Snapshot> {
+    let entries = fs::read_dir(data_dir.join(DIR));
+    let mut snapshots: Vec<Snapshot> = (entries.into_iter().flatten().flatten())
+        .filter_map(|entry| {
+            let path = entry.path();
+            let stem = path.file_stem()?.to_str()?;
+            let (taken, name) = match stem.split_once('_') {
+                Some((taken, name)) => (taken, Some(name.to_string())),
+                None => (stem, None),
+            };
+            let taken = NaiveDateTime::parse_from_str(taken, NAME_FORMAT).ok()?;
+            Some(Snapshot { taken, name, path })
+        })
+        .collect();
+    snapshots.sort_by_key(|snapshot| std::cmp::Reverse(snapshot.taken));
+    snapshots
+}
+
+fn load(path: &Path) -> Result<Vec<Task>, io::Error> {
+    serde_json::from_reader(BufReader::new(File::open(path)?)).map_err(io::Error::other)
+}
+
+/// Looking back through snapshots: first picking one, then the tasks to
+/// bring back from it.
+pub struct Browser {
+    pub snapshots: Vec<Snapshot>,
+    pub selected: usize,
+    /// The open snapshot's tasks; `None` while picking a snapshot.
+    pub tasks: Option<Vec<Task>>,
+    pub task_selected: usize,
+    pub marked: BTreeSet<u64>,
+}
+
+impl Browser {
+    pub fn new(data_dir: &Path) -> Browser {
+        Browser {
+            snapshots: list(data_dir),
+            selected: 0,
+            tasks: None,
+            task_selected: 0,
+            marked: BTreeSet::new(),
+        }
+    }
+
+    pub fn move_selection(&mut self, up: bool) {
+        let (selected, len) = match &self.tasks {
+            Some(tasks) => (&mut self.task_selected, tasks.len()),
+            None => (&mut self.selected, self.snapshots.len()),
+        };
+        *selected = if up {
+            selected.saturating_sub(1)
+        } else {
+            (*selected + 1).min(len.saturating_sub(1))
+        };
+    }
+
+    pub fn open(&mut self) -> Result<(), io::Error> {
+        let Some(snapshot) = self.snapshots.get(self.selected) else {
+            return Ok(());
+        };
+        self.tasks = Some(load(&snapshot.path)?);
+        self.task_selected = 0;
+        self.marked.clear();
+        Ok(())
+    }
+
+    /// Back to picking a snapshot.
+    pub fn close(&mut self) {
+        self.tasks = None;
+    }
+
+    pub fn toggle_mark(&mut self) {
+        let Some(task) = (self.tasks.as_ref()).and_then(|t| t.get(self.task_selected)) else {
+            return;
+        };
+        if !self.marked.remove(&task.id) {
+            self.marked.insert(task.id);
+        }
+    }
+
+    /// The marked tasks, or the selected one when none is marked.
+    pub fn picked(&self) -> Vec<Task> {
+        let tasks = self.tasks.as_deref().unwrap_or_default();
+        if self.marked.is_empty() {
+            return tasks.get(self.task_selected).cloned().into_iter().collect();
+        }
+        (tasks.iter())
+            .filter(|t| self.marked.contains(&t.id))
+            .cloned()
+            .collect()
+    }
+
+    pub fn taken(&self) -> Option<NaiveDateTime> {
+        self.snapshots.get(self.selected).map(|s| s.taken)
+    }
+
+    /// The selected snapshot's name and time, for messages.
+    pub fn label(&self, date_format: &str) -> String {
+        let snapshot = self.snapshots.get(self.selected);
+        snapshot.map_or(String::new(), |snapshot| snapshot.label(date_format))
+    }
+}
+
+/// How a task in a snapshot compares with the list now.
+fn compare(old: &Task, now: Option<&Task>) -> &'static str {
+    match now {
+        None => "deleted since",
+        Some(now)
+            if now.description != old.description
+                || now.deadline != old.deadline
+                || now.completed != old.completed =>
+        {
+            "changed since"
+        }
+        Some(_) => "unchanged",
+    }
+}
+
+pub fn render_snapshots<B: Backend>(f: &mut Frame<B>, app_state: &AppState, chunk: Rect) {
+    let Some(browser) = &app_state.snapshots else {
+        return;
+    };
+    let date_format = format!("{} %H:%M", app_state.config.date_format);
+    let dim = Style::default()
+        .fg(Color::DarkGray)
+        .add_modifier(Modifier::ITALIC);
+    let (title, lines, selected) = match &browser.tasks {
+        None => {
+            let lines: Vec<Spans> = (browser.snapshots.iter().enumerate())
+                .map(|(i, snapshot)| {
+                    let style = if i == browser.selected {
+                        app_state.styles.selected
+                    } else {
+                        Style::default()
+                    };
+                    Spans::from(Span::styled(snapshot.label(&date_format), style))
+                })
+                .collect();
+            ("Snapshots".to_string(), lines, browser.selected)
+        }
+        Some(tasks) => {
+            let lines: Vec<Spans> = (tasks.iter().enumerate())
+                .map(|(i, task)| {
+                    let mark = if browser.marked.contains(&task.id) {
+                        "x"
+                    } else {
+                        " "
+                    };
+                    let style = if i == browser.task_selected {
+                        app_state.styles.selected
+                    } else if task.completed {
+                        app_state.styles.completed
+                    } else {
+                        Style::default()
+                    };
+                    let now = app_state.tasks.iter().find(|t| t.id == task.id);
+                    Spans::from(vec![
+                        Span::styled(format!("[{}] {}", mark, app_state.description(task)), style),
+                        Span::styled(format!("  ({})", compare(task, now)), dim),
+                    ])
+                })
+                .collect();
+            let title = format!("The list as of {}", browser.label(&date_format));
+            (title, lines, browser.task_selected)
+        }
+    };
+    let block = Block::default().borders(Borders::ALL).title(title);
+    if lines.is_empty() {
+        f.render_widget(
+            Paragraph::new("Nothing in this snapshot").block(block),
+            chunk,
+        );
+        return;
+    }
+    // Keeps the selection on screen in a long list.
+    let height = chunk.height.saturating_sub(2) as usize;
+    let scroll = selected.saturating_sub(height.saturating_sub(1));
+    let paragraph = Paragraph::new(lines)
+        .block(block)
+        .scroll((scroll as u16, 0));
+    f.render_widget(paragraph, chunk);
+}
@@ -0,0 +1,90 @@
+use crate::clock;
+use crate::tasks::Tasks;
+use crate::{AppState, Mode, Task};
+
+/// Names usable as `{name}` in `status_format`.
+pub const PLACEHOLDERS: [&str; 10] = [
+    "mode", "active", "total", "done", "today", "overdue", "shown", "filter", "date", "health",
+];
+
+/// Splits a format string into literal text and placeholder names.
+fn parse(format: &str) -> Result<Vec<(&str, Option<&str>)>, String> {
+    let mut parts = Vec::new();
+    let mut rest = format;
+    while let Some(start) = rest.find('{') {
+        let end = rest[start..]
+            .find('}')
+            .ok_or_else(|| "unclosed '{' in status_format".to_string())?;
+        let name = &rest[start + 1..start + end];
+        if !PLACEHOLDERS.contains(&name) {
+            return Err(format!(
+                "unknown status_format placeholder '{{{}}}', expected one of {}",
+                name,
+                PLACEHOLDERS.join(", ")
+            ));
+        }
+        parts.push((&rest[..start], Some(name)));
+        rest = &rest[start + end + 1..];
+    }
+    parts.push((rest, None));
+    Ok(parts)
+}
+
+pub fn check(format: &str) -> Result<(), String> {
+    parse(format).map(|_| ())
+}
+
+fn mode_name(mode: &Mode) -> &'static str {
+    match mode {
+        Mode::Normal => "NORMAL",
+        Mode::Input | Mode::Placeholder | Mode::Template => "INPUT",
+        Mode::Edit => "EDIT",
+        Mode::DeadlineInput | Mode::DeadlineEntry | Mode::Postpone => "DEADLINE",
+        Mode::Search => "SEARCH",
+        Mode::Scratch => "SCRATCH",
+        Mode::Agenda => "AGENDA",
+        Mode::Kanban => "BOARD",
+        Mode::Conflict => "CONFLICT",
+        Mode::MergePick | Mode::Merge => "MERGE",
+        _ => "VIEW",
+    }
+}
+
+fn value(app_state: &AppState, name: &str) -> String {
+    let now = clock::now();
+    let in_scope = |t: &Task| app_state.in_scope(t);
+    let tasks = || Tasks::new(&app_state.tasks).query().filter(in_scope);
+    match name {
+        "mode" => mode_name(&app_state.mode).to_string(),
+        "active" => tasks().open().count().to_string(),
+        "total" => tasks().count().to_string(),
+        "done" => tasks().done().count().to_string(),
+        "today" => tasks().open().due_on(now.date()).count().to_string(),
+        "overdue" => tasks().open().due_before(now.date()).count().to_string(),
+        "shown" => app_state.visible_tasks().len().to_string(),
+        "filter" => app_state
+            .filter
+            .as_ref()
+            .map_or(String::new(), |(text, _)| text.clone()),
+        "date" => now.format(&app_state.config.date_format).to_string(),
+        "health" => app_state
+            .health
+            .as_ref()
+            .map_or(String::new(), |h| format!("{}%", h.score())),
+        _ => String::new(),
+    }
+}
+
+/// The status bar text for a validated `status_format`.
+pub fn render(format: &str, app_state: &AppState) -> String {
+    let Ok(parts) = parse(format) else {
+        return format.to_string();
+    };
+    parts
+        .into_iter()
+        .map(|(text, name)| match name {
+            Some(name) => text.to_string() + &value(app_state, name),
+            None => text.to_string(),
+        })
+        .collect()
+}
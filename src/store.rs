@@ -0,0 +1,453 @@
+use chrono::NaiveDateTime;
+use rusqlite::{Connection, OptionalExtension, TransactionBehavior};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fs::File;
+use std::io::{self, BufReader};
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+use crate::journal::{Event, Journal};
+use crate::{Task, DATABASE_FILE, JOURNAL_FILE, TASKS_FILE};
+
+/// How the main list and its journal are kept, as `storage` in the config.
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum Storage {
+    /// `tasks.json` and `journal.jsonl` in the data directory.
+    #[default]
+    Json,
+    /// Starts from the task file but never writes it: changes last until
+    /// the app exits, e.g. for trying things out as `--demo` does.
+    Memory,
+    /// `todo.db` in the data directory, one row per open task and event.
+    Sqlite,
+}
+
+/// Where the main list's open tasks and the journal of changes to them
+/// live. Everything the app persists about them goes through here, so a
+/// backend can be swapped without touching the UI.
+pub trait TaskStore {
+    /// The saved open tasks, or `None` when nothing was saved yet.
+    fn load(&self) -> Result<Option<Vec<Task>>, io::Error>;
+
+    /// Replaces the saved open tasks.
+    fn save(&mut self, tasks: &[&Task]) -> Result<(), io::Error>;
+
+    fn append_event(&mut self, event: &Event) -> Result<(), io::Error>;
+
+    /// Drops the events `keep` marks `false`, given them all in order,
+    /// returning how many went. Offsets past the last event kept stay
+    /// good where the backend allows it.
+    fn retain_events(&mut self, keep: &dyn Fn(&[Event]) -> Vec<bool>) -> Result<usize, io::Error>;
+
+    /// The complete events after `offset`, appended by this process or
+    /// another, and the offset to carry on from; `0` reads them all.
+    fn watch(&self, offset: u64) -> Result<(Vec<Event>, u64), io::Error>;
+
+    /// Where the journal ends now, to watch from.
+    fn offset(&self) -> u64;
+
+    fn events(&self) -> Result<Vec<Event>, io::Error> {
+        Ok(self.watch(0)?.0)
+    }
+
+    fn max_task_id(&self) -> u64 {
+        self.events()
+            .map(|events| events.iter().map(|e| e.task).max().unwrap_or(0))
+            .unwrap_or(0)
+    }
+
+    /// When each task was last changed.
+    fn last_activity(&self) -> Result<HashMap<u64, NaiveDateTime>, io::Error> {
+        let mut latest = HashMap::new();
+        for event in self.events()? {
+            let at = latest.entry(event.task).or_insert(event.at);
+            *at = (*at).max(event.at);
+        }
+        Ok(latest)
+    }
+
+    /// Whether changes outlive the app. When they don't, nothing else is
+    /// written to the data directory either: no archive, exports,
+    /// snapshots or session.
+    fn persists(&self) -> bool {
+        true
+    }
+
+    fn history(&self, task: u64) -> Result<Vec<Event>, io::Error> {
+        Ok(self
+            .events()?
+            .into_iter()
+            .filter(|e| e.task == task)
+            .collect())
+    }
+}
+
+/// Opens the configured store for `data_dir`.
+pub fn open(storage: Storage, data_dir: &Path) -> Result<Box<dyn TaskStore>, io::Error> {
+    let json = JsonStore::new(data_dir);
+    let store: Box<dyn TaskStore> = match storage {
+        Storage::Json => Box::new(json),
+        Storage::Memory => {
+            let tasks = read_tasks(&json.tasks_path)?.unwrap_or_default();
+            Box::new(MemoryStore::new(tasks))
+        }
+        Storage::Sqlite => Box::new(SqliteStore::open(&data_dir.join(DATABASE_FILE))?),
+    };
+    Ok(store)
+}
+
+/// The tasks in a task file, if there is one.
+fn read_tasks(path: &Path) -> Result<Option<Vec<Task>>, io::Error> {
+    match File::open(path) {
+        Ok(file) => serde_json::from_reader(BufReader::new(file)).map_err(io::Error::other),
+        Err(ref e) if e.kind() == io::ErrorKind::NotFound => Ok(None),
+        Err(e) => Err(e),
+    }
+}
+
+/// The events `keep` marks to stay, and how many it drops.
+fn retained(events: Vec<Event>, keep: &dyn Fn(&[Event]) -> Vec<bool>) -> (Vec<Event>, usize) {
+    let marks = keep(&events);
+    let total = events.len();
+    let kept: Vec<Event> = (events.into_iter().zip(marks))
+        .filter_map(|(event, keep)| keep.then_some(event))
+        .collect();
+    let dropped = total - kept.len();
+    (kept, dropped)
+}
+
+/// The task file and the journal, side by side in the data directory.
+pub struct JsonStore {
+    tasks_path: PathBuf,
+    journal: Journal,
+}
+
+impl JsonStore {
+    pub fn new(data_dir: &Path) -> JsonStore {
+        JsonStore {
+            tasks_path: data_dir.join(TASKS_FILE),
+            journal: Journal::new(data_dir.join(JOURNAL_FILE)),
+        }
+    }
+}
+
+impl TaskStore for JsonStore {
+    /// Creates an empty task file when there is none yet.
+    fn load(&self) -> Result<Option<Vec<Task>>, io::Error> {
+        let tasks = read_tasks(&self.tasks_path)?;
+        if tasks.is_none() {
+            let file = File::create(&self.tasks_path)?;
+            serde_json::to_writer(&file, &Vec::<Task>::new())?;
+        }
+        Ok(tasks)
+    }
+
+    fn save(&mut self, tasks: &[&Task]) -> Result<(), io::Error> {
+        let file = File::create(&self.tasks_path)?;
+        serde_json::to_writer(file, tasks)?;
+        Ok(())
+    }
+
+    fn append_event(&mut self, event: &Event) -> Result<(), io::Error> {
+        self.journal.append(event)
+    }
+
+    fn retain_events(&mut self, keep: &dyn Fn(&[Event]) -> Vec<bool>) -> Result<usize, io::Error> {
+        self.journal.retain(keep)
+    }
+
+    fn watch(&self, offset: u64) -> Result<(Vec<Event>, u64), io::Error> {
+        self.journal.read_from(offset)
+    }
+
+    fn offset(&self) -> u64 {
+        self.journal.end()
+    }
+
+    /// Reads every line it can, unlike `watch`, which stops short of one
+    /// still being written.
+    fn events(&self) -> Result<Vec<Event>, io::Error> {
+        self.journal.read_all()
+    }
+}
+
+/// Tasks and events held only in memory. Offsets count events.
+#[derive(Default)]
+pub struct MemoryStore {
+    tasks: Vec<Task>,
+    events: Vec<Event>,
+}
+
+impl MemoryStore {
+    pub fn new(tasks: Vec<Task>) -> MemoryStore {
+        MemoryStore {
+            tasks,
+            events: Vec::new(),
+        }
+    }
+}
+
+impl TaskStore for MemoryStore {
+    fn load(&self) -> Result<Option<Vec<Task>>, io::Error> {
+        Ok(Some(self.tasks.clone()))
+    }
+
+    fn save(&mut self, tasks: &[&Task]) -> Result<(), io::Error> {
+        self.tasks = tasks.iter().map(|t| (*t).clone()).collect();
+        Ok(())
+    }
+
+    fn append_event(&mut self, event: &Event) -> Result<(), io::Error> {
+        self.events.push(event.clone());
+        Ok(())
+    }
+
+    fn retain_events(&mut self, keep: &dyn Fn(&[Event]) -> Vec<bool>) -> Result<usize, io::Error> {
+        let (events, dropped) = retained(std::mem::take(&mut self.events), keep);
+        self.events = events;
+        Ok(dropped)
+    }
+
+    fn watch(&self, offset: u64) -> Result<(Vec<Event>, u64), io::Error> {
+        let events = self.events.get(offset as usize..).unwrap_or_default();
+        Ok((events.to_vec(), self.offset()))
+    }
+
+    fn offset(&self) -> u64 {
+        self.events.len() as u64
+    }
+
+    fn persists(&self) -> bool {
+        false
+    }
+}
+
+/// The open tasks and the journal in one SQLite database, which other
+/// processes can write at the same time. Offsets are event row ids, which
+/// are never reused, so they stay good across compaction.
+pub struct SqliteStore {
+    connection: Connection,
+}
+
+const EVENTS_TABLE: &str =
+    "CREATE TABLE IF NOT EXISTS events (id INTEGER PRIMARY KEY AUTOINCREMENT, event TEXT)";
+const TASKS_TABLE: &str = "CREATE TABLE tasks (position INTEGER PRIMARY KEY, task TEXT)";
+
+impl SqliteStore {
+    pub fn open(path: &Path) -> Result<SqliteStore, io::Error> {
+        let connection = sql(Connection::open(path))?;
+        // Waits out another process's write instead of failing.
+        sql(connection.busy_timeout(Duration::from_secs(5)))?;
+        sql(connection.execute_batch(EVENTS_TABLE))?;
+        Ok(SqliteStore { connection })
+    }
+
+    /// Replaces the rows of `table` with `values`, in one transaction.
+    fn replace<T: Serialize>(&mut self, table: &str, values: &[T]) -> Result<(), io::Error> {
+        let transaction = sql(self.connection.transaction())?;
+        sql(transaction.execute(&format!("DELETE FROM {}", table), []))?;
+        let insert = format!("INSERT INTO {} VALUES (NULL, ?1)", table);
+        for value in values {
+            sql(transaction.execute(&insert, [to_json(value)?]))?;
+        }
+        sql(transaction.commit())
+    }
+}
+
+fn sql<T>(result: rusqlite::Result<T>) -> Result<T, io::Error> {
+    result.map_err(io::Error::other)
+}
+
+fn to_json<T: Serialize>(value: &T) -> Result<String, io::Error> {
+    serde_json::to_string(value).map_err(io::Error::other)
+}
+
+fn from_json<T: for<'de> Deserialize<'de>>(text: &str) -> Result<T, io::Error> {
+    serde_json::from_str(text).map_err(io::Error::other)
+}
+
+impl TaskStore for SqliteStore {
+    /// Creates an empty task table when there is none yet, as `JsonStore`
+    /// creates the file.
+    fn load(&self) -> Result<Option<Vec<Task>>, io::Error> {
+        let find = "SELECT 1 FROM sqlite_master WHERE type = 'table' AND name = 'tasks'";
+        let exists = sql(self.connection.query_row(find, [], |_| Ok(())).optional())?;
+        if exists.is_none() {
+            sql(self.connection.execute_batch(TASKS_TABLE))?;
+            return Ok(None);
+        }
+        let select = "SELECT task FROM tasks ORDER BY position";
+        let mut statement = sql(self.connection.prepare(select))?;
+        let rows = sql(statement.query_map([], |row| row.get::<_, String>(0)))?;
+        let mut tasks = Vec::new();
+        for row in rows {
+            tasks.push(from_json(&sql(row)?)?);
+        }
+        Ok(Some(tasks))
+    }
+
+    fn save(&mut self, tasks: &[&Task]) -> Result<(), io::Error> {
+        self.replace("tasks", tasks)
+    }
+
+    fn append_event(&mut self, event: &Event) -> Result<(), io::Error> {
+        let insert = "INSERT INTO events VALUES (NULL, ?1)";
+        sql(self.connection.execute(insert, [to_json(event)?]))?;
+        Ok(())
+    }
+
+    /// Deletes just the dropped rows, in a transaction that keeps other
+    /// processes from appending in between, then vacuums the database.
+    fn retain_events(&mut self, keep: &dyn Fn(&[Event]) -> Vec<bool>) -> Result<usize, io::Error> {
+        let transaction = sql(self
+            .connection
+            .transaction_with_behavior(TransactionBehavior::Immediate))?;
+        let mut rows: Vec<(i64, Event)> = Vec::new();
+        {
+            let mut statement =
+                sql(transaction.prepare("SELECT id, event FROM events ORDER BY id"))?;
+            let query = statement.query_map([], |row| Ok((row.get(0)?, row.get::<_, String>(1)?)));
+            for row in sql(query)? {
+                let (id, event) = sql(row)?;
+                rows.push((id, from_json(&event)?));
+            }
+        }
+        let events: Vec<Event> = rows.iter().map(|(_, event)| event.clone()).collect();
+        let mut dropped = 0;
+        for ((id, _), keep) in rows.iter().zip(keep(&events)) {
+            if !keep {
+                sql(transaction.execute("DELETE FROM events WHERE id = ?1", [id]))?;
+                dropped += 1;
+            }
+        }
+        sql(transaction.commit())?;
+        // Hands the freed pages back, as a compacted journal file shrinks.
+        // The events are gone either way, so a busy database only delays it.
+        if dropped > 0 {
+            let _ = self.connection.execute_batch("VACUUM");
+        }
+        Ok(dropped)
+    }
+
+    fn watch(&self, offset: u64) -> Result<(Vec<Event>, u64), io::Error> {
+        let select = "SELECT id, event FROM events WHERE id > ?1 ORDER BY id";
+        let mut statement = sql(self.connection.prepare(select))?;
+        let rows = statement.query_map([offset], |row| Ok((row.get(0)?, row.get::<_, String>(1)?)));
+        let mut events = Vec::new();
+        let mut end = offset;
+        for row in sql(rows)? {
+            let (id, event) = sql(row)?;
+            events.push(from_json(&event)?);
+            end = id;
+        }
+        Ok((events, end))
+    }
+
+    fn offset(&self) -> u64 {
+        let select = "SELECT COALESCE(MAX(id), 0) FROM events";
+        self.connection
+            .query_row(select, [], |row| row.get(0))
+            .unwrap_or(0)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::journal::EventKind;
+    use crate::AppState;
+    use std::fs;
+
+    fn events(store: &mut dyn TaskStore) {
+        assert!(store.events().unwrap().is_empty());
+        store
+            .append_event(&Event::now(1, EventKind::Completed))
+            .unwrap();
+        let offset = store.offset();
+        store
+            .append_event(&Event::now(2, EventKind::Started))
+            .unwrap();
+        let (news, end) = store.watch(offset).unwrap();
+        assert_eq!(news.len(), 1);
+        assert_eq!(news[0].task, 2);
+        assert_eq!(end, store.offset());
+        assert!(store.watch(end).unwrap().0.is_empty());
+        assert_eq!(store.max_task_id(), 2);
+
+        let dropped = store.retain_events(&|events| events.iter().map(|e| e.task == 2).collect());
+        assert_eq!(dropped.unwrap(), 1);
+        let tasks: Vec<u64> = store.events().unwrap().iter().map(|e| e.task).collect();
+        assert_eq!(tasks, vec![2]);
+    }
+
+    #[test]
+    fn memory_store_keeps_tasks_and_events() {
+        let mut store = MemoryStore::default();
+        let task = Task::new(1, "Kept".to_string(), None);
+        store.save(&[&task]).unwrap();
+        let saved = store.load().unwrap().unwrap();
+        assert_eq!(saved[0].description, "Kept");
+        assert!(!store.persists());
+        events(&mut store);
+    }
+
+    #[test]
+    fn sqlite_store_keeps_tasks_and_events() {
+        let dir = std::env::temp_dir().join(format!("todo-rs-sqlite-{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        let path = dir.join(DATABASE_FILE);
+        let mut store = SqliteStore::open(&path).unwrap();
+        assert!(store.load().unwrap().is_none());
+        assert_eq!(store.load().unwrap().unwrap().len(), 0);
+        let first = Task::new(1, "First".to_string(), None);
+        let second = Task::new(2, "Second".to_string(), None);
+        store.save(&[&first, &second]).unwrap();
+        events(&mut store);
+
+        // Another process sees the same list.
+        let other = SqliteStore::open(&path).unwrap();
+        let saved = other.load().unwrap().unwrap();
+        let descriptions: Vec<&str> = saved.iter().map(|t| t.description.as_str()).collect();
+        assert_eq!(descriptions, vec!["First", "Second"]);
+        assert_eq!(other.offset(), store.offset());
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn sqlite_offsets_survive_another_process_compacting() {
+        let dir = std::env::temp_dir().join(format!("todo-rs-compact-{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        let path = dir.join(DATABASE_FILE);
+        let mut compacting = SqliteStore::open(&path).unwrap();
+        let mut watching = SqliteStore::open(&path).unwrap();
+        for task in 1..=3 {
+            (compacting.append_event(&Event::now(task, EventKind::Completed))).unwrap();
+        }
+        let (_, offset) = watching.watch(0).unwrap();
+
+        let keep_last =
+            |events: &[Event]| (0..events.len()).map(|i| i + 1 == events.len()).collect();
+        assert_eq!(compacting.retain_events(&keep_last).unwrap(), 2);
+        (watching.append_event(&Event::now(4, EventKind::Started))).unwrap();
+        let (news, _) = watching.watch(offset).unwrap();
+        let tasks: Vec<u64> = news.iter().map(|e| e.task).collect();
+        assert_eq!(tasks, vec![4]);
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn memory_mode_leaves_the_data_dir_alone() {
+        let mut app_state = AppState::new();
+        app_state.data_dir = std::env::temp_dir().join("todo-rs-memory-test");
+        app_state.add_task("Done".to_string(), None, None);
+        app_state.selected_task = Some(0);
+        app_state.toggle_selected();
+        app_state.save_tasks().unwrap();
+        app_state.snapshot_before("pre-test").unwrap();
+        app_state.save_session();
+        assert!(!app_state.data_dir.exists());
+        assert_eq!(app_state.store.load().unwrap().unwrap().len(), 0);
+    }
+}
@@ -0,0 +1,53 @@
+use crate::Task;
+
+/// How many ancestors of `tasks[index]` are in the list. Parents that were
+/// deleted or archived end the chain.
+pub fn depth(tasks: &[Task], index: usize) -> usize {
+    let mut depth = 0;
+    let mut parent = tasks[index].parent;
+    // Bounded by the list length in case a journal merge produced a cycle.
+    while let Some(id) = parent.filter(|_| depth < tasks.len()) {
+        let Some(task) = tasks.iter().find(|t| t.id == id) else {
+            break;
+        };
+        depth += 1;
+        parent = task.parent;
+    }
+    depth
+}
+
+fn is_descendant(tasks: &[Task], index: usize, ancestor: u64) -> bool {
+    let mut parent = tasks[index].parent;
+    for _ in 0..tasks.len() {
+        match parent {
+            Some(id) if id == ancestor => return true,
+            Some(id) => parent = tasks.iter().find(|t| t.id == id).and_then(|t| t.parent),
+            None => return false,
+        }
+    }
+    false
+}
+
+/// Indices of every task nested under `id`, at any depth.
+pub fn descendants(tasks: &[Task], id: u64) -> Vec<usize> {
+    (0..tasks.len())
+        .filter(|i| is_descendant(tasks, *i, id))
+        .collect()
+}
+
+/// Whether `task` has subtasks still open, so it can't be completed yet.
+pub fn is_blocked(tasks: &[Task], task: &Task) -> bool {
+    !task.completed && (tasks.iter()).any(|t| t.parent == Some(task.id) && !t.completed)
+}
+
+/// Where a new child of `tasks[index]` goes: after the parent's existing
+/// subtree, so the list reads as an outline.
+pub fn insert_position(tasks: &[Task], index: usize) -> usize {
+    let id = tasks[index].id;
+    descendants(tasks, id)
+        .into_iter()
+        .max()
+        .unwrap_or(index)
+        .max(index)
+        + 1
+}
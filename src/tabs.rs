@@ -0,0 +1,175 @@
+use chrono::NaiveDate;
+use std::collections::{BTreeMap, BTreeSet};
+use std::path::Path;
+use tui::{
+    backend::Backend,
+    layout::Rect,
+    style::{Color, Modifier, Style},
+    text::{Span, Spans},
+    widgets::Tabs,
+    Frame,
+};
+
+use crate::archive;
+use crate::clock;
+use crate::query;
+use crate::tags;
+use crate::workspace;
+use crate::AppState;
+
+/// A quick filter above the task list: everything, a saved filter, or a project.
+pub struct Tab {
+    pub label: String,
+    pub query: Option<String>,
+    /// Set on project tabs, which also show the project's progress.
+    pub project: Option<String>,
+    /// Set on the Today tab: the tasks committed to today when planning.
+    pub ids: Option<Vec<u64>>,
+}
+
+/// "All", today's plan if there is one, then the saved filters, then each
+/// file of a workspace, then every project mentioned by a task.
+pub fn tabs(app_state: &AppState) -> Vec<Tab> {
+    let mut tabs = vec![Tab {
+        label: "All".to_string(),
+        query: None,
+        project: None,
+        ids: None,
+    }];
+    if let Some(ids) = app_state.todays_plan() {
+        tabs.push(Tab {
+            label: "Today".to_string(),
+            query: None,
+            project: None,
+            ids: Some(ids),
+        });
+    }
+    for name in app_state.config.filters.keys() {
+        tabs.push(Tab {
+            label: name.clone(),
+            query: Some(format!("filter:{}", name)),
+            project: None,
+            ids: None,
+        });
+    }
+    let files = &app_state.config.workspace;
+    let names = std::iter::once(workspace::MAIN).chain(files.keys().map(String::as_str));
+    for name in names.filter(|_| !files.is_empty()) {
+        tabs.push(Tab {
+            label: format!("file:{}", name),
+            query: Some(format!("file:{}", name)),
+            project: None,
+            ids: None,
+        });
+    }
+    let projects: BTreeSet<&str> = app_state
+        .tasks
+        .iter()
+        .flat_map(|t| tags::projects(&t.description))
+        .collect();
+    for project in projects {
+        tabs.push(Tab {
+            label: format!("+{}", project),
+            query: Some(format!("+{}", project)),
+            project: Some(project.to_string()),
+            ids: None,
+        });
+    }
+    tabs
+}
+
+/// Open and overdue tasks the tab would show.
+fn counts(app_state: &AppState, tab: &Tab) -> (usize, usize) {
+    let today = clock::today();
+    let query = match &tab.ids {
+        Some(ids) => Some(query::Query::Ids(ids.clone())),
+        None => {
+            (tab.query.as_ref()).and_then(|text| query::parse(text, &app_state.config.filters).ok())
+        }
+    };
+    let open = app_state
+        .tasks
+        .iter()
+        .filter(|t| !t.completed && query.as_ref().is_none_or(|q| q.matches(t, today)));
+    open.fold((0, 0), |(active, overdue), task| {
+        let late = task.deadline.is_some_and(|d| d.date() < today);
+        (active + 1, overdue + late as usize)
+    })
+}
+
+/// Ids of the archived tasks in each project, lowercased as `+project`
+/// queries match, so progress can count work that left the task list.
+pub fn archived_projects(data_dir: &Path) -> BTreeMap<String, BTreeSet<u64>> {
+    let mut projects: BTreeMap<String, BTreeSet<u64>> = BTreeMap::new();
+    for task in archive::load(data_dir).unwrap_or_default() {
+        for project in tags::projects(&task.description) {
+            projects
+                .entry(project.to_lowercase())
+                .or_default()
+                .insert(task.id);
+        }
+    }
+    projects
+}
+
+/// How much of a project is done, in percent, and its nearest open deadline.
+fn progress(app_state: &AppState, project: &str, active: usize) -> (usize, Option<NaiveDate>) {
+    let project = project.to_lowercase();
+    let in_project =
+        |description: &str| tags::projects(description).any(|p| p.to_lowercase() == project);
+    let mut done = app_state
+        .archived_projects
+        .get(&project)
+        .cloned()
+        .unwrap_or_default();
+    let tasks = app_state
+        .tasks
+        .iter()
+        .filter(|t| in_project(&t.description));
+    let mut nearest: Option<NaiveDate> = None;
+    for task in tasks {
+        if task.completed {
+            done.insert(task.id);
+            continue;
+        }
+        // Reopened, but still archived until the next save.
+        done.remove(&task.id);
+        if let Some(deadline) = task.deadline {
+            nearest = Some(nearest.map_or(deadline.date(), |n| n.min(deadline.date())));
+        }
+    }
+    let total = done.len() + active;
+    let percent = (done.len() * 100).checked_div(total).unwrap_or(0);
+    (percent, nearest)
+}
+
+pub fn render_tabs<B: Backend>(f: &mut Frame<B>, app_state: &AppState, chunk: Rect) {
+    let titles = tabs(app_state)
+        .iter()
+        .map(|tab| {
+            let (active, overdue) = counts(app_state, tab);
+            let mut spans = vec![Span::raw(format!("{} {}", tab.label, active))];
+            if overdue > 0 {
+                spans.push(Span::styled(
+                    format!(" {}!", overdue),
+                    app_state.styles.overdue,
+                ));
+            }
+            if let Some(project) = &tab.project {
+                let (percent, nearest) = progress(app_state, project, active);
+                let mut text = format!(" {}%", percent);
+                if let Some(date) = nearest {
+                    let date_format = &app_state.config.date_format;
+                    text.push_str(&format!(" due {}", date.format(date_format)));
+                }
+                spans.push(Span::raw(text));
+            }
+            Spans::from(spans)
+        })
+        .collect();
+    let tabs = Tabs::new(titles)
+        .select(app_state.tab)
+        .style(Style::default().fg(Color::DarkGray))
+        .highlight_style(app_state.styles.selected.add_modifier(Modifier::BOLD));
+    f.render_widget(tabs, chunk);
+}
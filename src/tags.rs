@@ -0,0 +1,32 @@
+/// `#tag` words in a description, without the `#`.
+pub fn tags(description: &str) -> impl Iterator<Item = &str> {
+    prefixed_words(description, '#')
+}
+
+/// `+project` words in a description, without the `+`.
+pub fn projects(description: &str) -> impl Iterator<Item = &str> {
+    prefixed_words(description, '+')
+}
+
+fn prefixed_words(description: &str, prefix: char) -> impl Iterator<Item = &str> {
+    description
+        .split_whitespace()
+        .filter_map(move |word| word.strip_prefix(prefix))
+        .map(|word| word.trim_end_matches([',', '.', ';', ':', '!', '?']))
+        .filter(|word| !word.is_empty())
+}
+
+/// `@context` words in a description, without the `@`.
+pub fn contexts(description: &str) -> impl Iterator<Item = &str> {
+    prefixed_words(description, '@')
+}
+
+/// `assignee:name` words in a description, without the `assignee:`, e.g.
+/// `alice` for a task delegated to Alice.
+pub fn assignees(description: &str) -> impl Iterator<Item = &str> {
+    description
+        .split_whitespace()
+        .filter_map(|word| word.strip_prefix("assignee:"))
+        .map(|word| word.trim_end_matches([',', '.', ';', ':', '!', '?']))
+        .filter(|word| !word.is_empty())
+}
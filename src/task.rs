@@ -0,0 +1,122 @@
+use chrono::NaiveDateTime;
+use serde::{Deserialize, Serialize};
+
+use crate::clock;
+use crate::privacy;
+
+/// What `file:` calls the list in the data directory.
+pub const MAIN: &str = "main";
+
+/// Where a task from another file of the workspace lives, and the id it
+/// has there; in the app it gets an id that can't clash with the main list.
+#[derive(Clone)]
+pub struct Source {
+    pub name: String,
+    pub id: u64,
+}
+
+#[derive(Serialize, Deserialize, Clone)]
+pub struct Task {
+    #[serde(default)]
+    pub id: u64,
+    pub description: String,
+    pub completed: bool,
+    #[serde(default)]
+    pub in_progress: bool,
+    #[serde(default)]
+    pub parent: Option<u64>,
+    #[serde(default)]
+    pub someday: bool,
+    /// Shell command that opens the task's work, e.g. `code ~/proj`.
+    #[serde(default)]
+    pub command: Option<String>,
+    /// Kept hidden on screen unless revealed; see `privacy`.
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub private: bool,
+    /// Files referenced from the task, as absolute paths.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub attachments: Vec<String>,
+    /// Authors of the task and of its completion, on shared lists.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub created_by: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub completed_by: Option<String>,
+    /// Its deadline is its parent's, and moves with it; see `inherit`.
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub inherits_deadline: bool,
+    /// Its id in the app it was imported from, to know it when importing
+    /// again.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub external_id: Option<String>,
+    /// Set on tasks from another file of the workspace; see `workspace`.
+    #[serde(skip)]
+    pub source: Option<Source>,
+
+    #[serde(
+        serialize_with = "clock::serialize_date",
+        deserialize_with = "clock::deserialize_date",
+        default
+    )]
+    pub deadline: Option<NaiveDateTime>,
+
+    #[serde(
+        serialize_with = "clock::serialize_date",
+        deserialize_with = "clock::deserialize_date",
+        default
+    )]
+    pub created_at: Option<NaiveDateTime>,
+
+    #[serde(
+        serialize_with = "clock::serialize_date",
+        deserialize_with = "clock::deserialize_date",
+        default
+    )]
+    pub completed_at: Option<NaiveDateTime>,
+}
+
+impl Task {
+    pub fn new(id: u64, description: String, deadline: Option<NaiveDateTime>) -> Task {
+        Task {
+            id,
+            description,
+            completed: false,
+            in_progress: false,
+            parent: None,
+            someday: false,
+            private: false,
+            command: None,
+            attachments: Vec::new(),
+            created_by: None,
+            completed_by: None,
+            inherits_deadline: false,
+            external_id: None,
+            source: None,
+            deadline,
+            created_at: Some(clock::now()),
+            completed_at: None,
+        }
+    }
+
+    pub fn set_completed(&mut self, completed: bool, at: NaiveDateTime) {
+        self.completed = completed;
+        if completed {
+            self.in_progress = false;
+        }
+        self.completed_at = completed.then_some(at);
+    }
+
+    /// The description for anything that leaves the machine, such as
+    /// notifications and exports: hidden when private, revealed or not.
+    pub fn shared_description(&self) -> String {
+        if self.private {
+            privacy::mask(&self.description)
+        } else {
+            self.description.clone()
+        }
+    }
+
+    /// The workspace file it belongs to, by name.
+    pub fn file(&self) -> &str {
+        self.source.as_ref().map_or(MAIN, |s| s.name.as_str())
+    }
+}
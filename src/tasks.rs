@@ -0,0 +1,295 @@
+use chrono::NaiveDate;
+use std::borrow::Borrow;
+use std::cmp::Ordering;
+
+use crate::clock;
+use crate::estimate;
+use crate::query::{Comparison, DateField, DateValue, Query};
+use crate::tags;
+use crate::urgency::{self, UrgencyConfig};
+use crate::Task;
+
+const SORT_KEYS: &str =
+    "due, created, age, description, tags, project, estimate, file, urgency, manual";
+
+/// Order of the task list; `Manual` keeps the file order, with subtasks
+/// under their parents.
+#[derive(Clone, Copy, PartialEq, Debug, Default)]
+pub enum SortKey {
+    #[default]
+    Manual,
+    Due,
+    Created,
+    /// Youngest first, the reverse of `Created`.
+    Age,
+    Description,
+    /// By the first `#tag`.
+    Tags,
+    /// By the first `+project`.
+    Project,
+    Estimate,
+    /// By the workspace file, the main list last.
+    File,
+    /// Most urgent first; see `urgency`.
+    Urgency,
+}
+
+/// Orders two values, putting a missing one last whichever the direction.
+fn present<T: Ord>(a: Option<T>, b: Option<T>, descending: bool) -> Ordering {
+    match (a, b) {
+        (Some(a), Some(b)) if descending => b.cmp(&a),
+        (Some(a), Some(b)) => a.cmp(&b),
+        (a, b) => b.is_some().cmp(&a.is_some()),
+    }
+}
+
+impl SortKey {
+    /// Reads a sort's name, as given to the `sort` command or `--sort`.
+    pub fn parse(name: &str) -> Result<SortKey, String> {
+        Ok(match name {
+            "due" => SortKey::Due,
+            "created" => SortKey::Created,
+            "age" => SortKey::Age,
+            "description" => SortKey::Description,
+            "tags" => SortKey::Tags,
+            "project" => SortKey::Project,
+            "estimate" => SortKey::Estimate,
+            "file" => SortKey::File,
+            "urgency" => SortKey::Urgency,
+            "manual" => SortKey::Manual,
+            _ => {
+                let message = format!("unknown sort '{}', expected one of {}", name, SORT_KEYS);
+                return Err(message);
+            }
+        })
+    }
+
+    /// Tasks without the value sort last.
+    pub fn compare(self, a: &Task, b: &Task, descending: bool) -> Ordering {
+        let word = |words: &mut dyn Iterator<Item = &str>| words.next().map(str::to_lowercase);
+        match self {
+            SortKey::Manual => Ordering::Equal,
+            SortKey::Due => present(a.deadline, b.deadline, descending),
+            SortKey::Created => present(a.created_at, b.created_at, descending),
+            SortKey::Age => present(a.created_at, b.created_at, !descending),
+            SortKey::Description => present(
+                Some(a.description.to_lowercase()),
+                Some(b.description.to_lowercase()),
+                descending,
+            ),
+            SortKey::Tags => present(
+                word(&mut tags::tags(&a.description)),
+                word(&mut tags::tags(&b.description)),
+                descending,
+            ),
+            SortKey::Project => present(
+                word(&mut tags::projects(&a.description)),
+                word(&mut tags::projects(&b.description)),
+                descending,
+            ),
+            SortKey::Estimate => present(
+                estimate::from_description(&a.description),
+                estimate::from_description(&b.description),
+                descending,
+            ),
+            // Scores depend on the other tasks and the config, so
+            // `TaskQuery::indices` sorts by them itself.
+            SortKey::Urgency => Ordering::Equal,
+            SortKey::File => present(
+                a.source.as_ref().map(|s| &s.name),
+                b.source.as_ref().map(|s| &s.name),
+                descending,
+            ),
+        }
+    }
+}
+
+/// A task list to pick from a condition at a time, e.g.
+/// `Tasks::new(&tasks).query().open().tag("work").sorted_by(SortKey::Urgency, false)`.
+/// Each condition means what it does typed into the search prompt, so the
+/// task table, the status bar and `todo-rs list` agree on what matches.
+pub struct Tasks<'a> {
+    tasks: &'a [Task],
+}
+
+impl<'a> Tasks<'a> {
+    pub fn new(tasks: &'a [Task]) -> Tasks<'a> {
+        Tasks { tasks }
+    }
+
+    pub fn query(&self) -> TaskQuery<'a> {
+        TaskQuery {
+            tasks: self.tasks,
+            conditions: Vec::new(),
+            sort: SortKey::Manual,
+            descending: false,
+            urgency: None,
+            today: clock::today(),
+        }
+    }
+}
+
+type Condition<'a> = Box<dyn Fn(&Task) -> bool + 'a>;
+
+/// The conditions and order asked for so far; every condition must hold.
+pub struct TaskQuery<'a> {
+    tasks: &'a [Task],
+    conditions: Vec<Condition<'a>>,
+    sort: SortKey,
+    descending: bool,
+    /// Weights for `SortKey::Urgency`; the default ones unless given.
+    urgency: Option<&'a UrgencyConfig>,
+    today: NaiveDate,
+}
+
+impl<'a> TaskQuery<'a> {
+    fn holds(self, query: impl Borrow<Query> + 'a) -> TaskQuery<'a> {
+        let today = self.today;
+        self.filter(move |task| query.borrow().matches(task, today))
+    }
+
+    /// Tasks a parsed search lets through, such as the search prompt's.
+    pub fn matching(self, query: &'a Query) -> TaskQuery<'a> {
+        self.holds(query)
+    }
+
+    /// Tasks `include` takes, for what no search term says.
+    pub fn filter(mut self, include: impl Fn(&Task) -> bool + 'a) -> TaskQuery<'a> {
+        self.conditions.push(Box::new(include));
+        self
+    }
+
+    pub fn open(self) -> TaskQuery<'a> {
+        self.holds(Query::Open)
+    }
+
+    pub fn done(self) -> TaskQuery<'a> {
+        self.holds(Query::Done)
+    }
+
+    /// Open tasks in progress.
+    pub fn started(self) -> TaskQuery<'a> {
+        self.holds(Query::Started)
+    }
+
+    pub fn tag(self, tag: &str) -> TaskQuery<'a> {
+        self.holds(Query::Tag(tag.to_string()))
+    }
+
+    pub fn project(self, project: &str) -> TaskQuery<'a> {
+        self.holds(Query::Project(project.to_string()))
+    }
+
+    fn due(self, comparison: Comparison, date: NaiveDate) -> TaskQuery<'a> {
+        let query = Query::Date(DateField::Due, comparison, DateValue::On(date));
+        self.holds(query)
+    }
+
+    pub fn due_before(self, date: NaiveDate) -> TaskQuery<'a> {
+        self.due(Comparison::Less, date)
+    }
+
+    pub fn due_on(self, date: NaiveDate) -> TaskQuery<'a> {
+        self.due(Comparison::Equal, date)
+    }
+
+    /// Orders the results; `SortKey::Manual` leaves them in list order.
+    pub fn sorted_by(mut self, sort: SortKey, descending: bool) -> TaskQuery<'a> {
+        self.sort = sort;
+        self.descending = descending;
+        self
+    }
+
+    pub fn weighted_by(mut self, urgency: &'a UrgencyConfig) -> TaskQuery<'a> {
+        self.urgency = Some(urgency);
+        self
+    }
+
+    /// Positions in the list of the tasks that match, in order.
+    pub fn indices(&self) -> Vec<usize> {
+        let mut found: Vec<usize> = (0..self.tasks.len())
+            .filter(|i| self.conditions.iter().all(|holds| holds(&self.tasks[*i])))
+            .collect();
+        // Stable, so ties keep the list order.
+        if self.sort == SortKey::Urgency {
+            let default = UrgencyConfig::default();
+            let weights = self.urgency.unwrap_or(&default);
+            let scores = urgency::scores(self.tasks, weights, clock::now());
+            found.sort_by(|a, b| {
+                let (a, b) = (scores[*a], scores[*b]);
+                if self.descending {
+                    a.total_cmp(&b)
+                } else {
+                    b.total_cmp(&a)
+                }
+            });
+            return found;
+        }
+        let (tasks, sort, descending) = (self.tasks, self.sort, self.descending);
+        found.sort_by(|a, b| sort.compare(&tasks[*a], &tasks[*b], descending));
+        found
+    }
+
+    pub fn tasks(&self) -> Vec<&'a Task> {
+        let tasks = self.tasks;
+        self.indices().into_iter().map(|i| &tasks[i]).collect()
+    }
+
+    pub fn count(&self) -> usize {
+        let matches = |task: &&Task| self.conditions.iter().all(|holds| holds(task));
+        self.tasks.iter().filter(matches).count()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::NaiveDateTime;
+
+    fn due(description: &str, deadline: &str) -> Task {
+        let deadline = NaiveDateTime::parse_from_str(deadline, "%Y-%m-%d %H:%M").ok();
+        Task::new(0, description.to_string(), deadline)
+    }
+
+    #[test]
+    fn conditions_all_hold_and_sorting_keeps_missing_values_last() {
+        let mut done = due("Filed #work", "2026-01-01 09:00");
+        done.completed = true;
+        let tasks = vec![
+            due("Someday #work", ""),
+            due("Later #work", "2026-03-01 09:00"),
+            done,
+            due("Sooner #work", "2026-02-01 09:00"),
+            due("Groceries #home", "2026-01-15 09:00"),
+        ];
+        let found = Tasks::new(&tasks)
+            .query()
+            .open()
+            .tag("work")
+            .sorted_by(SortKey::Due, false);
+        let descriptions: Vec<&str> = found
+            .tasks()
+            .iter()
+            .map(|t| t.description.as_str())
+            .collect();
+        assert_eq!(
+            descriptions,
+            ["Sooner #work", "Later #work", "Someday #work"]
+        );
+        assert_eq!(found.count(), 3);
+
+        let reversed = Tasks::new(&tasks)
+            .query()
+            .open()
+            .sorted_by(SortKey::Due, true);
+        assert_eq!(reversed.indices(), [1, 3, 4, 0]);
+    }
+
+    #[test]
+    fn sort_keys_parse_by_name() {
+        assert_eq!(SortKey::parse("urgency"), Ok(SortKey::Urgency));
+        assert!(SortKey::parse("size")
+            .unwrap_err()
+            .contains("expected one of"));
+    }
+}
@@ -0,0 +1,73 @@
+use std::collections::BTreeMap;
+
+/// Placeholder names in a template such as `Review PR {{number}} for {{repo}}`,
+/// in order of first appearance.
+pub fn placeholders(template: &str) -> Result<Vec<String>, String> {
+    let mut names = Vec::new();
+    let mut rest = template;
+    while let Some(start) = rest.find("{{") {
+        let after = &rest[start + 2..];
+        let end = after
+            .find("}}")
+            .ok_or_else(|| "unclosed '{{'".to_string())?;
+        let name = after[..end].trim();
+        if name.is_empty() {
+            return Err("empty placeholder '{{}}'".to_string());
+        }
+        if !names.iter().any(|n| n == name) {
+            names.push(name.to_string());
+        }
+        rest = &after[end + 2..];
+    }
+    Ok(names)
+}
+
+/// Replaces every placeholder with its value; unknown names are left as is.
+pub fn fill(template: &str, values: &BTreeMap<String, String>) -> String {
+    let mut text = String::new();
+    let mut rest = template;
+    while let Some(start) = rest.find("{{") {
+        text.push_str(&rest[..start]);
+        let after = &rest[start + 2..];
+        let Some(end) = after.find("}}") else {
+            break;
+        };
+        match values.get(after[..end].trim()) {
+            Some(value) => text.push_str(value),
+            None => text.push_str(&rest[start..start + end + 4]),
+        }
+        rest = &after[end + 2..];
+    }
+    text.push_str(rest);
+    text
+}
+
+/// A template being instantiated, one placeholder prompt at a time.
+pub struct TemplateFill {
+    pub template: String,
+    pub names: Vec<String>,
+    pub values: BTreeMap<String, String>,
+}
+
+impl TemplateFill {
+    pub fn new(template: &str) -> TemplateFill {
+        TemplateFill {
+            template: template.to_string(),
+            // Config validation has already rejected malformed templates.
+            names: placeholders(template).unwrap_or_default(),
+            values: BTreeMap::new(),
+        }
+    }
+
+    /// The placeholder to ask for next, if any are left.
+    pub fn next_name(&self) -> Option<&str> {
+        self.names
+            .iter()
+            .find(|n| !self.values.contains_key(*n))
+            .map(String::as_str)
+    }
+
+    pub fn text(&self) -> String {
+        fill(&self.template, &self.values)
+    }
+}
@@ -0,0 +1,196 @@
+use crossterm::{
+    cursor,
+    event::{DisableBracketedPaste, DisableMouseCapture, EnableBracketedPaste, EnableMouseCapture},
+    execute, queue,
+    terminal::{self, ClearType},
+};
+use std::io::{self, Stdout, Write};
+use std::process::{Command, ExitStatus};
+use tui::{backend::CrosstermBackend, buffer::Cell, layout::Rect, Terminal};
+
+use crate::palette::{self, Support};
+
+/// The crossterm backend, with colors the terminal can't show swapped for
+/// ones it can before they're drawn; see `palette`.
+pub struct Backend {
+    inner: CrosstermBackend<Stdout>,
+    support: Support,
+}
+
+impl tui::backend::Backend for Backend {
+    fn draw<'a, I>(&mut self, content: I) -> Result<(), io::Error>
+    where
+        I: Iterator<Item = (u16, u16, &'a Cell)>,
+    {
+        match self.support {
+            Support::TrueColor => return self.inner.draw(content),
+            Support::Basic => return self.draw_basic(content),
+            Support::Indexed | Support::None => {}
+        }
+        let cells: Vec<(u16, u16, Cell)> = content
+            .map(|(x, y, cell)| (x, y, palette::adapt(self.support, cell)))
+            .collect();
+        self.inner
+            .draw(cells.iter().map(|(x, y, cell)| (*x, *y, cell)))
+    }
+
+    fn hide_cursor(&mut self) -> Result<(), io::Error> {
+        self.inner.hide_cursor()
+    }
+
+    fn show_cursor(&mut self) -> Result<(), io::Error> {
+        self.inner.show_cursor()
+    }
+
+    fn get_cursor(&mut self) -> Result<(u16, u16), io::Error> {
+        self.inner.get_cursor()
+    }
+
+    fn set_cursor(&mut self, x: u16, y: u16) -> Result<(), io::Error> {
+        self.inner.set_cursor(x, y)
+    }
+
+    fn clear(&mut self) -> Result<(), io::Error> {
+        self.inner.clear()
+    }
+
+    fn size(&self) -> Result<Rect, io::Error> {
+        self.inner.size()
+    }
+
+    fn flush(&mut self) -> Result<(), io::Error> {
+        tui::backend::Backend::flush(&mut self.inner)
+    }
+}
+
+impl Backend {
+    /// crossterm writes even the basic colors as palette entries, which
+    /// 8-color terminals don't understand, so their codes are written here.
+    fn draw_basic<'a>(
+        &mut self,
+        content: impl Iterator<Item = (u16, u16, &'a Cell)>,
+    ) -> Result<(), io::Error> {
+        let mut last: Option<(u16, u16)> = None;
+        let mut style = None;
+        for (x, y, cell) in content {
+            if !last.is_some_and(|(last_x, last_y)| x == last_x + 1 && y == last_y) {
+                queue!(self.inner, cursor::MoveTo(x, y))?;
+            }
+            last = Some((x, y));
+            let cell = palette::adapt(Support::Basic, cell);
+            if style != Some((cell.fg, cell.bg, cell.modifier)) {
+                style = Some((cell.fg, cell.bg, cell.modifier));
+                write!(self.inner, "{}", palette::basic_sgr(&cell))?;
+            }
+            write!(self.inner, "{}", cell.symbol)?;
+        }
+        write!(self.inner, "\x1b[0m")
+    }
+}
+
+impl Write for Backend {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.inner.write(buf)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Write::flush(&mut self.inner)
+    }
+}
+
+/// Puts the terminal into raw mode (and mouse capture if enabled) for the UI.
+pub fn initialize(mouse: bool) -> Result<Terminal<Backend>, io::Error> {
+    check_platform_support()?;
+
+    terminal::enable_raw_mode()?;
+    let mut stdout = io::stdout();
+    // Pastes arrive as one event instead of a burst of key presses.
+    execute!(stdout, EnableBracketedPaste)?;
+    if mouse {
+        execute!(stdout, EnableMouseCapture)?;
+    }
+    let backend = Backend {
+        inner: CrosstermBackend::new(stdout),
+        support: palette::detect(),
+    };
+    let mut terminal = Terminal::new(backend)?;
+    terminal.clear()?;
+    Ok(terminal)
+}
+
+/// Stops the process like a shell job (Ctrl+Z), restoring the terminal first
+/// and taking it back over once the shell resumes us with SIGCONT.
+#[cfg(unix)]
+pub fn suspend(terminal: &mut Terminal<Backend>, mouse: bool) -> Result<(), io::Error> {
+    restore(terminal)?;
+    // SIGSTOP rather than SIGTSTP: our own SIGTSTP handler would just bounce it back.
+    signal_hook::low_level::raise(signal_hook::consts::SIGSTOP)?;
+    resume(terminal, mouse)
+}
+
+/// Takes the terminal back after it was handed to the shell or a command.
+fn resume(terminal: &mut Terminal<Backend>, mouse: bool) -> Result<(), io::Error> {
+    terminal::enable_raw_mode()?;
+    execute!(terminal.backend_mut(), EnableBracketedPaste)?;
+    if mouse {
+        execute!(terminal.backend_mut(), EnableMouseCapture)?;
+    }
+    execute!(terminal.backend_mut(), cursor::Hide)?;
+    // Clearing also resets tui's buffers, so the next draw repaints everything.
+    terminal.clear()
+}
+
+/// Runs a shell command with the terminal restored to normal, so interactive
+/// programs such as editors work, then takes the terminal back.
+pub fn run_command(
+    terminal: &mut Terminal<Backend>,
+    mouse: bool,
+    command: &str,
+) -> Result<ExitStatus, io::Error> {
+    restore(terminal)?;
+    let status = if cfg!(windows) {
+        Command::new("cmd").arg("/C").arg(command).status()
+    } else {
+        Command::new("sh").arg("-c").arg(command).status()
+    };
+    resume(terminal, mouse)?;
+    status
+}
+
+/// Windows consoles have no job control, so there is nothing to suspend to.
+#[cfg(not(unix))]
+pub fn suspend(_terminal: &mut Terminal<Backend>, _mouse: bool) -> Result<(), io::Error> {
+    Ok(())
+}
+
+/// Undoes [`initialize`], leaving the shell usable again.
+pub fn restore(terminal: &mut Terminal<Backend>) -> Result<(), io::Error> {
+    terminal.clear()?;
+    execute!(
+        terminal.backend_mut(),
+        DisableMouseCapture,
+        DisableBracketedPaste,
+        cursor::MoveTo(0, 0),
+        terminal::Clear(ClearType::All),
+        cursor::Show
+    )?;
+    terminal::disable_raw_mode()
+}
+
+#[cfg(windows)]
+fn check_platform_support() -> Result<(), io::Error> {
+    // Consoles older than Windows 10 can't interpret the escape sequences tui emits.
+    if crossterm::ansi_support::supports_ansi() {
+        Ok(())
+    } else {
+        Err(io::Error::new(
+            io::ErrorKind::Unsupported,
+            "this console doesn't support ANSI escape sequences; use Windows Terminal or a newer console",
+        ))
+    }
+}
+
+#[cfg(unix)]
+fn check_platform_support() -> Result<(), io::Error> {
+    Ok(())
+}
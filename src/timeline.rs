@@ -0,0 +1,272 @@
+use chrono::{Duration, NaiveDate, NaiveDateTime, NaiveTime};
+use tui::{
+    backend::Backend,
+    layout::{Constraint, Direction, Layout, Rect},
+    style::{Color, Modifier, Style},
+    text::{Span, Spans},
+    widgets::{Block, Borders, Paragraph},
+    Frame,
+};
+
+use crate::calendar::{self, Event};
+use crate::clock;
+use crate::config::{self, CalendarConfig};
+use crate::estimate;
+use crate::{AppState, Task};
+
+/// Each row of the timeline covers this many minutes.
+const SLOT_MINUTES: i64 = 30;
+
+/// Something taking up time on the day: a calendar event, or a task due
+/// at a time of day.
+struct Busy {
+    start: NaiveDateTime,
+    end: NaiveDateTime,
+    title: String,
+    task: Option<u64>,
+}
+
+/// Whether a deadline is for a time of day rather than the whole day.
+fn is_timed(deadline: NaiveDateTime) -> bool {
+    deadline.time() != NaiveTime::MIN
+}
+
+/// How long a task takes: its `~estimate`, or an hour.
+fn length(task: &Task) -> Duration {
+    let minutes =
+        estimate::from_description(&task.description).unwrap_or(calendar::DEFAULT_MINUTES);
+    Duration::minutes(minutes as i64)
+}
+
+/// Open tasks due on `day`, those without a time first, then by time.
+fn due_on(tasks: &[Task], day: NaiveDate) -> Vec<&Task> {
+    let mut due: Vec<&Task> = (tasks.iter())
+        .filter(|t| !t.completed && !t.someday)
+        .filter(|t| t.deadline.is_some_and(|d| d.date() == day))
+        .collect();
+    due.sort_by_key(|t| t.deadline.filter(|d| is_timed(*d)));
+    due
+}
+
+/// A day laid out in half-hour slots: the calendar's events, timed tasks,
+/// and the time left free between them. A task due that day is picked and
+/// put into a free slot, which sets the time of its deadline.
+pub struct Timeline {
+    pub day: NaiveDate,
+    start: NaiveDateTime,
+    slots: usize,
+    events: Vec<Event>,
+    /// Why the calendar couldn't be read, shown in the title.
+    pub error: Option<String>,
+    /// The slot under the cursor.
+    pub slot: usize,
+    /// The task being placed, by id.
+    pub picked: Option<u64>,
+}
+
+impl Timeline {
+    pub fn new(tasks: &[Task], config: &CalendarConfig) -> Timeline {
+        let day = clock::today();
+        let time = |text: &str| NaiveTime::parse_from_str(text, "%H:%M").unwrap_or(NaiveTime::MIN);
+        let (start, end) = (time(&config.day_start), time(&config.day_end));
+        let minutes = (end - start).num_minutes().max(SLOT_MINUTES);
+        let (events, error) = match &config.events_ics {
+            None => (Vec::new(), None),
+            Some(path) => match calendar::events_on(&config::expand_home(path), day) {
+                Ok(events) => (events, None),
+                Err(e) => (Vec::new(), Some(format!("{}: {}", path, e))),
+            },
+        };
+        let mut timeline = Timeline {
+            day,
+            start: day.and_time(start),
+            slots: ((minutes + SLOT_MINUTES - 1) / SLOT_MINUTES) as usize,
+            events,
+            error,
+            slot: 0,
+            picked: None,
+        };
+        let now = clock::now();
+        if now > timeline.start {
+            let slot = (now - timeline.start).num_minutes() / SLOT_MINUTES;
+            timeline.slot = (slot as usize).min(timeline.slots - 1);
+        }
+        timeline.picked = due_on(tasks, day).first().map(|t| t.id);
+        timeline
+    }
+
+    fn slot_start(&self, slot: usize) -> NaiveDateTime {
+        self.start + Duration::minutes(slot as i64 * SLOT_MINUTES)
+    }
+
+    /// When the cursor's slot starts.
+    pub fn cursor(&self) -> NaiveDateTime {
+        self.slot_start(self.slot)
+    }
+
+    pub fn move_cursor(&mut self, up: bool) {
+        self.slot = if up {
+            self.slot.saturating_sub(1)
+        } else {
+            (self.slot + 1).min(self.slots - 1)
+        };
+    }
+
+    /// Picks the next or previous task due on the day.
+    pub fn cycle(&mut self, tasks: &[Task], forward: bool) {
+        let due = due_on(tasks, self.day);
+        if due.is_empty() {
+            self.picked = None;
+            return;
+        }
+        let position = due.iter().position(|t| Some(t.id) == self.picked);
+        let next = match (position, forward) {
+            (Some(p), true) => (p + 1) % due.len(),
+            (Some(p), false) => (p + due.len() - 1) % due.len(),
+            (None, _) => 0,
+        };
+        self.picked = Some(due[next].id);
+    }
+
+    /// Events and timed tasks on the day, tasks titled as the list shows
+    /// them right now.
+    fn busy(&self, app_state: &AppState) -> Vec<Busy> {
+        let mut busy: Vec<Busy> = (self.events.iter())
+            .map(|event| Busy {
+                start: event.start,
+                end: event.end,
+                title: event.summary.clone(),
+                task: None,
+            })
+            .collect();
+        for task in due_on(&app_state.tasks, self.day) {
+            let Some(start) = task.deadline.filter(|d| is_timed(*d)) else {
+                continue;
+            };
+            busy.push(Busy {
+                start,
+                end: start + length(task),
+                title: app_state.description(task),
+                task: Some(task.id),
+            });
+        }
+        busy.sort_by_key(|b| b.start);
+        busy
+    }
+
+    /// The deadline placing `task` at the cursor gives it, or what's in
+    /// the way.
+    pub fn place(&self, app_state: &AppState, task: &Task) -> Result<NaiveDateTime, String> {
+        let start = self.cursor();
+        let end = start + length(task);
+        let clash = (self.busy(app_state).into_iter())
+            .filter(|b| b.task != Some(task.id))
+            .find(|b| b.start < end && start < b.end);
+        match clash {
+            Some(busy) => Err(format!(
+                "{} to {} overlaps '{}'",
+                start.format("%H:%M"),
+                end.format("%H:%M"),
+                busy.title
+            )),
+            None => Ok(start),
+        }
+    }
+}
+
+pub fn render_timeline<B: Backend>(f: &mut Frame<B>, app_state: &AppState, chunk: Rect) {
+    let Some(timeline) = &app_state.timeline else {
+        return;
+    };
+    let panes = Layout::default()
+        .direction(Direction::Horizontal)
+        .constraints([Constraint::Percentage(60), Constraint::Percentage(40)].as_ref())
+        .split(chunk);
+    let dim = Style::default()
+        .fg(Color::DarkGray)
+        .add_modifier(Modifier::ITALIC);
+    let event_style = Style::default().fg(Color::Cyan);
+
+    let busy = timeline.busy(app_state);
+    let lines: Vec<Spans> = (0..timeline.slots)
+        .map(|slot| {
+            let start = timeline.slot_start(slot);
+            let end = start + Duration::minutes(SLOT_MINUTES);
+            let label = Span::raw(format!("{} ", start.format("%H:%M")));
+            let here: Vec<&Busy> = (busy.iter())
+                .filter(|b| b.start < end && start < b.end)
+                .collect();
+            let mut spans = vec![label];
+            if here.is_empty() {
+                spans.push(Span::styled("free", dim));
+            }
+            for (i, b) in here.iter().enumerate() {
+                if i > 0 {
+                    spans.push(Span::raw(", "));
+                }
+                let style = if b.task.is_some() {
+                    Style::default()
+                } else {
+                    event_style
+                };
+                let text = if b.start >= start {
+                    format!(
+                        "{} ({}-{})",
+                        b.title,
+                        b.start.format("%H:%M"),
+                        b.end.format("%H:%M")
+                    )
+                } else {
+                    format!("| {}", b.title)
+                };
+                spans.push(Span::styled(text, style));
+            }
+            if slot == timeline.slot {
+                spans = (spans.into_iter())
+                    .map(|s| Span::styled(s.content, s.style.patch(app_state.styles.selected)))
+                    .collect();
+            }
+            Spans::from(spans)
+        })
+        .collect();
+    let title = match &timeline.error {
+        Some(e) => format!("Day planner - {}", e),
+        None => format!(
+            "Day planner, {}",
+            timeline.day.format(&app_state.config.date_format)
+        ),
+    };
+    let block = Block::default().borders(Borders::ALL).title(title);
+    // Keeps the cursor on screen on a long day.
+    let height = panes[0].height.saturating_sub(2) as usize;
+    let scroll = timeline.slot.saturating_sub(height.saturating_sub(1));
+    let paragraph = Paragraph::new(lines)
+        .block(block)
+        .scroll((scroll as u16, 0));
+    f.render_widget(paragraph, panes[0]);
+
+    let due = due_on(&app_state.tasks, timeline.day);
+    let block = Block::default().borders(Borders::ALL).title("Due today");
+    if due.is_empty() {
+        f.render_widget(Paragraph::new("No tasks due today").block(block), panes[1]);
+        return;
+    }
+    let lines: Vec<Spans> = (due.iter())
+        .map(|task| {
+            let style = if Some(task.id) == timeline.picked {
+                app_state.styles.selected
+            } else {
+                Style::default()
+            };
+            let when = match task.deadline.filter(|d| is_timed(*d)) {
+                Some(at) => at.format("%H:%M").to_string(),
+                None => "unplaced".to_string(),
+            };
+            Spans::from(vec![
+                Span::styled(app_state.description(task), style),
+                Span::styled(format!("  ({})", when), dim),
+            ])
+        })
+        .collect();
+    f.render_widget(Paragraph::new(lines).block(block), panes[1]);
+}
@@ -0,0 +1,136 @@
+use tui::{
+    backend::Backend,
+    layout::Rect,
+    style::{Modifier, Style},
+    widgets::{Block, Borders, Paragraph, Wrap},
+    Frame,
+};
+
+use crate::keymap::{self, Action};
+use crate::tags;
+use crate::{AppState, Mode, Task};
+
+/// Rows the tutorial panel takes above the list.
+pub const HEIGHT: u16 = 5;
+
+#[derive(Clone, Copy, PartialEq)]
+enum Step {
+    Add,
+    Edit,
+    Tag,
+    Complete,
+    Filter,
+    Finish,
+}
+
+const STEPS: [Step; 6] = [
+    Step::Add,
+    Step::Edit,
+    Step::Tag,
+    Step::Complete,
+    Step::Filter,
+    Step::Finish,
+];
+
+/// `todo-rs tutorial`: steps through the basics on the sample list, each
+/// one done by actually doing it.
+pub struct Tutorial {
+    step: usize,
+    /// The tasks as the step found them, to tell what the user changed.
+    before: Vec<Task>,
+}
+
+impl Tutorial {
+    pub fn new(tasks: &[Task]) -> Tutorial {
+        Tutorial {
+            step: 0,
+            before: tasks.to_vec(),
+        }
+    }
+
+    fn text(&self, app_state: &AppState) -> String {
+        let key = |action: Action| keymap::format_key(app_state.keymap.key_for(action));
+        match STEPS[self.step] {
+            Step::Add => format!(
+                "Press {} to add a task, type what needs doing, e.g. Call the bank, and \
+                 press Enter. Press Enter again to skip the deadline.",
+                key(Action::NewTask)
+            ),
+            Step::Edit => format!(
+                "Move to a task with Up/Down and press {} to edit it. Change the text, \
+                 then press Enter twice.",
+                key(Action::EditTask)
+            ),
+            Step::Tag => format!(
+                "Words starting with # are tags. Edit a task with {} again and add one, \
+                 e.g. #errand; Tab completes tags already in use.",
+                key(Action::EditTask)
+            ),
+            Step::Complete => format!(
+                "Press {} on a task to mark it done.",
+                key(Action::ToggleComplete)
+            ),
+            Step::Filter => format!(
+                "Press {} to search, type #work and press Enter to see only those tasks.",
+                key(Action::Search)
+            ),
+            Step::Finish => format!(
+                "That's the basics. {} opens every other action. Press {} when you're \
+                 done; this practice list is thrown away.",
+                key(Action::ShowMenu),
+                key(Action::Quit)
+            ),
+        }
+    }
+
+    /// Whether the user has done what the current step asks.
+    fn done(&self, app_state: &AppState) -> bool {
+        let was = |task: &Task| self.before.iter().find(|b| b.id == task.id);
+        let mut tasks = app_state.tasks.iter();
+        match STEPS[self.step] {
+            Step::Add => tasks.any(|t| was(t).is_none()),
+            Step::Edit => tasks.any(|t| was(t).is_some_and(|b| b.description != t.description)),
+            Step::Tag => tasks.any(|t| {
+                let had = |tag: &str| {
+                    was(t).is_some_and(|b| tags::tags(&b.description).any(|x| x == tag))
+                };
+                tags::tags(&t.description).any(|tag| !had(tag))
+            }),
+            Step::Complete => tasks.any(|t| t.completed && was(t).is_some_and(|b| !b.completed)),
+            Step::Filter => app_state.filter.is_some(),
+            Step::Finish => false,
+        }
+    }
+}
+
+/// Moves to the next step once the current one is done, checked back in
+/// Normal mode so a half-typed task doesn't count.
+pub fn advance(app_state: &mut AppState) {
+    let Some(tutorial) = &app_state.tutorial else {
+        return;
+    };
+    if app_state.mode != Mode::Normal || !tutorial.done(app_state) {
+        return;
+    }
+    let tasks = app_state.tasks.clone();
+    if let Some(tutorial) = &mut app_state.tutorial {
+        tutorial.step += 1;
+        tutorial.before = tasks;
+    }
+    app_state.set_status("Well done!".to_string(), false);
+}
+
+pub fn render_tutorial<B: Backend>(f: &mut Frame<B>, app_state: &AppState, chunk: Rect) {
+    let Some(tutorial) = &app_state.tutorial else {
+        return;
+    };
+    let title = format!("Tutorial, step {} of {}", tutorial.step + 1, STEPS.len());
+    let block = Block::default()
+        .borders(Borders::ALL)
+        .title(title)
+        .border_style(Style::default().add_modifier(Modifier::BOLD));
+    let paragraph = Paragraph::new(tutorial.text(app_state))
+        .block(block)
+        .wrap(Wrap { trim: true });
+    f.render_widget(paragraph, chunk);
+}
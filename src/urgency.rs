@@ -0,0 +1,154 @@
+use chrono::NaiveDateTime;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+use crate::tags;
+use crate::Task;
+
+/// Age at which a task's age counts fully.
+const MAX_AGE_DAYS: f64 = 365.0;
+
+/// Weights of what makes a task urgent, as the `[urgency]` table. The
+/// score is their sum, each weighted by how much it applies, from 0 to 1.
+#[derive(Serialize, Deserialize, Clone)]
+#[serde(default)]
+pub struct UrgencyConfig {
+    /// Overdue by a week or more counts fully, due in over two weeks 0.2.
+    pub due: f64,
+    /// Grows over a year since the task was created.
+    pub age: f64,
+    /// Tasks in progress.
+    pub started: f64,
+    /// Per open task waiting on this one: each task a subtask is under.
+    pub blocking: f64,
+    pub someday: f64,
+    /// Per `#tag`, e.g. `high = 6.0`; priorities are written as tags.
+    pub tags: BTreeMap<String, f64>,
+}
+
+impl Default for UrgencyConfig {
+    fn default() -> UrgencyConfig {
+        UrgencyConfig {
+            due: 12.0,
+            age: 2.0,
+            started: 4.0,
+            blocking: 8.0,
+            someday: -5.0,
+            tags: BTreeMap::from([("flagged".to_string(), 4.0)]),
+        }
+    }
+}
+
+/// How far the deadline counts, ramping up from two weeks ahead to a week
+/// overdue.
+fn due_factor(deadline: NaiveDateTime, now: NaiveDateTime) -> f64 {
+    let overdue_days = (now - deadline).num_minutes() as f64 / (24.0 * 60.0);
+    if overdue_days >= 7.0 {
+        1.0
+    } else if overdue_days >= -14.0 {
+        (overdue_days + 14.0) * 0.8 / 21.0 + 0.2
+    } else {
+        0.2
+    }
+}
+
+/// Open tasks above `task`, which can't be completed before it.
+fn blocked(tasks: &[Task], task: &Task) -> usize {
+    let mut count = 0;
+    let mut parent = task.parent;
+    while let Some(above) = parent.and_then(|id| tasks.iter().find(|t| t.id == id)) {
+        count += usize::from(!above.completed);
+        parent = above.parent;
+    }
+    count
+}
+
+/// The urgency of `task` among `tasks`; completed tasks have none.
+pub fn score(task: &Task, tasks: &[Task], weights: &UrgencyConfig, now: NaiveDateTime) -> f64 {
+    if task.completed {
+        return 0.0;
+    }
+    let mut score = 0.0;
+    if let Some(deadline) = task.deadline {
+        score += weights.due * due_factor(deadline, now);
+    }
+    if let Some(created) = task.created_at {
+        let days = (now - created).num_days() as f64;
+        score += weights.age * (days / MAX_AGE_DAYS).clamp(0.0, 1.0);
+    }
+    if task.in_progress {
+        score += weights.started;
+    }
+    if task.someday {
+        score += weights.someday;
+    }
+    score += weights.blocking * blocked(tasks, task) as f64;
+    for tag in tags::tags(&task.description) {
+        let weight = (weights.tags.iter()).find(|(name, _)| name.eq_ignore_ascii_case(tag));
+        score += weight.map_or(0.0, |(_, weight)| *weight);
+    }
+    score
+}
+
+/// The urgency of every task, by index.
+pub fn scores(tasks: &[Task], weights: &UrgencyConfig, now: NaiveDateTime) -> Vec<f64> {
+    (tasks.iter())
+        .map(|task| score(task, tasks, weights, now))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Duration;
+
+    fn now() -> NaiveDateTime {
+        NaiveDateTime::parse_from_str("2026-10-16 12:00", "%Y-%m-%d %H:%M").unwrap()
+    }
+
+    fn task(id: u64, description: &str) -> Task {
+        let mut task = Task::new(id, description.to_string(), None);
+        task.created_at = Some(now());
+        task
+    }
+
+    fn due_in(days: i64) -> f64 {
+        due_factor(now() + Duration::days(days), now())
+    }
+
+    #[test]
+    fn the_deadline_ramps_up_until_a_week_overdue() {
+        assert_eq!(due_in(-7), 1.0);
+        assert_eq!(due_in(-30), 1.0);
+        assert_eq!(due_in(14), 0.2);
+        assert_eq!(due_in(60), 0.2);
+        assert!((due_in(0) - (14.0 * 0.8 / 21.0 + 0.2)).abs() < 1e-9);
+        assert!(due_in(1) < due_in(0));
+    }
+
+    #[test]
+    fn adds_up_the_weights() {
+        let weights = UrgencyConfig::default();
+        let mut started = task(1, "Ship it #FLAGGED #other");
+        started.in_progress = true;
+        started.created_at = Some(now() - Duration::days(730));
+        let score = score(&started, &[], &weights, now());
+        assert_eq!(score, weights.started + weights.age + 4.0);
+
+        let mut later = task(2, "Learn the cello");
+        later.someday = true;
+        assert_eq!(super::score(&later, &[], &weights, now()), weights.someday);
+    }
+
+    #[test]
+    fn counts_each_open_task_above() {
+        let weights = UrgencyConfig::default();
+        let mut tasks = vec![task(1, "House"), task(2, "Roof"), task(3, "Tiles")];
+        tasks[1].parent = Some(1);
+        tasks[2].parent = Some(2);
+        assert_eq!(scores(&tasks, &weights, now()), [0.0, 8.0, 16.0]);
+
+        tasks[1].set_completed(true, now());
+        assert_eq!(scores(&tasks, &weights, now()), [0.0, 0.0, 8.0]);
+    }
+}
@@ -0,0 +1,93 @@
+use serde::Deserialize;
+use tui::{
+    backend::Backend,
+    layout::Rect,
+    style::{Color, Modifier, Style},
+    text::{Span, Spans},
+    widgets::{Block, Borders, Paragraph, Wrap},
+    Frame,
+};
+
+use crate::keymap::{self, Action};
+use crate::AppState;
+
+/// Release notes, embedded so the screen works without any files around.
+const CHANGELOG: &str = include_str!("../CHANGELOG.toml");
+
+#[derive(Deserialize)]
+struct Changelog {
+    release: Vec<Release>,
+}
+
+#[derive(Deserialize)]
+pub struct Release {
+    pub version: String,
+    pub features: Vec<String>,
+    /// Actions worth pointing out, shown with whatever key they're bound to.
+    #[serde(default)]
+    pub actions: Vec<Action>,
+}
+
+/// `1.2.3` as comparable numbers; anything unparsable counts as 0.
+pub fn parse_version(version: &str) -> Vec<u32> {
+    version
+        .split('.')
+        .map(|part| part.parse().unwrap_or(0))
+        .collect()
+}
+
+/// Releases newer than `last_version`, newest first; all of them when the
+/// last version is unknown.
+pub fn releases_since(last_version: Option<&str>) -> Vec<Release> {
+    let releases = toml::from_str::<Changelog>(CHANGELOG)
+        .map(|changelog| changelog.release)
+        .unwrap_or_default();
+    let current = parse_version(env!("CARGO_PKG_VERSION"));
+    let last = last_version.map(parse_version);
+    releases
+        .into_iter()
+        .filter(|r| {
+            let version = parse_version(&r.version);
+            version <= current && last.as_ref().is_none_or(|last| version > *last)
+        })
+        .collect()
+}
+
+pub fn render_whats_new<B: Backend>(f: &mut Frame<B>, app_state: &AppState, chunk: Rect) {
+    let mut lines = Vec::new();
+    for release in &app_state.whats_new {
+        lines.push(Spans::from(Span::styled(
+            format!("todo-rs {}", release.version),
+            Style::default().add_modifier(Modifier::BOLD),
+        )));
+        for feature in &release.features {
+            lines.push(Spans::from(format!("  • {}", feature)));
+        }
+        if !release.actions.is_empty() {
+            lines.push(Spans::from(""));
+            lines.push(Spans::from(Span::styled(
+                "  New keys",
+                Style::default().fg(Color::DarkGray),
+            )));
+        }
+        for action in &release.actions {
+            lines.push(Spans::from(vec![
+                Span::styled(
+                    format!(
+                        "  {:<10}",
+                        keymap::format_key(app_state.keymap.key_for(*action))
+                    ),
+                    app_state.styles.selected,
+                ),
+                Span::raw(action.description()),
+            ]));
+        }
+        lines.push(Spans::from(""));
+    }
+
+    let block = Block::default().borders(Borders::ALL).title("What's new");
+    let paragraph = Paragraph::new(lines)
+        .block(block)
+        .wrap(Wrap { trim: false });
+    f.render_widget(paragraph, chunk);
+}
@@ -0,0 +1,181 @@
+use tui::{
+    backend::Backend,
+    layout::Rect,
+    style::{Color, Modifier, Style},
+    text::{Span, Spans},
+    widgets::{Block, Borders, Paragraph, Wrap},
+    Frame,
+};
+
+use crate::config::{Config, Theme, WeekStart};
+use crate::keymap::Key;
+
+#[derive(Clone, Copy, PartialEq)]
+enum Step {
+    DataLocation,
+    Theme,
+    WeekStart,
+    Autosave,
+    Notifications,
+}
+
+const STEPS: [Step; 5] = [
+    Step::DataLocation,
+    Step::Theme,
+    Step::WeekStart,
+    Step::Autosave,
+    Step::Notifications,
+];
+
+/// First-run questionnaire that fills in a [`Config`] one answer at a time.
+pub struct Wizard {
+    step: usize,
+    pub config: Config,
+    typing_path: bool,
+    input: String,
+}
+
+impl Wizard {
+    pub fn new() -> Wizard {
+        Wizard {
+            step: 0,
+            config: Config::default(),
+            typing_path: false,
+            input: String::new(),
+        }
+    }
+
+    fn platform_data_dir() -> Option<String> {
+        dirs::data_dir().map(|d| d.join("todo-rs").to_string_lossy().into_owned())
+    }
+
+    fn options(&self) -> Vec<String> {
+        match STEPS[self.step] {
+            Step::DataLocation => {
+                let mut options = vec!["Current directory".to_string()];
+                if let Some(dir) = Wizard::platform_data_dir() {
+                    options.push(dir);
+                }
+                options.push("Enter a path...".to_string());
+                options
+            }
+            Step::Theme => Theme::presets()
+                .into_iter()
+                .map(|(name, _)| name.to_string())
+                .collect(),
+            Step::WeekStart => vec!["Sunday".to_string(), "Monday".to_string()],
+            Step::Autosave | Step::Notifications => vec!["Yes".to_string(), "No".to_string()],
+        }
+    }
+
+    fn question(&self) -> &'static str {
+        match STEPS[self.step] {
+            Step::DataLocation => "Where should tasks be stored?",
+            Step::Theme => "Pick a color theme:",
+            Step::WeekStart => "Which day does your week start on?",
+            Step::Autosave => "Save after every change (instead of only on quit)?",
+            Step::Notifications => "Show desktop notifications for tasks due today?",
+        }
+    }
+
+    /// Handles a key press, returning true once every question is answered.
+    /// Esc finishes early, keeping defaults for the remaining questions.
+    pub fn handle_key(&mut self, key: Key) -> bool {
+        if self.typing_path {
+            match key {
+                Key::Char('\n') if !self.input.trim().is_empty() => {
+                    self.config.data_dir = self.input.trim().to_string();
+                    self.typing_path = false;
+                    return self.advance();
+                }
+                Key::Char(c) if c != '\n' => self.input.push(c),
+                Key::Backspace => {
+                    self.input.pop();
+                }
+                Key::Esc => {
+                    self.typing_path = false;
+                    self.input.clear();
+                }
+                _ => {}
+            }
+            return false;
+        }
+
+        let choice = match key {
+            Key::Esc => return true,
+            Key::Char(c) => match c.to_digit(10) {
+                Some(n) if n >= 1 && (n as usize) <= self.options().len() => n as usize - 1,
+                _ => return false,
+            },
+            _ => return false,
+        };
+
+        match STEPS[self.step] {
+            Step::DataLocation => {
+                let options = self.options();
+                if choice == options.len() - 1 {
+                    self.typing_path = true;
+                    return false;
+                }
+                self.config.data_dir = match choice {
+                    0 => ".".to_string(),
+                    _ => options[choice].clone(),
+                };
+            }
+            Step::Theme => self.config.theme = Theme::presets().swap_remove(choice).1,
+            Step::WeekStart => {
+                self.config.week_start = Some(if choice == 0 {
+                    WeekStart::Sunday
+                } else {
+                    WeekStart::Monday
+                });
+            }
+            Step::Autosave => self.config.autosave = choice == 0,
+            Step::Notifications => self.config.notifications = choice == 0,
+        }
+        self.advance()
+    }
+
+    fn advance(&mut self) -> bool {
+        self.step += 1;
+        self.step == STEPS.len()
+    }
+}
+
+pub fn render_wizard<B: Backend>(f: &mut Frame<B>, wizard: &Wizard, chunk: Rect) {
+    let mut lines = vec![
+        Spans::from(Span::styled(
+            wizard.question(),
+            Style::default().add_modifier(Modifier::BOLD),
+        )),
+        Spans::from(""),
+    ];
+
+    if wizard.typing_path {
+        lines.push(Spans::from(format!("Path: {}", wizard.input)));
+        lines.push(Spans::from(""));
+        lines.push(Spans::from(Span::styled(
+            "Enter: confirm  Esc: back to the list",
+            Style::default().fg(Color::DarkGray),
+        )));
+    } else {
+        for (i, option) in wizard.options().iter().enumerate() {
+            lines.push(Spans::from(format!("  {}: {}", i + 1, option)));
+        }
+        lines.push(Spans::from(""));
+        lines.push(Spans::from(Span::styled(
+            "Press a number to choose, Esc to skip and keep the defaults",
+            Style::default().fg(Color::DarkGray),
+        )));
+    }
+
+    let title = format!(
+        "Welcome to todo-rs - setup {}/{}",
+        wizard.step + 1,
+        STEPS.len()
+    );
+    let paragraph = Paragraph::new(lines)
+        .wrap(Wrap { trim: false })
+        .block(Block::default().borders(Borders::ALL).title(title));
+    f.render_widget(paragraph, chunk);
+}
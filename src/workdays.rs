@@ -0,0 +1,199 @@
+use chrono::{Datelike, Duration, NaiveDate, Weekday};
+use std::collections::BTreeSet;
+use std::fs;
+
+use crate::calendar;
+use crate::config::{self, WorkdaysConfig};
+
+/// Longest all-day event read from a holiday calendar, so a stray
+/// multi-year event can't blank out the calendar.
+const MAX_EVENT_DAYS: i64 = 31;
+
+/// Which days deadlines may land on. When disabled every day counts, so
+/// callers can apply it unconditionally.
+#[derive(Clone, Default)]
+pub struct Workdays {
+    enabled: bool,
+    holidays: BTreeSet<NaiveDate>,
+}
+
+impl Workdays {
+    /// Reads the configured holidays, including those in `holidays_ics`.
+    pub fn from_config(config: &WorkdaysConfig) -> Result<Workdays, String> {
+        let mut holidays = BTreeSet::new();
+        for text in &config.holidays {
+            let date = NaiveDate::parse_from_str(text, "%Y-%m-%d")
+                .map_err(|_| format!("invalid holiday '{}', expected YYYY-MM-DD", text))?;
+            holidays.insert(date);
+        }
+        if let Some(path) = &config.holidays_ics {
+            let path = config::expand_home(path);
+            let text = fs::read_to_string(&path)
+                .map_err(|e| format!("workdays.holidays_ics {}: {}", path.display(), e))?;
+            holidays.extend(parse_ics(&text));
+        }
+        Ok(Workdays {
+            enabled: config.enabled,
+            holidays,
+        })
+    }
+
+    pub fn enabled(&self) -> bool {
+        self.enabled
+    }
+
+    pub fn is_workday(&self, date: NaiveDate) -> bool {
+        !self.enabled
+            || !(matches!(date.weekday(), Weekday::Sat | Weekday::Sun)
+                || self.holidays.contains(&date))
+    }
+
+    /// The first workday on or after `date`.
+    pub fn on_or_after(&self, mut date: NaiveDate) -> NaiveDate {
+        while !self.is_workday(date) {
+            date += Duration::days(1);
+        }
+        date
+    }
+
+    /// The last workday on or before `date` but not before `floor`; past
+    /// `floor` the next workday after it instead.
+    pub fn on_or_before(&self, date: NaiveDate, floor: NaiveDate) -> NaiveDate {
+        let mut day = date;
+        while day >= floor {
+            if self.is_workday(day) {
+                return day;
+            }
+            day -= Duration::days(1);
+        }
+        self.on_or_after(date)
+    }
+
+    /// `date` moved forward by `days` workdays.
+    pub fn add_days(&self, mut date: NaiveDate, days: u32) -> NaiveDate {
+        for _ in 0..days {
+            date = self.on_or_after(date + Duration::days(1));
+        }
+        date
+    }
+}
+
+/// Days covered by the events in an iCalendar file. All-day events span up
+/// to their exclusive `DTEND`; timed events count for their start day.
+fn parse_ics(text: &str) -> Vec<NaiveDate> {
+    let lines = calendar::unfold(text);
+    let mut days = Vec::new();
+    let (mut start, mut end) = (None, None);
+    for line in &lines {
+        let Some((name, value)) = line.split_once(':') else {
+            continue;
+        };
+        let property = name.split(';').next().unwrap_or_default();
+        let date = || NaiveDate::parse_from_str(value.get(..8)?, "%Y%m%d").ok();
+        match property.to_uppercase().as_str() {
+            "BEGIN" => (start, end) = (None, None),
+            "DTSTART" => start = date(),
+            "DTEND" if name.to_uppercase().contains("VALUE=DATE") => end = date(),
+            "END" if value.eq_ignore_ascii_case("VEVENT") => {
+                let Some(first) = start else {
+                    continue;
+                };
+                let length = end.map_or(1, |e| (e - first).num_days().clamp(1, MAX_EVENT_DAYS));
+                days.extend((0..length).map(|i| first + Duration::days(i)));
+            }
+            _ => {}
+        }
+    }
+    days
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn date(text: &str) -> NaiveDate {
+        NaiveDate::parse_from_str(text, "%Y-%m-%d").unwrap()
+    }
+
+    /// Workdays on, with Friday 2026-10-23 off.
+    fn workdays() -> Workdays {
+        let config = WorkdaysConfig {
+            enabled: true,
+            holidays: vec!["2026-10-23".to_string()],
+            holidays_ics: None,
+        };
+        Workdays::from_config(&config).unwrap()
+    }
+
+    #[test]
+    fn skips_weekends_and_holidays() {
+        let workdays = workdays();
+        assert!(workdays.is_workday(date("2026-10-22")));
+        assert!(!workdays.is_workday(date("2026-10-23")));
+        assert!(!workdays.is_workday(date("2026-10-24")));
+        assert_eq!(workdays.on_or_after(date("2026-10-23")), date("2026-10-26"));
+        assert_eq!(workdays.add_days(date("2026-10-21"), 2), date("2026-10-26"));
+    }
+
+    #[test]
+    fn looks_back_no_further_than_the_floor() {
+        let workdays = workdays();
+        let sunday = date("2026-10-25");
+        assert_eq!(
+            workdays.on_or_before(sunday, date("2026-10-20")),
+            date("2026-10-22")
+        );
+        assert_eq!(
+            workdays.on_or_before(sunday, date("2026-10-23")),
+            date("2026-10-26")
+        );
+    }
+
+    #[test]
+    fn disabled_counts_every_day() {
+        let workdays = Workdays::default();
+        assert!(workdays.is_workday(date("2026-10-24")));
+        assert_eq!(workdays.add_days(date("2026-10-23"), 1), date("2026-10-24"));
+    }
+
+    #[test]
+    fn rejects_malformed_holidays() {
+        let config = WorkdaysConfig {
+            holidays: vec!["23.10.2026".to_string()],
+            ..WorkdaysConfig::default()
+        };
+        assert_eq!(
+            Workdays::from_config(&config).err().unwrap(),
+            "invalid holiday '23.10.2026', expected YYYY-MM-DD"
+        );
+    }
+
+    #[test]
+    fn reads_holiday_calendars() {
+        let ics = "BEGIN:VCALENDAR\r\n\
+            BEGIN:VEVENT\r\n\
+            DTSTART;VALUE=DATE:20261224\r\n\
+            DTEND;VALUE=DATE:20261227\r\n\
+            END:VEVENT\r\n\
+            BEGIN:VEVENT\r\n\
+            DTSTART:20261231T090000Z\r\n\
+            DTEND:20261231T120000Z\r\n\
+            END:VEVENT\r\n\
+            BEGIN:VEVENT\r\n\
+            DTSTART;VALUE=DATE:20200101\r\n\
+            DTEND;VALUE=DATE:20300101\r\n\
+            END:VEVENT\r\n\
+            END:VCALENDAR\r\n";
+        let days = parse_ics(ics);
+        assert_eq!(
+            days[..4],
+            [
+                date("2026-12-24"),
+                date("2026-12-25"),
+                date("2026-12-26"),
+                date("2026-12-31")
+            ]
+        );
+        assert_eq!(days.len(), 4 + MAX_EVENT_DAYS as usize);
+    }
+}
@@ -0,0 +1,95 @@
+use std::collections::HashMap;
+use std::fs::{self, File};
+use std::io::{self, BufReader};
+use std::path::{Path, PathBuf};
+
+use crate::archive;
+use crate::config;
+use crate::Task;
+pub use todo_rs::task::{Source, MAIN};
+
+pub fn validate_name(name: &str) -> Result<(), String> {
+    if name == MAIN {
+        return Err(format!("'{}' is the data directory's own list", MAIN));
+    }
+    let valid = |c: char| c.is_alphanumeric() || c == '-' || c == '_';
+    if name.is_empty() || !name.chars().all(valid) {
+        return Err("names are single words of letters, digits, - and _".to_string());
+    }
+    Ok(())
+}
+
+/// Completed tasks of `path` are kept beside it, e.g. `work.archive.json`
+/// for `work.json`, so lists sharing a directory don't share an archive.
+fn archive_path(path: &Path) -> PathBuf {
+    let stem = path.file_stem().unwrap_or_default().to_string_lossy();
+    path.with_file_name(format!("{}.archive.json", stem))
+}
+
+/// The open tasks of the file at `path` under the name `name`, numbered
+/// from `next_id` on. A file that doesn't exist yet is an empty list.
+pub fn load(name: &str, path: &str, next_id: u64) -> Result<Vec<Task>, io::Error> {
+    let mut tasks: Vec<Task> = match File::open(config::expand_home(path)) {
+        Ok(file) => serde_json::from_reader(BufReader::new(file)).map_err(io::Error::other)?,
+        Err(ref e) if e.kind() == io::ErrorKind::NotFound => Vec::new(),
+        Err(e) => return Err(e),
+    };
+    // Tasks saved before ids existed get one after the file's highest.
+    let mut file_id = tasks.iter().map(|t| t.id).max().unwrap_or(0);
+    let ids: HashMap<u64, u64> = (tasks.iter_mut().zip(next_id..))
+        .map(|(task, id)| {
+            if task.id == 0 {
+                file_id += 1;
+                task.id = file_id;
+            }
+            (task.id, id)
+        })
+        .collect();
+    for task in &mut tasks {
+        task.source = Some(Source {
+            name: name.to_string(),
+            id: task.id,
+        });
+        task.id = ids[&task.id];
+        task.parent = task.parent.and_then(|p| ids.get(&p).copied());
+    }
+    Ok(tasks)
+}
+
+/// An id for a new task in the file `name` at `path`, one not taken by a
+/// task in its archive either.
+pub fn next_file_id(tasks: &[Task], name: &str, path: &str) -> u64 {
+    let sources = tasks.iter().filter_map(|t| t.source.as_ref());
+    let open = sources.filter(|s| s.name == name).map(|s| s.id);
+    let archived = archive::load_file(&archive_path(&config::expand_home(path)));
+    let archived = archived.unwrap_or_default().into_iter().map(|t| t.id);
+    open.chain(archived).max().unwrap_or(0) + 1
+}
+
+/// Writes the open tasks of `name` back to `path` with their own ids, and
+/// its completed ones to its archive.
+pub fn save(name: &str, path: &str, tasks: &[Task]) -> Result<(), io::Error> {
+    let own: Vec<&Task> = tasks
+        .iter()
+        .filter(|t| t.source.as_ref().is_some_and(|s| s.name == name))
+        .collect();
+    let ids: HashMap<u64, u64> = (own.iter())
+        .filter_map(|t| Some((t.id, t.source.as_ref()?.id)))
+        .collect();
+    let saved: Vec<Task> = (own.into_iter())
+        .map(|task| {
+            let mut task = task.clone();
+            task.id = ids[&task.id];
+            task.parent = task.parent.and_then(|p| ids.get(&p).copied());
+            task
+        })
+        .collect();
+
+    let path = config::expand_home(path);
+    if let Some(parent) = path.parent().filter(|p| !p.as_os_str().is_empty()) {
+        fs::create_dir_all(parent)?;
+    }
+    let open: Vec<&Task> = saved.iter().filter(|t| !t.completed).collect();
+    serde_json::to_writer(File::create(&path)?, &open)?;
+    archive::update_file(&archive_path(&path), &saved)
+}
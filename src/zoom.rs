@@ -0,0 +1,164 @@
+use tui::{
+    backend::Backend,
+    layout::{Alignment, Rect},
+    style::{Modifier, Style},
+    text::{Span, Spans},
+    widgets::{Block, Borders, Paragraph, Wrap},
+    Frame,
+};
+
+use crate::AppState;
+
+/// Rows in a large glyph.
+const HEIGHT: usize = 5;
+
+/// A 5-row block font; `#` cells are drawn. Characters without a glyph
+/// (lowercase is folded to uppercase) make the view fall back to plain text.
+fn glyph(c: char) -> Option<[&'static str; HEIGHT]> {
+    let rows = match c.to_ascii_uppercase() {
+        'A' => [" ## ", "#  #", "####", "#  #", "#  #"],
+        'B' => ["### ", "#  #", "### ", "#  #", "### "],
+        'C' => [" ###", "#   ", "#   ", "#   ", " ###"],
+        'D' => ["### ", "#  #", "#  #", "#  #", "### "],
+        'E' => ["####", "#   ", "### ", "#   ", "####"],
+        'F' => ["####", "#   ", "### ", "#   ", "#   "],
+        'G' => [" ###", "#   ", "# ##", "#  #", " ###"],
+        'H' => ["#  #", "#  #", "####", "#  #", "#  #"],
+        'I' => ["###", " # ", " # ", " # ", "###"],
+        'J' => ["  ##", "   #", "   #", "#  #", " ## "],
+        'K' => ["#  #", "# # ", "##  ", "# # ", "#  #"],
+        'L' => ["#   ", "#   ", "#   ", "#   ", "####"],
+        'M' => ["#   #", "## ##", "# # #", "#   #", "#   #"],
+        'N' => ["#  #", "## #", "# ##", "#  #", "#  #"],
+        'O' => [" ## ", "#  #", "#  #", "#  #", " ## "],
+        'P' => ["### ", "#  #", "### ", "#   ", "#   "],
+        'Q' => [" ## ", "#  #", "#  #", "# # ", " # #"],
+        'R' => ["### ", "#  #", "### ", "# # ", "#  #"],
+        'S' => [" ###", "#   ", " ## ", "   #", "### "],
+        'T' => ["###", " # ", " # ", " # ", " # "],
+        'U' => ["#  #", "#  #", "#  #", "#  #", " ## "],
+        'V' => ["#   #", "#   #", "#   #", " # # ", "  #  "],
+        'W' => ["#   #", "#   #", "# # #", "## ##", "#   #"],
+        'X' => ["#  #", "#  #", " ## ", "#  #", "#  #"],
+        'Y' => ["# #", "# #", " # ", " # ", " # "],
+        'Z' => ["####", "   #", " ## ", "#   ", "####"],
+        '0' => [" ## ", "#  #", "#  #", "#  #", " ## "],
+        '1' => [" # ", "## ", " # ", " # ", "###"],
+        '2' => ["### ", "   #", " ## ", "#   ", "####"],
+        '3' => ["### ", "   #", " ## ", "   #", "### "],
+        '4' => ["#  #", "#  #", "####", "   #", "   #"],
+        '5' => ["####", "#   ", "### ", "   #", "### "],
+        '6' => [" ## ", "#   ", "### ", "#  #", " ## "],
+        '7' => ["####", "   #", "  # ", " #  ", " #  "],
+        '8' => [" ## ", "#  #", " ## ", "#  #", " ## "],
+        '9' => [" ## ", "#  #", " ###", "   #", " ## "],
+        ' ' => ["  ", "  ", "  ", "  ", "  "],
+        '.' => [" ", " ", " ", " ", "#"],
+        ',' => ["  ", "  ", "  ", " #", "# "],
+        ':' => [" ", "#", " ", "#", " "],
+        '!' => ["#", "#", "#", " ", "#"],
+        '?' => ["### ", "   #", " ## ", "    ", " #  "],
+        '-' => ["   ", "   ", "###", "   ", "   "],
+        '+' => ["   ", " # ", "###", " # ", "   "],
+        '#' => [" # # ", "#####", " # # ", "#####", " # # "],
+        '/' => ["   #", "  # ", " #  ", "#   ", "    "],
+        '\'' => ["#", "#", " ", " ", " "],
+        '(' => [" #", "# ", "# ", "# ", " #"],
+        ')' => ["# ", " #", " #", " #", "# "],
+        _ => return None,
+    };
+    Some(rows)
+}
+
+/// Renders `word` in the block font, one string per row.
+fn big_word(word: &str) -> Option<Vec<String>> {
+    let mut rows = vec![String::new(); HEIGHT];
+    for (i, c) in word.chars().enumerate() {
+        let glyph = glyph(c)?;
+        for (row, line) in rows.iter_mut().zip(glyph) {
+            if i > 0 {
+                row.push(' ');
+            }
+            row.push_str(&line.replace('#', "█"));
+        }
+    }
+    Some(rows)
+}
+
+/// Lays the text out in large glyphs, wrapping at word boundaries. `None`
+/// when a character has no glyph or a line won't fit in `width`/`height`.
+fn big_text(text: &str, width: usize, height: usize) -> Option<Vec<String>> {
+    let mut lines: Vec<Vec<String>> = Vec::new();
+    let mut current: Option<Vec<String>> = None;
+    for word in text.split_whitespace() {
+        let big = big_word(word)?;
+        current = match current.take() {
+            Some(mut line) if line[0].chars().count() + 3 + big[0].chars().count() <= width => {
+                for (row, part) in line.iter_mut().zip(&big) {
+                    row.push_str("   ");
+                    row.push_str(part);
+                }
+                Some(line)
+            }
+            previous => {
+                lines.extend(previous);
+                Some(big)
+            }
+        };
+    }
+    lines.extend(current);
+
+    let fits_width = lines.iter().all(|l| l[0].chars().count() <= width);
+    // A blank row between lines of glyphs.
+    let rows = lines.len() * (HEIGHT + 1);
+    if !fits_width || rows > height + 1 {
+        return None;
+    }
+    let mut out = Vec::new();
+    for line in lines {
+        if !out.is_empty() {
+            out.push(String::new());
+        }
+        out.extend(line);
+    }
+    Some(out)
+}
+
+/// The selected task filling the body, large enough to read across the
+/// room or on a second monitor.
+pub fn render_zoom<B: Backend>(f: &mut Frame<B>, app_state: &AppState, chunk: Rect) {
+    let block = Block::default().borders(Borders::ALL).title("Current task");
+    let Some(task) = app_state.selected_task.and_then(|i| app_state.tasks.get(i)) else {
+        f.render_widget(Paragraph::new("No task selected").block(block), chunk);
+        return;
+    };
+
+    let inner = block.inner(chunk);
+    let (width, height) = (inner.width as usize, inner.height as usize);
+    let description = app_state.description(task);
+    let (lines, alignment) = match big_text(&description, width, height) {
+        // Padded by hand: centering each row would drop its trailing blanks
+        // and shear the glyphs.
+        Some(rows) => {
+            let rows = rows.into_iter().map(|row| {
+                let left = width.saturating_sub(row.chars().count()) / 2;
+                Spans::from(format!("{}{}", " ".repeat(left), row))
+            });
+            (rows.collect(), Alignment::Left)
+        }
+        None => {
+            let text = Span::styled(description, Style::default().add_modifier(Modifier::BOLD));
+            (vec![Spans::from(text)], Alignment::Center)
+        }
+    };
+    // Centered vertically as well.
+    let top = height.saturating_sub(lines.len()) / 2;
+    let mut padded = vec![Spans::from(""); top];
+    padded.extend(lines);
+
+    let paragraph = Paragraph::new(padded)
+        .block(block)
+        .alignment(alignment)
+        .wrap(Wrap { trim: false });
+    f.render_widget(paragraph, chunk);
+}